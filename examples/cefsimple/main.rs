@@ -9,6 +9,7 @@ use cef::{
         life_span_handler::{LifeSpanHandler, LifeSpanHandlerCallbacks}
     },
     settings::{Settings},
+    task::UiThread,
     window::WindowInfo,
     logging::Logger,
 };
@@ -31,14 +32,14 @@ impl ClientCallbacks for ClientCallbacksImpl {
 pub struct LifeSpanHandlerImpl {}
 
 impl LifeSpanHandlerCallbacks for LifeSpanHandlerImpl {
-    fn on_before_close(&self, _browser: Browser) {
+    fn on_before_close(&self, _ui_thread: UiThread, _browser: Browser) {
         cef::quit_message_loop().unwrap();
     }
 }
 
-fn main() {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app = App::new(AppCallbacksImpl {});
-    let result = cef::execute_process(Some(app.clone()), None);
+    let result = cef::execute_process(Some(app.clone()), None)?;
     if result >= 0 {
         std::process::exit(result);
     }
@@ -46,7 +47,7 @@ fn main() {
     let settings = Settings::new()
         .log_severity(cef::settings::LogSeverity::Info);
 
-    let context = cef::Context::initialize(&settings, Some(app), None).unwrap();
+    let context = cef::Context::initialize(&settings, Some(app), None)?;
     let mut logger_builder = Logger::builder();
     logger_builder.level(log::LevelFilter::Info);
     let logger = Box::new(logger_builder.build());
@@ -83,4 +84,5 @@ fn main() {
     context.run_message_loop();
 
     info!("Quit");
+    Ok(())
 }
@@ -28,6 +28,7 @@ use cef::{
     command_line::CommandLine,
     events::{EventFlags, MouseButtonType, MouseEvent},
     settings::{LogSeverity, Settings},
+    task::UiThread,
     window::{RawWindow, WindowInfo},
 };
 use cef_sys::cef_cursor_handle_t;
@@ -92,8 +93,8 @@ impl AppCallbacks for AppCallbacksImpl {
         command_line: CommandLine,
     ) {
         if process_type == None {
-            command_line.append_switch("disable-gpu");
-            command_line.append_switch("disable-gpu-compositing");
+            command_line.append_switch("disable-gpu").unwrap();
+            command_line.append_switch("disable-gpu-compositing").unwrap();
         }
     }
     fn get_browser_process_handler(&self) -> Option<BrowserProcessHandler> {
@@ -111,7 +112,7 @@ impl ClientCallbacks for ClientCallbacksImpl {
 }
 
 impl LifeSpanHandlerCallbacks for LifeSpanHandlerImpl {
-    fn on_before_close(&self, _browser: Browser) {
+    fn on_before_close(&self, _ui_thread: UiThread, _browser: Browser) {
         println!("close browser");
         self.proxy.lock().send_event(CefEvent::Quit).unwrap();
     }
@@ -282,13 +283,13 @@ impl<R: Renderer> RenderHandlerCallbacks for RenderHandlerCallbacksImpl<R> {
     fn update_drag_cursor(&self, _browser: Browser, _operation: DragOperation) {}
 }
 
-fn main() {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     match cef::process_type() {
         cef::ProcessType::Renderer
         | cef::ProcessType::Gpu
         | cef::ProcessType::Utility
         | cef::ProcessType::Other => {
-            let result = cef::execute_process(None, None);
+            let result = cef::execute_process(None, None)?;
             if result >= 0 {
                 std::process::exit(result);
             }
@@ -316,7 +317,7 @@ fn main() {
                 .external_message_pump(true)
                 .framework_dir_path("/Library/Frameworks/Chromium Embedded Framework.framework");
 
-            let context = cef::Context::initialize(&settings, Some(app), None).unwrap();
+            let context = cef::Context::initialize(&settings, Some(app), None)?;
 
             let window_builder = WindowBuilder::new()
                 .with_title("CEF Example Window");
@@ -560,6 +561,7 @@ fn main() {
             });
         }
     }
+    Ok(())
 }
 
 fn winit_keycode_to_windows_keycode(winit_keycode: VirtualKeyCode) -> Option<WindowsKeyCode> {
@@ -19,34 +19,41 @@ impl Browser {
     /// Returns the browser host object. This function can only be called in the
     /// browser process.
     pub fn get_host(&self) -> BrowserHost {
+        ffi_event!("Browser::get_host");
         unsafe { BrowserHost::from_ptr_unchecked((self.0.get_host.unwrap())(self.0.as_ptr())) }
     }
     /// Returns true if the browser can navigate backwards.
     pub fn can_go_back(&self) -> bool {
+        ffi_event!("Browser::can_go_back");
         unsafe { (self.0.can_go_back.unwrap())(self.0.as_ptr()) != 0 }
     }
     /// Navigate backwards.
     pub fn go_back(&self) {
+        ffi_event!("Browser::go_back");
         unsafe {
             (self.0.go_back.unwrap())(self.0.as_ptr());
         }
     }
     /// Returns true if the browser can navigate forwards.
     pub fn can_go_forward(&self) -> bool {
+        ffi_event!("Browser::can_go_forward");
         unsafe { (self.0.can_go_forward.unwrap())(self.0.as_ptr()) != 0 }
     }
     /// Navigate forwards.
     pub fn go_forward(&self) {
+        ffi_event!("Browser::go_forward");
         unsafe {
             (self.0.go_forward.unwrap())(self.0.as_ptr());
         }
     }
     /// Returns true if the browser is currently loading.
     pub fn is_loading(&self) -> bool {
+        ffi_event!("Browser::is_loading");
         unsafe { (self.0.is_loading.unwrap())(self.0.as_ptr()) != 0 }
     }
     /// Reload the current page, optionally ignoring any cached data.
     pub fn reload(&self, ignore_cache: bool) {
+        ffi_event!("Browser::reload");
         if ignore_cache {
             unsafe {
                 (self.0.reload.unwrap())(self.0.as_ptr());
@@ -59,6 +66,7 @@ impl Browser {
     }
     /// Stop loading the page.
     pub fn stop_load(&self) {
+        ffi_event!("Browser::stop_load");
         unsafe {
             (self.0.stop_load.unwrap())(self.0.as_ptr());
         }
@@ -66,26 +74,32 @@ impl Browser {
     /// Returns the globally unique identifier for this browser. This value is also
     /// used as the tabId for extension APIs.
     pub fn get_identifier(&self) -> i32 {
+        ffi_event!("Browser::get_identifier");
         unsafe { (self.0.get_identifier.unwrap())(self.0.as_ptr()) }
     }
     /// Returns true if the window is a popup window.
     pub fn is_popup(&self) -> bool {
+        ffi_event!("Browser::is_popup");
         unsafe { (self.0.is_popup.unwrap())(self.0.as_ptr()) != 0 }
     }
     /// Returns true if a document has been loaded in the browser.
     pub fn has_document(&self) -> bool {
+        ffi_event!("Browser::has_document");
         unsafe { (self.0.has_document.unwrap())(self.0.as_ptr()) != 0 }
     }
     /// Returns the main (top-level) frame for the browser window.
     pub fn get_main_frame(&self) -> Frame {
+        ffi_event!("Browser::get_main_frame");
         unsafe { Frame::from_ptr_unchecked((self.0.get_main_frame.unwrap())(self.0.as_ptr())) }
     }
     /// Returns the focused frame for the browser window.
     pub fn get_focused_frame(&self) -> Option<Frame> {
+        ffi_event!("Browser::get_focused_frame");
         unsafe { Frame::from_ptr((self.0.get_focused_frame.unwrap())(self.0.as_ptr())) }
     }
     /// Returns the frame with the specified identifier, or None if not found.
     pub fn get_frame_byident(&self, identifier: i64) -> Option<Frame> {
+        ffi_event!("Browser::get_frame_byident");
         unsafe {
             Frame::from_ptr((self.0.get_frame_byident.unwrap())(
                 self.0.as_ptr(),
@@ -95,6 +109,7 @@ impl Browser {
     }
     /// Returns the frame with the specified name, or None if not found.
     pub fn get_frame(&self, name: &str) -> Option<Frame> {
+        ffi_event!("Browser::get_frame");
         unsafe {
             Frame::from_ptr((self.0.get_frame.unwrap())(
                 self.0.as_ptr(),
@@ -104,10 +119,12 @@ impl Browser {
     }
     /// Returns the number of frames that currently exist.
     pub fn get_frame_count(&self) -> usize {
+        ffi_event!("Browser::get_frame_count");
         unsafe { (self.0.get_frame_count.unwrap())(self.0.as_ptr()) }
     }
     /// Returns the identifiers of all existing frames.
     pub fn get_frame_identifiers(&self) -> Vec<i64> {
+        ffi_event!("Browser::get_frame_identifiers");
         let mut count = self.get_frame_count();
         let mut result = vec![0; count];
         unsafe {
@@ -121,6 +138,7 @@ impl Browser {
     }
     /// Returns the names of all existing frames.
     pub fn get_frame_names(&self) -> Vec<String> {
+        ffi_event!("Browser::get_frame_names");
         let mut list = CefStringList::default();
         unsafe {
             (self.0.get_frame_names.unwrap())(self.0.as_ptr(), list.as_mut_ptr());
@@ -157,6 +175,18 @@ impl State {
     }
 }
 
+impl std::str::FromStr for State {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_ascii_lowercase() {
+            "default" => Ok(State::Default),
+            "enabled" | "true" => Ok(State::Enabled),
+            "disabled" | "false" => Ok(State::Disabled),
+            _ => Err(format!("unknown state `{}`", s)),
+        }
+    }
+}
+
 impl Default for State {
     fn default() -> State {
         State::Default
@@ -14,15 +14,16 @@ use crate::{
     request_context::RequestContext,
     send_protector::SendProtectorMut,
     string::{CefString, CefStringList},
+    task::UiThread,
     values::{DictionaryValue, Point, Range, Size, StoredValue},
     window::{RawWindow, WindowInfo},
 };
 use cef_sys::{
-    cef_browser_host_create_browser_sync, cef_browser_host_t,
-    cef_download_image_callback_t, cef_image_t, cef_navigation_entry_t,
+    cef_browser_host_t, cef_download_image_callback_t, cef_image_t, cef_navigation_entry_t,
     cef_navigation_entry_visitor_t, cef_paint_element_type_t, cef_pdf_print_callback_t,
     cef_string_t,
 };
+use crate::capi::cef_browser_host_create_browser_sync;
 use parking_lot::Mutex;
 use std::{
     collections::HashMap,
@@ -103,6 +104,7 @@ impl BrowserHost {
         request_context: Option<RequestContext>,
         context: &crate::Context,
     ) -> Browser {
+        ffi_event!("BrowserHost::create_browser_sync");
         let extra_info = extra_info.map(DictionaryValue::from);
 
         unsafe {
@@ -120,6 +122,7 @@ impl BrowserHost {
     }
     /// Returns the hosted browser object.
     pub fn get_browser(&self) -> Browser {
+        ffi_event!("BrowserHost::get_browser");
         unsafe { Browser::from_ptr_unchecked(self.0.get_browser.unwrap()(self.0.as_ptr())) }
     }
     /// Request that the browser close. The JavaScript 'onbeforeunload' event will
@@ -131,6 +134,7 @@ impl BrowserHost {
     /// [LifeSpanHandler::do_close] documentation for additional usage
     /// information.
     pub fn close_browser(&self, force_close: bool) {
+        ffi_event!("BrowserHost::close_browser");
         if let Some(close_browser) = self.0.close_browser {
             unsafe {
                 close_browser(self.0.as_ptr(), force_close as i32);
@@ -143,7 +147,8 @@ impl BrowserHost {
     /// is pending and true after the close has completed. See [close_browser]
     /// and [LifeSpanHandler::do_close] documentation for additional usage
     /// information. This function must be called on the browser process UI thread.
-    pub fn try_close_browser(&self) -> bool {
+    pub fn try_close_browser(&self, _ui_thread: UiThread) -> bool {
+        ffi_event!("BrowserHost::try_close_browser");
         self.0
             .try_close_browser
             .map(|try_close_browser| unsafe { try_close_browser(self.0.as_ptr()) != 0 })
@@ -151,6 +156,7 @@ impl BrowserHost {
     }
     /// Set whether the browser is focused.
     pub fn set_focus(&self, focus: bool) {
+        ffi_event!("BrowserHost::set_focus");
         if let Some(set_focus) = self.0.set_focus {
             unsafe {
                 set_focus(self.0.as_ptr(), focus as i32);
@@ -161,6 +167,7 @@ impl BrowserHost {
     /// a [BrowserView] this function should be called on the browser process
     /// UI thread and it will return the handle for the top-level native window.
     pub fn get_window_handle(&self) -> Option<RawWindow> {
+        ffi_event!("BrowserHost::get_window_handle");
         self.0
             .get_window_handle
             .and_then(|get_window_handle| unsafe { RawWindow::from_cef_handle(get_window_handle(self.0.as_ptr())) })
@@ -170,12 +177,14 @@ impl BrowserHost {
     /// [BrowserView]. This function can be used in combination with custom
     /// handling of modal windows.
     pub fn get_opener_window_handle(&self) -> Option<RawWindow> {
+        ffi_event!("BrowserHost::get_opener_window_handle");
         self.0
             .get_opener_window_handle
             .and_then(|get_opener_window_handle| unsafe { RawWindow::from_cef_handle(get_opener_window_handle(self.0.as_ptr())) })
     }
     /// Returns true if this browser is wrapped in a [BrowserView].
     pub fn has_view(&self) -> bool {
+        ffi_event!("BrowserHost::has_view");
         self.0
             .has_view
             .map(|has_view| unsafe { has_view(self.0.as_ptr()) != 0 })
@@ -183,11 +192,13 @@ impl BrowserHost {
     }
     /// Returns the client for this browser, None if the type is not correct.
     pub fn get_client(&self) -> Option<Client> {
+        ffi_event!("BrowserHost::get_client");
         let get_client = self.0.get_client.unwrap();
         unsafe{ Client::from_ptr(get_client(self.0.as_ptr())) }
     }
     /// Returns the request context for this browser.
     pub fn get_request_context(&self) -> RequestContext {
+        ffi_event!("BrowserHost::get_request_context");
         self.0
             .get_request_context
             .and_then(|get_request_context| unsafe {
@@ -197,7 +208,8 @@ impl BrowserHost {
     }
     /// Get the current zoom level. The default zoom level is 0.0. This function
     /// can only be called on the UI thread.
-    pub fn get_zoom_level(&self) -> f64 {
+    pub fn get_zoom_level(&self, _ui_thread: UiThread) -> f64 {
+        ffi_event!("BrowserHost::get_zoom_level");
         self.0
             .get_zoom_level
             .map(|get_zoom_level| unsafe { get_zoom_level(self.0.as_ptr()) })
@@ -207,6 +219,7 @@ impl BrowserHost {
     /// level. If called on the UI thread the change will be applied immediately.
     /// Otherwise, the change will be applied asynchronously on the UI thread.
     pub fn set_zoom_level(&self, zoom_level: f64) {
+        ffi_event!("BrowserHost::set_zoom_level");
         if let Some(set_zoom_level) = self.0.set_zoom_level {
             unsafe {
                 set_zoom_level(self.0.as_ptr(), zoom_level);
@@ -241,6 +254,7 @@ impl BrowserHost {
         selected_accept_filter: i32,
         callback: impl Send + FnOnce(usize, Option<Vec<String>>) + 'static,
     ) {
+        ffi_event!("BrowserHost::run_file_dialog");
         if let Some(run_file_dialog) = self.0.run_file_dialog {
             let title = title.map(CefString::new);
             let default_file_path = default_file_path.map(CefString::new);
@@ -261,6 +275,7 @@ impl BrowserHost {
     }
     /// Download the file at `url` using [DownloadHandler].
     pub fn start_download(&self, url: &str) {
+        ffi_event!("BrowserHost::start_download");
         if let Some(start_download) = self.0.start_download {
             unsafe {
                 start_download(self.0.as_ptr(), CefString::new(url).as_ptr());
@@ -290,6 +305,7 @@ impl BrowserHost {
         bypass_cache: bool,
         callback: impl Send + FnOnce(&str, u16, Option<Image>) + 'static,
     ) {
+        ffi_event!("BrowserHost::download_image");
         if let Some(download_image) = self.0.download_image {
             unsafe {
                 download_image(
@@ -307,6 +323,7 @@ impl BrowserHost {
     }
     /// Print the current browser contents.
     pub fn print(&self) {
+        ffi_event!("BrowserHost::print");
         if let Some(print) = self.0.print {
             unsafe {
                 print(self.0.as_ptr());
@@ -327,6 +344,7 @@ impl BrowserHost {
         settings: PDFPrintSettings,
         callback: impl Send + FnOnce(&str, bool) + 'static,
     ) {
+        ffi_event!("BrowserHost::print_to_pdf");
         if let Some(print_to_pdf) = self.0.print_to_pdf {
             unsafe {
                 print_to_pdf(
@@ -355,6 +373,7 @@ impl BrowserHost {
         match_case: bool,
         find_next: bool,
     ) {
+        ffi_event!("BrowserHost::find");
         if let Some(find) = self.0.find {
             unsafe {
                 find(
@@ -370,6 +389,7 @@ impl BrowserHost {
     }
     /// Cancel all searches that are currently going on.
     pub fn stop_finding(&self, clear_selection: bool) {
+        ffi_event!("BrowserHost::stop_finding");
         if let Some(stop_finding) = self.0.stop_finding {
             unsafe {
                 stop_finding(self.0.as_ptr(), clear_selection as i32);
@@ -390,6 +410,7 @@ impl BrowserHost {
         settings: Option<BrowserSettings>,
         inspect_element_at: Point,
     ) {
+        ffi_event!("BrowserHost::show_dev_tools");
         if let Some(show_dev_tools) = self.0.show_dev_tools {
             let client = client
                 .map(Client::into_raw)
@@ -408,6 +429,7 @@ impl BrowserHost {
     }
     /// Explicitly close the associated DevTools browser, if any.
     pub fn close_dev_tools(&self) {
+        ffi_event!("BrowserHost::close_dev_tools");
         if let Some(close_dev_tools) = self.0.close_dev_tools {
             unsafe {
                 close_dev_tools(self.0.as_ptr());
@@ -416,7 +438,8 @@ impl BrowserHost {
     }
     /// Returns true if this browser currently has an associated DevTools
     /// browser. Must be called on the browser process UI thread.
-    pub fn has_dev_tools(&self) -> bool {
+    pub fn has_dev_tools(&self, _ui_thread: UiThread) -> bool {
+        ffi_event!("BrowserHost::has_dev_tools");
         self.0
             .has_dev_tools
             .map(|has_dev_tools| unsafe { has_dev_tools(self.0.as_ptr()) != 0 })
@@ -433,6 +456,7 @@ impl BrowserHost {
         visitor: NavigationEntryVisitor,
         current_only: bool,
     ) {
+        ffi_event!("BrowserHost::get_navigation_entries");
         if let Some(get_navigation_entries) = self.0.get_navigation_entries {
             unsafe {
                 get_navigation_entries(
@@ -445,6 +469,7 @@ impl BrowserHost {
     }
     /// Set whether mouse cursor change is disabled.
     pub fn set_mouse_cursor_change_disabled(&self, disabled: bool) {
+        ffi_event!("BrowserHost::set_mouse_cursor_change_disabled");
         if let Some(set_mouse_cursor_change_disabled) = self.0.set_mouse_cursor_change_disabled {
             unsafe {
                 set_mouse_cursor_change_disabled(self.0.as_ptr(), disabled as i32);
@@ -453,6 +478,7 @@ impl BrowserHost {
     }
     /// Returns true if mouse cursor change is disabled.
     pub fn is_mouse_cursor_change_disabled(&self) -> bool {
+        ffi_event!("BrowserHost::is_mouse_cursor_change_disabled");
         self.0
             .is_mouse_cursor_change_disabled
             .map(|is_mouse_cursor_change_disabled| unsafe {
@@ -463,6 +489,7 @@ impl BrowserHost {
     /// If a misspelled word is currently selected in an editable node calling this
     /// function will replace it with the specified `word`.
     pub fn replace_misspelling(&self, word: &str) {
+        ffi_event!("BrowserHost::replace_misspelling");
         if let Some(replace_misspelling) = self.0.replace_misspelling {
             unsafe {
                 replace_misspelling(self.0.as_ptr(), CefString::new(word).as_ptr());
@@ -471,6 +498,7 @@ impl BrowserHost {
     }
     /// Add the specified `word` to the spelling dictionary.
     pub fn add_word_to_dictionary(&self, word: &str) {
+        ffi_event!("BrowserHost::add_word_to_dictionary");
         if let Some(add_word_to_dictionary) = self.0.add_word_to_dictionary {
             unsafe {
                 add_word_to_dictionary(self.0.as_ptr(), CefString::new(word).as_ptr());
@@ -479,6 +507,7 @@ impl BrowserHost {
     }
     /// Returns true if window rendering is disabled.
     pub fn is_window_rendering_disabled(&self) -> bool {
+        ffi_event!("BrowserHost::is_window_rendering_disabled");
         self.0
             .is_window_rendering_disabled
             .map(|is_window_rendering_disabled| unsafe {
@@ -491,6 +520,7 @@ impl BrowserHost {
     /// [RenderHandler::on_paint] asynchronously with the updated regions. This
     /// function is only used when window rendering is disabled.
     pub fn was_resized(&self) {
+        ffi_event!("BrowserHost::was_resized");
         if let Some(was_resized) = self.0.was_resized {
             unsafe {
                 was_resized(self.0.as_ptr());
@@ -501,6 +531,7 @@ impl BrowserHost {
     /// [RenderHandler::on_paint] notification will stop when the browser is
     /// hidden. This function is only used when window rendering is disabled.
     pub fn was_hidden(&self, hidden: bool) {
+        ffi_event!("BrowserHost::was_hidden");
         if let Some(was_hidden) = self.0.was_hidden {
             unsafe {
                 was_hidden(self.0.as_ptr(), hidden as i32);
@@ -514,6 +545,7 @@ impl BrowserHost {
     /// current display. This function is only used when window rendering is
     /// disabled.
     pub fn notify_screen_info_changed(&self) {
+        ffi_event!("BrowserHost::notify_screen_info_changed");
         if let Some(notify_screen_info_changed) = self.0.notify_screen_info_changed {
             unsafe {
                 notify_screen_info_changed(self.0.as_ptr());
@@ -524,6 +556,7 @@ impl BrowserHost {
     /// asynchronously. This function is only used when window rendering is
     /// disabled.
     pub fn invalidate(&self, element_type: PaintElementType) {
+        ffi_event!("BrowserHost::invalidate");
         if let Some(invalidate) = self.0.invalidate {
             unsafe {
                 invalidate(self.0.as_ptr(), element_type as crate::CEnumType);
@@ -533,6 +566,7 @@ impl BrowserHost {
     /// Issue a BeginFrame request to Chromium.  Only valid when
     /// [WindowInfo::external_begin_frame_enabled] is set to true.
     pub fn send_external_begin_frame(&self) {
+        ffi_event!("BrowserHost::send_external_begin_frame");
         if let Some(send_external_begin_frame) = self.0.send_external_begin_frame {
             unsafe {
                 send_external_begin_frame(self.0.as_ptr());
@@ -541,6 +575,7 @@ impl BrowserHost {
     }
     /// Send a key event to the browser.
     pub fn send_key_event(&self, event: KeyEvent) {
+        ffi_event!("BrowserHost::send_key_event");
         if let Some(send_key_event) = self.0.send_key_event {
             unsafe {
                 send_key_event(self.0.as_ptr(), &event.as_cef());
@@ -556,6 +591,7 @@ impl BrowserHost {
         mouse_up: bool,
         click_count: i32,
     ) {
+        ffi_event!("BrowserHost::send_mouse_click_event");
         if let Some(send_mouse_click_event) = self.0.send_mouse_click_event {
             unsafe {
                 send_mouse_click_event(
@@ -571,6 +607,7 @@ impl BrowserHost {
     /// Send a mouse move event to the browser. The `x` and `y` coordinates are
     /// relative to the upper-left corner of the view.
     pub fn send_mouse_move_event(&self, event: &MouseEvent, mouse_leave: bool) {
+        ffi_event!("BrowserHost::send_mouse_move_event");
         if let Some(send_mouse_move_event) = self.0.send_mouse_move_event {
             unsafe {
                 send_mouse_move_event(self.0.as_ptr(), event.as_cef(), mouse_leave as i32);
@@ -583,6 +620,7 @@ impl BrowserHost {
     /// In order to scroll inside select popups with window rendering disabled
     /// [RenderHandler::get_screen_point] should be implemented properly.
     pub fn send_mouse_wheel_event(&self, event: &MouseEvent, delta_x: i32, delta_y: i32) {
+        ffi_event!("BrowserHost::send_mouse_wheel_event");
         if let Some(send_mouse_wheel_event) = self.0.send_mouse_wheel_event {
             unsafe {
                 send_mouse_wheel_event(self.0.as_ptr(), event.as_cef(), delta_x, delta_y);
@@ -591,6 +629,7 @@ impl BrowserHost {
     }
     /// Send a touch event to the browser for a windowless browser.
     pub fn send_touch_event(&self, event: &TouchEvent) {
+        ffi_event!("BrowserHost::send_touch_event");
         if let Some(send_touch_event) = self.0.send_touch_event {
             unsafe {
                 send_touch_event(self.0.as_ptr(), event.as_cef());
@@ -599,6 +638,7 @@ impl BrowserHost {
     }
     /// Send a focus event to the browser.
     pub fn send_focus_event(&self, set_focus: bool) {
+        ffi_event!("BrowserHost::send_focus_event");
         if let Some(send_focus_event) = self.0.send_focus_event {
             unsafe {
                 send_focus_event(self.0.as_ptr(), set_focus as i32);
@@ -607,6 +647,7 @@ impl BrowserHost {
     }
     /// Send a capture lost event to the browser.
     pub fn send_capture_lost_event(&self) {
+        ffi_event!("BrowserHost::send_capture_lost_event");
         if let Some(send_capture_lost_event) = self.0.send_capture_lost_event {
             unsafe {
                 send_capture_lost_event(self.0.as_ptr());
@@ -616,6 +657,7 @@ impl BrowserHost {
     /// Notify the browser that the window hosting it is about to be moved or
     /// resized. This function is only used on Windows and Linux.
     pub fn notify_move_or_resize_started(&self) {
+        ffi_event!("BrowserHost::notify_move_or_resize_started");
         if let Some(notify_move_or_resize_started) = self.0.notify_move_or_resize_started {
             unsafe {
                 notify_move_or_resize_started(self.0.as_ptr());
@@ -627,7 +669,8 @@ impl BrowserHost {
     /// actual fps may be lower if the browser cannot generate frames at the
     /// requested rate. The minimum value is 1 and the maximum value is 60 (default
     /// 30). This function can only be called on the UI thread.
-    pub fn get_windowless_frame_rate(&self) -> i32 {
+    pub fn get_windowless_frame_rate(&self, _ui_thread: UiThread) -> i32 {
+        ffi_event!("BrowserHost::get_windowless_frame_rate");
         self.0
             .get_windowless_frame_rate
             .map(|get_windowless_frame_rate| unsafe { get_windowless_frame_rate(self.0.as_ptr()) })
@@ -639,6 +682,7 @@ impl BrowserHost {
     /// minimum value is 1 and the maximum value is 60 (default 30). Can also be
     /// set at browser creation via [BrowserSettings::windowless_frame_rate].
     pub fn set_windowless_frame_rate(&self, frame_rate: i32) {
+        ffi_event!("BrowserHost::set_windowless_frame_rate");
         if let Some(set_windowless_frame_rate) = self.0.set_windowless_frame_rate {
             unsafe {
                 set_windowless_frame_rate(self.0.as_ptr(), frame_rate);
@@ -674,6 +718,7 @@ impl BrowserHost {
         replacement_range: &Range,
         selection_range: &Range,
     ) {
+        ffi_event!("BrowserHost::ime_set_composition");
         if let Some(ime_set_composition) = self.0.ime_set_composition {
             unsafe {
                 ime_set_composition(
@@ -701,6 +746,7 @@ impl BrowserHost {
         replacement_range: Option<&Range>,
         relative_cursor_pos: i32,
     ) {
+        ffi_event!("BrowserHost::ime_commit_text");
         if let Some(ime_commit_text) = self.0.ime_commit_text {
             let text = text.map(|text| CefString::new(text));
             unsafe {
@@ -718,6 +764,7 @@ impl BrowserHost {
     /// will be discarded. See comments on [BrowserHost::ime_set_composition] for usage. This
     /// function is only used when window rendering is disabled.
     pub fn ime_finish_composing_text(&self, keep_selection: bool) {
+        ffi_event!("BrowserHost::ime_finish_composing_text");
         if let Some(ime_finish_composing_text) = self.0.ime_finish_composing_text {
             unsafe {
                 ime_finish_composing_text(self.0.as_ptr(), keep_selection as i32);
@@ -728,6 +775,7 @@ impl BrowserHost {
     /// without applying them. See comments on ImeSetComposition for usage. This
     /// function is only used when window rendering is disabled.
     pub fn ime_cancel_composition(&self) {
+        ffi_event!("BrowserHost::ime_cancel_composition");
         if let Some(ime_cancel_composition) = self.0.ime_cancel_composition {
             unsafe {
                 ime_cancel_composition(self.0.as_ptr());
@@ -747,6 +795,7 @@ impl BrowserHost {
         event: &MouseEvent,
         allowed_ops: DragOperation,
     ) {
+        ffi_event!("BrowserHost::drag_target_drag_enter");
         if let Some(drag_target_drag_enter) = self.0.drag_target_drag_enter {
             unsafe {
                 drag_target_drag_enter(
@@ -763,6 +812,7 @@ impl BrowserHost {
     /// [BrowserHost::drag_target_drag_leave]/[BrowserHost::drag_target_drop]). This function is only used when window
     /// rendering is disabled.
     pub fn drag_target_drag_over(&self, event: &MouseEvent, allowed_ops: DragOperation) {
+        ffi_event!("BrowserHost::drag_target_drag_over");
         if let Some(drag_target_drag_over) = self.0.drag_target_drag_over {
             unsafe {
                 drag_target_drag_over(
@@ -777,6 +827,7 @@ impl BrowserHost {
     /// calling [BrowserHost::drag_target_drag_enter]). This function is only used when window
     /// rendering is disabled.
     pub fn drag_target_drag_leave(&self) {
+        ffi_event!("BrowserHost::drag_target_drag_leave");
         if let Some(drag_target_drag_leave) = self.0.drag_target_drag_leave {
             unsafe {
                 drag_target_drag_leave(self.0.as_ptr());
@@ -789,6 +840,7 @@ impl BrowserHost {
     /// [BrowserHost::drag_target_drag_enter] call. This function is only used when window rendering
     /// is disabled.
     pub fn drag_target_drop(&self, event: &MouseEvent) {
+        ffi_event!("BrowserHost::drag_target_drop");
         if let Some(drag_target_drop) = self.0.drag_target_drop {
             unsafe {
                 drag_target_drop(self.0.as_ptr(), event.as_cef());
@@ -803,6 +855,7 @@ impl BrowserHost {
     /// drag_source_* methods. This function is only used when window rendering is
     /// disabled.
     pub fn drag_source_ended_at(&self, x: i32, y: i32, op: DragOperation) {
+        ffi_event!("BrowserHost::drag_source_ended_at");
         if let Some(drag_source_ended_at) = self.0.drag_source_ended_at {
             unsafe {
                 drag_source_ended_at(self.0.as_ptr(), x, y, cef_drag_operations_mask_t(op.bits()));
@@ -816,6 +869,7 @@ impl BrowserHost {
     /// then all drag_target_* functions should be called before drag_source_* methods.
     /// This function is only used when window rendering is disabled.
     pub fn drag_source_system_drag_ended(&self) {
+        ffi_event!("BrowserHost::drag_source_system_drag_ended");
         if let Some(drag_source_system_drag_ended) = self.0.drag_source_system_drag_ended {
             unsafe {
                 drag_source_system_drag_ended(self.0.as_ptr());
@@ -824,7 +878,8 @@ impl BrowserHost {
     }
     /// Returns the current visible navigation entry for this browser. This
     /// function can only be called on the UI thread.
-    pub fn get_visible_navigation_entry(&self) -> NavigationEntry {
+    pub fn get_visible_navigation_entry(&self, _ui_thread: UiThread) -> NavigationEntry {
+        ffi_event!("BrowserHost::get_visible_navigation_entry");
         let get_visible_navigation_entry = self.0.get_visible_navigation_entry.unwrap();
         unsafe {
             NavigationEntry::from_ptr_unchecked(get_visible_navigation_entry(self.0.as_ptr()))
@@ -853,6 +908,7 @@ impl BrowserHost {
     /// objects are not created. The client may implement platform accessibility
     /// objects using [AccessibiltyHandler] callbacks if desired.
     pub fn set_accessibility_state(&self, accessibility_state: State) {
+        ffi_event!("BrowserHost::set_accessibility_state");
         if let Some(set_accessibility_state) = self.0.set_accessibility_state {
             unsafe {
                 set_accessibility_state(self.0.as_ptr(), accessibility_state as crate::CEnumType);
@@ -863,6 +919,7 @@ impl BrowserHost {
     /// [DisplayHandler::on_auto_resize]. Notifications are disabled by default.
     /// `min_size` and `max_size` define the range of allowed sizes.
     pub fn set_auto_resize_enabled(&self, enabled: bool, min_size: &Size, max_size: &Size) {
+        ffi_event!("BrowserHost::set_auto_resize_enabled");
         if let Some(set_auto_resize_enabled) = self.0.set_auto_resize_enabled {
             unsafe {
                 set_auto_resize_enabled(
@@ -877,6 +934,7 @@ impl BrowserHost {
     /// Returns the extension hosted in this browser or None if no extension is
     /// hosted. See [RequestContest::load_extension] for details.
     pub fn get_extension(&self) -> Option<Extension> {
+        ffi_event!("BrowserHost::get_extension");
         self.0.get_extension.and_then(|get_extension| unsafe {
             Extension::from_ptr(get_extension(self.0.as_ptr()))
         })
@@ -885,6 +943,7 @@ impl BrowserHost {
     /// Background hosts do not have a window and are not displayable. See
     /// [RequestContext::load_extension] for details.
     pub fn is_background_host(&self) -> bool {
+        ffi_event!("BrowserHost::is_background_host");
         self.0
             .is_background_host
             .map(|is_background_host| unsafe { is_background_host(self.0.as_ptr()) != 0 })
@@ -892,6 +951,7 @@ impl BrowserHost {
     }
     ///  Set whether the browser's audio is muted.
     pub fn set_audio_muted(&self, mute: bool) {
+        ffi_event!("BrowserHost::set_audio_muted");
         if let Some(set_audio_muted) = self.0.set_audio_muted {
             unsafe {
                 set_audio_muted(self.0.as_ptr(), mute as i32);
@@ -900,7 +960,8 @@ impl BrowserHost {
     }
     /// Returns true if the browser's audio is muted. This function can only
     /// be called on the UI thread.
-    pub fn is_audio_muted(&self) -> bool {
+    pub fn is_audio_muted(&self, _ui_thread: UiThread) -> bool {
+        ffi_event!("BrowserHost::is_audio_muted");
         self.0
             .is_audio_muted
             .map(|is_audio_muted| unsafe { is_audio_muted(self.0.as_ptr()) != 0 })
@@ -3,6 +3,7 @@ use cef_sys::{cef_browser_process_handler_t, cef_command_line_t, cef_list_value_
 use crate::{
     command_line::CommandLine,
     refcounted::{RefCountedPtr, Wrapper},
+    task::{IoThread, UiThread},
     values::ListValue,
     // print_handler::PrintHandler,
 };
@@ -21,7 +22,7 @@ impl BrowserProcessHandler {
 pub trait BrowserProcessHandlerCallbacks: 'static + Sync + Send {
     /// Called on the browser process UI thread immediately after the CEF context
     /// has been initialized.
-    fn on_context_initialized(&self) {}
+    fn on_context_initialized(&self, ui_thread: UiThread) {}
     /// Called before a child process is launched. Will be called on the browser
     /// process UI thread when launching a render process and on the browser
     /// process IO thread when launching a GPU or plugin process. Provides an
@@ -32,7 +33,7 @@ pub trait BrowserProcessHandlerCallbacks: 'static + Sync + Send {
     /// information that will be passed to
     /// [RenderProcessHandlerCallbacks::on_render_thread_created()] in the render
     /// process.
-    fn on_render_process_thread_created(&self, _extra_info: ListValue) {}
+    fn on_render_process_thread_created(&self, io_thread: IoThread, _extra_info: ListValue) {}
     /// Return the handler for printing on Linux. If a print handler is not
     /// provided then printing will not be supported on the Linux platform.
     #[cfg(target_os = "linux")]
@@ -88,7 +89,7 @@ impl BrowserProcessHandlerWrapper {
 cef_callback_impl! {
     impl for BrowserProcessHandlerWrapper: cef_browser_process_handler_t {
         fn context_initialized(&self) {
-            self.delegate.on_context_initialized();
+            self.delegate.on_context_initialized(unsafe { UiThread::new_unchecked() });
         }
         fn before_child_process_launch(
             &self,
@@ -100,7 +101,7 @@ cef_callback_impl! {
             &self,
             extra_info: ListValue: *mut cef_list_value_t,
         ) {
-            self.delegate.on_render_process_thread_created(extra_info);
+            self.delegate.on_render_process_thread_created(unsafe { IoThread::new_unchecked() }, extra_info);
         }
         #[cfg(target_os = "linux")]
         fn get_print_handler(
@@ -15,12 +15,14 @@ ref_counted_ptr! {
 impl Callback {
     /// Continue processing.
     pub fn cont(&self) {
+        ffi_event!("Callback::cont");
         unsafe {
             self.0.cont.unwrap()(self.as_ptr());
         }
     }
     /// Cancel processing.
     pub fn cancel(&self) {
+        ffi_event!("Callback::cancel");
         unsafe {
             self.0.cancel.unwrap()(self.as_ptr());
         }
@@ -32,6 +34,7 @@ impl CompletionCallback {
         unsafe{ CompletionCallback::from_ptr_unchecked(CompletionCallbackWrapper(Mutex::new(Some(Box::new(f)))).wrap().into_raw()) }
     }
     pub fn on_complete(&self) {
+        ffi_event!("CompletionCallback::on_complete");
         unsafe {
             self.0.on_complete.unwrap()(self.as_ptr());
         }
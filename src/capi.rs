@@ -0,0 +1,168 @@
+//! The global functions of the CEF C API.
+//!
+//! All calls into libcef go through this module. Usually these are just the
+//! functions of cef-sys, resolved by the dynamic linker. With the
+//! `dynamic-loading` feature on Linux they're wrappers that call through the
+//! function table of the library loaded by [crate::load_library] instead.
+
+#[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+use cef_sys::{
+    cef_app_t, cef_binary_value_t, cef_browser_settings_t, cef_browser_t, cef_cert_status_t,
+    cef_client_t, cef_command_line_t, cef_dictionary_value_t, cef_drag_data_t, cef_image_t,
+    cef_list_value_t, cef_main_args_t, cef_menu_model_delegate_t, cef_menu_model_t,
+    cef_post_data_element_t, cef_post_data_t, cef_process_message_t,
+    cef_request_context_handler_t, cef_request_context_settings_t, cef_request_context_t,
+    cef_request_t, cef_resource_bundle_t, cef_response_t, cef_scheme_handler_factory_t,
+    cef_settings_t, cef_string_list_t, cef_string_map_t, cef_string_multimap_t, cef_string_t,
+    cef_string_userfree_t, cef_task_runner_t, cef_task_t, cef_thread_id_t, cef_time_t,
+    cef_urlrequest_client_t, cef_urlrequest_t, cef_v8accessor_t,
+    cef_v8array_buffer_release_callback_t, cef_v8context_t, cef_v8handler_t,
+    cef_v8interceptor_t, cef_v8stack_trace_t, cef_v8value_t, cef_value_t, cef_window_info_t,
+};
+#[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+use std::os::raw::{c_char, c_int, c_void};
+
+macro_rules! functions {
+    ($(fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;)*) => {
+        #[cfg(not(all(target_os = "linux", feature = "dynamic-loading")))]
+        pub(crate) use cef_sys::{$($name),*};
+
+        /// Pointers to all functions of the C API, resolved from the loaded library.
+        #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+        pub(crate) struct Functions {
+            $($name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,)*
+        }
+
+        #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+        impl Functions {
+            /// Resolve all functions from the library `handle`. Returns the names
+            /// of the missing symbols if there are any.
+            pub(crate) unsafe fn resolve(handle: *mut c_void) -> Result<Functions, Vec<&'static str>> {
+                let mut missing = Vec::new();
+                $(
+                    let $name = libc::dlsym(handle, concat!(stringify!($name), "\0").as_ptr() as *const c_char);
+                    if $name.is_null() {
+                        missing.push(stringify!($name));
+                    }
+                )*
+                if !missing.is_empty() {
+                    return Err(missing);
+                }
+                Ok(Functions {
+                    $($name: std::mem::transmute::<*mut c_void, unsafe extern "C" fn($($ty),*) $(-> $ret)?>($name),)*
+                })
+            }
+        }
+
+        $(
+            #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+            #[inline]
+            pub(crate) unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                (crate::dynamic_library::functions().$name)($($arg),*)
+            }
+        )*
+    };
+}
+
+// Functions that only exist on some platforms, none of them on Linux.
+#[cfg(target_os = "windows")]
+pub(crate) use cef_sys::{cef_enable_highdpi_support, cef_set_osmodal_loop};
+#[cfg(all(target_os = "windows", feature = "sandbox"))]
+pub(crate) use cef_sys::{cef_sandbox_info_create, cef_sandbox_info_destroy};
+#[cfg(target_os = "macos")]
+pub(crate) use cef_sys::{cef_load_library, cef_unload_library};
+
+functions! {
+    fn cef_api_hash(entry: c_int) -> *const c_char;
+    fn cef_version_info(entry: c_int) -> c_int;
+
+    fn cef_execute_process(args: *const cef_main_args_t, application: *mut cef_app_t, windows_sandbox_info: *mut c_void) -> c_int;
+    fn cef_initialize(args: *const cef_main_args_t, settings: *const cef_settings_t, application: *mut cef_app_t, windows_sandbox_info: *mut c_void) -> c_int;
+    fn cef_shutdown();
+    fn cef_do_message_loop_work();
+    fn cef_run_message_loop();
+    fn cef_quit_message_loop();
+
+    fn cef_get_min_log_level() -> c_int;
+    fn cef_log(file: *const c_char, line: c_int, severity: c_int, message: *const c_char);
+
+    fn cef_currently_on(thread_id: cef_thread_id_t::Type) -> c_int;
+    fn cef_post_task(thread_id: cef_thread_id_t::Type, task: *mut cef_task_t) -> c_int;
+    fn cef_post_delayed_task(thread_id: cef_thread_id_t::Type, task: *mut cef_task_t, delay_ms: i64) -> c_int;
+    fn cef_task_runner_get_for_current_thread() -> *mut cef_task_runner_t;
+    fn cef_task_runner_get_for_thread(thread_id: cef_thread_id_t::Type) -> *mut cef_task_runner_t;
+
+    fn cef_browser_host_create_browser(window_info: *const cef_window_info_t, client: *mut cef_client_t, url: *const cef_string_t, settings: *const cef_browser_settings_t, extra_info: *mut cef_dictionary_value_t, request_context: *mut cef_request_context_t) -> c_int;
+    fn cef_browser_host_create_browser_sync(window_info: *const cef_window_info_t, client: *mut cef_client_t, url: *const cef_string_t, settings: *const cef_browser_settings_t, extra_info: *mut cef_dictionary_value_t, request_context: *mut cef_request_context_t) -> *mut cef_browser_t;
+    fn cef_request_context_get_global_context() -> *mut cef_request_context_t;
+    fn cef_request_context_create_context(settings: *const cef_request_context_settings_t, handler: *mut cef_request_context_handler_t) -> *mut cef_request_context_t;
+    fn cef_create_context_shared(other: *mut cef_request_context_t, handler: *mut cef_request_context_handler_t) -> *mut cef_request_context_t;
+    fn cef_register_scheme_handler_factory(scheme_name: *const cef_string_t, domain_name: *const cef_string_t, factory: *mut cef_scheme_handler_factory_t) -> c_int;
+    fn cef_clear_scheme_handler_factories() -> c_int;
+    fn cef_resource_bundle_get_global() -> *mut cef_resource_bundle_t;
+    fn cef_is_cert_status_error(status: cef_cert_status_t) -> c_int;
+
+    fn cef_command_line_create() -> *mut cef_command_line_t;
+    fn cef_command_line_get_global() -> *mut cef_command_line_t;
+    fn cef_process_message_create(name: *const cef_string_t) -> *mut cef_process_message_t;
+    fn cef_request_create() -> *mut cef_request_t;
+    fn cef_response_create() -> *mut cef_response_t;
+    fn cef_post_data_create() -> *mut cef_post_data_t;
+    fn cef_post_data_element_create() -> *mut cef_post_data_element_t;
+    fn cef_urlrequest_create(request: *mut cef_request_t, client: *mut cef_urlrequest_client_t, request_context: *mut cef_request_context_t) -> *mut cef_urlrequest_t;
+    fn cef_drag_data_create() -> *mut cef_drag_data_t;
+    fn cef_image_create() -> *mut cef_image_t;
+    fn cef_menu_model_create(delegate: *mut cef_menu_model_delegate_t) -> *mut cef_menu_model_t;
+
+    fn cef_value_create() -> *mut cef_value_t;
+    fn cef_binary_value_create(data: *const c_void, data_size: usize) -> *mut cef_binary_value_t;
+    fn cef_dictionary_value_create() -> *mut cef_dictionary_value_t;
+    fn cef_list_value_create() -> *mut cef_list_value_t;
+
+    fn cef_register_extension(extension_name: *const cef_string_t, javascript_code: *const cef_string_t, handler: *mut cef_v8handler_t) -> c_int;
+    fn cef_v8context_get_current_context() -> *mut cef_v8context_t;
+    fn cef_v8context_get_entered_context() -> *mut cef_v8context_t;
+    fn cef_v8context_in_context() -> c_int;
+    fn cef_v8stack_trace_get_current(frame_limit: c_int) -> *mut cef_v8stack_trace_t;
+    fn cef_v8value_create_undefined() -> *mut cef_v8value_t;
+    fn cef_v8value_create_null() -> *mut cef_v8value_t;
+    fn cef_v8value_create_bool(value: c_int) -> *mut cef_v8value_t;
+    fn cef_v8value_create_int(value: i32) -> *mut cef_v8value_t;
+    fn cef_v8value_create_uint(value: u32) -> *mut cef_v8value_t;
+    fn cef_v8value_create_double(value: f64) -> *mut cef_v8value_t;
+    fn cef_v8value_create_date(date: *const cef_time_t) -> *mut cef_v8value_t;
+    fn cef_v8value_create_string(value: *const cef_string_t) -> *mut cef_v8value_t;
+    fn cef_v8value_create_object(accessor: *mut cef_v8accessor_t, interceptor: *mut cef_v8interceptor_t) -> *mut cef_v8value_t;
+    fn cef_v8value_create_array(length: c_int) -> *mut cef_v8value_t;
+    fn cef_v8value_create_array_buffer(buffer: *mut c_void, length: usize, release_callback: *mut cef_v8array_buffer_release_callback_t) -> *mut cef_v8value_t;
+    fn cef_v8value_create_function(name: *const cef_string_t, handler: *mut cef_v8handler_t) -> *mut cef_v8value_t;
+
+    fn cef_time_to_doublet(cef_time: *const cef_time_t, time: *mut f64) -> c_int;
+    fn cef_time_from_doublet(time: f64, cef_time: *mut cef_time_t) -> c_int;
+
+    fn cef_string_utf8_to_utf16(src: *const c_char, src_len: usize, output: *mut cef_string_t) -> c_int;
+    fn cef_string_userfree_utf16_alloc() -> cef_string_userfree_t;
+    fn cef_string_userfree_utf16_free(string: cef_string_userfree_t);
+    fn cef_string_list_alloc() -> cef_string_list_t;
+    fn cef_string_list_size(list: cef_string_list_t) -> usize;
+    fn cef_string_list_value(list: cef_string_list_t, index: usize, value: *mut cef_string_t) -> c_int;
+    fn cef_string_list_append(list: cef_string_list_t, value: *const cef_string_t);
+    fn cef_string_list_free(list: cef_string_list_t);
+    fn cef_string_map_alloc() -> cef_string_map_t;
+    fn cef_string_map_size(map: cef_string_map_t) -> usize;
+    fn cef_string_map_find(map: cef_string_map_t, key: *const cef_string_t, value: *mut cef_string_t) -> c_int;
+    fn cef_string_map_key(map: cef_string_map_t, index: usize, key: *mut cef_string_t) -> c_int;
+    fn cef_string_map_value(map: cef_string_map_t, index: usize, value: *mut cef_string_t) -> c_int;
+    fn cef_string_map_append(map: cef_string_map_t, key: *const cef_string_t, value: *const cef_string_t) -> c_int;
+    fn cef_string_map_clear(map: cef_string_map_t);
+    fn cef_string_map_free(map: cef_string_map_t);
+    fn cef_string_multimap_alloc() -> cef_string_multimap_t;
+    fn cef_string_multimap_size(map: cef_string_multimap_t) -> usize;
+    fn cef_string_multimap_find_count(map: cef_string_multimap_t, key: *const cef_string_t) -> usize;
+    fn cef_string_multimap_enumerate(map: cef_string_multimap_t, key: *const cef_string_t, value_index: usize, value: *mut cef_string_t) -> c_int;
+    fn cef_string_multimap_key(map: cef_string_multimap_t, index: usize, key: *mut cef_string_t) -> c_int;
+    fn cef_string_multimap_value(map: cef_string_multimap_t, index: usize, value: *mut cef_string_t) -> c_int;
+    fn cef_string_multimap_append(map: cef_string_multimap_t, key: *const cef_string_t, value: *const cef_string_t) -> c_int;
+    fn cef_string_multimap_clear(map: cef_string_multimap_t);
+    fn cef_string_multimap_free(map: cef_string_multimap_t);
+}
@@ -218,6 +218,7 @@ impl ContextMenuHandler {
         params: ContextMenuParams,
         model: MenuModel
     ) {
+        ffi_event!("ContextMenuHandler::on_before_context_menu");
         unsafe {
             self.0.on_before_context_menu.unwrap()(
                 self.as_ptr(),
@@ -242,6 +243,7 @@ impl ContextMenuHandler {
         model: MenuModel,
         callback: RunContextMenu
     ) -> bool {
+        ffi_event!("ContextMenuHandler::run_context_menu");
         unsafe {
             self.0.run_context_menu.unwrap()(
                 self.as_ptr(),
@@ -268,6 +270,7 @@ impl ContextMenuHandler {
         command_id: CommandId,
         event_flags: EventFlags
     ) -> bool {
+        ffi_event!("ContextMenuHandler::on_context_menu_command");
         unsafe {
             self.0.on_context_menu_command.unwrap()(
                 self.as_ptr(),
@@ -286,6 +289,7 @@ impl ContextMenuHandler {
         browser: Browser,
         frame: Frame
     ) {
+        ffi_event!("ContextMenuHandler::on_context_menu_dismissed");
         unsafe {
             self.0.on_context_menu_dismissed.unwrap()(
                 self.as_ptr(),
@@ -17,6 +17,7 @@ impl ContextMenuParams {
     /// Returns the X coordinate of the mouse where the context menu was invoked.
     /// Coords are relative to the associated RenderView's origin.
     pub fn get_xcoord(&self) -> i32 {
+        ffi_event!("ContextMenuParams::get_xcoord");
         unsafe {
             self.0.get_xcoord.unwrap()(self.as_ptr())
         }
@@ -24,6 +25,7 @@ impl ContextMenuParams {
     /// Returns the Y coordinate of the mouse where the context menu was invoked.
     /// Coords are relative to the associated RenderView's origin.
     pub fn get_ycoord(&self) -> i32 {
+        ffi_event!("ContextMenuParams::get_ycoord");
         unsafe {
             self.0.get_ycoord.unwrap()(self.as_ptr())
         }
@@ -31,6 +33,7 @@ impl ContextMenuParams {
     /// Returns flags representing the type of node that the context menu was
     /// invoked on.
     pub fn get_type_flags(&self) -> ContextMenuTypeFlags {
+        ffi_event!("ContextMenuParams::get_type_flags");
         unsafe {
             ContextMenuTypeFlags::from_bits_truncate(self.0.get_type_flags.unwrap()(self.as_ptr()).0)
         }
@@ -38,6 +41,7 @@ impl ContextMenuParams {
     /// Returns the URL of the link, if any, that encloses the node that the
     /// context menu was invoked on.
     pub fn get_link_url(&self) -> String {
+        ffi_event!("ContextMenuParams::get_link_url");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_link_url.unwrap()(self.as_ptr())).into()
         }
@@ -45,6 +49,7 @@ impl ContextMenuParams {
     /// Returns the link URL, if any, to be used ONLY for "copy link address". We
     /// don't validate this field in the frontend process.
     pub fn get_unfiltered_link_url(&self) -> String {
+        ffi_event!("ContextMenuParams::get_unfiltered_link_url");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_unfiltered_link_url.unwrap()(self.as_ptr())).into()
         }
@@ -52,6 +57,7 @@ impl ContextMenuParams {
     /// Returns the source URL, if any, for the element that the context menu was
     /// invoked on. Example of elements with source URLs are img, audio, and video.
     pub fn get_source_url(&self) -> String {
+        ffi_event!("ContextMenuParams::get_source_url");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_source_url.unwrap()(self.as_ptr())).into()
         }
@@ -59,6 +65,7 @@ impl ContextMenuParams {
     /// Returns `true` if the context menu was invoked on an image which has non-
     /// NULL contents.
     pub fn has_image_contents(&self) -> bool {
+        ffi_event!("ContextMenuParams::has_image_contents");
         unsafe {
             self.0.has_image_contents.unwrap()(self.as_ptr()) != 0
         }
@@ -66,18 +73,21 @@ impl ContextMenuParams {
     /// Returns the title text or the alt text if the context menu was invoked on
     /// an image.
     pub fn get_title_text(&self) -> String {
+        ffi_event!("ContextMenuParams::get_title_text");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_title_text.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the URL of the top level page that the context menu was invoked on.
     pub fn get_page_url(&self) -> String {
+        ffi_event!("ContextMenuParams::get_page_url");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_page_url.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the URL of the subframe that the context menu was invoked on.
     pub fn get_frame_url(&self) -> String {
+        ffi_event!("ContextMenuParams::get_frame_url");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_frame_url.unwrap()(self.as_ptr())).into()
         }
@@ -85,12 +95,14 @@ impl ContextMenuParams {
     /// Returns the character encoding of the subframe that the context menu was
     /// invoked on.
     pub fn get_frame_charset(&self) -> String {
+        ffi_event!("ContextMenuParams::get_frame_charset");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_frame_charset.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the type of context node that the context menu was invoked on.
     pub fn get_media_type(&self) -> ContextMenuTypeFlags {
+        ffi_event!("ContextMenuParams::get_media_type");
         unsafe {
             ContextMenuTypeFlags::from_bits_truncate(self.0.get_media_type.unwrap()(self.as_ptr()))
         }
@@ -98,6 +110,7 @@ impl ContextMenuParams {
     /// Returns flags representing the actions supported by the media element, if
     /// any, that the context menu was invoked on.
     pub fn get_media_state_flags(&self) -> ContextMenuMediaStateFlags {
+        ffi_event!("ContextMenuParams::get_media_state_flags");
         unsafe {
             ContextMenuMediaStateFlags::from_bits_truncate(self.0.get_media_state_flags.unwrap()(self.as_ptr()).0)
         }
@@ -105,6 +118,7 @@ impl ContextMenuParams {
     /// Returns the text of the selection, if any, that the context menu was
     /// invoked on.
     pub fn get_selection_text(&self) -> String {
+        ffi_event!("ContextMenuParams::get_selection_text");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_selection_text.unwrap()(self.as_ptr())).into()
         }
@@ -112,6 +126,7 @@ impl ContextMenuParams {
     /// Returns the text of the misspelled word, if any, that the context menu was
     /// invoked on.
     pub fn get_misspelled_word(&self) -> String {
+        ffi_event!("ContextMenuParams::get_misspelled_word");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_misspelled_word.unwrap()(self.as_ptr())).into()
         }
@@ -120,6 +135,7 @@ impl ContextMenuParams {
     /// `suggestions` from the spell check service for the misspelled word if there
     /// is one.
     pub fn get_dictionary_suggestions(&self) -> Option<Vec<String>> {
+        ffi_event!("ContextMenuParams::get_dictionary_suggestions");
         unsafe {
             let mut string_list = CefStringList::new();
             let has_suggestions = self.0.get_dictionary_suggestions.unwrap()(self.as_ptr(), string_list.as_mut_ptr());
@@ -132,6 +148,7 @@ impl ContextMenuParams {
     }
     /// Returns `true` if the context menu was invoked on an editable node.
     pub fn is_editable(&self) -> bool {
+        ffi_event!("ContextMenuParams::is_editable");
         unsafe {
             self.0.is_editable.unwrap()(self.as_ptr()) != 0
         }
@@ -139,6 +156,7 @@ impl ContextMenuParams {
     /// Returns `true` if the context menu was invoked on an editable node where
     /// spell-check is enabled.
     pub fn is_spell_check_enabled(&self) -> bool {
+        ffi_event!("ContextMenuParams::is_spell_check_enabled");
         unsafe {
             self.0.is_spell_check_enabled.unwrap()(self.as_ptr()) != 0
         }
@@ -146,6 +164,7 @@ impl ContextMenuParams {
     /// Returns flags representing the actions supported by the editable node, if
     /// any, that the context menu was invoked on.
     pub fn get_edit_state_flags(&self) -> ContextMenuEditStateFlags {
+        ffi_event!("ContextMenuParams::get_edit_state_flags");
         unsafe {
             ContextMenuEditStateFlags::from_bits_truncate(self.0.get_edit_state_flags.unwrap()(self.as_ptr()).0)
         }
@@ -154,12 +173,14 @@ impl ContextMenuParams {
     /// renderer process (for example, plugin placeholder or pepper plugin menu
     /// items).
     pub fn is_custom_menu(&self) -> bool {
+        ffi_event!("ContextMenuParams::is_custom_menu");
         unsafe {
             self.0.is_custom_menu.unwrap()(self.as_ptr()) != 0
         }
     }
     /// Returns `true` if the context menu was invoked from a pepper plugin.
     pub fn is_pepper_menu(&self) -> bool {
+        ffi_event!("ContextMenuParams::is_pepper_menu");
         unsafe {
             self.0.is_pepper_menu.unwrap()(self.as_ptr()) != 0
         }
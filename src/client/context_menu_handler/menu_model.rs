@@ -6,13 +6,9 @@ use crate::{
     values::Point,
 };
 use cef_sys::{
-    _cef_menu_model_t,
-    _cef_menu_model_delegate_t,
-    cef_menu_model_create,
-    cef_event_flags_t,
-    cef_point_t,
-    cef_string_t,
+    _cef_menu_model_t, _cef_menu_model_delegate_t, cef_event_flags_t, cef_point_t, cef_string_t,
 };
+use crate::capi::cef_menu_model_create;
 
 use std::{
     convert::TryInto,
@@ -42,30 +38,35 @@ ref_counted_ptr!{
 
 impl MenuModel {
     pub fn new(delegate: MenuModelDelegate) -> MenuModel {
+        ffi_event!("MenuModel::new");
         unsafe {
             MenuModel::from_ptr_unchecked(cef_menu_model_create(delegate.into_raw()))
         }
     }
     /// Returns `true` if this menu is a submenu.
     pub fn is_sub_menu(&self) -> bool {
+        ffi_event!("MenuModel::is_sub_menu");
         unsafe {
             self.0.is_sub_menu.unwrap()(self.as_ptr()) != 0
         }
     }
     /// Clears the menu. Returns `true` on success.
     pub fn clear(&self) -> bool {
+        ffi_event!("MenuModel::clear");
         unsafe {
             self.0.clear.unwrap()(self.as_ptr()) != 0
         }
     }
     /// Returns the number of items in this menu.
     pub fn get_count(&self) -> usize {
+        ffi_event!("MenuModel::get_count");
         unsafe {
             c(self.0.clear.unwrap()(self.as_ptr()))
         }
     }
     /// Add a separator to the menu. Returns `true` on success.
     pub fn add_separator(&self) -> bool {
+        ffi_event!("MenuModel::add_separator");
         unsafe {
             self.0.add_separator.unwrap()(self.as_ptr()) != 0
         }
@@ -76,6 +77,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> bool {
+        ffi_event!("MenuModel::add_item");
         unsafe {
             self.0.add_item.unwrap()(
                 self.as_ptr(),
@@ -90,6 +92,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> bool {
+        ffi_event!("MenuModel::add_check_item");
         unsafe {
             self.0.add_check_item.unwrap()(
                 self.as_ptr(),
@@ -106,6 +109,7 @@ impl MenuModel {
         label: &str,
         group_id: GroupId,
     ) -> bool {
+        ffi_event!("MenuModel::add_radio_item");
         unsafe {
             self.0.add_radio_item.unwrap()(
                 self.as_ptr(),
@@ -121,6 +125,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> MenuModel {
+        ffi_event!("MenuModel::add_sub_menu");
         unsafe {
             MenuModel::from_ptr_unchecked(self.0.add_sub_menu.unwrap()(
                 self.as_ptr(),
@@ -135,6 +140,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::insert_separator_at");
         unsafe {
             self.0.insert_separator_at.unwrap()(
                 self.as_ptr(),
@@ -150,6 +156,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> bool {
+        ffi_event!("MenuModel::insert_item_at");
         unsafe {
             self.0.insert_item_at.unwrap()(
                 self.as_ptr(),
@@ -167,6 +174,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> bool {
+        ffi_event!("MenuModel::insert_check_item_at");
         unsafe {
             self.0.insert_check_item_at.unwrap()(
                 self.as_ptr(),
@@ -186,6 +194,7 @@ impl MenuModel {
         label: &str,
         group_id: GroupId,
     ) -> bool {
+        ffi_event!("MenuModel::insert_radio_item_at");
         unsafe {
             self.0.insert_radio_item_at.unwrap()(
                 self.as_ptr(),
@@ -204,6 +213,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> MenuModel {
+        ffi_event!("MenuModel::insert_sub_menu_at");
         unsafe {
             MenuModel::from_ptr_unchecked(self.0.insert_sub_menu_at.unwrap()(
                 self.as_ptr(),
@@ -219,6 +229,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::remove");
         unsafe {
             self.0.remove.unwrap()(
                 self.as_ptr(),
@@ -231,6 +242,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::remove_at");
         unsafe {
             self.0.remove_at.unwrap()(
                 self.as_ptr(),
@@ -244,6 +256,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> Option<usize> {
+        ffi_event!("MenuModel::get_index_of");
         unsafe {
             let index = self.0.get_index_of.unwrap()(
                 self.as_ptr(),
@@ -263,6 +276,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> Option<usize> {
+        ffi_event!("MenuModel::get_command_id_at");
         unsafe {
             let index = self.0.get_command_id_at.unwrap()(
                 self.as_ptr(),
@@ -281,6 +295,7 @@ impl MenuModel {
         index: usize,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::set_command_id_at");
         unsafe {
             self.0.set_command_id_at.unwrap()(
                 self.as_ptr(),
@@ -294,6 +309,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> String {
+        ffi_event!("MenuModel::get_label");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_label.unwrap()(self.as_ptr(), command_id.get())).into()
         }
@@ -304,6 +320,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> String {
+        ffi_event!("MenuModel::get_label_at");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_label_at.unwrap()(self.as_ptr(), c(index))).into()
         }
@@ -314,6 +331,7 @@ impl MenuModel {
         command_id: CommandId,
         label: &str,
     ) -> bool {
+        ffi_event!("MenuModel::set_label");
         unsafe {
             self.0.set_label.unwrap()(
                 self.as_ptr(),
@@ -328,6 +346,7 @@ impl MenuModel {
         index: usize,
         label: &str,
     ) -> bool {
+        ffi_event!("MenuModel::set_label_at");
         unsafe {
             self.0.set_label_at.unwrap()(
                 self.as_ptr(),
@@ -341,6 +360,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> MenuItemType {
+        ffi_event!("MenuModel::get_type");
         unsafe {
             MenuItemType::from_unchecked(self.0.get_type.unwrap()(
                 self.as_ptr(),
@@ -353,6 +373,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> MenuItemType {
+        ffi_event!("MenuModel::get_type_at");
         unsafe {
             MenuItemType::from_unchecked(self.0.get_type.unwrap()(
                 self.as_ptr(),
@@ -365,6 +386,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> Option<GroupId> {
+        ffi_event!("MenuModel::get_group_id");
         unsafe {
             GroupId::new(self.0.get_group_id.unwrap()(
                 self.as_ptr(),
@@ -377,6 +399,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> Option<GroupId> {
+        ffi_event!("MenuModel::get_group_id_at");
         unsafe {
             GroupId::new(self.0.get_group_id_at.unwrap()(
                 self.as_ptr(),
@@ -391,6 +414,7 @@ impl MenuModel {
         command_id: CommandId,
         group_id: GroupId,
     ) -> bool {
+        ffi_event!("MenuModel::set_group_id");
         unsafe {
             self.0.set_group_id.unwrap()(
                 self.as_ptr(),
@@ -405,6 +429,7 @@ impl MenuModel {
         index: usize,
         group_id: GroupId,
     ) -> bool {
+        ffi_event!("MenuModel::set_group_id_at");
         unsafe {
             self.0.set_group_id_at.unwrap()(
                 self.as_ptr(),
@@ -418,6 +443,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> MenuModel {
+        ffi_event!("MenuModel::get_sub_menu");
         unsafe {
             MenuModel::from_ptr_unchecked(self.0.get_sub_menu.unwrap()(
                 self.as_ptr(),
@@ -430,6 +456,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> MenuModel {
+        ffi_event!("MenuModel::get_sub_menu_at");
         unsafe {
             MenuModel::from_ptr_unchecked(self.0.get_sub_menu_at.unwrap()(
                 self.as_ptr(),
@@ -442,6 +469,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::is_visible");
         unsafe {
             self.0.is_visible.unwrap()(
                 self.as_ptr(),
@@ -454,6 +482,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::is_visible_at");
         unsafe {
             self.0.is_visible_at.unwrap()(
                 self.as_ptr(),
@@ -468,6 +497,7 @@ impl MenuModel {
         command_id: CommandId,
         visible: bool,
     ) -> bool {
+        ffi_event!("MenuModel::set_visible");
         unsafe {
             self.0.set_visible.unwrap()(
                 self.as_ptr(),
@@ -483,6 +513,7 @@ impl MenuModel {
         index: usize,
         visible: bool,
     ) -> bool {
+        ffi_event!("MenuModel::set_visible_at");
         unsafe {
             self.0.set_visible_at.unwrap()(
                 self.as_ptr(),
@@ -496,6 +527,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::is_enabled");
         unsafe {
             self.0.is_enabled.unwrap()(
                 self.as_ptr(),
@@ -508,6 +540,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::is_enabled_at");
         unsafe {
             self.0.is_enabled_at.unwrap()(
                 self.as_ptr(),
@@ -522,6 +555,7 @@ impl MenuModel {
         command_id: CommandId,
         enabled: bool,
     ) -> bool {
+        ffi_event!("MenuModel::set_enabled");
         unsafe {
             self.0.set_enabled.unwrap()(
                 self.as_ptr(),
@@ -537,6 +571,7 @@ impl MenuModel {
         index: usize,
         enabled: bool,
     ) -> bool {
+        ffi_event!("MenuModel::set_enabled_at");
         unsafe {
             self.0.set_enabled_at.unwrap()(
                 self.as_ptr(),
@@ -551,6 +586,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::is_checked");
         unsafe {
             self.0.is_checked.unwrap()(
                 self.as_ptr(),
@@ -564,6 +600,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::is_checked_at");
         unsafe {
             self.0.is_checked_at.unwrap()(
                 self.as_ptr(),
@@ -578,6 +615,7 @@ impl MenuModel {
         command_id: CommandId,
         checked: bool,
     ) -> bool {
+        ffi_event!("MenuModel::set_checked");
         unsafe {
             self.0.set_checked.unwrap()(
                 self.as_ptr(),
@@ -593,6 +631,7 @@ impl MenuModel {
         index: usize,
         checked: bool,
     ) -> bool {
+        ffi_event!("MenuModel::set_checked_at");
         unsafe {
             self.0.set_checked_at.unwrap()(
                 self.as_ptr(),
@@ -607,6 +646,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::has_accelerator");
         unsafe {
             self.0.has_accelerator.unwrap()(
                 self.as_ptr(),
@@ -620,6 +660,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::has_accelerator_at");
         unsafe {
             self.0.has_accelerator_at.unwrap()(
                 self.as_ptr(),
@@ -634,6 +675,7 @@ impl MenuModel {
         command_id: CommandId,
         accelerator: Accelerator,
     ) -> bool {
+        ffi_event!("MenuModel::set_accelerator");
         unsafe {
             let (key, shift_pressed, ctrl_pressed, alt_pressed) = accelerator.to_raw();
             self.0.set_accelerator.unwrap()(
@@ -653,6 +695,7 @@ impl MenuModel {
         index: i32,
         accelerator: Accelerator,
     ) -> bool {
+        ffi_event!("MenuModel::set_accelerator_at");
         unsafe {
             let (key, shift_pressed, ctrl_pressed, alt_pressed) = accelerator.to_raw();
             self.0.set_accelerator_at.unwrap()(
@@ -671,6 +714,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> bool {
+        ffi_event!("MenuModel::remove_accelerator");
         unsafe {
             self.0.remove_accelerator.unwrap()(
                 self.as_ptr(),
@@ -684,6 +728,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> bool {
+        ffi_event!("MenuModel::remove_accelerator_at");
         unsafe {
             self.0.remove_accelerator_at.unwrap()(
                 self.as_ptr(),
@@ -697,6 +742,7 @@ impl MenuModel {
         &self,
         command_id: CommandId,
     ) -> Option<Accelerator> {
+        ffi_event!("MenuModel::get_accelerator");
         unsafe {
             let (mut key, mut shift_pressed, mut ctrl_pressed, mut alt_pressed) = (0, 0, 0, 0);
             let has_accelerator = self.0.get_accelerator.unwrap()(
@@ -720,6 +766,7 @@ impl MenuModel {
         &self,
         index: usize,
     ) -> Option<Accelerator> {
+        ffi_event!("MenuModel::get_accelerator_at");
         unsafe {
             let (mut key, mut shift_pressed, mut ctrl_pressed, mut alt_pressed) = (0, 0, 0, 0);
             let has_accelerator = self.0.get_accelerator_at.unwrap()(
@@ -747,6 +794,7 @@ impl MenuModel {
         color_type: MenuColorType,
         color: Color,
     ) -> bool {
+        ffi_event!("MenuModel::set_color");
         unsafe {
             self.0.set_color.unwrap()(
                 self.as_ptr(),
@@ -767,6 +815,7 @@ impl MenuModel {
         color_type: MenuColorType,
         color: Color,
     ) -> bool {
+        ffi_event!("MenuModel::set_color_at");
         unsafe {
             self.0.set_color_at.unwrap()(
                 self.as_ptr(),
@@ -784,6 +833,7 @@ impl MenuModel {
         command_id: CommandId,
         color_type: MenuColorType,
     ) -> Option<Color> {
+        ffi_event!("MenuModel::get_color");
         unsafe {
             let mut color = Color::wrap(0);
             let has_color = self.0.get_color.unwrap()(
@@ -808,6 +858,7 @@ impl MenuModel {
         index: usize,
         color_type: MenuColorType,
     ) -> Option<Color> {
+        ffi_event!("MenuModel::get_color_at");
         unsafe {
             let mut color = Color::wrap(0);
             let has_color = self.0.get_color_at.unwrap()(
@@ -838,6 +889,7 @@ impl MenuModel {
         command_id: CommandId,
         font_list: &str,
     ) -> bool {
+        ffi_event!("MenuModel::set_font_list");
         unsafe {
             self.0.set_font_list.unwrap()(
                 self.as_ptr(),
@@ -862,6 +914,7 @@ impl MenuModel {
         index: usize,
         font_list: &str,
     ) -> bool {
+        ffi_event!("MenuModel::set_font_list_at");
         unsafe {
             self.0.set_font_list_at.unwrap()(
                 self.as_ptr(),
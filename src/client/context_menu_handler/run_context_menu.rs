@@ -24,6 +24,7 @@ impl RunContextMenu {
     /// Complete context menu display by selecting the specified `command_id` and
     /// `event_flags`.
     pub fn cont(&self, command_id: CommandId, event_flags: EventFlags) {
+        ffi_event!("RunContextMenu::cont");
         unsafe {
             self.0.cont.unwrap()(
                 self.as_ptr(),
@@ -34,6 +35,7 @@ impl RunContextMenu {
     }
     /// Cancel context menu display.
     pub fn cancel(&self) {
+        ffi_event!("RunContextMenu::cancel");
         unsafe {
             self.0.cancel.unwrap()(
                 self.as_ptr(),
@@ -45,14 +45,17 @@ impl DownloadHandler {
 impl DownloadItemCallback {
     /// Call to cancel the download.
     pub fn cancel(&self) {
+        ffi_event!("DownloadItemCallback::cancel");
         unsafe{ self.0.cancel.unwrap()(self.as_ptr()) }
     }
     /// Call to pause the download.
     pub fn pause(&self) {
+        ffi_event!("DownloadItemCallback::pause");
         unsafe{ self.0.pause.unwrap()(self.as_ptr()) }
     }
     /// Call to resume the download.
     pub fn resume(&self) {
+        ffi_event!("DownloadItemCallback::resume");
         unsafe{ self.0.resume.unwrap()(self.as_ptr()) }
     }
 }
@@ -67,6 +70,7 @@ impl BeforeDownloadCallback {
         download_path: &str,
         show_dialog: bool,
     ) {
+        ffi_event!("BeforeDownloadCallback::cont");
         unsafe {
             self.0.cont.unwrap()(
                 self.as_ptr(),
@@ -81,27 +85,33 @@ impl DownloadItem {
     /// Returns `true` if this object is valid. Do not call any other functions
     /// if this function returns false (0).
     pub fn is_valid(&self) -> bool {
+        ffi_event!("DownloadItem::is_valid");
         unsafe{ self.0.is_valid.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns `true` if the download is in progress.
     pub fn is_in_progress(&self) -> bool {
+        ffi_event!("DownloadItem::is_in_progress");
         unsafe{ self.0.is_in_progress.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns `true` if the download is complete.
     pub fn is_complete(&self) -> bool {
+        ffi_event!("DownloadItem::is_complete");
         unsafe{ self.0.is_complete.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns `true` if the download has been canceled or interrupted.
     pub fn is_canceled(&self) -> bool {
+        ffi_event!("DownloadItem::is_canceled");
         unsafe{ self.0.is_canceled.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns a simple speed estimate in bytes/s.
     pub fn get_current_speed(&self) -> u64 {
+        ffi_event!("DownloadItem::get_current_speed");
         unsafe{ self.0.get_current_speed.unwrap()(self.as_ptr()) as u64 }
     }
     /// Returns the rough percent complete or `None` if the receive total size is
     /// unknown.
     pub fn get_percent_complete(&self) -> Option<u8> {
+        ffi_event!("DownloadItem::get_percent_complete");
         let percent = unsafe{ self.0.get_percent_complete.unwrap()(self.as_ptr()) };
         match percent {
             -1 => None,
@@ -110,46 +120,57 @@ impl DownloadItem {
     }
     /// Returns the total number of bytes.
     pub fn get_total_bytes(&self) -> u64 {
+        ffi_event!("DownloadItem::get_total_bytes");
         unsafe{ self.0.get_total_bytes.unwrap()(self.as_ptr()) as u64 }
     }
     /// Returns the number of received bytes.
     pub fn get_received_bytes(&self) -> u64 {
+        ffi_event!("DownloadItem::get_received_bytes");
         unsafe{ self.0.get_received_bytes.unwrap()(self.as_ptr()) as u64 }
     }
     /// Returns the time that the download started.
     pub fn get_start_time(&self) -> DateTime<Utc> {
+        ffi_event!("DownloadItem::get_start_time");
         crate::values::cef_time_to_date_time(unsafe{ self.0.get_start_time.unwrap()(self.as_ptr()) })
     }
     /// Returns the time that the download ended.
     pub fn get_end_time(&self) -> DateTime<Utc> {
+        ffi_event!("DownloadItem::get_end_time");
         crate::values::cef_time_to_date_time(unsafe{ self.0.get_end_time.unwrap()(self.as_ptr()) })
     }
     /// Returns the full path to the downloaded or downloading file.
     pub fn get_full_path(&self) -> String {
+        ffi_event!("DownloadItem::get_full_path");
         String::from(unsafe{ CefString::from_userfree_unchecked(self.0.get_full_path.unwrap()(self.as_ptr())) })
     }
     /// Returns the unique identifier for this download.
     pub fn get_id(&self) -> u32 {
+        ffi_event!("DownloadItem::get_id");
         unsafe{ self.0.get_id.unwrap()(self.as_ptr()) }
     }
     /// Returns the URL.
     pub fn get_url(&self) -> String {
+        ffi_event!("DownloadItem::get_url");
         String::from(unsafe{ CefString::from_userfree_unchecked(self.0.get_url.unwrap()(self.as_ptr())) })
     }
     /// Returns the original URL before any redirections.
     pub fn get_original_url(&self) -> String {
+        ffi_event!("DownloadItem::get_original_url");
         String::from(unsafe{ CefString::from_userfree_unchecked(self.0.get_original_url.unwrap()(self.as_ptr())) })
     }
     /// Returns the suggested file name.
     pub fn get_suggested_file_name(&self) -> String {
+        ffi_event!("DownloadItem::get_suggested_file_name");
         String::from(unsafe{ CefString::from_userfree_unchecked(self.0.get_suggested_file_name.unwrap()(self.as_ptr())) })
     }
     /// Returns the content disposition.
     pub fn get_content_disposition(&self) -> String {
+        ffi_event!("DownloadItem::get_content_disposition");
         String::from(unsafe{ CefString::from_userfree_unchecked(self.0.get_content_disposition.unwrap()(self.as_ptr())) })
     }
     /// Returns the mime type.
     pub fn get_mime_type(&self) -> String {
+        ffi_event!("DownloadItem::get_mime_type");
         String::from(unsafe{ CefString::from_userfree_unchecked(self.0.get_mime_type.unwrap()(self.as_ptr())) })
     }
 }
@@ -7,6 +7,7 @@ use crate::{
     values::DictionaryValue,
     window::WindowInfo,
     string::CefString,
+    task::UiThread,
 };
 use cef_sys::{
     cef_browser_t,
@@ -31,6 +32,8 @@ impl LifeSpanHandler {
     }
 }
 
+/// Trait used to implement browser life span callbacks. The functions of this
+/// trait will be called on the UI thread unless otherwise indicated.
 pub trait LifeSpanHandlerCallbacks: 'static + Send + Sync {
     /// Called on the UI thread before a new popup browser is created.
     /// # Parameters
@@ -55,6 +58,7 @@ pub trait LifeSpanHandlerCallbacks: 'static + Send + Sync {
     /// cef_render_process_handler_t::on_browser_created() in the render process.
     fn on_before_popup(
         &self,
+        ui_thread: UiThread,
         browser: Browser,
         frame: Frame,
         target_url: Option<&str>,
@@ -73,7 +77,7 @@ pub trait LifeSpanHandlerCallbacks: 'static + Send + Sync {
     }
     /// Called after a new browser is created. This callback will be the first
     /// notification that references `browser`.
-    fn on_after_created(&self, browser: Browser) {
+    fn on_after_created(&self, ui_thread: UiThread, browser: Browser) {
 
     }
 
@@ -164,7 +168,7 @@ pub trait LifeSpanHandlerCallbacks: 'static + Send + Sync {
     /// 11. Application exits by calling cef_quit_message_loop() if no other
     /// browsers
     ///     exist.
-    fn do_close(&self, browser: Browser) -> bool {
+    fn do_close(&self, ui_thread: UiThread, browser: Browser) -> bool {
         false
     }
     /// Called just before a browser is destroyed. Release all references to the
@@ -176,7 +180,7 @@ pub trait LifeSpanHandlerCallbacks: 'static + Send + Sync {
     /// cef_resource_request_handler_t callbacks related to those requests may
     /// still arrive on the IO thread after this function is called. See do_close()
     /// documentation for additional usage information.
-    fn on_before_close(&self, browser: Browser) {}
+    fn on_before_close(&self, ui_thread: UiThread, browser: Browser) {}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -260,6 +264,7 @@ cef_callback_impl!{
             let mut settings_rust = unsafe{ BrowserSettings::from_raw(&*settings) };
             let mut no_javascript_access_rust = *no_javascript_access != 0;
             let ret = self.0.on_before_popup(
+                unsafe { UiThread::new_unchecked() },
                 browser,
                 frame,
                 target_url
@@ -285,13 +290,13 @@ cef_callback_impl!{
             ret
         }
         fn on_after_created(&self, browser: Browser: *mut cef_browser_t) {
-            self.0.on_after_created(browser);
+            self.0.on_after_created(unsafe { UiThread::new_unchecked() }, browser);
         }
         fn do_close(&self, browser: Browser: *mut cef_browser_t) -> std::os::raw::c_int {
-            self.0.do_close(browser) as _
+            self.0.do_close(unsafe { UiThread::new_unchecked() }, browser) as _
         }
         fn on_before_close(&self, browser: Browser: *mut cef_browser_t) {
-            self.0.on_before_close(browser.clone());
+            self.0.on_before_close(unsafe { UiThread::new_unchecked() }, browser.clone());
             unsafe{ browser.poison(); }
         }
     }
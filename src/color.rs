@@ -39,6 +39,25 @@ impl Color {
     }
 }
 
+impl std::str::FromStr for Color {
+    type Err = String;
+    /// Parses `#RRGGBB` or `#RRGGBBAA`, the latter being the same format as the
+    /// [Debug] output. A missing alpha component is treated as fully opaque.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').ok_or_else(|| format!("color `{}` does not start with `#`", s))?;
+        // from_str_radix also accepts a leading `+`.
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("invalid color `{}`", s));
+        }
+        let value = u32::from_str_radix(hex, 16).map_err(|_| format!("invalid color `{}`", s))?;
+        match hex.len() {
+            6 => Ok(Self(0xFF00_0000 | value)),
+            8 => Ok(Self((value << 24) | (value >> 8))),
+            _ => Err(format!("invalid color `{}`", s)),
+        }
+    }
+}
+
 impl std::fmt::Debug for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         write!(f, "#{:02X}{:02X}{:02X}{:02X}", self.r(), self.g(), self.b(), self.a())
@@ -1,13 +1,16 @@
-use cef_sys::{
-    cef_command_line_create, cef_command_line_get_global, cef_command_line_t, cef_string_map_alloc,
-    cef_string_map_free, cef_string_map_key, cef_string_map_size, cef_string_map_value,
-    cef_string_userfree_utf16_free,
+use cef_sys::cef_command_line_t;
+use crate::capi::{
+    cef_command_line_create, cef_command_line_get_global, cef_string_map_alloc, cef_string_map_free,
+    cef_string_map_key, cef_string_map_size, cef_string_map_value, cef_string_userfree_utf16_free,
 };
 use std::{collections::HashMap, ptr::null_mut};
 #[cfg(not(target_os = "windows"))]
 use std::{ffi::CString, os::raw::c_char};
 
-use crate::string::{CefString, CefStringList};
+use crate::{
+    error::{check_writable, Error},
+    string::{CefString, CefStringList},
+};
 
 ref_counted_ptr! {
     /// Structure used to create and/or parse command line arguments. Arguments with
@@ -25,10 +28,12 @@ impl CommandLine {
     /// Returns the singleton global CommandLine object. The returned object
     /// will be read-only.
     pub fn get_global() -> Option<Self> {
+        ffi_event!("CommandLine::get_global");
         unsafe { CommandLine::from_ptr(cef_command_line_get_global()) }
     }
     /// Create a new CommandLine instance.
     pub fn new() -> Self {
+        ffi_event!("CommandLine::new");
         unsafe { CommandLine::from_ptr_unchecked(cef_command_line_create()) }
     }
 
@@ -36,6 +41,7 @@ impl CommandLine {
     /// if this function returns false.
     /// TODO: IF `is_valid` IS FALSE, DOES CALLING OTHER FUNCTIONS VIOLATE SAFETY --OSSPIAL
     pub fn is_valid(&self) -> bool {
+        ffi_event!("CommandLine::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) } != 0)
@@ -44,6 +50,7 @@ impl CommandLine {
     /// Returns true if the values of this object are read-only. Some APIs may
     /// expose read-only objects.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("CommandLine::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.as_ptr()) } != 0)
@@ -54,6 +61,7 @@ impl CommandLine {
     /// supported on non-Windows platforms.
     #[cfg(not(target_os = "windows"))]
     pub fn new_from_argv(argv: &[&str]) -> Self {
+        ffi_event!("CommandLine::new_from_argv");
         let instance = unsafe { cef_command_line_create() };
         let argv: Vec<*const c_char> = argv
             .iter()
@@ -68,6 +76,7 @@ impl CommandLine {
     /// Initialize the command line with the string returned by calling
     /// GetCommandLineW(). This function is only supported on Windows.
     pub fn new_from_string(command_line: &str) -> Self {
+        ffi_event!("CommandLine::new_from_string");
         let instance = CommandLine::new();
         unsafe {
             (instance.0.init_from_string.unwrap())(
@@ -79,14 +88,18 @@ impl CommandLine {
     }
     /// Reset the command-line switches and arguments but leave the program
     /// component unchanged.
-    pub fn reset(&self) {
+    pub fn reset(&self) -> Result<(), Error> {
+        ffi_event!("CommandLine::reset");
+        check_writable(self.is_valid(), || self.is_read_only())?;
         unsafe {
             (self.0.reset.unwrap())(self.as_ptr());
         }
+        Ok(())
     }
     /// Retrieve the original command line string as a vector of strings. The argv
     /// array: `{ program, [(--|-|/)switch[=value]]*, [--], [argument]* }`
     pub fn get_argv(&self) -> Vec<String> {
+        ffi_event!("CommandLine::get_argv");
         println!("{:?}", self.0.as_ptr());
         println!("{:?}", self.0.get_argv);
         let mut list = CefStringList::new();
@@ -98,6 +111,7 @@ impl CommandLine {
     /// Constructs and returns the represented command line string. Use this
     /// function cautiously because quoting behavior is unclear.
     pub fn get_command_line_string(&self) -> String {
+        ffi_event!("CommandLine::get_command_line_string");
         let command_line = unsafe { (self.0.get_command_line_string.unwrap())(self.as_ptr()) };
 
         let command_line_str = String::from_utf16_lossy(unsafe {
@@ -111,6 +125,7 @@ impl CommandLine {
     }
     /// Get the program part of the command line string (the first item).
     pub fn get_program(&self) -> String {
+        ffi_event!("CommandLine::get_program");
         let program = unsafe { (self.0.get_program.unwrap())(self.as_ptr()) };
         let program_str = String::from_utf16_lossy(unsafe {
             std::slice::from_raw_parts((*program).str, (*program).length)
@@ -122,23 +137,29 @@ impl CommandLine {
         program_str
     }
     /// Set the program part of the command line string (the first item).
-    pub fn set_program(&self, program: &str) {
+    pub fn set_program(&self, program: &str) -> Result<(), Error> {
+        ffi_event!("CommandLine::set_program");
+        check_writable(self.is_valid(), || self.is_read_only())?;
         let program = CefString::new(program);
         unsafe {
             (self.0.set_program.unwrap())(self.as_ptr(), program.as_ptr());
         }
+        Ok(())
     }
     /// Returns true if the command line has switches.
     pub fn has_switches(&self) -> bool {
+        ffi_event!("CommandLine::has_switches");
         unsafe { (self.0.has_switches.unwrap())(self.as_ptr()) != 0 }
     }
     /// Returns true if the command line contains the given switch.
     pub fn has_switch(&self, name: &str) -> bool {
+        ffi_event!("CommandLine::has_switch");
         unsafe { (self.0.has_switch.unwrap())(self.as_ptr(), CefString::new(name).as_ptr()) != 0 }
     }
     /// Returns the value associated with the given switch. If the switch has no
     /// value or isn't present this function returns None.
     pub fn get_switch_value(&self, name: &str) -> Option<String> {
+        ffi_event!("CommandLine::get_switch_value");
         let value = unsafe {
             (self.0.get_switch_value.unwrap())(self.as_ptr(), CefString::new(name).as_ptr())
         };
@@ -157,6 +178,7 @@ impl CommandLine {
     /// Returns the map of switch names and values. If a switch has no value
     /// None is returned.
     pub fn get_switches(&self) -> HashMap<String, Option<String>> {
+        ffi_event!("CommandLine::get_switches");
         let switches = unsafe { cef_string_map_alloc() };
         unsafe {
             (self.0.get_switches.unwrap())(self.as_ptr(), switches);
@@ -182,13 +204,18 @@ impl CommandLine {
         result
     }
     /// Add a switch to the end of the command line.
-    pub fn append_switch(&self, name: &str) {
+    pub fn append_switch(&self, name: &str) -> Result<(), Error> {
+        ffi_event!("CommandLine::append_switch");
+        check_writable(self.is_valid(), || self.is_read_only())?;
         unsafe {
             (self.0.append_switch.unwrap())(self.as_ptr(), CefString::new(name).as_ptr());
         }
+        Ok(())
     }
     /// Add a switch with the specified value to the end of the command line.
-    pub fn append_switch_with_value(&self, name: &str, value: &str) {
+    pub fn append_switch_with_value(&self, name: &str, value: &str) -> Result<(), Error> {
+        ffi_event!("CommandLine::append_switch_with_value");
+        check_writable(self.is_valid(), || self.is_read_only())?;
         unsafe {
             (self.0.append_switch_with_value.unwrap())(
                 self.as_ptr(),
@@ -196,13 +223,16 @@ impl CommandLine {
                 CefString::new(value).as_ptr(),
             );
         }
+        Ok(())
     }
     /// True if there are remaining command line arguments.
     pub fn has_arguments(&self) -> bool {
+        ffi_event!("CommandLine::has_arguments");
         unsafe { (self.0.has_arguments.unwrap())(self.as_ptr()) != 0 }
     }
     /// Get the remaining command line arguments.
     pub fn get_arguments(&self) -> Vec<String> {
+        ffi_event!("CommandLine::get_arguments");
         let mut list = CefStringList::new();
         unsafe {
             (self.0.get_arguments.unwrap())(self.as_ptr(), list.as_mut_ptr());
@@ -210,17 +240,23 @@ impl CommandLine {
         list.into()
     }
     /// Add an argument to the end of the command line.
-    pub fn append_argument(&self, argument: &str) {
+    pub fn append_argument(&self, argument: &str) -> Result<(), Error> {
+        ffi_event!("CommandLine::append_argument");
+        check_writable(self.is_valid(), || self.is_read_only())?;
         unsafe {
             (self.0.append_argument.unwrap())(self.as_ptr(), CefString::new(argument).as_ptr());
         }
+        Ok(())
     }
     /// Insert a command before the current command. Common for debuggers, like
     /// "valgrind" or "`gdb --args`".
-    pub fn prepend_wrapper(&self, wrapper: &str) {
+    pub fn prepend_wrapper(&self, wrapper: &str) -> Result<(), Error> {
+        ffi_event!("CommandLine::prepend_wrapper");
+        check_writable(self.is_valid(), || self.is_read_only())?;
         unsafe {
             (self.0.prepend_wrapper.unwrap())(self.as_ptr(), CefString::new(wrapper).as_ptr());
         }
+        Ok(())
     }
 }
 
@@ -0,0 +1,553 @@
+//! Loading of [Settings] and [BrowserSettings] from configuration files and the
+//! environment.
+//!
+//! Values are merged in layers: the defaults passed to the builder come first,
+//! then every configuration file in the order it was added, and finally
+//! environment variables. Every layer only overrides the values it actually
+//! specifies. Example usage:
+//!
+//! ```no_run
+//!  let config = cef::config::Config::builder()
+//!      .defaults(cef::settings::Settings::new().windowless_rendering_enabled(true))
+//!      .optional_file("/etc/myapp/cef.toml")
+//!      .env_prefix("MYAPP_CEF")
+//!      .build()
+//!      .unwrap();
+//!
+//!  let context = cef::Context::initialize(&config.settings, None, None).unwrap();
+//! ```
+//!
+//! Files are parsed as TOML or JSON depending on their extension. Both formats
+//! use a `settings` and a `browser` table, with keys named like the fields of
+//! [Settings] and [BrowserSettings]:
+//!
+//! ```toml
+//! [settings]
+//! remote_debugging_port = 9222
+//! log_severity = "verbose"
+//! log_file = "/tmp/cef.log"
+//!
+//! [browser]
+//! windowless_frame_rate = 30
+//! webgl = "disabled"
+//! ```
+//!
+//! Environment variables are named after the field in upper case, prefixed with
+//! the configured prefix (`CEF` by default). [BrowserSettings] fields
+//! additionally carry a `BROWSER_` prefix, so the example above corresponds to
+//! `CEF_REMOTE_DEBUGGING_PORT=9222` and `CEF_BROWSER_WEBGL=disabled`.
+
+use crate::{
+    browser::{BrowserSettings, State},
+    color::Color,
+    settings::{LogSeverity, Settings},
+};
+use serde::{Deserialize, Deserializer};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use uuid::Uuid;
+
+/// Errors that can occur while loading the configuration.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A configuration file could not be read.
+    Io(PathBuf, std::io::Error),
+    /// A configuration file does not have a `.toml` or `.json` extension.
+    UnknownFormat(PathBuf),
+    /// A TOML configuration file could not be parsed.
+    Toml(PathBuf, toml::de::Error),
+    /// A JSON configuration file could not be parsed.
+    Json(PathBuf, serde_json::Error),
+    /// An environment variable contains a value that cannot be parsed.
+    Env {
+        name: String,
+        value: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::UnknownFormat(path) => {
+                write!(f, "{}: unknown configuration format", path.display())
+            }
+            ConfigError::Toml(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Json(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Env {
+                name,
+                value,
+                message,
+            } => write!(f, "{}={}: {}", name, value, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(_, err) => Some(err),
+            ConfigError::Toml(_, err) => Some(err),
+            ConfigError::Json(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// The result of merging all configuration layers.
+pub struct Config {
+    pub settings: Settings,
+    pub browser_settings: BrowserSettings,
+}
+
+impl Config {
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+}
+
+pub struct ConfigBuilder {
+    settings: Settings,
+    browser_settings: BrowserSettings,
+    files: Vec<(PathBuf, bool)>,
+    env_prefix: Option<String>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> ConfigBuilder {
+        ConfigBuilder {
+            settings: Settings::new(),
+            browser_settings: BrowserSettings::new(),
+            files: Vec::new(),
+            env_prefix: Some("CEF".to_owned()),
+        }
+    }
+    /// Set the [Settings] that form the lowest layer.
+    pub fn defaults(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+    /// Set the [BrowserSettings] that form the lowest layer.
+    pub fn browser_defaults(mut self, browser_settings: BrowserSettings) -> Self {
+        self.browser_settings = browser_settings;
+        self
+    }
+    /// Add a configuration file. Loading fails if it doesn't exist.
+    pub fn file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.files.push((path.into(), true));
+        self
+    }
+    /// Add a configuration file that is silently skipped if it doesn't exist.
+    pub fn optional_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.files.push((path.into(), false));
+        self
+    }
+    /// Set the prefix of the environment variables to consider. Defaults to `CEF`.
+    pub fn env_prefix<S: Into<String>>(mut self, env_prefix: S) -> Self {
+        self.env_prefix = Some(env_prefix.into());
+        self
+    }
+    /// Ignore the environment.
+    pub fn no_env(mut self) -> Self {
+        self.env_prefix = None;
+        self
+    }
+
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let mut settings = self.settings;
+        let mut browser_settings = self.browser_settings;
+
+        for (path, required) in self.files {
+            let file = match ConfigFile::load(&path) {
+                Err(ConfigError::Io(_, ref err))
+                    if !required && err.kind() == std::io::ErrorKind::NotFound =>
+                {
+                    continue
+                }
+                file => file?,
+            };
+            file.settings.apply_to(&mut settings);
+            file.browser.apply_to(&mut browser_settings);
+        }
+
+        if let Some(prefix) = self.env_prefix {
+            SettingsOverrides::from_env(&prefix)?.apply_to(&mut settings);
+            BrowserSettingsOverrides::from_env(&prefix)?.apply_to(&mut browser_settings);
+        }
+
+        Ok(Config {
+            settings,
+            browser_settings,
+        })
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    settings: SettingsOverrides,
+    browser: BrowserSettingsOverrides,
+}
+
+impl ConfigFile {
+    fn load(path: &Path) -> Result<ConfigFile, ConfigError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let parse: fn(&Path, &str) -> Result<ConfigFile, ConfigError> = match extension.as_deref() {
+            Some("toml") => |path, text| {
+                toml::from_str(text).map_err(|err| ConfigError::Toml(path.to_owned(), err))
+            },
+            Some("json") => |path, text| {
+                serde_json::from_str(text).map_err(|err| ConfigError::Json(path.to_owned(), err))
+            },
+            _ => return Err(ConfigError::UnknownFormat(path.to_owned())),
+        };
+        let text =
+            std::fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_owned(), err))?;
+        parse(path, &text)
+    }
+}
+
+fn env_var<T>(prefix: &str, section: &str, field: &str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let name = format!("{}_{}{}", prefix, section, field.to_ascii_uppercase());
+    match std::env::var(&name) {
+        Ok(value) => match value.parse() {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(err) => Err(ConfigError::Env {
+                message: err.to_string(),
+                name,
+                value,
+            }),
+        },
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(value)) => Err(ConfigError::Env {
+            value: value.to_string_lossy().into_owned(),
+            message: "not valid unicode".to_owned(),
+            name,
+        }),
+    }
+}
+
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+macro_rules! from_str_deserialize {
+    ($($ty:ty),*) => {
+        $(
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_from_str(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+from_str_deserialize!(LogSeverity, State, Color);
+
+macro_rules! overrides {
+    (
+        $(#[$meta:meta])*
+        struct $Overrides:ident for $Target:ty, env $section:literal {
+            $($field:ident: $kind:ident $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Default, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        struct $Overrides {
+            $($field: Option<$ty>,)*
+        }
+
+        impl $Overrides {
+            fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+                Ok(Self {
+                    $($field: env_var(prefix, $section, stringify!($field))?,)*
+                })
+            }
+            fn apply_to(self, target: &mut $Target) {
+                $(
+                    if let Some(value) = self.$field {
+                        overrides!(@assign $kind target.$field, value);
+                    }
+                )*
+            }
+        }
+    };
+    (@assign opt $target:expr, $value:expr) => { $target = Some($value) };
+    (@assign val $target:expr, $value:expr) => { $target = $value };
+}
+
+overrides! {
+    /// Partial [Settings], only the fields that are present override the lower layers.
+    struct SettingsOverrides for Settings, env "" {
+        browser_subprocess_path: opt PathBuf,
+        framework_dir_path: opt PathBuf,
+        main_bundle_path: opt PathBuf,
+        multi_threaded_message_loop: val bool,
+        external_message_pump: val bool,
+        windowless_rendering_enabled: val bool,
+        command_line_args_disabled: val bool,
+        cache_path: opt PathBuf,
+        root_cache_path: opt PathBuf,
+        user_data_path: opt PathBuf,
+        persist_session_cookies: val bool,
+        persist_user_preferences: val bool,
+        user_agent: opt String,
+        product_version: opt String,
+        locale: opt String,
+        log_file: opt PathBuf,
+        log_severity: val LogSeverity,
+        javascript_flags: opt String,
+        resources_dir_path: opt PathBuf,
+        locales_dir_path: opt PathBuf,
+        pack_loading_disabled: val bool,
+        remote_debugging_port: val u16,
+        uncaught_exception_stack_size: val u32,
+        ignore_certificate_errors: val bool,
+        background_color: val Color,
+        accept_language_list: opt String,
+        application_client_id_for_file_scanning: opt Uuid,
+    }
+}
+
+overrides! {
+    /// Partial [BrowserSettings], only the fields that are present override the lower layers.
+    struct BrowserSettingsOverrides for BrowserSettings, env "BROWSER_" {
+        windowless_frame_rate: val i32,
+        standard_font_family: val String,
+        fixed_font_family: val String,
+        serif_font_family: val String,
+        sans_serif_font_family: val String,
+        cursive_font_family: val String,
+        fantasy_font_family: val String,
+        default_font_size: val i32,
+        default_fixed_font_size: val i32,
+        minimum_font_size: val i32,
+        minimum_logical_font_size: val i32,
+        default_encoding: val String,
+        remote_fonts: val State,
+        javascript: val State,
+        javascript_close_windows: val State,
+        javascript_access_clipboard: val State,
+        javascript_dom_paste: val State,
+        plugins: val State,
+        universal_access_from_file_urls: val State,
+        file_access_from_file_urls: val State,
+        web_security: val State,
+        image_loading: val State,
+        image_shrink_standalone_to_fit: val State,
+        text_area_resize: val State,
+        tab_to_links: val State,
+        local_storage: val State,
+        databases: val State,
+        application_cache: val State,
+        webgl: val State,
+        background_color: val Color,
+        accept_language_list: val String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn layers_override_in_order() {
+        let dir = TempDir::new("config");
+        let toml = dir.write(
+            "layers.toml",
+            r#"
+                [settings]
+                remote_debugging_port = 9222
+                log_severity = "verbose"
+                log_file = "/tmp/cef.log"
+
+                [browser]
+                windowless_frame_rate = 30
+                webgl = "disabled"
+            "#,
+        );
+        let json = dir.write(
+            "layers.json",
+            r##"{ "settings": { "remote_debugging_port": 9333 }, "browser": { "background_color": "#102030" } }"##,
+        );
+        std::env::set_var("CEF_TEST_LAYERS_LOG_SEVERITY", "error");
+        std::env::set_var("CEF_TEST_LAYERS_BROWSER_WINDOWLESS_FRAME_RATE", "15");
+
+        let config = Config::builder()
+            .defaults(Settings::new().locale("de").remote_debugging_port(1))
+            .browser_defaults(BrowserSettings {
+                default_font_size: 20,
+                ..BrowserSettings::new()
+            })
+            .file(&toml)
+            .file(&json)
+            .optional_file(std::env::temp_dir().join("cef-config-missing.toml"))
+            .env_prefix("CEF_TEST_LAYERS")
+            .build()
+            .unwrap();
+
+        // Defaults survive unless overridden.
+        assert_eq!(config.settings.locale.as_deref(), Some("de"));
+        assert_eq!(config.browser_settings.default_font_size, 20);
+        // Later files override earlier ones, but only for the keys they contain.
+        assert_eq!(config.settings.remote_debugging_port, 9333);
+        assert_eq!(
+            config.settings.log_file,
+            Some(PathBuf::from("/tmp/cef.log"))
+        );
+        assert_eq!(config.browser_settings.webgl, State::Disabled);
+        assert_eq!(
+            config.browser_settings.background_color,
+            "#102030".parse().unwrap()
+        );
+        // The environment overrides all files.
+        assert_eq!(config.settings.log_severity, LogSeverity::Error);
+        assert_eq!(config.browser_settings.windowless_frame_rate, 15);
+    }
+
+    #[test]
+    fn no_env_ignores_environment() {
+        std::env::set_var("CEF_TEST_NO_ENV_REMOTE_DEBUGGING_PORT", "9222");
+        let config = Config::builder()
+            .env_prefix("CEF_TEST_NO_ENV")
+            .no_env()
+            .build()
+            .unwrap();
+        assert_eq!(config.settings.remote_debugging_port, 0);
+    }
+
+    #[test]
+    fn missing_required_file() {
+        let path = std::env::temp_dir().join("cef-config-missing.toml");
+        match Config::builder().file(&path).no_env().build() {
+            Err(ConfigError::Io(err_path, err)) => {
+                assert_eq!(err_path, path);
+                assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+            }
+            _ => panic!("expected an I/O error"),
+        }
+    }
+
+    #[test]
+    fn unknown_format() {
+        let dir = TempDir::new("config");
+        let ini = dir.write("config.ini", "[settings]\n");
+        match Config::builder().file(&ini).no_env().build() {
+            Err(ConfigError::UnknownFormat(path)) => assert_eq!(path, ini),
+            _ => panic!("expected an unknown format error"),
+        }
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        let dir = TempDir::new("config");
+        let toml = dir.write("invalid.toml", "[settings]\nremote_debuging_port = 9222\n");
+        assert!(matches!(
+            Config::builder().file(&toml).no_env().build(),
+            Err(ConfigError::Toml(..))
+        ));
+        let json = dir.write("invalid.json", r#"{ "browser": { "webgl": "sometimes" } }"#);
+        assert!(matches!(
+            Config::builder().file(&json).no_env().build(),
+            Err(ConfigError::Json(..))
+        ));
+    }
+
+    #[test]
+    fn invalid_env_value() {
+        std::env::set_var("CEF_TEST_INVALID_REMOTE_DEBUGGING_PORT", "port");
+        match Config::builder().env_prefix("CEF_TEST_INVALID").build() {
+            Err(ConfigError::Env { name, value, .. }) => {
+                assert_eq!(name, "CEF_TEST_INVALID_REMOTE_DEBUGGING_PORT");
+                assert_eq!(value, "port");
+            }
+            _ => panic!("expected an environment error"),
+        }
+    }
+
+    #[test]
+    fn parse_color() {
+        let color: Color = "#102030".parse().unwrap();
+        assert_eq!(
+            (color.r(), color.g(), color.b(), color.a()),
+            (0x10, 0x20, 0x30, 0xFF)
+        );
+        let color: Color = "#10203040".parse().unwrap();
+        assert_eq!(
+            (color.r(), color.g(), color.b(), color.a()),
+            (0x10, 0x20, 0x30, 0x40)
+        );
+        assert_eq!(format!("{:?}", color), "#10203040");
+        assert_eq!(
+            "#abcdef".parse::<Color>().unwrap(),
+            "#ABCDEF".parse().unwrap()
+        );
+
+        for invalid in &[
+            "102030",
+            "#12345",
+            "#1234567",
+            "#GG2030",
+            "#+12345",
+            "#+1234567",
+            "#-12345",
+            "",
+        ] {
+            assert!(
+                invalid.parse::<Color>().is_err(),
+                "{:?} was accepted",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn parse_state() {
+        assert_eq!("default".parse(), Ok(State::Default));
+        assert_eq!("Enabled".parse(), Ok(State::Enabled));
+        assert_eq!("true".parse(), Ok(State::Enabled));
+        assert_eq!("DISABLED".parse(), Ok(State::Disabled));
+        assert_eq!("false".parse(), Ok(State::Disabled));
+        assert!("on".parse::<State>().is_err());
+    }
+
+    #[test]
+    fn parse_log_severity() {
+        assert_eq!("default".parse(), Ok(LogSeverity::Default));
+        assert_eq!("verbose".parse(), Ok(LogSeverity::Verbose));
+        assert_eq!("debug".parse(), Ok(LogSeverity::Verbose));
+        assert_eq!("Info".parse(), Ok(LogSeverity::Info));
+        assert_eq!("WARNING".parse(), Ok(LogSeverity::Warning));
+        assert_eq!("error".parse(), Ok(LogSeverity::Error));
+        assert_eq!("fatal".parse(), Ok(LogSeverity::Fatal));
+        assert_eq!("disable".parse(), Ok(LogSeverity::Disable));
+        assert!("trace".parse::<LogSeverity>().is_err());
+    }
+}
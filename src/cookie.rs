@@ -137,6 +137,7 @@ impl CookieManager {
         include_defaults: bool,
         on_completion: impl 'static + Send + FnOnce()
     ) {
+        ffi_event!("CookieManager::set_supported_schemes");
         let mut string_list = schemes.into_iter().collect::<CefStringList>();
         unsafe {
             self.0.set_supported_schemes.unwrap()(
@@ -154,6 +155,7 @@ impl CookieManager {
         &self,
         visitor: impl CookieVisitorFn,
     ) -> bool {
+        ffi_event!("CookieManager::visit_all_cookies");
         unsafe {
             self.0.visit_all_cookies.unwrap()(
                 self.as_ptr(),
@@ -172,6 +174,7 @@ impl CookieManager {
         include_http_only: bool,
         visitor: impl CookieVisitorFn,
     ) -> bool {
+        ffi_event!("CookieManager::visit_url_cookies");
         unsafe {
             self.0.visit_url_cookies.unwrap()(
                 self.as_ptr(),
@@ -194,6 +197,7 @@ impl CookieManager {
         cookie: &Cookie,
         callback: impl 'static + Send + FnOnce(bool)
     ) -> bool {
+        ffi_event!("CookieManager::set_cookie");
         unsafe {
             self.0.set_cookie.unwrap()(
                 self.as_ptr(),
@@ -218,6 +222,7 @@ impl CookieManager {
         cookie_name: &str,
         callback: impl 'static + Send + FnOnce(usize),
     ) -> bool {
+        ffi_event!("CookieManager::delete_cookies");
         unsafe {
             self.0.delete_cookies.unwrap()(
                 self.as_ptr(),
@@ -234,6 +239,7 @@ impl CookieManager {
         &self,
         on_completion: impl 'static + Send + FnOnce()
     ) -> bool {
+        ffi_event!("CookieManager::flush_store");
         unsafe {
             self.0.flush_store.unwrap()(
                 self.as_ptr(),
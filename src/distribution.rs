@@ -0,0 +1,454 @@
+//! Discovery and verification of the files of a CEF binary distribution on Linux.
+//!
+//! CEF needs a number of data files next to `libcef.so` and fails with rather
+//! cryptic log messages if any of them are missing. This module looks for them
+//! in the usual places, fills in [Settings::resources_dir_path] and
+//! [Settings::locales_dir_path] and produces a detailed report otherwise.
+//! Example usage:
+//!
+//! ```no_run
+//!  let settings = match cef::distribution::discover().configure(cef::settings::Settings::new()) {
+//!      Ok(settings) => settings,
+//!      Err(report) => {
+//!          eprintln!("{}", report);
+//!          std::process::exit(1);
+//!      }
+//!  };
+//! ```
+//!
+//! The following layouts are recognized, relative to the root directory:
+//! - everything in one directory, which is how applications are usually shipped,
+//! - the `Release`/`Debug` and `Resources` directories of the CEF binary
+//!   distribution, with the root being either the distribution itself or
+//!   the `Release`/`Debug` directory.
+
+use crate::settings::Settings;
+use std::{
+    fmt,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// Files that have to be located next to `libcef.so`.
+const LIBRARY_FILES: &[(&str, bool)] = &[
+    ("libcef.so", true),
+    ("snapshot_blob.bin", true),
+    ("v8_context_snapshot.bin", true),
+    ("chrome-sandbox", false),
+    ("libEGL.so", false),
+    ("libGLESv2.so", false),
+];
+
+/// Files that have to be located in the resources directory.
+const RESOURCE_FILES: &[(&str, bool)] = &[
+    ("icudtl.dat", true),
+    ("cef.pak", true),
+    ("cef_100_percent.pak", true),
+    ("cef_200_percent.pak", true),
+    ("cef_extensions.pak", false),
+    ("devtools_resources.pak", false),
+];
+
+/// The locale that Chromium falls back to, which therefore always has to exist.
+const FALLBACK_LOCALE: &str = "en-US.pak";
+
+/// The state of a single file of the distribution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    /// The file exists and looks fine.
+    Found,
+    /// The file doesn't exist. Whether that's a problem depends on [FileReport::required].
+    Missing,
+    /// The file exists, but can't be what CEF expects.
+    Mismatched(String),
+}
+
+/// The result of looking for a single file or directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub required: bool,
+    pub status: FileStatus,
+}
+
+impl FileReport {
+    /// Returns true if this file prevents CEF from working.
+    pub fn is_problem(&self) -> bool {
+        match self.status {
+            FileStatus::Found => false,
+            FileStatus::Missing => self.required,
+            FileStatus::Mismatched(_) => true,
+        }
+    }
+}
+
+/// A report on all files of the distribution found below a root directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistributionReport {
+    /// The directory the search started from.
+    pub root: PathBuf,
+    /// The directory containing `libcef.so`.
+    pub library_dir: PathBuf,
+    /// The directory containing `icudtl.dat` and the `.pak` files.
+    pub resources_dir: PathBuf,
+    /// The `locales` directory.
+    pub locales_dir: PathBuf,
+    /// All files that were checked.
+    pub files: Vec<FileReport>,
+}
+
+impl DistributionReport {
+    /// Returns true if all required files were found and none of them are mismatched.
+    pub fn is_complete(&self) -> bool {
+        !self.files.iter().any(FileReport::is_problem)
+    }
+    /// Returns all files that prevent CEF from working.
+    pub fn problems(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| file.is_problem())
+    }
+    /// Fill in [Settings::resources_dir_path] and [Settings::locales_dir_path]
+    /// unless they're already set. Returns the report itself as the error if the
+    /// distribution is incomplete.
+    pub fn configure(self, mut settings: Settings) -> Result<Settings, DistributionReport> {
+        if !self.is_complete() {
+            return Err(self);
+        }
+        if settings.resources_dir_path.is_none() {
+            settings.resources_dir_path = Some(self.resources_dir);
+        }
+        if settings.locales_dir_path.is_none() {
+            settings.locales_dir_path = Some(self.locales_dir);
+        }
+        Ok(settings)
+    }
+}
+
+impl fmt::Display for DistributionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_complete() {
+            writeln!(f, "CEF distribution in {} is complete", self.root.display())?;
+        } else {
+            writeln!(f, "CEF distribution in {} is incomplete", self.root.display())?;
+        }
+        for file in &self.files {
+            let status = match &file.status {
+                FileStatus::Found => "found".to_owned(),
+                FileStatus::Missing if file.required => "MISSING".to_owned(),
+                FileStatus::Missing => "missing (optional)".to_owned(),
+                FileStatus::Mismatched(reason) => format!("MISMATCHED: {}", reason),
+            };
+            writeln!(f, "  {}: {}", file.path.display(), status)?;
+        }
+        Ok(())
+    }
+}
+
+/// Look for the distribution relative to the directory of the current executable.
+pub fn discover() -> DistributionReport {
+    let root = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_owned))
+        .unwrap_or_else(|| PathBuf::from("."));
+    discover_in(root)
+}
+
+/// Look for the distribution relative to `root`.
+pub fn discover_in<P: Into<PathBuf>>(root: P) -> DistributionReport {
+    let root = root.into();
+    let library_dir = find_dir(&root, &["", "Release", "Debug"], "libcef.so");
+    let resources_dir = find_dir(&root, &["", "Resources", "../Resources"], "icudtl.dat");
+    let locales_dir = resources_dir.join("locales");
+
+    let mut files = Vec::new();
+    for &(name, required) in LIBRARY_FILES {
+        let path = library_dir.join(name);
+        let status = if name == "libcef.so" {
+            check_library(&path)
+        } else {
+            check_file(&path)
+        };
+        files.push(FileReport { path, required, status });
+    }
+    for &(name, required) in RESOURCE_FILES {
+        let path = resources_dir.join(name);
+        let status = check_file(&path);
+        files.push(FileReport { path, required, status });
+    }
+    files.push(FileReport {
+        status: check_locales(&locales_dir),
+        path: locales_dir.clone(),
+        required: true,
+    });
+    let fallback_locale = locales_dir.join(FALLBACK_LOCALE);
+    files.push(FileReport {
+        status: check_file(&fallback_locale),
+        path: fallback_locale,
+        required: true,
+    });
+
+    DistributionReport {
+        root,
+        library_dir,
+        resources_dir,
+        locales_dir,
+        files,
+    }
+}
+
+/// Returns the first candidate below `root` that contains `marker`, or `root` if
+/// there is none, so that the report lists the files as missing there.
+fn find_dir(root: &Path, candidates: &[&str], marker: &str) -> PathBuf {
+    candidates
+        .iter()
+        .map(|candidate| root.join(candidate))
+        .find(|dir| dir.join(marker).exists())
+        .map(|dir| dunce::canonicalize(&dir).unwrap_or(dir))
+        .unwrap_or_else(|| root.to_owned())
+}
+
+fn check_file(path: &Path) -> FileStatus {
+    match std::fs::metadata(path) {
+        Err(_) => FileStatus::Missing,
+        Ok(metadata) if !metadata.is_file() => FileStatus::Mismatched("not a file".to_owned()),
+        Ok(metadata) if metadata.len() == 0 => FileStatus::Mismatched("empty file".to_owned()),
+        Ok(_) => FileStatus::Found,
+    }
+}
+
+fn check_locales(path: &Path) -> FileStatus {
+    match std::fs::read_dir(path) {
+        Err(_) if !path.exists() => FileStatus::Missing,
+        Err(err) => FileStatus::Mismatched(err.to_string()),
+        Ok(entries) => {
+            let has_pak = entries
+                .filter_map(Result::ok)
+                .any(|entry| entry.path().extension().map(|ext| ext == "pak").unwrap_or(false));
+            if has_pak {
+                FileStatus::Found
+            } else {
+                FileStatus::Mismatched("contains no .pak files".to_owned())
+            }
+        }
+    }
+}
+
+/// Checks that the library is an ELF shared object for the architecture of
+/// the running executable.
+fn check_library(path: &Path) -> FileStatus {
+    match check_file(path) {
+        FileStatus::Found => {}
+        status => return status,
+    }
+    let mut header = [0u8; 20];
+    if let Err(err) = File::open(path).and_then(|mut file| file.read_exact(&mut header)) {
+        return FileStatus::Mismatched(err.to_string());
+    }
+    if &header[0..4] != b"\x7fELF" {
+        return FileStatus::Mismatched("not an ELF file".to_owned());
+    }
+    let class = if cfg!(target_pointer_width = "64") { 2 } else { 1 };
+    if header[4] != class {
+        return FileStatus::Mismatched(format!("built for {}-bit", if header[4] == 2 { 64 } else { 32 }));
+    }
+    let machine = if header[5] == 1 {
+        u16::from_le_bytes([header[18], header[19]])
+    } else {
+        u16::from_be_bytes([header[18], header[19]])
+    };
+    match native_machine() {
+        Some(expected) if expected != machine => FileStatus::Mismatched(format!("built for ELF machine type {}", machine)),
+        _ => FileStatus::Found,
+    }
+}
+
+/// The ELF machine type of the running executable, if it's one CEF supports.
+fn native_machine() -> Option<u16> {
+    if cfg!(target_arch = "x86_64") {
+        Some(62)
+    } else if cfg!(target_arch = "x86") {
+        Some(3)
+    } else if cfg!(target_arch = "aarch64") {
+        Some(183)
+    } else if cfg!(target_arch = "arm") {
+        Some(40)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// The first bytes of an ELF header with the given class and machine type.
+    fn elf_header(class: u8, machine: u16) -> Vec<u8> {
+        let mut header = vec![0; 64];
+        header[..4].copy_from_slice(b"\x7fELF");
+        header[4] = class;
+        header[5] = 1;
+        header[18..20].copy_from_slice(&machine.to_le_bytes());
+        header
+    }
+
+    fn native_library() -> Vec<u8> {
+        let class = if cfg!(target_pointer_width = "64") { 2 } else { 1 };
+        elf_header(class, native_machine().unwrap_or(0))
+    }
+
+    /// Writes all files of a distribution, the library ones to `library_dir`
+    /// and the others to `resources_dir`.
+    fn write_distribution(dir: &TempDir, library_dir: &str, resources_dir: &str) {
+        for &(name, _) in LIBRARY_FILES {
+            let contents = if name == "libcef.so" { native_library() } else { b"data".to_vec() };
+            dir.write(Path::new(library_dir).join(name), contents);
+        }
+        for &(name, _) in RESOURCE_FILES {
+            dir.write(Path::new(resources_dir).join(name), "data");
+        }
+        dir.write(Path::new(resources_dir).join("locales").join(FALLBACK_LOCALE), "data");
+    }
+
+    fn status(report: &DistributionReport, name: &str) -> FileStatus {
+        report
+            .files
+            .iter()
+            .find(|file| file.path.file_name() == Some(name.as_ref()))
+            .unwrap()
+            .status
+            .clone()
+    }
+
+    fn canonical(path: &Path) -> PathBuf {
+        dunce::canonicalize(path).unwrap()
+    }
+
+    #[test]
+    fn single_directory() {
+        let dir = TempDir::new("distribution");
+        write_distribution(&dir, "", "");
+        let report = discover_in(dir.path());
+        assert!(report.is_complete(), "{}", report);
+        assert_eq!(report.problems().count(), 0);
+        assert_eq!(report.library_dir, canonical(dir.path()));
+        assert_eq!(report.resources_dir, canonical(dir.path()));
+        assert_eq!(report.locales_dir, canonical(dir.path()).join("locales"));
+        assert!(report.to_string().starts_with(&format!("CEF distribution in {} is complete\n", dir.path().display())));
+    }
+
+    #[test]
+    fn binary_distribution_layout() {
+        let dir = TempDir::new("distribution");
+        write_distribution(&dir, "Release", "Resources");
+        let report = discover_in(dir.path());
+        assert!(report.is_complete(), "{}", report);
+        assert_eq!(report.library_dir, canonical(&dir.join("Release")));
+        assert_eq!(report.resources_dir, canonical(&dir.join("Resources")));
+
+        // Starting from the Release directory finds the resources next to it.
+        let report = discover_in(dir.join("Release"));
+        assert!(report.is_complete(), "{}", report);
+        assert_eq!(report.library_dir, canonical(&dir.join("Release")));
+        assert_eq!(report.resources_dir, canonical(&dir.join("Resources")));
+    }
+
+    #[test]
+    fn missing_resources() {
+        let dir = TempDir::new("distribution");
+        write_distribution(&dir, "", "");
+        std::fs::remove_file(dir.join("icudtl.dat")).unwrap();
+        std::fs::remove_file(dir.join("cef.pak")).unwrap();
+        std::fs::remove_file(dir.join("devtools_resources.pak")).unwrap();
+        std::fs::remove_dir_all(dir.join("locales")).unwrap();
+
+        let report = discover_in(dir.path());
+        assert!(!report.is_complete());
+        // Without icudtl.dat, the resources are looked for in the root itself.
+        assert_eq!(report.resources_dir, dir.path());
+        let problems: Vec<_> = report.problems().map(|file| file.path.clone()).collect();
+        assert_eq!(
+            problems,
+            vec![
+                dir.join("icudtl.dat"),
+                dir.join("cef.pak"),
+                dir.join("locales"),
+                dir.join("locales").join(FALLBACK_LOCALE),
+            ]
+        );
+        assert_eq!(status(&report, "devtools_resources.pak"), FileStatus::Missing);
+        let text = report.to_string();
+        assert!(text.contains("is incomplete"), "{}", text);
+        assert!(text.contains(&format!("{}: MISSING\n", dir.join("cef.pak").display())), "{}", text);
+        assert!(
+            text.contains(&format!("{}: missing (optional)\n", dir.join("devtools_resources.pak").display())),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn locales_without_pak_files() {
+        let dir = TempDir::new("distribution");
+        write_distribution(&dir, "", "");
+        std::fs::remove_file(dir.join("locales").join(FALLBACK_LOCALE)).unwrap();
+        dir.write("locales/README", "no locales");
+        let report = discover_in(dir.path());
+        assert_eq!(status(&report, "locales"), FileStatus::Mismatched("contains no .pak files".to_owned()));
+        assert_eq!(status(&report, FALLBACK_LOCALE), FileStatus::Missing);
+    }
+
+    #[test]
+    fn library_checks() {
+        let dir = TempDir::new("distribution");
+        let check = |contents: &[u8]| check_library(&dir.write("libcef.so", contents));
+        assert_eq!(check(&native_library()), FileStatus::Found);
+        assert_eq!(check(b""), FileStatus::Mismatched("empty file".to_owned()));
+        assert_eq!(check(b"#!/bin/sh\necho not a library\n"), FileStatus::Mismatched("not an ELF file".to_owned()));
+        // Shorter than the part of the header that is checked.
+        match check(&native_library()[..12]) {
+            FileStatus::Mismatched(_) => {}
+            status => panic!("truncated library is {:?}", status),
+        }
+        let other_class = if cfg!(target_pointer_width = "64") { 1 } else { 2 };
+        let other_bits = if other_class == 2 { "64" } else { "32" };
+        assert_eq!(
+            check(&elf_header(other_class, native_machine().unwrap_or(0))),
+            FileStatus::Mismatched(format!("built for {}-bit", other_bits))
+        );
+        if native_machine().is_some() {
+            let class = if cfg!(target_pointer_width = "64") { 2 } else { 1 };
+            assert_eq!(
+                check(&elf_header(class, 0xbeef)),
+                FileStatus::Mismatched("built for ELF machine type 48879".to_owned())
+            );
+        }
+
+        std::fs::remove_file(dir.join("libcef.so")).unwrap();
+        assert_eq!(check_library(&dir.join("libcef.so")), FileStatus::Missing);
+        dir.create_dir("libcef.so");
+        assert_eq!(check_library(&dir.join("libcef.so")), FileStatus::Mismatched("not a file".to_owned()));
+    }
+
+    #[test]
+    fn configure_fills_in_paths() {
+        let dir = TempDir::new("distribution");
+        write_distribution(&dir, "Release", "Resources");
+        let resources = canonical(&dir.join("Resources"));
+
+        let settings = discover_in(dir.path()).configure(Settings::new()).unwrap();
+        assert_eq!(settings.resources_dir_path, Some(resources.clone()));
+        assert_eq!(settings.locales_dir_path, Some(resources.join("locales")));
+
+        // Paths that are already set are kept.
+        let settings = discover_in(dir.path())
+            .configure(Settings::new().locales_dir_path("/usr/share/cef/locales"))
+            .unwrap();
+        assert_eq!(settings.resources_dir_path, Some(resources));
+        assert_eq!(settings.locales_dir_path, Some(PathBuf::from("/usr/share/cef/locales")));
+
+        std::fs::remove_file(dir.join("Release/snapshot_blob.bin")).unwrap();
+        let report = discover_in(dir.path());
+        assert_eq!(report.clone().configure(Settings::new()), Err(report));
+    }
+}
@@ -39,71 +39,88 @@ ref_counted_ptr! {
 impl DOMNode {
     /// Returns the type for this node.
     pub fn get_type(&self) -> DOMNodeType {
+        ffi_event!("DOMNode::get_type");
         unsafe{ DOMNodeType::from_unchecked((self.0.get_type.unwrap())(self.as_ptr())) }
     }
     /// Returns true if this is a text node.
     pub fn is_text(&self) -> bool {
+        ffi_event!("DOMNode::is_text");
         unsafe{ (self.0.is_text.unwrap())(self.as_ptr()) != 0 }
     }
     /// Returns true if this is an element node.
     pub fn is_element(&self) -> bool {
+        ffi_event!("DOMNode::is_element");
         unsafe{ (self.0.is_element.unwrap())(self.as_ptr()) != 0 }
     }
     /// Returns true if this is an editable node.
     pub fn is_editable(&self) -> bool {
+        ffi_event!("DOMNode::is_editable");
         unsafe{ (self.0.is_editable.unwrap())(self.as_ptr()) != 0 }
     }
     /// Returns true if this is a form control element node.
     pub fn is_form_control_element(&self) -> bool {
+        ffi_event!("DOMNode::is_form_control_element");
         unsafe{ (self.0.is_form_control_element.unwrap())(self.as_ptr()) != 0 }
     }
     /// Returns the type of this form control element node.
     pub fn get_form_control_element_type(&self) -> String {
+        ffi_event!("DOMNode::get_form_control_element_type");
         unsafe{ CefString::from_userfree_unchecked((self.0.get_form_control_element_type.unwrap())(self.as_ptr())).into() }
     }
     /// Returns the name of this node.
     pub fn get_name(&self) -> String {
+        ffi_event!("DOMNode::get_name");
         unsafe{ CefString::from_userfree_unchecked((self.0.get_name.unwrap())(self.as_ptr())).into() }
     }
     /// Returns the value of this node.
     pub fn get_value(&self) -> String {
+        ffi_event!("DOMNode::get_value");
         unsafe{ CefString::from_userfree_unchecked((self.0.get_value.unwrap())(self.as_ptr())).into() }
     }
     /// Set the value of this node. Returns true on success.
     pub fn set_value(&self, value: &str) -> bool {
+        ffi_event!("DOMNode::set_value");
         let value = CefString::new(value);
         unsafe{ (self.0.set_value.unwrap())(self.as_ptr(), value.as_ptr()) != 0 }
     }
     /// Returns the contents of this node as markup.
     pub fn get_as_markup(&self) -> String {
+        ffi_event!("DOMNode::get_as_markup");
         unsafe{ CefString::from_userfree_unchecked((self.0.get_as_markup.unwrap())(self.as_ptr())).into() }
     }
     /// Returns the document associated with this node.
     pub fn get_document(&self) -> DOMDocument {
+        ffi_event!("DOMNode::get_document");
         unsafe{ DOMDocument::from_ptr_unchecked((self.0.get_document.unwrap())(self.as_ptr())) }
     }
     /// Returns the parent node.
     pub fn get_parent(&self) -> Option<Self> {
+        ffi_event!("DOMNode::get_parent");
         unsafe{ DOMNode::from_ptr((self.0.get_parent.unwrap())(self.as_ptr())) }
     }
     /// Returns the previous sibling node.
     pub fn get_previous_sibling(&self) -> Option<Self> {
+        ffi_event!("DOMNode::get_previous_sibling");
         unsafe{ DOMNode::from_ptr((self.0.get_previous_sibling.unwrap())(self.as_ptr())) }
     }
     /// Returns the next sibling node.
     pub fn get_next_sibling(&self) -> Option<Self> {
+        ffi_event!("DOMNode::get_next_sibling");
         unsafe{ DOMNode::from_ptr((self.0.get_next_sibling.unwrap())(self.as_ptr())) }
     }
     /// Returns true if this node has child nodes.
     pub fn has_children(&self) -> bool {
+        ffi_event!("DOMNode::has_children");
         unsafe{ (self.0.has_children.unwrap())(self.as_ptr()) != 0 }
     }
     /// Return the first child node.
     pub fn get_first_child(&self) -> Option<Self> {
+        ffi_event!("DOMNode::get_first_child");
         unsafe{ DOMNode::from_ptr((self.0.get_first_child.unwrap())(self.as_ptr())) }
     }
     /// Returns the last child node.
     pub fn get_last_child(&self) -> Option<Self> {
+        ffi_event!("DOMNode::get_last_child");
         unsafe{ DOMNode::from_ptr((self.0.get_last_child.unwrap())(self.as_ptr())) }
     }
 
@@ -111,24 +128,29 @@ impl DOMNode {
 
     /// Returns the tag name of this element.
     pub fn get_element_tag_name(&self) -> Option<String> {
+        ffi_event!("DOMNode::get_element_tag_name");
         unsafe{ CefString::from_userfree((self.0.get_element_tag_name.unwrap())(self.as_ptr())).map(String::from) }
     }
     /// Returns true if this element has attributes.
     pub fn has_element_attributes(&self) -> bool {
+        ffi_event!("DOMNode::has_element_attributes");
         unsafe{ (self.0.has_element_attributes.unwrap())(self.as_ptr()) != 0 }
     }
     /// Returns true if this element has an attribute named `attrName`.
     pub fn has_element_attribute(&self, attr_name: &str) -> bool {
+        ffi_event!("DOMNode::has_element_attribute");
         let attr_name = CefString::new(attr_name);
         unsafe{ (self.0.has_element_attribute.unwrap())(self.as_ptr(), attr_name.as_ptr()) != 0 }
     }
     /// Returns the element attribute named |attrName|.
     pub fn get_element_attribute(&self, attr_name: &str) -> Option<String> {
+        ffi_event!("DOMNode::get_element_attribute");
         let attr_name = CefString::new(attr_name);
         unsafe{ CefString::from_userfree(self.0.get_element_attribute.unwrap()(self.as_ptr(), attr_name.as_ptr())).map(String::from) }
     }
     /// Returns a map of all element attributes.
     pub fn get_element_attributes(&self) -> HashMap<String, String> {
+        ffi_event!("DOMNode::get_element_attributes");
         let mut string_map = CefStringMap::new();
         unsafe {
             self.0.get_element_attributes.unwrap()(
@@ -141,16 +163,19 @@ impl DOMNode {
     /// Set the value for the element attribute named `attr_name`. Returns true
     /// on success.
     pub fn set_element_attribute(&self, attr_name: &str, value: &str) -> bool {
+        ffi_event!("DOMNode::set_element_attribute");
         let attr_name = CefString::new(attr_name);
         let value = CefString::new(value);
         unsafe{ (self.0.set_element_attribute.unwrap())(self.as_ptr(), attr_name.as_ptr(), value.as_ptr()) != 0 }
     }
     /// Returns the inner text of the element.
     pub fn get_element_inner_text(&self) -> Option<String> {
+        ffi_event!("DOMNode::get_element_inner_text");
         unsafe{ CefString::from_userfree((self.0.get_element_inner_text.unwrap())(self.as_ptr())).map(String::from) }
     }
     /// Returns the bounds of the element.
     pub fn get_element_bounds(&self) -> Rect {
+        ffi_event!("DOMNode::get_element_bounds");
         let rect = unsafe{ (self.0.get_element_bounds.unwrap())(self.as_ptr()) };
         Rect {
             x: rect.x,
@@ -1,4 +1,5 @@
-use cef_sys::{cef_drag_data_create, cef_drag_data_t, cef_drag_operations_mask_t};
+use cef_sys::{cef_drag_data_t, cef_drag_operations_mask_t};
+use crate::capi::cef_drag_data_create;
 use std::ptr;
 use crate::{
     string::CefString,
@@ -38,52 +39,64 @@ ref_counted_ptr! {
 
 impl DragData {
     pub fn new() -> Self {
+        ffi_event!("DragData::new");
         unsafe { Self::from_ptr_unchecked(cef_drag_data_create()) }
     }
 
     /// Returns `true` if this object is read-only.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("DragData::is_read_only");
         unsafe { self.0.is_read_only.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns `true` if the drag data is a link.
     pub fn is_link(&self) -> bool {
+        ffi_event!("DragData::is_link");
         unsafe { self.0.is_link.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns `true` if the drag data is a text or html fragment.
     pub fn is_fragment(&self) -> bool {
+        ffi_event!("DragData::is_fragment");
         unsafe { self.0.is_fragment.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns `true` if the drag data is a file.
     pub fn is_file(&self) -> bool {
+        ffi_event!("DragData::is_file");
         unsafe { self.0.is_file.unwrap()(self.as_ptr()) != 0 }
     }
     /// Return the link URL that is being dragged.
     pub fn get_link_url(&self) -> Option<String> {
+        ffi_event!("DragData::get_link_url");
         unsafe { CefString::from_userfree(self.0.get_link_url.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Return the title associated with the link being dragged.
     pub fn get_link_title(&self) -> Option<String> {
+        ffi_event!("DragData::get_link_title");
         unsafe { CefString::from_userfree(self.0.get_link_title.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Return the metadata, if any, associated with the link being dragged.
     pub fn get_link_metadata(&self) -> Option<String> {
+        ffi_event!("DragData::get_link_metadata");
         unsafe { CefString::from_userfree(self.0.get_link_metadata.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Return the plain text fragment that is being dragged.
     pub fn get_fragment_text(&self) -> Option<String> {
+        ffi_event!("DragData::get_fragment_text");
         unsafe { CefString::from_userfree(self.0.get_fragment_text.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Return the text/html fragment that is being dragged.
     pub fn get_fragment_html(&self) -> Option<String> {
+        ffi_event!("DragData::get_fragment_html");
         unsafe { CefString::from_userfree(self.0.get_fragment_html.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Return the base URL that the fragment came from. This value is used for
     /// resolving relative URLs and may be empty.
     pub fn get_fragment_base_url(&self) -> Option<String> {
+        ffi_event!("DragData::get_fragment_base_url");
         unsafe { CefString::from_userfree(self.0.get_fragment_base_url.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Return the name of the file being dragged out of the browser window.
     pub fn get_file_name(&self) -> Option<String> {
+        ffi_event!("DragData::get_file_name");
         unsafe { CefString::from_userfree(self.0.get_file_name.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Write the contents of the file being dragged out of the web view into
@@ -91,11 +104,13 @@ impl DragData {
     /// `None` this method will return the size of the file contents in bytes.
     /// Call [`get_file_name`] to get a suggested name for the file.
     pub fn get_file_contents(&self, writer: Option<StreamWriter>) -> usize {
+        ffi_event!("DragData::get_file_contents");
         unsafe { self.0.get_file_contents.unwrap()(self.as_ptr(), writer.map(|w| w.into_raw()).unwrap_or(ptr::null_mut())) }
     }
     /// Retrieve the list of file names that are being dragged into the browser
     /// window.
     pub fn get_file_names(&self, names: &mut Vec<String>) -> bool {
+        ffi_event!("DragData::get_file_names");
         let mut string_list = CefStringList::new();
         let result = unsafe { self.0.get_file_names.unwrap()(self.as_ptr(), string_list.as_mut_ptr()) };
         names.extend(string_list.into_iter().map(String::from));
@@ -103,31 +118,37 @@ impl DragData {
     }
     /// Set the link URL that is being dragged.
     pub fn set_link_url(&self, url: &str) {
+        ffi_event!("DragData::set_link_url");
         let url = CefString::new(url);
         unsafe{ self.0.set_link_url.unwrap()(self.as_ptr(), url.as_ptr()) };
     }
     /// Set the title associated with the link being dragged.
     pub fn set_link_title(&self, title: &str) {
+        ffi_event!("DragData::set_link_title");
         let title = CefString::new(title);
         unsafe{ self.0.set_link_title.unwrap()(self.as_ptr(), title.as_ptr()) };
     }
     /// Set the metadata associated with the link being dragged.
     pub fn set_link_metadata(&self, data: &str) {
+        ffi_event!("DragData::set_link_metadata");
         let data = CefString::new(data);
         unsafe{ self.0.set_link_metadata.unwrap()(self.as_ptr(), data.as_ptr()) };
     }
     /// Set the plain text fragment that is being dragged.
     pub fn set_fragment_text(&self, text: &str) {
+        ffi_event!("DragData::set_fragment_text");
         let text = CefString::new(text);
         unsafe{ self.0.set_fragment_text.unwrap()(self.as_ptr(), text.as_ptr()) };
     }
     /// Set the text/html fragment that is being dragged.
     pub fn set_fragment_html(&self, html: &str) {
+        ffi_event!("DragData::set_fragment_html");
         let html = CefString::new(html);
         unsafe{ self.0.set_fragment_html.unwrap()(self.as_ptr(), html.as_ptr()) };
     }
     /// Set the base URL that the fragment came from.
     pub fn set_fragment_base_url(&self, base_url: &str) {
+        ffi_event!("DragData::set_fragment_base_url");
         let base_url = CefString::new(base_url);
         unsafe{ self.0.set_fragment_base_url.unwrap()(self.as_ptr(), base_url.as_ptr()) };
     }
@@ -135,10 +156,12 @@ impl DragData {
     /// [`CefBrowserHost::DragTargetDragEnter`] as the web view does not allow us to
     /// drag in this kind of data.
     pub fn reset_file_contents(&self) {
+        ffi_event!("DragData::reset_file_contents");
         unsafe { self.0.reset_file_contents.unwrap()(self.as_ptr()) };
     }
     /// Add a file that is being dragged into the webview.
     pub fn add_file(&self, path: &str, display_name: &str) {
+        ffi_event!("DragData::add_file");
         let path = CefString::new(path);
         let display_name = CefString::new(display_name);
         unsafe{ self.0.add_file.unwrap()(self.as_ptr(), path.as_ptr(), display_name.as_ptr()) };
@@ -146,14 +169,17 @@ impl DragData {
     /// Get the image representation of drag data. May return `None` if no image
     /// representation is available.
     pub fn get_image(&self) -> Option<Image> {
+        ffi_event!("DragData::get_image");
         unsafe { Image::from_ptr(self.0.get_image.unwrap()(self.as_ptr())) }
     }
     /// Get the image hotspot (drag start location relative to image dimensions).
     pub fn get_image_hotspot(&self) -> Point {
+        ffi_event!("DragData::get_image_hotspot");
         unsafe { self.0.get_image_hotspot.unwrap()(self.as_ptr()).into() }
     }
     /// Returns true if an image representation of drag data is available.
     pub fn has_image(&self) -> bool {
+        ffi_event!("DragData::has_image");
         unsafe { self.0.has_image.unwrap()(self.as_ptr()) != 0 }
     }
 }
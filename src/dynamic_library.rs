@@ -0,0 +1,244 @@
+//! Loading libcef.so at runtime instead of linking against it.
+//!
+//! With the `dynamic-loading` feature every call into CEF goes through a table
+//! of function pointers that is resolved from the library loaded by
+//! [load_library], so the library can be chosen at runtime and a missing or
+//! incompatible one is reported as a [LibraryError]. cef-sys still links
+//! against libcef.so, so it's needed at build time, and the executable may
+//! still list it as a dependency. Check the `NEEDED` entries of `readelf -d`
+//! before relying on the executable to start without libcef.so installed.
+//!
+//! [load_library] should be called at the beginning of `main` in every process,
+//! before any other function of this crate:
+//!
+//! ```no_run
+//!  if let Err(err) = cef::load_library("/opt/myapp/cef/libcef.so") {
+//!      eprintln!("The browser runtime is not installed: {}", err);
+//!      std::process::exit(1);
+//!  }
+//!  let result = cef::execute_process(None, None).unwrap();
+//! ```
+//!
+//! Otherwise the library is loaded on first use, from the directory of the
+//! executable or the default library search path. If that fails,
+//! [crate::execute_process] and [crate::Context::initialize] return a
+//! [LibraryError]. Any other function of this crate that calls into CEF panics
+//! if no library could be loaded, or if it was unloaded with [unload_library].
+//!
+//! The `sandbox` feature doesn't call into libcef on Linux, so it can be
+//! combined with this one.
+
+use crate::capi::Functions;
+use std::{
+    ffi::{CStr, CString},
+    fmt,
+    os::{raw::c_void, unix::ffi::OsStrExt},
+    path::{Path, PathBuf},
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Errors returned by [load_library].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryError {
+    /// A library is already loaded.
+    AlreadyLoaded,
+    /// `dlopen` failed. Contains the message returned by `dlerror`.
+    Open(PathBuf, String),
+    /// The library doesn't export all functions used by this crate. This usually
+    /// means that it's not libcef.so or a version without some of the APIs.
+    MissingSymbols(PathBuf, Vec<&'static str>),
+}
+
+impl fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LibraryError::AlreadyLoaded => write!(f, "libcef is already loaded"),
+            LibraryError::Open(path, message) => write!(f, "{}: {}", path.display(), message),
+            LibraryError::MissingSymbols(path, symbols) => write!(f, "{}: missing symbols {}", path.display(), symbols.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+struct Library {
+    handle: *mut c_void,
+    functions: Functions,
+}
+
+static LIBRARY: AtomicPtr<Library> = AtomicPtr::new(null_mut());
+
+/// Load the CEF library at the specified `path` and resolve all functions used
+/// by this crate.
+///
+/// If this isn't called, the first call into CEF loads the library from the
+/// default locations. Only [crate::execute_process] and
+/// [crate::Context::initialize] report a failure to do so as an error, all other
+/// functions that call into CEF panic.
+pub fn load_library<P: AsRef<Path>>(path: P) -> Result<(), LibraryError> {
+    let path = path.as_ref();
+    if !LIBRARY.load(Ordering::SeqCst).is_null() {
+        return Err(LibraryError::AlreadyLoaded);
+    }
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| LibraryError::Open(path.to_owned(), err.to_string()))?;
+    let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+    if handle.is_null() {
+        return Err(LibraryError::Open(path.to_owned(), dlerror()));
+    }
+    let functions = match unsafe { Functions::resolve(handle) } {
+        Ok(functions) => functions,
+        Err(missing) => {
+            unsafe { libc::dlclose(handle) };
+            return Err(LibraryError::MissingSymbols(path.to_owned(), missing));
+        }
+    };
+    let library = Box::into_raw(Box::new(Library { handle, functions }));
+    if LIBRARY.compare_exchange(null_mut(), library, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        unsafe {
+            drop(Box::from_raw(library));
+            libc::dlclose(handle);
+        }
+        return Err(LibraryError::AlreadyLoaded);
+    }
+    Ok(())
+}
+
+/// Unload the CEF library that was previously loaded. Returns false if no
+/// library is loaded or a [crate::Context] is still alive. Calling into CEF
+/// afterwards loads the library again from the default locations.
+pub fn unload_library() -> bool {
+    if crate::misc_fns::CONTEXT_INITIALIZED.load(Ordering::SeqCst) {
+        return false;
+    }
+    let library = LIBRARY.swap(null_mut(), Ordering::SeqCst);
+    if library.is_null() {
+        return false;
+    }
+    // The function table is leaked, since other threads may still hold a
+    // reference to it.
+    unsafe { libc::dlclose((*library).handle) == 0 }
+}
+
+/// Returns true if [load_library] succeeded and the library wasn't unloaded since.
+pub fn is_library_loaded() -> bool {
+    !LIBRARY.load(Ordering::SeqCst).is_null()
+}
+
+/// Make sure that a library is loaded, loading it from the default locations if
+/// [load_library] wasn't called.
+pub(crate) fn ensure_loaded() -> Result<(), LibraryError> {
+    if is_library_loaded() {
+        return Ok(());
+    }
+    let next_to_executable = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("libcef.so")))
+        .filter(|path| path.exists());
+    let result = match next_to_executable {
+        Some(path) => load_library(path),
+        // Let dlopen search LD_LIBRARY_PATH and the system directories.
+        None => load_library("libcef.so"),
+    };
+    match result {
+        Err(LibraryError::AlreadyLoaded) => Ok(()),
+        result => result,
+    }
+}
+
+/// The function table of the loaded library. Loads it from the default
+/// locations if necessary, and panics if that fails.
+pub(crate) fn functions() -> &'static Functions {
+    let library = LIBRARY.load(Ordering::Acquire);
+    if !library.is_null() {
+        return unsafe { &(*library).functions };
+    }
+    if let Err(err) = ensure_loaded() {
+        panic!("cef: could not load libcef ({}), call cef::load_library first", err);
+    }
+    let library = LIBRARY.load(Ordering::Acquire);
+    if library.is_null() {
+        panic!("cef: libcef was unloaded while in use");
+    }
+    unsafe { &(*library).functions }
+}
+
+fn dlerror() -> String {
+    unsafe {
+        let message = libc::dlerror();
+        if message.is_null() {
+            "unknown error".to_owned()
+        } else {
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    // None of these load a library, so they don't interfere with each other.
+
+    #[test]
+    fn missing_file() {
+        let dir = TempDir::new("dynamic-library");
+        let path = dir.join("libcef.so");
+        match load_library(&path) {
+            Err(LibraryError::Open(err_path, message)) => {
+                assert_eq!(err_path, path);
+                assert!(message.contains("No such file"), "{}", message);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(!is_library_loaded());
+    }
+
+    #[test]
+    fn not_a_library() {
+        let dir = TempDir::new("dynamic-library");
+        let path = dir.write("libcef.so", "#!/bin/sh\necho not a library\n");
+        match load_library(&path) {
+            Err(LibraryError::Open(err_path, message)) => {
+                assert_eq!(err_path, path);
+                assert!(message.starts_with(&*path.to_string_lossy()), "{}", message);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(!is_library_loaded());
+    }
+
+    #[test]
+    fn nul_in_path() {
+        assert!(matches!(load_library("lib\0cef.so"), Err(LibraryError::Open(..))));
+    }
+
+    #[test]
+    fn other_library() {
+        // Loads fine, but has none of CEF's functions.
+        match load_library("libc.so.6") {
+            Err(LibraryError::MissingSymbols(path, symbols)) => {
+                assert_eq!(path, Path::new("libc.so.6"));
+                assert!(symbols.contains(&"cef_initialize"), "{:?}", symbols);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(!is_library_loaded());
+    }
+
+    #[test]
+    fn error_messages() {
+        let path = PathBuf::from("/opt/cef/libcef.so");
+        assert_eq!(LibraryError::AlreadyLoaded.to_string(), "libcef is already loaded");
+        assert_eq!(
+            LibraryError::Open(path.clone(), "file too short".to_owned()).to_string(),
+            "/opt/cef/libcef.so: file too short"
+        );
+        assert_eq!(
+            LibraryError::MissingSymbols(path, vec!["cef_initialize", "cef_shutdown"]).to_string(),
+            "/opt/cef/libcef.so: missing symbols cef_initialize, cef_shutdown"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::task::ThreadId;
+use std::fmt;
+
+/// Why a call into CEF failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The object is read-only, e.g. a value that was passed to a callback or
+    /// the global [crate::command_line::CommandLine].
+    ReadOnly,
+    /// The object is no longer valid, e.g. a value whose data has been
+    /// transferred to another object, or a V8 value whose context was released.
+    InvalidHandle,
+    /// The function has to be called on another thread.
+    WrongThread(ThreadId),
+    /// CEF reported an error with a message, e.g. a JavaScript exception.
+    Cef(String),
+    /// CEF reported a failure without a reason. Contains the name of the
+    /// failed operation.
+    Failed(&'static str),
+    /// CEF didn't respond in time, e.g. because the browser was closed.
+    TimedOut,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ReadOnly => write!(f, "the object is read-only"),
+            Error::InvalidHandle => write!(f, "the object is no longer valid"),
+            Error::WrongThread(thread_id) => {
+                write!(f, "must be called on the {:?} thread", thread_id)
+            }
+            Error::Cef(message) => write!(f, "{}", message),
+            Error::Failed(operation) => write!(f, "{} failed", operation),
+            Error::TimedOut => write!(f, "the operation timed out"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Result type of fallible CEF calls.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Turns the success flag returned by CEF into a [Result].
+pub(crate) fn check(success: bool, operation: &'static str) -> Result<()> {
+    if success {
+        Ok(())
+    } else {
+        Err(Error::Failed(operation))
+    }
+}
+
+/// Returns [Error::InvalidHandle] or [Error::ReadOnly] if an object with these
+/// properties can't be modified. `is_read_only` is only called on valid objects.
+pub(crate) fn check_writable(is_valid: bool, is_read_only: impl FnOnce() -> bool) -> Result<()> {
+    if !is_valid {
+        Err(Error::InvalidHandle)
+    } else if is_read_only() {
+        Err(Error::ReadOnly)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns [Error::WrongThread] if not called on `thread_id`.
+pub(crate) fn check_thread(thread_id: ThreadId) -> Result<()> {
+    if crate::task::TaskRunner::currently_on(thread_id) {
+        Ok(())
+    } else {
+        Err(Error::WrongThread(thread_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writable() {
+        assert_eq!(check_writable(true, || false), Ok(()));
+        assert_eq!(check_writable(true, || true), Err(Error::ReadOnly));
+        // Invalid objects aren't asked whether they're read-only.
+        assert_eq!(
+            check_writable(false, || panic!("called on an invalid object")),
+            Err(Error::InvalidHandle)
+        );
+    }
+
+    #[test]
+    fn failed() {
+        assert_eq!(check(true, "CommandLine::reset"), Ok(()));
+        let err = check(false, "CommandLine::reset").unwrap_err();
+        assert_eq!(err, Error::Failed("CommandLine::reset"));
+        assert_eq!(err.to_string(), "CommandLine::reset failed");
+    }
+}
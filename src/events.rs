@@ -232,7 +232,7 @@ impl Into<cef_touch_event_t> for TouchEvent {
 
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct WindowsKeyCode(i32);
+pub struct WindowsKeyCode(pub(crate) i32);
 
 #[allow(non_upper_case_globals)]
 impl WindowsKeyCode {
@@ -45,6 +45,7 @@ impl Extension {
     /// extension public key, if available, or on the extension path. See
     /// https://developer.chrome.com/extensions/manifest/key for details.
     pub fn get_identifier(&self) -> String {
+        ffi_event!("Extension::get_identifier");
         self.0
             .get_identifier
             .and_then(|get_identifier| unsafe { get_identifier(self.as_ptr()).as_mut() })
@@ -57,6 +58,7 @@ impl Extension {
     /// will be prefixed with PK_DIR_RESOURCES if a relative path was passed to
     /// [RequestContext::load_extension].
     pub fn get_path(&self) -> PathBuf {
+        ffi_event!("Extension::get_path");
         self.0
             .get_path
             .and_then(|get_path| unsafe { get_path(self.as_ptr()).as_mut() })
@@ -68,6 +70,7 @@ impl Extension {
     // Returns the extension manifest contents as a dictionary object.
     // See https://developer.chrome.com/extensions/manifest for details.
     pub fn get_manifest(&self) -> HashMap<String, StoredValue> {
+        ffi_event!("Extension::get_manifest");
         self.0
             .get_manifest
             .and_then(|get_manifest| unsafe { DictionaryValue::from_ptr(get_manifest(self.0.as_ptr())) })
@@ -77,6 +80,7 @@ impl Extension {
     /// Returns the handler for this extension. Will return None for internal
     /// extensions or if no handler was passed to [RequestContext::load_extension].
     pub fn get_handler(&self) -> Option<ExtensionHandler> {
+        ffi_event!("Extension::get_handler");
         self.0
             .get_handler
             .and_then(|get_handler| unsafe{ ExtensionHandler::from_ptr(get_handler(self.as_ptr()))})
@@ -86,6 +90,7 @@ impl Extension {
     /// [RequestContext::load_extension] documentation for more information
     /// about loader contexts. Must be called on the browser process UI thread.
     pub fn get_loader_context(&self) -> Option<RequestContext> {
+        ffi_event!("Extension::get_loader_context");
         self.0
             .get_loader_context
             .and_then(|get_loader_context| unsafe {
@@ -95,6 +100,7 @@ impl Extension {
     /// Returns `true` if this extension is currently loaded. Must be called on
     /// the browser process UI thread.
     pub fn is_loaded(&self) -> bool {
+        ffi_event!("Extension::is_loaded");
         self.0
             .is_loaded
             .map(|is_loaded| unsafe { is_loaded(self.0.as_ptr()) != 0 })
@@ -104,6 +110,7 @@ impl Extension {
     /// loaded. Will result in a call to
     /// [ExtensionHandler::on_extension_unloaded] on success.
     pub fn unload(&self) {
+        ffi_event!("Extension::unload");
         if let Some(unload) = self.0.unload {
             unsafe {
                 unload(self.0.as_ptr());
@@ -114,9 +121,11 @@ impl Extension {
 
 impl GetExtensionResourceCallback {
     pub fn cont(&self, stream: StreamReader) {
+        ffi_event!("GetExtensionResourceCallback::cont");
         unsafe { self.0.cont.unwrap()(self.as_ptr(), stream.into_raw()) }
     }
     pub fn cancel(&self) {
+        ffi_event!("GetExtensionResourceCallback::cancel");
         unsafe { self.0.cancel.unwrap()(self.as_ptr()) }
     }
 }
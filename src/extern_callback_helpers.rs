@@ -1,4 +1,6 @@
 use crate::{
+    browser::Browser,
+    frame::Frame,
     process::{ProcessId},
     string::{CefString, CefStringList},
     url_request::URLRequestStatus,
@@ -336,6 +338,114 @@ impl<T> CToRustType for *mut T {
     }
 }
 
+/// The value `cef_callback_impl!` returns to CEF when a callback panicked.
+/// Callbacks only return C integers, enums and pointers, for which this is zero
+/// (false, or the first enum variant) or null.
+pub trait CallbackReturn {
+    fn fallback() -> Self;
+}
+
+impl CallbackReturn for () {
+    fn fallback() -> Self {}
+}
+
+macro_rules! callback_return_zero {
+    ($($ty:ty),*) => {
+        $(
+            impl CallbackReturn for $ty {
+                fn fallback() -> Self {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+callback_return_zero!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl CallbackReturn for f64 {
+    fn fallback() -> Self {
+        0.0
+    }
+}
+
+impl<T> CallbackReturn for *const T {
+    fn fallback() -> Self {
+        std::ptr::null()
+    }
+}
+
+impl<T> CallbackReturn for *mut T {
+    fn fallback() -> Self {
+        std::ptr::null_mut()
+    }
+}
+
+/// Used by `cef_callback_impl!` to find the browser and frame identifiers among
+/// the arguments of a callback. Arguments of type [Browser] and [Frame] resolve
+/// to [CallbackIds], all others to [NoCallbackIds] through auto-ref.
+pub(crate) struct Probe<'a, T>(pub &'a T);
+
+pub(crate) trait CallbackIds {
+    fn browser_id(&self) -> Option<i32>;
+    fn frame_id(&self) -> Option<i64>;
+}
+
+impl CallbackIds for Probe<'_, Browser> {
+    fn browser_id(&self) -> Option<i32> {
+        Some(self.0.get_identifier())
+    }
+    fn frame_id(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl CallbackIds for Probe<'_, Option<Browser>> {
+    fn browser_id(&self) -> Option<i32> {
+        self.0.as_ref().map(Browser::get_identifier)
+    }
+    fn frame_id(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl CallbackIds for Probe<'_, Frame> {
+    fn browser_id(&self) -> Option<i32> {
+        None
+    }
+    fn frame_id(&self) -> Option<i64> {
+        self.0.get_identifier()
+    }
+}
+
+impl CallbackIds for Probe<'_, Option<Frame>> {
+    fn browser_id(&self) -> Option<i32> {
+        None
+    }
+    fn frame_id(&self) -> Option<i64> {
+        self.0.as_ref().and_then(Frame::get_identifier)
+    }
+}
+
+pub(crate) trait NoCallbackIds {
+    fn browser_id(&self) -> Option<i32> {
+        None
+    }
+    fn frame_id(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl<T> NoCallbackIds for &Probe<'_, T> {}
+
+/// Emits a `tracing` event for a call into CEF if the `tracing` feature is enabled.
+macro_rules! ffi_event {
+    ($method:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: "cef::ffi", method = $method);
+    };
+}
+
 macro_rules! cef_callback_impl {
     (impl$(<$($generic:ident $(: $bound:path)?),+>)? for $RefCounted:ty: $CType:ty {
         $(
@@ -355,11 +465,48 @@ macro_rules! cef_callback_impl {
                         fn inner(&$self, $($field_name: $field_ty),*) $(-> $ret)? $body
                     }
                     let this = unsafe { crate::refcounted::RefCounted::<$RefCounted>::wrapper(self_) };
-                    $(
-                        let $field_name: $field_ty = unsafe{ <$field_ty as crate::extern_callback_helpers::CToRustType>::from_c_type($field_name) };
-                    )*
-                    let ret = this.inner($($field_name),*);
-                    ret
+                    let callback_name = concat!(stringify!($RefCounted), "::", stringify!($fn_name));
+                    // Set once the arguments are converted, so a panic during the
+                    // conversion is reported without the ids.
+                    let panic_browser_id = std::cell::Cell::new(None::<i32>);
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        $(
+                            let $field_name: $field_ty = unsafe{ <$field_ty as crate::extern_callback_helpers::CToRustType>::from_c_type($field_name) };
+                        )*
+                        let collect_ids = cfg!(feature = "tracing") || crate::panic_policy::wants_browser_id();
+                        let (browser_id, frame_id) = if collect_ids {
+                            #[allow(unused_imports)]
+                            use crate::extern_callback_helpers::{CallbackIds as _, NoCallbackIds as _};
+                            (
+                                None::<i32>$(.or_else(|| (&crate::extern_callback_helpers::Probe(&$field_name)).browser_id()))*,
+                                None::<i64>$(.or_else(|| (&crate::extern_callback_helpers::Probe(&$field_name)).frame_id()))*,
+                            )
+                        } else {
+                            (None, None)
+                        };
+                        panic_browser_id.set(browser_id);
+                        #[cfg(feature = "tracing")]
+                        let span = tracing::trace_span!("cef_callback", callback = callback_name, browser_id = tracing::field::Empty, frame_id = tracing::field::Empty);
+                        #[cfg(feature = "tracing")]
+                        {
+                            if let Some(browser_id) = browser_id {
+                                span.record("browser_id", &browser_id);
+                            }
+                            if let Some(frame_id) = frame_id {
+                                span.record("frame_id", &frame_id);
+                            }
+                        }
+                        #[cfg(feature = "tracing")]
+                        let _enter = span.enter();
+                        this.inner($($field_name),*)
+                    }));
+                    match result {
+                        Ok(ret) => ret,
+                        Err(payload) => {
+                            crate::panic_policy::handle_panic(callback_name, panic_browser_id.get(), payload);
+                            crate::extern_callback_helpers::CallbackReturn::fallback()
+                        }
+                    }
                 }
             )*
         }
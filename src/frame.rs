@@ -7,7 +7,8 @@ use crate::{
     v8context::V8Context,
     process::{ProcessId, ProcessMessage},
 };
-use cef_sys::{cef_frame_t, cef_string_userfree_utf16_free};
+use cef_sys::cef_frame_t;
+use crate::capi::cef_string_userfree_utf16_free;
 
 ref_counted_ptr! {
     /// Structure used to represent a frame in the browser window. When used in the
@@ -20,6 +21,7 @@ ref_counted_ptr! {
 impl Frame {
     /// True if this object is currently attached to a valid frame.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("Frame::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.0.as_ptr()) } != 0)
@@ -27,6 +29,7 @@ impl Frame {
     }
     /// Execute undo in this frame.
     pub fn undo(&self) {
+        ffi_event!("Frame::undo");
         if let Some(undo) = self.0.undo {
             unsafe {
                 undo(self.0.as_ptr());
@@ -35,6 +38,7 @@ impl Frame {
     }
     /// Execute redo in this frame.
     pub fn redo(&self) {
+        ffi_event!("Frame::redo");
         if let Some(redo) = self.0.redo {
             unsafe {
                 redo(self.0.as_ptr());
@@ -43,6 +47,7 @@ impl Frame {
     }
     /// Execute cut in this frame.
     pub fn cut(&self) {
+        ffi_event!("Frame::cut");
         if let Some(cut) = self.0.cut {
             unsafe {
                 cut(self.0.as_ptr());
@@ -51,6 +56,7 @@ impl Frame {
     }
     /// Execute copy in this frame.
     pub fn copy(&self) {
+        ffi_event!("Frame::copy");
         if let Some(copy) = self.0.copy {
             unsafe {
                 copy(self.0.as_ptr());
@@ -59,6 +65,7 @@ impl Frame {
     }
     /// Execute paste in this frame.
     pub fn paste(&self) {
+        ffi_event!("Frame::paste");
         if let Some(paste) = self.0.paste {
             unsafe {
                 paste(self.0.as_ptr());
@@ -67,6 +74,7 @@ impl Frame {
     }
     /// Execute delete in this frame.
     pub fn del(&self) {
+        ffi_event!("Frame::del");
         if let Some(del) = self.0.del {
             unsafe {
                 del(self.0.as_ptr());
@@ -75,6 +83,7 @@ impl Frame {
     }
     /// Execute select all in this frame.
     pub fn select_all(&self) {
+        ffi_event!("Frame::select_all");
         if let Some(select_all) = self.0.select_all {
             unsafe {
                 select_all(self.0.as_ptr());
@@ -85,6 +94,7 @@ impl Frame {
     /// default text viewing application. This function can only be called from the
     /// browser process.
     pub fn view_source(&self) {
+        ffi_event!("Frame::view_source");
         if let Some(view_source) = self.0.view_source {
             unsafe {
                 view_source(self.0.as_ptr());
@@ -94,6 +104,7 @@ impl Frame {
     /// Retrieve this frame's HTML source as a string sent to the specified
     /// visitor.
     pub fn get_source(&self, visitor: StringVisitor) {
+        ffi_event!("Frame::get_source");
         if let Some(get_source) = self.0.get_source {
             unsafe {
                 get_source(self.0.as_ptr(), visitor.into_raw());
@@ -103,6 +114,7 @@ impl Frame {
     /// Retrieve this frame's display text as a string sent to the specified
     /// visitor.
     pub fn get_text(&self, visitor: StringVisitor) {
+        ffi_event!("Frame::get_text");
         if let Some(get_text) = self.0.get_text {
             unsafe {
                 get_text(self.0.as_ptr(), visitor.into_raw());
@@ -111,6 +123,7 @@ impl Frame {
     }
     /// Load the request represented by the |request| object.
     pub fn load_request(&self, request: Request) {
+        ffi_event!("Frame::load_request");
         if let Some(load_request) = self.0.load_request {
             unsafe {
                 load_request(self.0.as_ptr(), request.as_ptr());
@@ -119,6 +132,7 @@ impl Frame {
     }
     /// Load the specified `url`.
     pub fn load_url(&self, url: &str) {
+        ffi_event!("Frame::load_url");
         if let Some(load_url) = self.0.load_url {
             unsafe {
                 load_url(self.0.as_ptr(), CefString::new(url).as_ptr());
@@ -131,6 +145,7 @@ impl Frame {
     /// error.  The `start_line` parameter is the base line number to use for error
     /// reporting.
     pub fn execute_java_script(&self, code: &str, script_url: &str, start_line: i32) {
+        ffi_event!("Frame::execute_java_script");
         if let Some(execute_java_script) = self.0.execute_java_script {
             unsafe {
                 execute_java_script(
@@ -144,6 +159,7 @@ impl Frame {
     }
     /// Returns true if this is the main (top-level) frame.
     pub fn is_main(&self) -> bool {
+        ffi_event!("Frame::is_main");
         if let Some(is_main) = self.0.is_main {
             unsafe { is_main(self.0.as_ptr()) != 0 }
         } else {
@@ -152,6 +168,7 @@ impl Frame {
     }
     /// Returns true if this is the focused frame.
     pub fn is_focused(&self) -> bool {
+        ffi_event!("Frame::is_focused");
         if let Some(is_focused) = self.0.is_focused {
             unsafe { is_focused(self.0.as_ptr()) != 0 }
         } else {
@@ -164,6 +181,7 @@ impl Frame {
     /// parent hierarchy. The main (top-level) frame will always have an None name
     /// value.
     pub fn get_name(&self) -> Option<String> {
+        ffi_event!("Frame::get_name");
         if let Some(get_name) = self.0.get_name {
             let name = unsafe { get_name(self.0.as_ptr()) };
             let result = unsafe { CefString::from_ptr(name) }.map(String::from);
@@ -180,6 +198,7 @@ impl Frame {
     /// Returns the globally unique identifier for this frame or None if the
     /// underlying frame does not yet exist.
     pub fn get_identifier(&self) -> Option<i64> {
+        ffi_event!("Frame::get_identifier");
         if let Some(get_identifier) = self.0.get_identifier {
             let id = unsafe { get_identifier(self.0.as_ptr()) };
             if id < 0 {
@@ -194,6 +213,7 @@ impl Frame {
     /// Returns the parent of this frame or None if this is the main (top-level)
     /// frame.
     pub fn get_parent(&self) -> Option<Frame> {
+        ffi_event!("Frame::get_parent");
         if let Some(get_parent) = self.0.get_parent {
             unsafe { Frame::from_ptr(get_parent(self.0.as_ptr())) }
         } else {
@@ -202,6 +222,7 @@ impl Frame {
     }
     /// Returns the URL currently loaded in this frame.
     pub fn get_url(&self) -> String {
+        ffi_event!("Frame::get_url");
         self.0
             .get_url
             .and_then(|get_url| unsafe { get_url(self.as_ptr()).as_mut() })
@@ -214,18 +235,21 @@ impl Frame {
     }
     /// Returns the browser that this frame belongs to.
     pub fn get_browser(&self) -> Browser {
+        ffi_event!("Frame::get_browser");
         let browser = unsafe { self.0.get_browser.unwrap()(self.0.as_ptr()) };
         unsafe { Browser::from_ptr(browser).expect("CEF: Frame without a browser!") }
     }
     /// Get the V8 context associated with the frame. This function can only be
     /// called from the render process.
     pub fn get_v8context(&self) -> V8Context {
+        ffi_event!("Frame::get_v8context");
         let context = unsafe { self.0.get_v8context.unwrap()(self.0.as_ptr()) };
         unsafe { V8Context::from_ptr(context).expect("CEF: Frame without a V8 context!") }
     }
     /// Visit the DOM document. This function can only be called from the render
     /// process.
     pub fn visit_dom(&self, visitor: DOMVisitor) {
+        ffi_event!("Frame::visit_dom");
         if let Some(visit_dom) = self.0.visit_dom {
             unsafe { visit_dom(self.0.as_ptr(), visitor.into_raw()) };
         }
@@ -255,6 +279,7 @@ impl Frame {
         request: Request,
         client: URLRequestClient,
     ) -> URLRequest {
+        ffi_event!("Frame::create_urlrequest");
         unsafe {
             let urlrequest = self.0.create_urlrequest.unwrap()(
                 self.0.as_ptr(),
@@ -273,6 +298,7 @@ impl Frame {
         &self,
         message: ProcessMessage,
     ) {
+        ffi_event!("Frame::send_process_message");
         if let Some(send_process_message) = self.0.send_process_message {
             let target_process = match crate::process_type() {
                 crate::ProcessType::Browser => ProcessId::Renderer,
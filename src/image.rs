@@ -1,4 +1,5 @@
-use cef_sys::{cef_alpha_type_t, cef_color_type_t, cef_image_create, cef_image_t};
+use cef_sys::{cef_alpha_type_t, cef_color_type_t, cef_image_t};
+use crate::capi::cef_image_create;
 
 use crate::values::BinaryValue;
 
@@ -58,11 +59,13 @@ impl Image {
     /// Create a new [Image]. It will initially be empty. Use the add_*() functions
     /// to add representations at different scale factors.
     pub fn new() -> Self {
+        ffi_event!("Image::new");
         unsafe { Self::from_ptr(cef_image_create()).unwrap() }
     }
 
     /// Returns true if this [Image] is empty.
     pub fn is_empty(&self) -> bool {
+        ffi_event!("Image::is_empty");
         self.0
             .is_empty
             .map(|is_empty| unsafe { is_empty(self.as_ptr()) != 0 })
@@ -71,6 +74,7 @@ impl Image {
     // Returns true if this [Image] and `that` [Image] share the same underlying
     // storage. Will also return true if both images are empty.
     pub fn is_same(&self, that: &Self) -> bool {
+        ffi_event!("Image::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.as_ptr()) != 0 })
@@ -90,6 +94,7 @@ impl Image {
         alpha_type: AlphaType,
         pixel_data: &[u8],
     ) -> bool {
+        ffi_event!("Image::add_bitmap");
         self.0
             .add_bitmap
             .map(|add_bitmap| unsafe {
@@ -110,6 +115,7 @@ impl Image {
     /// data. Any alpha transparency in the PNG data will
     /// be maintained.
     pub fn add_png(&self, scale_factor: f32, png_data: &[u8]) -> bool {
+        ffi_event!("Image::add_png");
         self.0
             .add_png
             .map(|add_png| unsafe {
@@ -126,6 +132,7 @@ impl Image {
     /// image data. The JPEG format does not support
     /// transparency so the alpha byte will be set to `0xFF` for all pixels.
     pub fn add_jpeg(&self, scale_factor: f32, jpeg_data: &[u8]) -> bool {
+        ffi_event!("Image::add_jpeg");
         self.0
             .add_jpeg
             .map(|add_jpeg| unsafe {
@@ -140,6 +147,7 @@ impl Image {
     }
     /// Returns the image width in density independent pixel (DIP) units.
     pub fn get_width(&self) -> usize {
+        ffi_event!("Image::get_width");
         self.0
             .get_width
             .map(|get_width| unsafe { get_width(self.as_ptr()) })
@@ -147,6 +155,7 @@ impl Image {
     }
     /// Returns the image height in density independent pixel (DIP) units.
     pub fn get_height(&self) -> usize {
+        ffi_event!("Image::get_height");
         self.0
             .get_height
             .map(|get_height| unsafe { get_height(self.as_ptr()) })
@@ -155,6 +164,7 @@ impl Image {
     /// Returns true if this image contains a representation for
     /// `scale_factor`.
     pub fn has_representation(&self, scale_factor: f32) -> bool {
+        ffi_event!("Image::has_representation");
         self.0
             .has_representation
             .map(|has_representation| unsafe {
@@ -164,6 +174,7 @@ impl Image {
     }
     /// Removes the representation for `scale_factor`. Returns true on success.
     pub fn remove_representation(&self, scale_factor: f32) -> bool {
+        ffi_event!("Image::remove_representation");
         self.0
             .remove_representation
             .map(|remove_representation| unsafe {
@@ -174,6 +185,7 @@ impl Image {
     /// Returns information for the representation that most closely matches
     /// `scale_factor`.
     pub fn get_representation_info(&self, scale_factor: f32) -> Option<RepresentationInfo> {
+        ffi_event!("Image::get_representation_info");
         self.0
             .get_representation_info
             .and_then(|get_representation_info| {
@@ -209,6 +221,7 @@ impl Image {
         color_type: ColorType,
         alpha_type: AlphaType,
     ) -> Option<BinaryImage> {
+        ffi_event!("Image::get_as_bitmap");
         self.0.get_as_bitmap.and_then(|get_as_bitmap| {
             let mut pixel_width = 0;
             let mut pixel_height = 0;
@@ -233,6 +246,7 @@ impl Image {
     /// `with_transparency` is true any alpha transparency in the image will be
     /// represented in the resulting PNG data.
     pub fn get_as_png(&self, scale_factor: f32, with_transparency: bool) -> Option<BinaryImage> {
+        ffi_event!("Image::get_as_png");
         self.0.get_as_png.and_then(|get_as_png| {
             let mut pixel_width = 0;
             let mut pixel_height = 0;
@@ -257,6 +271,7 @@ impl Image {
     /// highest. The JPEG format does not support alpha transparency and the alpha
     /// channel, if any, will be discarded.
     pub fn get_as_jpeg(&self, scale_factor: f32, quality: u8) -> Option<BinaryImage> {
+        ffi_event!("Image::get_as_jpeg");
         self.0.get_as_jpeg.and_then(|get_as_jpeg| {
             let mut pixel_width = 0;
             let mut pixel_height = 0;
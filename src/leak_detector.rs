@@ -0,0 +1,257 @@
+//! Tracking of reference-counted objects, for finding the ones that leak.
+//!
+//! Enabled by the `leak-detection` feature. Two kinds of objects are counted
+//! per type:
+//! - handles to CEF objects that are held on the Rust side, like [crate::v8context::V8Value],
+//! - objects implemented in Rust that are handed to CEF, like the wrappers
+//!   around handler callbacks.
+//!
+//! CEF asserts at shutdown if any of them are still alive. Dropping the
+//! [crate::Context] therefore calls [report] before shutting CEF down. With the
+//! `leak-backtraces` feature, the report also contains where each leaked
+//! Rust-implemented object was created.
+
+use crate::refcounted::RefCounter;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::{collections::HashMap, fmt};
+
+/// What kind of object is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ObjectKind {
+    /// A reference to a CEF object that is held by Rust code.
+    Handle,
+    /// An object implemented in Rust that may be referenced by CEF.
+    Object,
+}
+
+/// The number of live objects of a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveCount {
+    pub kind: ObjectKind,
+    pub type_name: &'static str,
+    pub count: usize,
+}
+
+impl fmt::Display for LiveCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            ObjectKind::Handle => "handle",
+            ObjectKind::Object => "object",
+        };
+        write!(f, "{} x {} {}", self.count, kind, self.type_name)
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    // Signed, as handles written to out-parameters can be released without
+    // ever having been counted.
+    counts: HashMap<(ObjectKind, &'static str), isize>,
+    #[cfg(feature = "leak-backtraces")]
+    backtraces: HashMap<usize, (&'static str, backtrace::Backtrace)>,
+}
+
+impl Registry {
+    fn add(&mut self, kind: ObjectKind, type_name: &'static str, delta: isize) {
+        *self.counts.entry((kind, type_name)).or_insert(0) += delta;
+    }
+
+    fn live_counts(&self) -> Vec<LiveCount> {
+        let mut counts: Vec<_> = self
+            .counts
+            .iter()
+            .filter(|(_, &count)| count > 0)
+            .map(|(&(kind, type_name), &count)| LiveCount {
+                kind,
+                type_name,
+                count: count as usize,
+            })
+            .collect();
+        counts.sort_by(|a, b| (a.kind, a.type_name).cmp(&(b.kind, b.type_name)));
+        counts
+    }
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+fn add(kind: ObjectKind, type_name: &'static str, delta: isize) {
+    REGISTRY.lock().add(kind, type_name, delta);
+}
+
+pub(crate) fn handle_acquired<C: RefCounter>() {
+    add(ObjectKind::Handle, C::TYPE_NAME, 1);
+}
+
+pub(crate) fn handle_released<C: RefCounter>() {
+    add(ObjectKind::Handle, C::TYPE_NAME, -1);
+}
+
+#[cfg_attr(not(feature = "leak-backtraces"), allow(unused_variables))]
+pub(crate) fn object_created<W>(address: usize) {
+    let type_name = std::any::type_name::<W>();
+    add(ObjectKind::Object, type_name, 1);
+    #[cfg(feature = "leak-backtraces")]
+    REGISTRY
+        .lock()
+        .backtraces
+        .insert(address, (type_name, backtrace::Backtrace::new_unresolved()));
+}
+
+#[cfg_attr(not(feature = "leak-backtraces"), allow(unused_variables))]
+pub(crate) fn object_destroyed<W>(address: usize) {
+    add(ObjectKind::Object, std::any::type_name::<W>(), -1);
+    #[cfg(feature = "leak-backtraces")]
+    REGISTRY.lock().backtraces.remove(&address);
+}
+
+/// Returns all types with live objects, sorted by kind and type name.
+pub fn live_counts() -> Vec<LiveCount> {
+    REGISTRY.lock().live_counts()
+}
+
+/// The lines logged by [report] for `counts`.
+fn report_lines(counts: &[LiveCount]) -> Vec<String> {
+    let header = format!(
+        "{} types of reference-counted objects are still alive:",
+        counts.len()
+    );
+    std::iter::once(header)
+        .chain(counts.iter().map(|count| format!("  {}", count)))
+        .collect()
+}
+
+/// Log all live objects as warnings. Returns true if there were any.
+pub fn report() -> bool {
+    let counts = live_counts();
+    if counts.is_empty() {
+        return false;
+    }
+    for line in report_lines(&counts) {
+        log::warn!("{}", line);
+    }
+    #[cfg(feature = "leak-backtraces")]
+    {
+        let mut registry = REGISTRY.lock();
+        for (address, (type_name, backtrace)) in registry.backtraces.iter_mut() {
+            backtrace.resolve();
+            log::warn!("{} at {:#x} created at:\n{:?}", type_name, address, backtrace);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cef_sys::cef_base_ref_counted_t;
+
+    #[repr(C)]
+    struct FakeCef {
+        base: cef_base_ref_counted_t,
+    }
+    ref_counter!(@named "leak_detector::tests::FakeHandle", FakeCef);
+
+    struct FakeObject;
+
+    fn live(kind: ObjectKind, type_name: &str) -> Option<usize> {
+        live_counts()
+            .into_iter()
+            .find(|count| count.kind == kind && count.type_name == type_name)
+            .map(|count| count.count)
+    }
+
+    #[test]
+    fn counts_per_type() {
+        let mut registry = Registry::default();
+        registry.add(ObjectKind::Object, "Client", 1);
+        registry.add(ObjectKind::Handle, "V8Value", 1);
+        registry.add(ObjectKind::Handle, "V8Value", 1);
+        registry.add(ObjectKind::Handle, "Browser", 1);
+        registry.add(ObjectKind::Handle, "Browser", -1);
+        registry.add(ObjectKind::Handle, "Frame", 1);
+        assert_eq!(
+            registry.live_counts(),
+            vec![
+                LiveCount {
+                    kind: ObjectKind::Handle,
+                    type_name: "Frame",
+                    count: 1,
+                },
+                LiveCount {
+                    kind: ObjectKind::Handle,
+                    type_name: "V8Value",
+                    count: 2,
+                },
+                LiveCount {
+                    kind: ObjectKind::Object,
+                    type_name: "Client",
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_leaks() {
+        let mut registry = Registry::default();
+        assert_eq!(registry.live_counts(), vec![]);
+        registry.add(ObjectKind::Handle, "V8Value", 1);
+        registry.add(ObjectKind::Object, "Client", 1);
+        registry.add(ObjectKind::Handle, "V8Value", -1);
+        registry.add(ObjectKind::Object, "Client", -1);
+        // Released without having been counted.
+        registry.add(ObjectKind::Handle, "Browser", -1);
+        assert_eq!(registry.live_counts(), vec![]);
+        // The release makes up for the next handle.
+        registry.add(ObjectKind::Handle, "Browser", 1);
+        assert_eq!(registry.live_counts(), vec![]);
+    }
+
+    #[test]
+    fn report_contents() {
+        let counts = [
+            LiveCount {
+                kind: ObjectKind::Handle,
+                type_name: "V8Value",
+                count: 2,
+            },
+            LiveCount {
+                kind: ObjectKind::Object,
+                type_name: "cef::client::ClientWrapper",
+                count: 1,
+            },
+        ];
+        assert_eq!(
+            report_lines(&counts),
+            vec![
+                "2 types of reference-counted objects are still alive:",
+                "  2 x handle V8Value",
+                "  1 x object cef::client::ClientWrapper",
+            ]
+        );
+    }
+
+    #[test]
+    fn global_counters() {
+        // Other tests may create objects concurrently, so only the types of this
+        // test are checked.
+        let handle = FakeCef::TYPE_NAME;
+        let object = std::any::type_name::<FakeObject>();
+        handle_acquired::<FakeCef>();
+        handle_acquired::<FakeCef>();
+        object_created::<FakeObject>(0x1000);
+        assert_eq!(live(ObjectKind::Handle, handle), Some(2));
+        assert_eq!(live(ObjectKind::Object, object), Some(1));
+        assert_eq!(live(ObjectKind::Object, handle), None);
+
+        handle_released::<FakeCef>();
+        object_destroyed::<FakeObject>(0x1000);
+        assert_eq!(live(ObjectKind::Handle, handle), Some(1));
+        assert_eq!(live(ObjectKind::Object, object), None);
+        handle_released::<FakeCef>();
+        assert_eq!(live(ObjectKind::Handle, handle), None);
+    }
+}
@@ -5,9 +5,16 @@ mod refcounted;
 #[macro_use]
 mod extern_callback_helpers;
 pub mod helper_traits;
+pub mod error;
+pub use error::{Error, Result};
 mod ptr_hash;
+mod capi;
 mod misc_fns;
 pub use misc_fns::*;
+#[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+mod dynamic_library;
+#[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+pub use dynamic_library::{is_library_loaded, load_library, unload_library, LibraryError};
 
 pub mod accessibility_handler;
 pub mod string;
@@ -38,11 +45,17 @@ pub mod image;
 
 pub mod command_line;
 pub mod app;
+pub mod runtime;
+pub use runtime::run;
 
 pub mod sandbox;
 mod main_args;
 pub mod scheme;
 pub mod settings;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(target_os = "linux")]
+pub mod distribution;
 pub mod color;
 pub mod events;
 pub mod drag;
@@ -57,8 +70,19 @@ pub mod request_handler;
 pub mod stream;
 pub mod ssl;
 pub mod task;
+pub mod message_pump;
+pub mod osr;
+pub use task::spawn_on;
+#[cfg(target_os = "linux")]
+pub mod ui_thread;
 pub mod logging;
+pub mod panic_policy;
+#[cfg(feature = "leak-detection")]
+pub mod leak_detector;
+pub mod version;
 mod send_protector;
+#[cfg(test)]
+mod test_util;
 
 /// Return value types.
 #[repr(C)]
@@ -1,8 +1,12 @@
-use cef_sys::{cef_log, cef_log_severity_t, cef_get_min_log_level};
+use cef_sys::cef_log_severity_t;
+use crate::capi::{cef_log, cef_get_min_log_level};
 use log::{Record, Level, Metadata};
 use std::borrow::Cow;
 use std::ffi::CString;
 
+mod log_file;
+pub use log_file::*;
+
 /// Integration of Rust's log crate with CEF. Example usage:
 ///
 /// ```rust
@@ -58,6 +62,10 @@ impl log::Log for Logger {
     }
 
     fn log(&self, record: &Record) {
+        // Don't send messages forwarded from CEF's log file back into it.
+        if record.target() == FORWARDED_TARGET {
+            return;
+        }
         if self.enabled(record.metadata()) {
             let log_module_path = record.module_path().or(record.module_path_static());
             let log_level = record.metadata().level();
@@ -0,0 +1,400 @@
+use crate::settings::Settings;
+use log::Level;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// Target of all records forwarded by [LogFileForwarder]. [Logger] ignores
+/// them, so they're not sent back into CEF.
+pub const FORWARDED_TARGET: &str = "cef::chromium";
+
+/// Prefix of the module paths of records forwarded by [LogFileForwarder].
+const MODULE_PATH_PREFIX: &str = "chromium";
+
+/// Follows CEF's log file and re-emits its messages through [log]. Example usage:
+///
+/// ```ignore
+///  let settings = Settings::new().log_file("/tmp/cef.log");
+///  let forwarder = cef::logging::LogFileForwarder::from_settings(&settings).unwrap().unwrap();
+///  let context = cef::Context::initialize(&settings, Some(app), None).unwrap();
+/// ```
+///
+/// Messages are parsed from Chromium's `[pid:tid:date/time:LEVEL:file(line)] message`
+/// format. The target is [FORWARDED_TARGET] and the module path is derived from
+/// the source file, so `../../gpu/ipc/service/gpu_init.cc` becomes
+/// `chromium::gpu::ipc::service::gpu_init`. Lines that don't start a new message
+/// are appended to the previous one, which is therefore only forwarded once the
+/// next message starts or no new data was written for one interval. Messages
+/// that were logged from Rust through [Logger] are skipped, since they have
+/// already been seen by [log].
+pub struct LogFileForwarder {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LogFileForwarder {
+    /// Start following the log file configured in [Settings::log_file]. Returns
+    /// None if it's not set.
+    pub fn from_settings(settings: &Settings) -> Option<io::Result<LogFileForwarder>> {
+        settings.log_file.as_ref().map(LogFileForwarder::start)
+    }
+    /// Start following the log file at `path`, beginning with its current content.
+    /// The file doesn't have to exist yet.
+    pub fn start<P: AsRef<Path>>(path: P) -> io::Result<LogFileForwarder> {
+        LogFileForwarder::start_with_interval(path, Duration::from_millis(100))
+    }
+    /// Like [LogFileForwarder::start], but checks for new messages every `interval`.
+    pub fn start_with_interval<P: AsRef<Path>>(path: P, interval: Duration) -> io::Result<LogFileForwarder> {
+        let path = path.as_ref().to_owned();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("cef-log-file".to_owned())
+            .spawn(move || {
+                let mut tail = Tail::new(path);
+                while !thread_stop.load(Ordering::SeqCst) {
+                    tail.poll();
+                    std::thread::sleep(interval);
+                }
+                tail.poll();
+                tail.flush();
+            })?;
+        Ok(LogFileForwarder {
+            stop,
+            thread: Some(thread),
+        })
+    }
+    /// Forward the remaining messages and stop following the file.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for LogFileForwarder {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+struct Tail {
+    path: PathBuf,
+    offset: u64,
+    assembler: Assembler,
+}
+
+impl Tail {
+    fn new(path: PathBuf) -> Tail {
+        Tail {
+            path,
+            offset: 0,
+            assembler: Assembler::default(),
+        }
+    }
+
+    fn poll(&mut self) {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return,
+        };
+        let len = match file.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return,
+        };
+        if len < self.offset {
+            // The file has been truncated or replaced, start over.
+            self.offset = 0;
+            self.flush();
+            self.assembler = Assembler::default();
+        }
+        if len == self.offset {
+            // Nothing new, so the last message is complete.
+            self.flush();
+            return;
+        }
+        if file.seek(SeekFrom::Start(self.offset)).is_err() {
+            return;
+        }
+        let mut data = Vec::new();
+        match file.take(len - self.offset).read_to_end(&mut data) {
+            Ok(read) => self.offset += read as u64,
+            Err(_) => return,
+        }
+        self.assembler.push(&data, Message::emit);
+    }
+
+    fn flush(&mut self) {
+        if let Some(message) = self.assembler.flush() {
+            message.emit();
+        }
+    }
+}
+
+/// Splits the contents of the log file into messages. Data may end in the
+/// middle of a line or a multi-line message, which are kept until more data
+/// arrives.
+#[derive(Default)]
+struct Assembler {
+    /// Bytes of an incomplete last line.
+    partial: Vec<u8>,
+    /// The last message, which may still get continuation lines.
+    pending: Option<Message>,
+}
+
+impl Assembler {
+    /// Appends `data` and calls `emit` for every message that is complete.
+    fn push(&mut self, data: &[u8], mut emit: impl FnMut(Message)) {
+        self.partial.extend_from_slice(data);
+        let complete = match self.partial.iter().rposition(|&b| b == b'\n') {
+            Some(end) => end + 1,
+            None => return,
+        };
+        let rest = self.partial.split_off(complete);
+        let data = std::mem::replace(&mut self.partial, rest);
+
+        let text = String::from_utf8_lossy(&data);
+        for line in text.lines() {
+            match Message::parse(line) {
+                Some(message) => {
+                    if let Some(previous) = self.pending.replace(message) {
+                        emit(previous);
+                    }
+                }
+                // Continuation of a multi-line message.
+                None => match &mut self.pending {
+                    Some(message) => {
+                        message.text.push('\n');
+                        message.text.push_str(line);
+                    }
+                    None if !line.is_empty() => emit(Message::unparsed(line)),
+                    None => {}
+                },
+            }
+        }
+    }
+
+    /// Returns the last message, assuming that no more lines belong to it.
+    fn flush(&mut self) -> Option<Message> {
+        self.pending.take()
+    }
+}
+
+/// A message parsed from the log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Message {
+    pid: Option<String>,
+    tid: Option<String>,
+    level: Level,
+    file: String,
+    line: Option<u32>,
+    text: String,
+}
+
+impl Message {
+    /// Parses the first line of a message: `[pid:tid:date/time:LEVEL:file(line)] message`.
+    /// The process id, thread id and timestamp are optional, depending on how
+    /// Chromium was configured.
+    fn parse(line: &str) -> Option<Message> {
+        if !line.starts_with('[') {
+            return None;
+        }
+        let end = line.find("] ")?;
+        let text = line[end + 2..].to_owned();
+        let mut parts: Vec<&str> = line[1..end].split(':').collect();
+        let location = parts.pop()?;
+        let level = parse_level(parts.pop()?)?;
+        let (file, line) = match location.rfind('(') {
+            Some(open) if location.ends_with(')') => {
+                (&location[..open], location[open + 1..location.len() - 1].parse().ok())
+            }
+            _ => (location, None),
+        };
+        // What's left is pid, tid and timestamp, in that order, each optional.
+        let ids: Vec<&str> = parts.into_iter().filter(|part| !part.contains('/')).collect();
+        Some(Message {
+            pid: ids.first().map(|&pid| pid.to_owned()),
+            tid: ids.get(1).map(|&tid| tid.to_owned()),
+            level,
+            file: file.to_owned(),
+            line,
+            text,
+        })
+    }
+
+    fn unparsed(line: &str) -> Message {
+        Message {
+            pid: None,
+            tid: None,
+            level: Level::Info,
+            file: String::new(),
+            line: None,
+            text: line.to_owned(),
+        }
+    }
+
+    fn emit(self) {
+        if self.file.ends_with(".rs") {
+            return;
+        }
+        let module_path = module_path(&self.file);
+        let logger = log::logger();
+        if !logger.enabled(self.record(&module_path, format_args!("")).metadata()) {
+            return;
+        }
+        match (&self.pid, &self.tid) {
+            (Some(pid), Some(tid)) => logger.log(&self.record(&module_path, format_args!("[{}:{}] {}", pid, tid, self.text))),
+            (Some(pid), None) => logger.log(&self.record(&module_path, format_args!("[{}] {}", pid, self.text))),
+            _ => logger.log(&self.record(&module_path, format_args!("{}", self.text))),
+        }
+    }
+
+    fn record<'a>(&'a self, module_path: &'a str, args: fmt::Arguments<'a>) -> log::Record<'a> {
+        log::Record::builder()
+            .args(args)
+            .level(self.level)
+            .target(FORWARDED_TARGET)
+            .module_path(Some(module_path))
+            .file(if self.file.is_empty() { None } else { Some(&self.file) })
+            .line(self.line)
+            .build()
+    }
+}
+
+fn parse_level(level: &str) -> Option<Level> {
+    match level {
+        "FATAL" | "ERROR" => Some(Level::Error),
+        "WARNING" => Some(Level::Warn),
+        "INFO" => Some(Level::Info),
+        "VERBOSE1" => Some(Level::Debug),
+        level if level.starts_with("VERBOSE") => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+/// `../../gpu/ipc/service/gpu_init.cc` -> `chromium::gpu::ipc::service::gpu_init`
+fn module_path(file: &str) -> String {
+    let mut path = MODULE_PATH_PREFIX.to_owned();
+    let components = file
+        .split(&['/', '\\'][..])
+        .filter(|&component| !component.is_empty() && component != "." && component != "..");
+    for component in components {
+        path.push_str("::");
+        path.push_str(component.split('.').next().unwrap_or(component));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(assembler: &mut Assembler, data: &str) -> Vec<Message> {
+        let mut messages = Vec::new();
+        assembler.push(data.as_bytes(), |message| messages.push(message));
+        messages
+    }
+
+    #[test]
+    fn parse_full_header() {
+        let message = Message::parse("[1234:5678:0101/120000.123456:WARNING:../../gpu/ipc/gpu_init.cc(443)] Hello: world").unwrap();
+        assert_eq!(
+            message,
+            Message {
+                pid: Some("1234".to_owned()),
+                tid: Some("5678".to_owned()),
+                level: Level::Warn,
+                file: "../../gpu/ipc/gpu_init.cc".to_owned(),
+                line: Some(443),
+                text: "Hello: world".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_optional_parts() {
+        let message = Message::parse("[0101/120000.123:ERROR:foo.cc(12)] no ids").unwrap();
+        assert_eq!((message.pid, message.tid), (None, None));
+        assert_eq!((message.level, message.line), (Level::Error, Some(12)));
+        assert_eq!(message.text, "no ids");
+
+        let message = Message::parse("[1234:INFO:foo.cc] pid only").unwrap();
+        assert_eq!((message.pid.as_deref(), message.tid), (Some("1234"), None));
+        assert_eq!((message.file.as_str(), message.line), ("foo.cc", None));
+
+        let message = Message::parse("[FATAL:foo.cc(1)] ").unwrap();
+        assert_eq!((message.level, message.text.as_str()), (Level::Error, ""));
+    }
+
+    #[test]
+    fn parse_levels() {
+        let level = |level| Message::parse(&format!("[{}:foo.cc(1)] x", level)).map(|message| message.level);
+        assert_eq!(level("INFO"), Some(Level::Info));
+        assert_eq!(level("VERBOSE1"), Some(Level::Debug));
+        assert_eq!(level("VERBOSE2"), Some(Level::Trace));
+        assert_eq!(level("NOTICE"), None);
+    }
+
+    #[test]
+    fn parse_rejects_other_lines() {
+        assert_eq!(Message::parse("continued line"), None);
+        assert_eq!(Message::parse("[no closing bracket"), None);
+        assert_eq!(Message::parse("[foo.cc(1)] no level"), None);
+        assert_eq!(Message::parse(""), None);
+    }
+
+    #[test]
+    fn module_paths() {
+        assert_eq!(module_path("../../gpu/ipc/service/gpu_init.cc"), "chromium::gpu::ipc::service::gpu_init");
+        assert_eq!(module_path(r"..\..\base\logging.cc"), "chromium::base::logging");
+        assert_eq!(module_path(""), "chromium");
+    }
+
+    #[test]
+    fn multi_line_message_waits_for_next_header() {
+        let mut assembler = Assembler::default();
+        assert_eq!(collect(&mut assembler, "[INFO:a.cc(1)] first\ncontinued\n"), vec![]);
+        assert_eq!(collect(&mut assembler, "still continued\n"), vec![]);
+
+        let messages = collect(&mut assembler, "[ERROR:b.cc(2)] second\n");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].text, "first\ncontinued\nstill continued");
+
+        let last = assembler.flush().unwrap();
+        assert_eq!((last.file.as_str(), last.text.as_str()), ("b.cc", "second"));
+        assert_eq!(assembler.flush(), None);
+    }
+
+    #[test]
+    fn partial_lines_are_kept() {
+        let mut assembler = Assembler::default();
+        assert_eq!(collect(&mut assembler, "[INFO:a.cc(1)] fir"), vec![]);
+        assert_eq!(collect(&mut assembler, "st\n[INFO:a.cc(2)] sec"), vec![]);
+        let messages = collect(&mut assembler, "ond\n[INFO:a.cc(3)] third\n");
+        let texts: Vec<_> = messages.iter().map(|message| message.text.as_str()).collect();
+        assert_eq!(texts, ["first", "second"]);
+        assert_eq!(assembler.flush().unwrap().text, "third");
+    }
+
+    #[test]
+    fn unparsed_lines_before_first_header() {
+        let mut assembler = Assembler::default();
+        let messages = collect(&mut assembler, "garbage\n\n[INFO:a.cc(1)] x\n");
+        assert_eq!(messages, vec![Message::unparsed("garbage")]);
+        assert_eq!(assembler.flush().unwrap().text, "x");
+    }
+}
@@ -0,0 +1,329 @@
+//! Integration of the CEF message loop into another event loop.
+//!
+//! With [Settings::external_message_pump] enabled, CEF calls
+//! [BrowserProcessHandlerCallbacks::on_schedule_message_pump_work] from any
+//! thread whenever it needs [Context::do_message_loop_work] to be called on the
+//! UI thread. [MessagePump] keeps track of those requests, coalesces them into a
+//! single deadline and wakes up the host event loop, which only has to sleep
+//! until [MessagePump::run_due_work] tells it to.
+//!
+//! With tokio, on a current-thread runtime running on the main thread:
+//!
+//! ```ignore
+//!  let notify = Arc::new(tokio::sync::Notify::new());
+//!  let pump = MessagePump::new({
+//!      let notify = notify.clone();
+//!      move || notify.notify_one()
+//!  });
+//!  let app = App::new(MyApp { browser_process_handler: BrowserProcessHandler::new(pump.handler()) });
+//!  let context = Context::initialize(&MessagePump::configure(settings), Some(app), None).unwrap();
+//!  loop {
+//!      let deadline = pump.run_due_work(&context);
+//!      tokio::select! {
+//!          _ = notify.notified() => {}
+//!          _ = tokio::time::sleep_until(deadline.into()) => {}
+//!      }
+//!  }
+//! ```
+//!
+//! With calloop, the wake function pings a `calloop::ping::Ping` source, and the
+//! loop is dispatched with a timeout of `pump.run_due_work(&context) - Instant::now()`.
+
+use crate::{
+    browser_process_handler::BrowserProcessHandlerCallbacks,
+    misc_fns::Context,
+    settings::Settings,
+};
+use parking_lot::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The longest time to go without calling [Context::do_message_loop_work], even
+/// if CEF didn't ask for it. Same as in cefclient.
+pub const MAX_DELAY: Duration = Duration::from_millis(1000 / 30);
+
+#[derive(Debug, Default)]
+struct Schedule {
+    /// Set if work was requested with a delay <= 0.
+    immediate: bool,
+    /// The pending delayed work. A new delayed request replaces it.
+    timer: Option<Instant>,
+}
+
+struct PumpState {
+    schedule: Mutex<Schedule>,
+    /// Set while [Context::do_message_loop_work] runs.
+    active: AtomicBool,
+    /// Set if [MessagePump::run_due_work] was called from within
+    /// [Context::do_message_loop_work], e.g. from a nested event loop.
+    reentrancy_detected: AtomicBool,
+    wake: Box<dyn Fn() + Send + Sync>,
+}
+
+/// Schedules [Context::do_message_loop_work] calls on a host event loop.
+#[derive(Clone)]
+pub struct MessagePump(Arc<PumpState>);
+
+impl MessagePump {
+    /// Create a new pump. `wake` is called from any thread whenever the deadline
+    /// moves closer and has to wake up the host event loop, so that it calls
+    /// [MessagePump::run_due_work] again.
+    pub fn new(wake: impl Fn() + Send + Sync + 'static) -> MessagePump {
+        MessagePump(Arc::new(PumpState {
+            schedule: Mutex::new(Schedule::default()),
+            active: AtomicBool::new(false),
+            reentrancy_detected: AtomicBool::new(false),
+            wake: Box::new(wake),
+        }))
+    }
+    /// Enable [Settings::external_message_pump], which is required for CEF to
+    /// request work through [BrowserProcessHandlerCallbacks::on_schedule_message_pump_work].
+    pub fn configure(settings: Settings) -> Settings {
+        settings.external_message_pump(true)
+    }
+    /// Returns browser process callbacks that only forward work requests to this
+    /// pump. If you need other browser process callbacks as well, call
+    /// [MessagePump::schedule_work] from your own implementation instead.
+    pub fn handler(&self) -> MessagePumpHandler {
+        MessagePumpHandler(self.clone())
+    }
+    /// Request a call to [Context::do_message_loop_work] after `delay_ms`
+    /// milliseconds, or as soon as possible if it's <= 0. Delays are clamped to
+    /// [MAX_DELAY]. A delayed request replaces the previous delayed one. Can be
+    /// called from any thread.
+    pub fn schedule_work(&self, delay_ms: i64) {
+        let wake = {
+            let mut schedule = self.0.schedule.lock();
+            let previous = deadline(&schedule);
+            if delay_ms <= 0 {
+                schedule.immediate = true;
+            } else {
+                let delay = Duration::from_millis(delay_ms as u64).min(MAX_DELAY);
+                schedule.timer = Some(Instant::now() + delay);
+            }
+            previous.map(|previous| deadline(&schedule) < Some(previous)).unwrap_or(true)
+        };
+        // Nothing to do if the host loop is already going to wake up earlier.
+        if wake {
+            (self.0.wake)();
+        }
+    }
+    /// Returns when [MessagePump::run_due_work] has to be called next.
+    pub fn next_deadline(&self) -> Instant {
+        let schedule = self.0.schedule.lock();
+        deadline(&schedule).unwrap_or_else(|| Instant::now() + MAX_DELAY)
+    }
+    /// Call [Context::do_message_loop_work] if it's due and return when this has to
+    /// be called next. Has to be called on the main thread, the one `context` was
+    /// created on.
+    ///
+    /// Calls from within [Context::do_message_loop_work] are ignored and followed
+    /// up by another call as soon as the outer one has returned.
+    pub fn run_due_work(&self, context: &Context) -> Instant {
+        self.run_due(|| context.do_message_loop_work())
+    }
+
+    fn run_due(&self, work: impl FnOnce()) -> Instant {
+        if self.0.active.load(Ordering::SeqCst) {
+            self.0.reentrancy_detected.store(true, Ordering::SeqCst);
+            return self.next_deadline();
+        }
+        let now = Instant::now();
+        let due = {
+            let mut schedule = self.0.schedule.lock();
+            let timer_due = schedule.timer.map(|timer| timer <= now).unwrap_or(false);
+            if timer_due {
+                schedule.timer = None;
+            }
+            // Not compared with `deadline`, which is always later than `now` for
+            // immediate work.
+            let due = schedule.immediate || timer_due;
+            schedule.immediate = false;
+            // Nothing scheduled, poll at the maximum delay to be safe.
+            if !due && schedule.timer.is_none() {
+                schedule.timer = Some(now + MAX_DELAY);
+            }
+            due
+        };
+        if due {
+            self.0.active.store(true, Ordering::SeqCst);
+            let _active = ActiveGuard(&self.0);
+            work();
+        }
+        self.next_deadline()
+    }
+}
+
+/// Resets [PumpState::active] when the work is done, even if it panicked, and
+/// schedules the work that was skipped because of reentrancy.
+struct ActiveGuard<'a>(&'a PumpState);
+
+impl Drop for ActiveGuard<'_> {
+    fn drop(&mut self) {
+        self.0.active.store(false, Ordering::SeqCst);
+        if self.0.reentrancy_detected.swap(false, Ordering::SeqCst) {
+            self.0.schedule.lock().immediate = true;
+        }
+    }
+}
+
+fn deadline(schedule: &Schedule) -> Option<Instant> {
+    if schedule.immediate {
+        Some(Instant::now())
+    } else {
+        schedule.timer
+    }
+}
+
+/// Browser process callbacks that forward
+/// [BrowserProcessHandlerCallbacks::on_schedule_message_pump_work] to a
+/// [MessagePump]. Returned by [MessagePump::handler].
+pub struct MessagePumpHandler(MessagePump);
+
+impl BrowserProcessHandlerCallbacks for MessagePumpHandler {
+    fn on_schedule_message_pump_work(&self, delay_ms: i64) {
+        self.0.schedule_work(delay_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        sync::atomic::AtomicUsize,
+    };
+
+    /// Returns a pump together with the number of times it woke the host loop.
+    fn pump() -> (MessagePump, Arc<AtomicUsize>) {
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let pump = MessagePump::new({
+            let wakes = wakes.clone();
+            move || {
+                wakes.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        (pump, wakes)
+    }
+
+    /// Runs due work and returns how often the work ran.
+    fn run(pump: &MessagePump) -> usize {
+        let mut count = 0;
+        pump.run_due(|| count += 1);
+        count
+    }
+
+    #[test]
+    fn immediate_work_is_coalesced() {
+        let (pump, wakes) = pump();
+        pump.schedule_work(0);
+        pump.schedule_work(-1);
+        pump.schedule_work(0);
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert!(pump.next_deadline() <= Instant::now());
+
+        assert_eq!(run(&pump), 1);
+        assert_eq!(run(&pump), 0);
+    }
+
+    #[test]
+    fn delayed_work() {
+        let (pump, wakes) = pump();
+        pump.schedule_work(20);
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        // An earlier deadline wakes the host loop, a later one doesn't.
+        pump.schedule_work(10);
+        assert_eq!(wakes.load(Ordering::SeqCst), 2);
+        pump.schedule_work(25);
+        assert_eq!(wakes.load(Ordering::SeqCst), 2);
+        // Immediate work is always earlier.
+        pump.schedule_work(0);
+        assert_eq!(wakes.load(Ordering::SeqCst), 3);
+
+        let start = Instant::now();
+        assert_eq!(run(&pump), 1);
+        // The delayed work is still pending and replaced the earlier request.
+        let deadline = pump.next_deadline();
+        assert!(deadline > start + Duration::from_millis(10));
+        assert!(deadline <= start + Duration::from_millis(25));
+        assert_eq!(run(&pump), 0);
+
+        std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        assert_eq!(run(&pump), 1);
+    }
+
+    #[test]
+    fn delay_is_clamped() {
+        let (pump, _) = pump();
+        pump.schedule_work(60_000);
+        assert!(pump.next_deadline() <= Instant::now() + MAX_DELAY);
+    }
+
+    #[test]
+    fn polls_without_requests() {
+        let (pump, wakes) = pump();
+        let start = Instant::now();
+        assert!(pump.next_deadline() >= start + MAX_DELAY);
+
+        let deadline = pump.run_due(|| panic!("no work was scheduled"));
+        assert!(deadline >= start + MAX_DELAY);
+        assert!(deadline <= Instant::now() + MAX_DELAY);
+        assert_eq!(pump.next_deadline(), deadline);
+        assert_eq!(wakes.load(Ordering::SeqCst), 0);
+
+        std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        assert_eq!(run(&pump), 1);
+    }
+
+    #[test]
+    fn reentrant_call() {
+        let (pump, _) = pump();
+        pump.schedule_work(0);
+        let mut nested = 0;
+        pump.run_due(|| {
+            pump.schedule_work(0);
+            pump.run_due(|| nested += 1);
+        });
+        assert_eq!(nested, 0);
+        // The skipped call is made up for as soon as possible.
+        assert!(pump.next_deadline() <= Instant::now());
+        assert_eq!(run(&pump), 1);
+        assert_eq!(run(&pump), 0);
+    }
+
+    #[test]
+    fn panicking_work() {
+        let (pump, _) = pump();
+        pump.schedule_work(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pump.run_due(|| panic!("work failed"));
+        }));
+        assert!(result.is_err());
+
+        pump.schedule_work(0);
+        assert_eq!(run(&pump), 1);
+    }
+
+    #[test]
+    fn deadline_of_schedule() {
+        let timer = Instant::now() + Duration::from_secs(1);
+        assert_eq!(deadline(&Schedule::default()), None);
+        let schedule = Schedule {
+            immediate: false,
+            timer: Some(timer),
+        };
+        assert_eq!(deadline(&schedule), Some(timer));
+        let schedule = Schedule {
+            immediate: true,
+            timer: Some(timer),
+        };
+        let before = Instant::now();
+        let immediate = deadline(&schedule).unwrap();
+        assert!(immediate >= before && immediate <= Instant::now());
+    }
+}
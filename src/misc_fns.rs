@@ -6,12 +6,14 @@
 use crate::{
     app::App,
     main_args::MainArgs,
+    runtime::RunError,
     sandbox::SandboxInfo,
-    settings::Settings,
+    settings::{Settings, ValidationOptions},
+    version::CefVersion,
 };
-use cef_sys::{
-    cef_do_message_loop_work, cef_execute_process,
-    cef_initialize, cef_quit_message_loop, cef_run_message_loop, cef_shutdown,
+use crate::capi::{
+    cef_do_message_loop_work, cef_execute_process, cef_initialize, cef_quit_message_loop,
+    cef_run_message_loop, cef_shutdown,
 };
 use std::{
     ptr::null_mut,
@@ -55,7 +57,7 @@ fn enable_highdpi_support() {
                     winbase::VerifyVersionInfoA(&mut version_info, winnt::VER_MAJORVERSION | winnt::VER_MINORVERSION | winnt::VER_SERVICEPACKMAJOR, c) != 0
                 };
                 if windows_7_or_greater {
-                    cef_sys::cef_enable_highdpi_support();
+                    crate::capi::cef_enable_highdpi_support();
                 }
             }
         }
@@ -72,20 +74,28 @@ fn enable_highdpi_support() {
 /// secondary process it will block until the process should exit and then return
 /// the process exit code. The `application` parameter may be None. The
 /// `sandbox_info` parameter may be None (see [SandboxInfo] for details).
+///
+/// Returns an error without calling into CEF if the loaded libcef doesn't
+/// match the version this crate was built against (see [CefVersion::check]),
+/// or if libcef couldn't be loaded with the `dynamic-loading` feature.
 pub fn execute_process(
     application: Option<App>,
     sandbox_info: Option<&SandboxInfo>,
-) -> i32 {
+) -> Result<i32, RunError> {
+    ffi_event!("execute_process");
+    #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+    crate::dynamic_library::ensure_loaded()?;
+    CefVersion::check()?;
     let args = MainArgs::new();
     enable_highdpi_support();
     unsafe {
-        cef_execute_process(
+        Ok(cef_execute_process(
             args.get(),
             application.map(|app| app.into_raw()).unwrap_or_else(null_mut),
             sandbox_info
                 .map(|wsi| wsi.get())
                 .unwrap_or_else(null_mut),
-        )
+        ))
     }
 }
 
@@ -118,7 +128,7 @@ pub fn process_type() -> ProcessType {
 
 pub struct Context(std::marker::PhantomData<&'static std::cell::Cell<u8>>); // not Send nor Sync
 
-static CONTEXT_INITIALIZED: AtomicBool = AtomicBool::new(false);
+pub(crate) static CONTEXT_INITIALIZED: AtomicBool = AtomicBool::new(false);
 
 impl Context {
     /// This function should be called on the main application thread to initialize
@@ -128,11 +138,29 @@ impl Context {
     /// value of true indicates that it succeeded and false indicates that it
     /// failed. The `windows_sandbox_info` parameter is only used on Windows and may
     /// be None (see [SandboxInfo] for details).
+    ///
+    /// The settings are checked with [Settings::validate] first. If that fails, an
+    /// error of kind [std::io::ErrorKind::InvalidInput] wrapping the
+    /// [crate::settings::SettingsErrors] is returned and CEF is not initialized.
+    /// Likewise, an error of kind [std::io::ErrorKind::InvalidData] wrapping a
+    /// [crate::version::VersionMismatch] is returned if the loaded libcef is
+    /// incompatible, and one of kind [std::io::ErrorKind::NotFound] wrapping a
+    /// [crate::LibraryError] if libcef couldn't be loaded with the
+    /// `dynamic-loading` feature.
     pub fn initialize(
         settings: &Settings,
         application: Option<App>,
         sandbox_info: Option<&SandboxInfo>,
     ) -> Result<Context, std::io::Error> {
+        ffi_event!("Context::initialize");
+        #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+        crate::dynamic_library::ensure_loaded()
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::NotFound, err))?;
+        CefVersion::check()
+            .map_err(|mismatch| std::io::Error::new(std::io::ErrorKind::InvalidData, mismatch))?;
+        settings
+            .validate(&ValidationOptions::default())
+            .map_err(|errors| std::io::Error::new(std::io::ErrorKind::InvalidInput, errors))?;
         let args = MainArgs::new();
         enable_highdpi_support();
         CONTEXT_INITIALIZED.swap(true, Ordering::SeqCst);
@@ -170,6 +198,7 @@ impl Context {
     /// [Settings::multi_threaded_message_loop] value of false. This function
     /// will not block.
     pub fn do_message_loop_work(&self) {
+        ffi_event!("Context::do_message_loop_work");
         unsafe {
             cef_do_message_loop_work();
         }
@@ -183,6 +212,7 @@ impl Context {
     /// [CefSettings::multi_threaded_message_loop] value of false. This function
     /// will block until a quit message is received by the system.
     pub fn run_message_loop(&self) {
+        ffi_event!("Context::run_message_loop");
         unsafe {
             cef_run_message_loop();
         }
@@ -192,6 +222,7 @@ impl Context {
     /// This function should only be called on the main application thread and only
     /// if [App::run_message_loop] was used.
     pub fn quit_message_loop(&self) {
+        ffi_event!("Context::quit_message_loop");
         unsafe {
             cef_quit_message_loop();
         }
@@ -200,7 +231,10 @@ impl Context {
 
 impl Drop for Context {
     fn drop(&mut self) {
+        ffi_event!("Context::shutdown");
         CONTEXT_INITIALIZED.swap(false, Ordering::SeqCst);
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::report();
         unsafe {
             println!("drop context");
             cef_shutdown();
@@ -214,8 +248,9 @@ impl Drop for Context {
 /// Set to false after exiting the modal message loop.
 #[cfg(target_os = "windows")]
 pub fn set_osmodal_loop(os_modal_loop: bool) {
+    ffi_event!("set_osmodal_loop");
     unsafe {
-        cef_sys::cef_set_osmodal_loop(os_modal_loop as i32);
+        crate::capi::cef_set_osmodal_loop(os_modal_loop as i32);
     }
 }
 
@@ -227,6 +262,7 @@ pub enum QuitMessageLoopError {
 
 /// Quit the CEF message loop that was started by calling [App::run_message_loop].
 pub fn quit_message_loop() -> Result<(), QuitMessageLoopError> {
+    ffi_event!("quit_message_loop");
     if !crate::task::TaskRunner::currently_on(crate::task::ThreadId::UI) {
         return Err(QuitMessageLoopError::NotOnMainThread);
     }
@@ -243,11 +279,12 @@ pub fn quit_message_loop() -> Result<(), QuitMessageLoopError> {
 /// Load the CEF library at the specified |path|. Returns true on
 /// success and false on failure.
 pub fn load_library<P: AsRef<std::path::Path>>(path: P) -> bool {
+    ffi_event!("load_library");
     let path = path.as_ref().as_os_str().as_bytes();
     let mut path = path.to_vec();
     path.push(0);
     unsafe {
-        cef_sys::cef_load_library(path.as_ptr() as *const _) != 0
+        crate::capi::cef_load_library(path.as_ptr() as *const _) != 0
     }
 }
 
@@ -255,7 +292,8 @@ pub fn load_library<P: AsRef<std::path::Path>>(path: P) -> bool {
 /// Unload the CEF library that was previously loaded. Returns true (1)
 /// on success and false (0) on failure.
 pub fn unload_library() -> bool {
+    ffi_event!("unload_library");
     unsafe {
-        cef_sys::cef_unload_library() != 0
+        crate::capi::cef_unload_library() != 0
     }
 }
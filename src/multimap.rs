@@ -1,8 +1,8 @@
-use cef_sys::{
+use cef_sys::cef_string_multimap_t;
+use crate::capi::{
     cef_string_multimap_alloc, cef_string_multimap_append, cef_string_multimap_clear,
     cef_string_multimap_enumerate, cef_string_multimap_find_count, cef_string_multimap_free,
-    cef_string_multimap_key, cef_string_multimap_size, cef_string_multimap_t,
-    cef_string_multimap_value,
+    cef_string_multimap_key, cef_string_multimap_size, cef_string_multimap_value,
 };
 use std::collections::HashMap;
 
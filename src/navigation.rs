@@ -14,33 +14,43 @@ ref_counted_ptr! {
 
 impl NavigationEntry {
     pub fn is_valid(&self) -> bool {
+        ffi_event!("NavigationEntry::is_valid");
         unsafe{ self.0.is_valid.unwrap()(self.as_ptr()) != 0 }
     }
     pub fn get_url(&self) -> String {
+        ffi_event!("NavigationEntry::get_url");
         unsafe{ CefString::from_userfree_unchecked(self.0.get_url.unwrap()(self.as_ptr())).into() }
     }
     pub fn get_display_url(&self) -> String {
+        ffi_event!("NavigationEntry::get_display_url");
         unsafe{ CefString::from_userfree_unchecked(self.0.get_display_url.unwrap()(self.as_ptr())).into() }
     }
     pub fn get_original_url(&self) -> String {
+        ffi_event!("NavigationEntry::get_original_url");
         unsafe{ CefString::from_userfree_unchecked(self.0.get_original_url.unwrap()(self.as_ptr())).into() }
     }
     pub fn get_title(&self) -> String {
+        ffi_event!("NavigationEntry::get_title");
         unsafe{ CefString::from_userfree_unchecked(self.0.get_title.unwrap()(self.as_ptr())).into() }
     }
     pub fn get_transition_type(&self) -> TransitionType {
+        ffi_event!("NavigationEntry::get_transition_type");
         unsafe{ self.0.get_transition_type.unwrap()(self.as_ptr()).0.try_into().unwrap() }
     }
     pub fn has_post_data(&self) -> bool {
+        ffi_event!("NavigationEntry::has_post_data");
         unsafe{ self.0.has_post_data.unwrap()(self.as_ptr()) != 0 }
     }
     pub fn get_completion_time(&self) -> DateTime<Utc> {
+        ffi_event!("NavigationEntry::get_completion_time");
         crate::values::cef_time_to_date_time(unsafe{ self.0.get_completion_time.unwrap()(self.as_ptr()) })
     }
     pub fn get_http_status_code(&self) -> u16 {
+        ffi_event!("NavigationEntry::get_http_status_code");
         unsafe{ self.0.get_http_status_code.unwrap()(self.as_ptr()) as u16 }
     }
     pub fn get_ssl_status(&self) -> SSLStatus {
+        ffi_event!("NavigationEntry::get_ssl_status");
         unsafe{ SSLStatus::from_ptr_unchecked(self.0.get_sslstatus.unwrap()(self.as_ptr())) }
     }
 }
@@ -0,0 +1,36 @@
+//! Helpers for windowless (off-screen) rendering, where CEF paints into
+//! buffers passed to [crate::client::render_handler::RenderHandlerCallbacks::on_paint]
+//! instead of a native window.
+//!
+//! Some helpers, like full-page screenshots, need information from the render
+//! process. They only work if [on_process_message_received] is called from the
+//! process message callbacks of both the browser and the render process.
+//!
+//! [capture_element] finds the element with JavaScript's `document.querySelector`
+//! and measures it with `getBoundingClientRect`, instead of using
+//! [crate::dom::DOMNode::get_element_bounds], because CEF's DOM API has no
+//! selector queries. The element is scrolled into view with `scrollIntoView`
+//! first. The script runs in the page's own JavaScript context, so it is
+//! affected by pages that replace these functions.
+
+mod compositor;
+pub mod convert;
+#[cfg(feature = "golden-images")]
+pub mod golden;
+mod input;
+#[cfg(target_os = "linux")]
+pub mod memfd;
+mod messages;
+mod recorder;
+mod scheduler;
+mod screenshot;
+#[cfg(all(unix, feature = "terminal"))]
+pub mod terminal;
+mod util;
+#[cfg(feature = "vnc")]
+pub mod vnc;
+pub use compositor::*;
+pub use messages::on_process_message_received;
+pub use recorder::*;
+pub use scheduler::*;
+pub use screenshot::*;
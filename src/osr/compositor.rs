@@ -0,0 +1,1111 @@
+use super::convert;
+use crate::{
+    browser::Browser,
+    browser_host::PaintElementType,
+    client::render_handler::{CursorHandle, CursorType, RenderHandlerCallbacks, ScreenInfo},
+    drag::DragOperation,
+    values::{Point, Rect, Size},
+};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::HashMap,
+    os::raw::c_void,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Byte order of the pixels of a [ComposedFrame]. CEF always paints BGRA with
+/// premultiplied alpha, [PixelFormat::Rgba] swaps the red and blue channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Bgra,
+    Rgba,
+}
+
+/// The view size, in view coordinates, and device scale factor reported to CEF
+/// for a browser.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub size: Size,
+    pub device_scale_factor: f32,
+}
+
+impl Viewport {
+    pub fn new(width: i32, height: i32) -> Viewport {
+        Viewport {
+            size: Size { width, height },
+            device_scale_factor: 1.0,
+        }
+    }
+    pub fn device_scale_factor(self, device_scale_factor: f32) -> Viewport {
+        Viewport {
+            device_scale_factor,
+            ..self
+        }
+    }
+    /// The size of painted frames in pixels.
+    pub fn pixel_size(&self) -> Size {
+        Size {
+            width: (self.size.width as f32 * self.device_scale_factor).round() as i32,
+            height: (self.size.height as f32 * self.device_scale_factor).round() as i32,
+        }
+    }
+}
+
+/// The view of a browser with the popup widget, like the dropdown of a `<select>`,
+/// drawn on top. Pixels have 4 bytes, rows are tightly packed and the origin is
+/// the upper-left corner.
+///
+/// Clones share the pixel data. The compositor only copies it when it paints
+/// while an older clone is still alive, so listeners can keep frames around
+/// cheaply.
+#[derive(Clone)]
+pub struct ComposedFrame {
+    width: i32,
+    height: i32,
+    format: PixelFormat,
+    sequence: u64,
+    data: Arc<Vec<u8>>,
+}
+
+impl ComposedFrame {
+    fn new(width: i32, height: i32, format: PixelFormat) -> ComposedFrame {
+        ComposedFrame {
+            width,
+            height,
+            format,
+            sequence: 0,
+            data: Arc::new(vec![0; 4 * width.max(0) as usize * height.max(0) as usize]),
+        }
+    }
+    #[cfg(test)]
+    pub(crate) fn from_data(
+        width: i32,
+        height: i32,
+        format: PixelFormat,
+        sequence: u64,
+        data: Vec<u8>,
+    ) -> ComposedFrame {
+        assert_eq!(data.len(), 4 * width as usize * height as usize);
+        ComposedFrame {
+            width,
+            height,
+            format,
+            sequence,
+            data: Arc::new(data),
+        }
+    }
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+    /// The number of bytes per row.
+    pub fn stride(&self) -> usize {
+        4 * self.width as usize
+    }
+    /// Incremented every time the frame changes.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Returns the pixel data, which is only copied if it's shared with another
+    /// frame.
+    pub fn into_data(self) -> Vec<u8> {
+        Arc::try_unwrap(self.data).unwrap_or_else(|data| Vec::clone(&data))
+    }
+    /// Returns the pixel at `x`, `y` in the frame's [PixelFormat].
+    pub fn pixel(&self, x: i32, y: i32) -> Option<[u8; 4]> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        let offset = 4 * (y * self.width + x) as usize;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.data[offset..offset + 4]);
+        Some(pixel)
+    }
+    /// Returns a copy of `rect` of this frame, in pixel coordinates. Returns None
+    /// if `rect` doesn't overlap the frame, otherwise it's clipped to the frame.
+    pub fn crop(&self, rect: Rect) -> Option<ComposedFrame> {
+        let bounds = Rect {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        };
+        let rect = intersect(rect, bounds)?;
+        let mut data = vec![0; 4 * rect.width as usize * rect.height as usize];
+        copy_rect(
+            &self.data,
+            self.width,
+            &mut data,
+            rect.width,
+            rect,
+            Point::new(0, 0),
+            false,
+        );
+        Some(ComposedFrame {
+            width: rect.width,
+            height: rect.height,
+            format: self.format,
+            sequence: self.sequence,
+            data: Arc::new(data),
+        })
+    }
+}
+
+/// The regions of a [ComposedFrame] that changed, in pixel coordinates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Damage {
+    rects: Vec<Rect>,
+}
+
+impl Damage {
+    /// Beyond this number of rectangles, damage is merged into its bounding box.
+    pub(crate) const MAX_RECTS: usize = 16;
+
+    pub(crate) fn full(width: i32, height: i32) -> Damage {
+        let mut damage = Damage::default();
+        damage.add(Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+        damage
+    }
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+    /// The smallest rectangle containing all damaged regions.
+    pub fn bounds(&self) -> Option<Rect> {
+        self.rects
+            .iter()
+            .copied()
+            .fold(None, |bounds, rect| match bounds {
+                Some(bounds) => Some(union(bounds, rect)),
+                None => Some(rect),
+            })
+    }
+    pub(crate) fn add(&mut self, rect: Rect) {
+        if is_empty(rect) || self.rects.iter().any(|&damaged| contains(damaged, rect)) {
+            return;
+        }
+        self.rects.retain(|&damaged| !contains(rect, damaged));
+        self.rects.push(rect);
+        if self.rects.len() > Damage::MAX_RECTS {
+            let bounds = self.bounds().unwrap();
+            self.rects = vec![bounds];
+        }
+    }
+    pub(crate) fn extend(&mut self, other: &Damage) {
+        for &rect in &other.rects {
+            self.add(rect);
+        }
+    }
+}
+
+pub(crate) fn is_empty(rect: Rect) -> bool {
+    rect.width <= 0 || rect.height <= 0
+}
+
+pub(crate) fn intersect(a: Rect, b: Rect) -> Option<Rect> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+    let rect = Rect {
+        x,
+        y,
+        width: right - x,
+        height: bottom - y,
+    };
+    if is_empty(rect) {
+        None
+    } else {
+        Some(rect)
+    }
+}
+
+fn union(a: Rect, b: Rect) -> Rect {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    Rect {
+        x,
+        y,
+        width: (a.x + a.width).max(b.x + b.width) - x,
+        height: (a.y + a.height).max(b.y + b.height) - y,
+    }
+}
+
+fn contains(outer: Rect, inner: Rect) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width <= outer.x + outer.width
+        && inner.y + inner.height <= outer.y + outer.height
+}
+
+/// Copies `rect` of `src` to `dst` at `to`. Both buffers have 4 bytes per pixel
+/// and tightly packed rows. The rectangle must lie within both buffers.
+fn copy_rect(
+    src: &[u8],
+    src_width: i32,
+    dst: &mut [u8],
+    dst_width: i32,
+    rect: Rect,
+    to: Point,
+    swap_red_blue: bool,
+) {
+    let len = 4 * rect.width as usize;
+    for row in 0..rect.height {
+        let src_offset = 4 * ((rect.y + row) * src_width + rect.x) as usize;
+        let dst_offset = 4 * ((to.y + row) * dst_width + to.x) as usize;
+        let dst_row = &mut dst[dst_offset..dst_offset + len];
+        let src_row = &src[src_offset..src_offset + len];
+        if swap_red_blue {
+            convert::swap_red_blue(src_row, dst_row);
+        } else {
+            dst_row.copy_from_slice(src_row);
+        }
+    }
+}
+
+/// Identifies a listener added with [Compositor::add_frame_listener].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameListenerId(usize);
+
+type FrameListener = Arc<dyn Fn(&Browser, &ComposedFrame, &Damage) + Send + Sync>;
+
+#[derive(Default)]
+struct Popup {
+    visible: bool,
+    /// Location and size in view coordinates, as passed to `on_popup_size`.
+    rect: Option<Rect>,
+    width: i32,
+    height: i32,
+    data: Vec<u8>,
+}
+
+struct Surface {
+    viewport: Viewport,
+    /// The view without the popup, in the output format.
+    view: Vec<u8>,
+    frame: ComposedFrame,
+    popup: Popup,
+    /// Damage since the last [Compositor::take_damage].
+    damage: Damage,
+}
+
+impl Surface {
+    fn new(viewport: Viewport, format: PixelFormat) -> Surface {
+        Surface {
+            viewport,
+            view: Vec::new(),
+            frame: ComposedFrame::new(0, 0, format),
+            popup: Popup::default(),
+            damage: Damage::default(),
+        }
+    }
+    fn swap_red_blue(&self) -> bool {
+        self.frame.format == PixelFormat::Rgba
+    }
+    fn bounds(&self) -> Rect {
+        Rect {
+            x: 0,
+            y: 0,
+            width: self.frame.width,
+            height: self.frame.height,
+        }
+    }
+    /// Where the popup is drawn in pixel coordinates. Like Chromium, the popup
+    /// is moved into the view if it doesn't fit.
+    fn popup_rect(&self) -> Option<Rect> {
+        let popup = &self.popup;
+        let rect = popup.rect?;
+        if !popup.visible || popup.data.is_empty() {
+            return None;
+        }
+        let scale = self.viewport.device_scale_factor;
+        let x = ((rect.x as f32 * scale).round() as i32)
+            .min(self.frame.width - popup.width)
+            .max(0);
+        let y = ((rect.y as f32 * scale).round() as i32)
+            .min(self.frame.height - popup.height)
+            .max(0);
+        intersect(
+            Rect {
+                x,
+                y,
+                width: popup.width,
+                height: popup.height,
+            },
+            self.bounds(),
+        )
+    }
+    /// Redraws `rect` of the composed frame from the view and popup layers.
+    fn recompose(&mut self, rect: Rect, damage: &mut Damage) {
+        let rect = match intersect(rect, self.bounds()) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let width = self.frame.width;
+        let popup_rect = self.popup_rect();
+        // Copies the frame if a listener still holds on to it.
+        let frame = Arc::make_mut(&mut self.frame.data);
+        copy_rect(
+            &self.view,
+            width,
+            frame,
+            width,
+            rect,
+            Point::new(rect.x, rect.y),
+            false,
+        );
+        if let Some(popup_rect) = popup_rect {
+            if let Some(overlap) = intersect(rect, popup_rect) {
+                let from = Rect {
+                    x: overlap.x - popup_rect.x,
+                    y: overlap.y - popup_rect.y,
+                    ..overlap
+                };
+                copy_rect(
+                    &self.popup.data,
+                    self.popup.width,
+                    frame,
+                    width,
+                    from,
+                    Point::new(overlap.x, overlap.y),
+                    false,
+                );
+            }
+        }
+        damage.add(rect);
+    }
+    fn paint_view(
+        &mut self,
+        dirty_rects: &[Rect],
+        buffer: &[u8],
+        width: i32,
+        height: i32,
+        damage: &mut Damage,
+    ) {
+        let full = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        let resized = width != self.frame.width || height != self.frame.height;
+        if resized {
+            self.view = vec![0; 4 * width.max(0) as usize * height.max(0) as usize];
+            let sequence = self.frame.sequence;
+            self.frame = ComposedFrame::new(width, height, self.frame.format);
+            self.frame.sequence = sequence;
+        }
+        let dirty_rects: Vec<Rect> = if self.view.is_empty() {
+            Vec::new()
+        } else if resized || dirty_rects.is_empty() {
+            vec![full]
+        } else {
+            dirty_rects
+                .iter()
+                .filter_map(|&rect| intersect(rect, full))
+                .collect()
+        };
+        let swap_red_blue = self.swap_red_blue();
+        for &rect in &dirty_rects {
+            copy_rect(
+                buffer,
+                width,
+                &mut self.view,
+                width,
+                rect,
+                Point::new(rect.x, rect.y),
+                swap_red_blue,
+            );
+        }
+        for rect in dirty_rects {
+            self.recompose(rect, damage);
+        }
+    }
+    fn paint_popup(
+        &mut self,
+        dirty_rects: &[Rect],
+        buffer: &[u8],
+        width: i32,
+        height: i32,
+        damage: &mut Damage,
+    ) {
+        let full = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        let old_rect = self.popup_rect();
+        let resized = width != self.popup.width || height != self.popup.height;
+        if resized {
+            self.popup.width = width;
+            self.popup.height = height;
+            self.popup.data = vec![0; 4 * width.max(0) as usize * height.max(0) as usize];
+        }
+        let dirty_rects: Vec<Rect> = if resized || dirty_rects.is_empty() {
+            vec![full]
+        } else {
+            dirty_rects
+                .iter()
+                .filter_map(|&rect| intersect(rect, full))
+                .collect()
+        };
+        let swap_red_blue = self.swap_red_blue();
+        for &rect in &dirty_rects {
+            copy_rect(
+                buffer,
+                width,
+                &mut self.popup.data,
+                width,
+                rect,
+                Point::new(rect.x, rect.y),
+                swap_red_blue,
+            );
+        }
+        if let Some(old_rect) = old_rect.filter(|_| resized) {
+            self.recompose(old_rect, damage);
+        }
+        if let Some(popup_rect) = self.popup_rect() {
+            for rect in dirty_rects {
+                let rect = Rect {
+                    x: rect.x + popup_rect.x,
+                    y: rect.y + popup_rect.y,
+                    ..rect
+                };
+                self.recompose(rect, damage);
+            }
+        }
+    }
+    fn show_popup(&mut self, show: bool, damage: &mut Damage) {
+        let old_rect = self.popup_rect();
+        self.popup.visible = show;
+        if !show {
+            self.popup = Popup::default();
+        }
+        if let Some(old_rect) = old_rect {
+            self.recompose(old_rect, damage);
+        }
+    }
+    fn move_popup(&mut self, rect: Rect, damage: &mut Damage) {
+        let old_rect = self.popup_rect();
+        self.popup.rect = Some(rect);
+        if let Some(old_rect) = old_rect {
+            self.recompose(old_rect, damage);
+        }
+        if let Some(new_rect) = self.popup_rect() {
+            self.recompose(new_rect, damage);
+        }
+    }
+}
+
+struct CompositorState {
+    format: PixelFormat,
+    default_viewport: Viewport,
+    surfaces: Mutex<HashMap<i32, Surface>>,
+    listeners: RwLock<Vec<(FrameListenerId, FrameListener)>>,
+    next_listener_id: AtomicUsize,
+}
+
+/// Keeps a persistent framebuffer per windowless browser and applies the dirty
+/// rectangles of every [RenderHandlerCallbacks::on_paint] call to it. The popup
+/// widget is composed on top of the view, using the location from
+/// [RenderHandlerCallbacks::on_popup_size]. Example usage:
+///
+/// ```ignore
+///  let compositor = Compositor::new(PixelFormat::Rgba, Viewport::new(1280, 720));
+///  compositor.add_frame_listener(|browser, frame, damage| {
+///      upload(frame.data(), frame.stride(), damage.rects());
+///  });
+///  let client = Client::new(MyClient { render_handler: RenderHandler::new(compositor.handler()) });
+/// ```
+///
+/// If you need other render handler callbacks as well, call
+/// [Compositor::on_paint], [Compositor::on_popup_show] and [Compositor::on_popup_size]
+/// from your own implementation instead of using [Compositor::handler].
+#[derive(Clone)]
+pub struct Compositor(Arc<CompositorState>);
+
+impl Compositor {
+    /// Create a compositor producing frames in `format`. `default_viewport` is
+    /// reported to CEF by [Compositor::handler] for browsers without their own
+    /// [Compositor::set_viewport].
+    pub fn new(format: PixelFormat, default_viewport: Viewport) -> Compositor {
+        Compositor(Arc::new(CompositorState {
+            format,
+            default_viewport,
+            surfaces: Mutex::new(HashMap::new()),
+            listeners: RwLock::new(Vec::new()),
+            next_listener_id: AtomicUsize::new(0),
+        }))
+    }
+    /// Returns render handler callbacks that report the viewport of each browser
+    /// to CEF and feed the painted frames to this compositor.
+    pub fn handler(&self) -> CompositorHandler {
+        CompositorHandler(self.clone())
+    }
+    pub fn format(&self) -> PixelFormat {
+        self.0.format
+    }
+    /// Returns the viewport of the browser with the identifier `browser_id`.
+    pub fn viewport(&self, browser_id: i32) -> Viewport {
+        self.0
+            .surfaces
+            .lock()
+            .get(&browser_id)
+            .map(|surface| surface.viewport)
+            .unwrap_or(self.0.default_viewport)
+    }
+    /// Change the viewport of a browser. Call [crate::browser_host::BrowserHost::was_resized]
+    /// or [crate::browser_host::BrowserHost::notify_screen_info_changed] afterwards,
+    /// so that CEF picks it up.
+    pub fn set_viewport(&self, browser_id: i32, viewport: Viewport) {
+        let format = self.0.format;
+        self.0
+            .surfaces
+            .lock()
+            .entry(browser_id)
+            .or_insert_with(|| Surface::new(viewport, format))
+            .viewport = viewport;
+    }
+    /// Returns the current frame of a browser, or None if nothing has been
+    /// painted yet. The pixel data is shared, not copied.
+    pub fn frame(&self, browser_id: i32) -> Option<ComposedFrame> {
+        self.with_frame(browser_id, ComposedFrame::clone)
+    }
+    /// Calls `fun` with the current frame of a browser while the compositor is
+    /// locked. `fun` must not call back into the compositor.
+    pub fn with_frame<T>(
+        &self,
+        browser_id: i32,
+        fun: impl FnOnce(&ComposedFrame) -> T,
+    ) -> Option<T> {
+        let surfaces = self.0.surfaces.lock();
+        surfaces
+            .get(&browser_id)
+            .filter(|surface| !surface.frame.data.is_empty())
+            .map(|surface| fun(&surface.frame))
+    }
+    /// Returns the regions that changed since the last call.
+    pub fn take_damage(&self, browser_id: i32) -> Damage {
+        self.0
+            .surfaces
+            .lock()
+            .get_mut(&browser_id)
+            .map(|surface| std::mem::take(&mut surface.damage))
+            .unwrap_or_default()
+    }
+    /// Forget the frame of a browser, e.g. after it was closed.
+    pub fn remove(&self, browser_id: i32) {
+        self.0.surfaces.lock().remove(&browser_id);
+    }
+    /// Call `listener` after every change of a frame with the frame and the
+    /// regions that changed. Cloning the frame doesn't copy its pixels. Listeners are called on the thread that painted
+    /// the frame, which is the UI thread, without holding any locks, so they may
+    /// call back into the compositor.
+    pub fn add_frame_listener(
+        &self,
+        listener: impl Fn(&Browser, &ComposedFrame, &Damage) + Send + Sync + 'static,
+    ) -> FrameListenerId {
+        let id = FrameListenerId(self.0.next_listener_id.fetch_add(1, Ordering::SeqCst));
+        self.0.listeners.write().push((id, Arc::new(listener)));
+        id
+    }
+    pub fn remove_frame_listener(&self, id: FrameListenerId) {
+        self.0
+            .listeners
+            .write()
+            .retain(|(listener_id, _)| *listener_id != id);
+    }
+    /// Apply a paint to the frame of `browser`. The arguments are those of
+    /// [RenderHandlerCallbacks::on_paint].
+    pub fn on_paint(
+        &self,
+        browser: &Browser,
+        type_: PaintElementType,
+        dirty_rects: &[Rect],
+        buffer: &[u8],
+        width: i32,
+        height: i32,
+    ) {
+        if buffer.len() < 4 * width.max(0) as usize * height.max(0) as usize {
+            return;
+        }
+        self.update(browser, |surface, damage| match type_ {
+            PaintElementType::View => {
+                surface.paint_view(dirty_rects, buffer, width, height, damage)
+            }
+            PaintElementType::Popup => {
+                surface.paint_popup(dirty_rects, buffer, width, height, damage)
+            }
+        });
+    }
+    /// Show or hide the popup of `browser`, see [RenderHandlerCallbacks::on_popup_show].
+    pub fn on_popup_show(&self, browser: &Browser, show: bool) {
+        self.update(browser, |surface, damage| surface.show_popup(show, damage));
+    }
+    /// Move the popup of `browser`, see [RenderHandlerCallbacks::on_popup_size].
+    pub fn on_popup_size(&self, browser: &Browser, rect: Rect) {
+        self.update(browser, |surface, damage| surface.move_popup(rect, damage));
+    }
+
+    fn update(&self, browser: &Browser, fun: impl FnOnce(&mut Surface, &mut Damage)) {
+        let mut surfaces = self.0.surfaces.lock();
+        let (format, default_viewport) = (self.0.format, self.0.default_viewport);
+        let surface = surfaces
+            .entry(browser.get_identifier())
+            .or_insert_with(|| Surface::new(default_viewport, format));
+        let mut damage = Damage::default();
+        fun(surface, &mut damage);
+        if damage.is_empty() {
+            return;
+        }
+        surface.frame.sequence += 1;
+        surface.damage.extend(&damage);
+        let listeners: Vec<FrameListener> = self
+            .0
+            .listeners
+            .read()
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect();
+        if listeners.is_empty() {
+            return;
+        }
+        // Shares the pixels with the surface until its next paint.
+        let frame = surface.frame.clone();
+        drop(surfaces);
+        for listener in listeners {
+            listener(browser, &frame, &damage);
+        }
+    }
+}
+
+/// Render handler callbacks for windowless browsers without a window, which
+/// report the [Viewport] of each browser and forward paints to a [Compositor].
+/// Returned by [Compositor::handler].
+pub struct CompositorHandler(Compositor);
+
+impl RenderHandlerCallbacks for CompositorHandler {
+    fn get_view_rect(&self, browser: Browser) -> Rect {
+        let viewport = self.0.viewport(browser.get_identifier());
+        Rect {
+            x: 0,
+            y: 0,
+            width: viewport.size.width,
+            height: viewport.size.height,
+        }
+    }
+    fn get_screen_point(&self, _browser: Browser, view_point: Point) -> Option<Point> {
+        Some(view_point)
+    }
+    fn get_screen_info(&self, browser: Browser) -> Option<ScreenInfo> {
+        let viewport = self.0.viewport(browser.get_identifier());
+        let rect = Rect {
+            x: 0,
+            y: 0,
+            width: viewport.size.width,
+            height: viewport.size.height,
+        };
+        Some(ScreenInfo {
+            device_scale_factor: viewport.device_scale_factor,
+            depth: 32,
+            depth_per_component: 8,
+            is_monochrome: false,
+            rect,
+            available_rect: rect,
+        })
+    }
+    fn on_popup_show(&self, browser: Browser, show: bool) {
+        self.0.on_popup_show(&browser, show);
+    }
+    fn on_popup_size(&self, browser: Browser, rect: Rect) {
+        self.0.on_popup_size(&browser, rect);
+    }
+    fn on_paint(
+        &self,
+        browser: Browser,
+        type_: PaintElementType,
+        dirty_rects: &[Rect],
+        buffer: &[u8],
+        width: i32,
+        height: i32,
+    ) {
+        self.0
+            .on_paint(&browser, type_, dirty_rects, buffer, width, height);
+    }
+    fn on_accelerated_paint(
+        &self,
+        _browser: Browser,
+        _type_: PaintElementType,
+        _dirty_rects: &[Rect],
+        _shared_handle: *mut c_void,
+    ) {
+    }
+    fn on_cursor_change(&self, _browser: Browser, _cursor: CursorHandle, _type_: CursorType<'_>) {}
+    fn update_drag_cursor(&self, _browser: Browser, _operation: DragOperation) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEW: [u8; 4] = [10, 20, 30, 255];
+    const POPUP: [u8; 4] = [40, 50, 60, 255];
+    const OTHER: [u8; 4] = [70, 80, 90, 255];
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn solid(width: i32, height: i32, pixel: [u8; 4]) -> Vec<u8> {
+        pixel.repeat((width * height) as usize)
+    }
+
+    /// A frame whose pixels all differ.
+    fn numbered(width: i32, height: i32) -> ComposedFrame {
+        let data = (0..4 * width * height).map(|i| i as u8).collect();
+        ComposedFrame::from_data(width, height, PixelFormat::Bgra, 3, data)
+    }
+
+    /// A surface with an 8x8 view painted in [VIEW].
+    fn painted(viewport: Viewport) -> Surface {
+        let mut surface = Surface::new(viewport, PixelFormat::Bgra);
+        let size = viewport.pixel_size();
+        let mut damage = Damage::default();
+        let buffer = solid(size.width, size.height, VIEW);
+        surface.paint_view(&[], &buffer, size.width, size.height, &mut damage);
+        surface
+    }
+
+    /// Shows a 3x3 popup in [POPUP] at `at`, in view coordinates.
+    fn open_popup(surface: &mut Surface, at: Rect) -> Damage {
+        let mut damage = Damage::default();
+        surface.show_popup(true, &mut damage);
+        surface.move_popup(at, &mut damage);
+        surface.paint_popup(&[], &solid(3, 3, POPUP), 3, 3, &mut damage);
+        damage
+    }
+
+    /// Returns the rectangles of `frame` with the color `pixel`, one per row.
+    fn rows_with(frame: &ComposedFrame, pixel: [u8; 4]) -> Vec<Rect> {
+        let mut rows = Vec::new();
+        for y in 0..frame.height() {
+            let xs: Vec<i32> = (0..frame.width())
+                .filter(|&x| frame.pixel(x, y) == Some(pixel))
+                .collect();
+            if let (Some(&first), Some(&last)) = (xs.first(), xs.last()) {
+                assert_eq!(
+                    xs.len() as i32,
+                    last - first + 1,
+                    "row {} isn't contiguous",
+                    y
+                );
+                rows.push(rect(first, y, xs.len() as i32, 1));
+            }
+        }
+        rows
+    }
+
+    fn bounds_of(rows: &[Rect]) -> Option<Rect> {
+        rows.iter().copied().fold(None, |bounds, row| match bounds {
+            Some(bounds) => Some(union(bounds, row)),
+            None => Some(row),
+        })
+    }
+
+    #[test]
+    fn paint_view() {
+        let mut surface = Surface::new(Viewport::new(4, 3), PixelFormat::Bgra);
+        let mut damage = Damage::default();
+        surface.paint_view(&[rect(1, 1, 1, 1)], &solid(4, 3, VIEW), 4, 3, &mut damage);
+        // The first paint and resizes are complete, whatever the dirty rects.
+        assert_eq!(damage.rects(), &[rect(0, 0, 4, 3)]);
+        assert_eq!((surface.frame.width(), surface.frame.height()), (4, 3));
+        assert_eq!(rows_with(&surface.frame, VIEW).len(), 3);
+
+        let mut damage = Damage::default();
+        let dirty = [rect(1, 1, 2, 1), rect(3, 2, 5, 5)];
+        surface.paint_view(&dirty, &solid(4, 3, OTHER), 4, 3, &mut damage);
+        // Dirty rects are clipped to the view.
+        assert_eq!(damage.rects(), &[rect(1, 1, 2, 1), rect(3, 2, 1, 1)]);
+        assert_eq!(
+            rows_with(&surface.frame, OTHER),
+            vec![rect(1, 1, 2, 1), rect(3, 2, 1, 1)]
+        );
+        assert_eq!(surface.frame.pixel(0, 1), Some(VIEW));
+
+        // Resizing keeps the sequence number.
+        surface.frame.sequence = 5;
+        let mut damage = Damage::default();
+        surface.paint_view(&[rect(0, 0, 1, 1)], &solid(2, 2, VIEW), 2, 2, &mut damage);
+        assert_eq!(damage.rects(), &[rect(0, 0, 2, 2)]);
+        assert_eq!((surface.frame.width(), surface.frame.height()), (2, 2));
+        assert_eq!(surface.frame.sequence(), 5);
+    }
+
+    #[test]
+    fn paint_view_rgba() {
+        let mut surface = Surface::new(Viewport::new(2, 2), PixelFormat::Rgba);
+        let mut damage = Damage::default();
+        surface.paint_view(&[], &solid(2, 2, [1, 2, 3, 4]), 2, 2, &mut damage);
+        assert_eq!(surface.frame.format(), PixelFormat::Rgba);
+        assert_eq!(surface.frame.pixel(1, 1), Some([3, 2, 1, 4]));
+    }
+
+    #[test]
+    fn paint_popup() {
+        let mut surface = painted(Viewport::new(8, 8));
+        let mut damage = Damage::default();
+        surface.show_popup(true, &mut damage);
+        surface.move_popup(rect(2, 2, 3, 3), &mut damage);
+        // Nothing to draw before the popup is painted.
+        assert!(damage.is_empty());
+
+        surface.paint_popup(&[], &solid(3, 3, POPUP), 3, 3, &mut damage);
+        assert_eq!(damage.rects(), &[rect(2, 2, 3, 3)]);
+        assert_eq!(
+            bounds_of(&rows_with(&surface.frame, POPUP)),
+            Some(rect(2, 2, 3, 3))
+        );
+
+        // Dirty rects of the popup are offset by its location.
+        let mut damage = Damage::default();
+        surface.paint_popup(&[rect(1, 1, 1, 1)], &solid(3, 3, OTHER), 3, 3, &mut damage);
+        assert_eq!(damage.rects(), &[rect(3, 3, 1, 1)]);
+        assert_eq!(rows_with(&surface.frame, OTHER), vec![rect(3, 3, 1, 1)]);
+
+        // The view is painted below the popup.
+        let mut damage = Damage::default();
+        surface.paint_view(&[], &solid(8, 8, OTHER), 8, 8, &mut damage);
+        assert_eq!(surface.frame.pixel(2, 2), Some(POPUP));
+        assert_eq!(surface.frame.pixel(1, 1), Some(OTHER));
+    }
+
+    #[test]
+    fn resize_popup() {
+        let mut surface = painted(Viewport::new(8, 8));
+        open_popup(&mut surface, rect(2, 2, 3, 3));
+        let mut damage = Damage::default();
+        surface.paint_popup(&[], &solid(2, 2, POPUP), 2, 2, &mut damage);
+        // The old area is redrawn from the view.
+        assert_eq!(damage.rects(), &[rect(2, 2, 3, 3)]);
+        assert_eq!(
+            bounds_of(&rows_with(&surface.frame, POPUP)),
+            Some(rect(2, 2, 2, 2))
+        );
+    }
+
+    #[test]
+    fn show_popup() {
+        let mut surface = painted(Viewport::new(8, 8));
+        open_popup(&mut surface, rect(2, 2, 3, 3));
+
+        let mut damage = Damage::default();
+        surface.show_popup(false, &mut damage);
+        assert_eq!(damage.rects(), &[rect(2, 2, 3, 3)]);
+        assert_eq!(rows_with(&surface.frame, POPUP), vec![]);
+        assert_eq!(rows_with(&surface.frame, VIEW).len(), 8);
+
+        // Hiding forgets the popup, it's painted again when shown the next time.
+        let mut damage = Damage::default();
+        surface.show_popup(true, &mut damage);
+        assert!(damage.is_empty());
+        assert_eq!(surface.popup_rect(), None);
+    }
+
+    #[test]
+    fn move_popup() {
+        let mut surface = painted(Viewport::new(8, 8));
+        open_popup(&mut surface, rect(1, 1, 3, 3));
+
+        let mut damage = Damage::default();
+        surface.move_popup(rect(4, 1, 3, 3), &mut damage);
+        assert_eq!(damage.rects(), &[rect(1, 1, 3, 3), rect(4, 1, 3, 3)]);
+        assert_eq!(
+            bounds_of(&rows_with(&surface.frame, POPUP)),
+            Some(rect(4, 1, 3, 3))
+        );
+
+        // Popups that don't fit are moved into the view.
+        let mut damage = Damage::default();
+        surface.move_popup(rect(7, 6, 3, 3), &mut damage);
+        assert_eq!(damage.rects(), &[rect(4, 1, 3, 3), rect(5, 5, 3, 3)]);
+        assert_eq!(
+            bounds_of(&rows_with(&surface.frame, POPUP)),
+            Some(rect(5, 5, 3, 3))
+        );
+    }
+
+    #[test]
+    fn popup_device_scale_factor() {
+        let mut surface = painted(Viewport::new(4, 4).device_scale_factor(2.0));
+        assert_eq!((surface.frame.width(), surface.frame.height()), (8, 8));
+        let damage = open_popup(&mut surface, rect(1, 2, 2, 2));
+        // The location is in view coordinates, the popup's size in pixels.
+        assert_eq!(damage.rects(), &[rect(2, 4, 3, 3)]);
+        assert_eq!(
+            bounds_of(&rows_with(&surface.frame, POPUP)),
+            Some(rect(2, 4, 3, 3))
+        );
+    }
+
+    #[test]
+    fn frames_share_pixels() {
+        let mut surface = painted(Viewport::new(2, 2));
+        let held = surface.frame.clone();
+        assert!(Arc::ptr_eq(&held.data, &surface.frame.data));
+
+        let mut damage = Damage::default();
+        surface.paint_view(&[], &solid(2, 2, OTHER), 2, 2, &mut damage);
+        // The held frame is copied on write and doesn't change.
+        assert!(!Arc::ptr_eq(&held.data, &surface.frame.data));
+        assert_eq!(held.pixel(0, 0), Some(VIEW));
+        assert_eq!(surface.frame.pixel(0, 0), Some(OTHER));
+        assert_eq!(held.into_data(), solid(2, 2, VIEW));
+    }
+
+    #[test]
+    fn damage_is_merged() {
+        let mut damage = Damage::default();
+        damage.add(rect(0, 0, 0, 5));
+        assert!(damage.is_empty());
+        assert_eq!(damage.bounds(), None);
+
+        damage.add(rect(2, 2, 2, 2));
+        // Contained rectangles are dropped, containing ones replace others.
+        damage.add(rect(3, 3, 1, 1));
+        assert_eq!(damage.rects(), &[rect(2, 2, 2, 2)]);
+        damage.add(rect(1, 1, 4, 4));
+        assert_eq!(damage.rects(), &[rect(1, 1, 4, 4)]);
+
+        let mut other = Damage::default();
+        other.add(rect(10, 0, 1, 1));
+        damage.extend(&other);
+        assert_eq!(damage.rects(), &[rect(1, 1, 4, 4), rect(10, 0, 1, 1)]);
+        assert_eq!(damage.bounds(), Some(rect(1, 0, 10, 5)));
+        assert_eq!(Damage::full(3, 2).rects(), &[rect(0, 0, 3, 2)]);
+    }
+
+    #[test]
+    fn damage_limit() {
+        let mut damage = Damage::default();
+        for i in 0..Damage::MAX_RECTS as i32 {
+            damage.add(rect(2 * i, i, 1, 1));
+        }
+        assert_eq!(damage.rects().len(), Damage::MAX_RECTS);
+        let last = Damage::MAX_RECTS as i32;
+        damage.add(rect(2 * last, last, 1, 1));
+        assert_eq!(damage.rects(), &[rect(0, 0, 2 * last + 1, last + 1)]);
+    }
+
+    #[test]
+    fn crop() {
+        let frame = numbered(4, 4);
+        let cropped = frame.crop(rect(1, 2, 2, 2)).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (2, 2));
+        assert_eq!(cropped.sequence(), frame.sequence());
+        assert_eq!(cropped.format(), frame.format());
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter().copied() {
+            assert_eq!(cropped.pixel(x, y), frame.pixel(x + 1, y + 2));
+        }
+
+        let clipped = frame.crop(rect(3, -1, 5, 2)).unwrap();
+        assert_eq!((clipped.width(), clipped.height()), (1, 1));
+        assert_eq!(clipped.pixel(0, 0), frame.pixel(3, 0));
+
+        assert!(frame.crop(rect(4, 0, 2, 2)).is_none());
+        assert!(frame.crop(rect(0, 0, 0, 4)).is_none());
+    }
+
+    #[test]
+    fn pixel_bounds() {
+        let frame = numbered(2, 2);
+        assert_eq!(frame.pixel(1, 1), Some([12, 13, 14, 15]));
+        assert_eq!(frame.pixel(2, 0), None);
+        assert_eq!(frame.pixel(0, -1), None);
+    }
+
+    #[test]
+    fn rect_operations() {
+        let a = rect(0, 0, 4, 4);
+        assert_eq!(intersect(a, rect(2, 1, 4, 2)), Some(rect(2, 1, 2, 2)));
+        assert_eq!(intersect(a, rect(1, 1, 1, 1)), Some(rect(1, 1, 1, 1)));
+        // Touching rectangles don't intersect.
+        assert_eq!(intersect(a, rect(4, 0, 2, 2)), None);
+        assert_eq!(intersect(a, rect(-3, -3, 2, 2)), None);
+
+        assert_eq!(union(a, rect(2, 1, 4, 2)), rect(0, 0, 6, 4));
+        assert_eq!(
+            union(rect(5, 5, 1, 1), rect(-1, 2, 1, 1)),
+            rect(-1, 2, 7, 4)
+        );
+        assert!(contains(a, rect(1, 1, 3, 3)));
+        assert!(!contains(a, rect(1, 1, 4, 3)));
+        assert!(is_empty(rect(3, 3, 0, 2)));
+        assert!(is_empty(rect(3, 3, 2, -1)));
+    }
+
+    #[test]
+    fn copy_rect_clipping() {
+        let src = numbered(4, 4).into_data();
+        let mut dst = vec![0; 4 * 3 * 2];
+        copy_rect(
+            &src,
+            4,
+            &mut dst,
+            3,
+            rect(2, 1, 2, 2),
+            Point::new(1, 0),
+            true,
+        );
+        #[rustfmt::skip]
+        let expected = [
+            0, 0, 0, 0,    26, 25, 24, 27,  30, 29, 28, 31,
+            0, 0, 0, 0,    42, 41, 40, 43,  46, 45, 44, 47,
+        ];
+        assert_eq!(dst, expected);
+
+        // Rectangles beyond the buffers are clipped by the callers.
+        let frame = ComposedFrame::from_data(4, 4, PixelFormat::Bgra, 0, src);
+        assert_eq!(
+            frame.crop(rect(2, 2, 100, 100)).unwrap().data(),
+            &[40, 41, 42, 43, 44, 45, 46, 47, 56, 57, 58, 59, 60, 61, 62, 63]
+        );
+        let mut surface = Surface::new(Viewport::new(4, 4), PixelFormat::Bgra);
+        let mut damage = Damage::default();
+        surface.paint_view(&[], &solid(4, 4, VIEW), 4, 4, &mut damage);
+        let mut damage = Damage::default();
+        surface.paint_view(
+            &[rect(-2, 3, 100, 100)],
+            &solid(4, 4, OTHER),
+            4,
+            4,
+            &mut damage,
+        );
+        assert_eq!(damage.rects(), &[rect(0, 3, 4, 1)]);
+        assert_eq!(rows_with(&surface.frame, OTHER), vec![rect(0, 3, 4, 1)]);
+    }
+}
@@ -0,0 +1,890 @@
+//! Fast conversions of the buffers passed to
+//! [crate::client::render_handler::RenderHandlerCallbacks::on_paint], which are
+//! BGRA with premultiplied alpha, into the formats graphics APIs and video
+//! encoders expect, and scaling of such buffers.
+//!
+//! Conversions work row by row, so [convert_rects] can update only the dirty
+//! rectangles of a persistent destination buffer with any row stride, like a
+//! mapped texture:
+//!
+//! ```ignore
+//!  fn on_paint(&self, browser: Browser, element_type: PaintElementType, dirty_rects: &[Rect], buffer: &[u8], width: i32, height: i32) {
+//!      let mut texture = self.staging_buffer.map();
+//!      convert_rects(ImageRef::new(buffer, width, height), dirty_rects, &mut texture, self.row_pitch, TargetFormat::Rgba);
+//!  }
+//! ```
+//!
+//! On x86-64 processors with SSSE3, reordering channels and computing luma use
+//! SIMD instructions. Elsewhere a portable version handles one 32 bit pixel at
+//! a time, which compilers vectorize well. Unpremultiplying uses a lookup table.
+
+use super::{compositor::intersect, ComposedFrame, PixelFormat};
+use crate::values::Rect;
+use lazy_static::lazy_static;
+
+/// A borrowed image with 4 bytes per pixel and premultiplied alpha.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageRef<'a> {
+    data: &'a [u8],
+    width: i32,
+    height: i32,
+    stride: usize,
+    format: PixelFormat,
+}
+
+impl<'a> ImageRef<'a> {
+    /// A BGRA image with tightly packed rows, like the buffer passed to
+    /// `on_paint`.
+    pub fn new(data: &'a [u8], width: i32, height: i32) -> ImageRef<'a> {
+        ImageRef::with_stride(data, width, height, 4 * width.max(0) as usize)
+    }
+    /// A BGRA image with rows `stride` bytes apart. Panics if `data` is too
+    /// small.
+    pub fn with_stride(data: &'a [u8], width: i32, height: i32, stride: usize) -> ImageRef<'a> {
+        let (width, height) = (width.max(0), height.max(0));
+        assert!(stride >= 4 * width as usize, "stride smaller than a row");
+        if height > 0 {
+            assert!(
+                data.len() >= stride * (height as usize - 1) + 4 * width as usize,
+                "image data too small"
+            );
+        }
+        ImageRef {
+            data,
+            width,
+            height,
+            stride,
+            format: PixelFormat::Bgra,
+        }
+    }
+    /// The order of the color channels. Defaults to [PixelFormat::Bgra].
+    pub fn format(self, format: PixelFormat) -> ImageRef<'a> {
+        ImageRef { format, ..self }
+    }
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+    fn bounds(&self) -> Rect {
+        Rect {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        }
+    }
+    /// Returns `width` pixels of row `y`, starting at column `x`.
+    fn pixels(&self, x: i32, y: i32, width: i32) -> &'a [u8] {
+        &self.data[y as usize * self.stride + 4 * x as usize..][..4 * width as usize]
+    }
+}
+
+impl<'a> From<&'a ComposedFrame> for ImageRef<'a> {
+    fn from(frame: &'a ComposedFrame) -> ImageRef<'a> {
+        ImageRef::with_stride(frame.data(), frame.width(), frame.height(), frame.stride())
+            .format(frame.format())
+    }
+}
+
+/// The pixel format written by [convert] and [convert_rects].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetFormat {
+    /// BGRA with premultiplied alpha.
+    Bgra,
+    /// RGBA with premultiplied alpha.
+    Rgba,
+    /// RGB with 3 bytes per pixel. Since alpha is premultiplied, this is the
+    /// image composed onto black.
+    Rgb,
+    /// RGBA with straight alpha, as expected by most image formats.
+    UnpremultipliedRgba,
+}
+
+impl TargetFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TargetFormat::Rgb => 3,
+            _ => 4,
+        }
+    }
+    fn order(self) -> PixelFormat {
+        match self {
+            TargetFormat::Bgra => PixelFormat::Bgra,
+            _ => PixelFormat::Rgba,
+        }
+    }
+}
+
+/// Converts `src` into `dst`, whose rows are `dst_stride` bytes apart. Panics
+/// if `dst` is too small.
+pub fn convert(src: ImageRef, dst: &mut [u8], dst_stride: usize, format: TargetFormat) {
+    convert_rects(src, &[src.bounds()], dst, dst_stride, format)
+}
+
+/// Converts the parts of `src` covered by `rects` into the same positions of
+/// `dst`, whose rows are `dst_stride` bytes apart. `dst` must have the size of
+/// `src`, the rest of it is left alone. Panics if `dst` is too small.
+pub fn convert_rects(
+    src: ImageRef,
+    rects: &[Rect],
+    dst: &mut [u8],
+    dst_stride: usize,
+    format: TargetFormat,
+) {
+    let bytes_per_pixel = format.bytes_per_pixel();
+    assert!(
+        dst_stride >= bytes_per_pixel * src.width as usize,
+        "stride smaller than a row"
+    );
+    if src.height > 0 {
+        assert!(
+            dst.len()
+                >= dst_stride * (src.height as usize - 1) + bytes_per_pixel * src.width as usize,
+            "destination too small"
+        );
+    }
+    let swap = src.format != format.order();
+    for rect in rects
+        .iter()
+        .filter_map(|&rect| intersect(rect, src.bounds()))
+    {
+        for y in rect.y..rect.y + rect.height {
+            let offset = y as usize * dst_stride + bytes_per_pixel * rect.x as usize;
+            let dst_row = &mut dst[offset..offset + bytes_per_pixel * rect.width as usize];
+            convert_row(src.pixels(rect.x, y, rect.width), dst_row, format, swap);
+        }
+    }
+}
+
+fn convert_row(src: &[u8], dst: &mut [u8], format: TargetFormat, swap: bool) {
+    match (format, swap) {
+        (TargetFormat::Bgra, false) | (TargetFormat::Rgba, false) => dst.copy_from_slice(src),
+        (TargetFormat::Bgra, true) | (TargetFormat::Rgba, true) => swap_red_blue(src, dst),
+        (TargetFormat::Rgb, _) => drop_alpha(src, dst, swap),
+        (TargetFormat::UnpremultipliedRgba, _) => unpremultiply(src, dst, swap),
+    }
+}
+
+/// Copies the pixels of `src` to `dst`, swapping the first and third channel.
+/// Both must have the same length.
+pub(crate) fn swap_red_blue(src: &[u8], dst: &mut [u8]) {
+    let done = simd::shuffle(src, dst, 4, &simd::SWAP_RED_BLUE);
+    for (src, dst) in src[4 * done..]
+        .chunks_exact(4)
+        .zip(dst[4 * done..].chunks_exact_mut(4))
+    {
+        let pixel = u32::from_le_bytes([src[0], src[1], src[2], src[3]]);
+        let swapped = pixel & 0xff00_ff00 | (pixel >> 16) & 0xff | (pixel & 0xff) << 16;
+        dst.copy_from_slice(&swapped.to_le_bytes());
+    }
+}
+
+fn drop_alpha(src: &[u8], dst: &mut [u8], swap: bool) {
+    let mask = if swap {
+        &simd::DROP_ALPHA_SWAPPED
+    } else {
+        &simd::DROP_ALPHA
+    };
+    let done = simd::shuffle(src, dst, 3, mask);
+    let (first, third) = if swap { (2, 0) } else { (0, 2) };
+    for (src, dst) in src[4 * done..]
+        .chunks_exact(4)
+        .zip(dst[3 * done..].chunks_exact_mut(3))
+    {
+        dst.copy_from_slice(&[src[first], src[1], src[third]]);
+    }
+}
+
+lazy_static! {
+    /// `UNPREMULTIPLY[alpha][color]` is the straight alpha value of a color
+    /// channel, rounded to the nearest value.
+    static ref UNPREMULTIPLY: Box<[[u8; 256]; 256]> = {
+        let mut table = Box::new([[0; 256]; 256]);
+        for (alpha, row) in table.iter_mut().enumerate().skip(1) {
+            for (color, value) in row.iter_mut().enumerate() {
+                *value = ((color * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+        table
+    };
+}
+
+fn unpremultiply(src: &[u8], dst: &mut [u8], swap: bool) {
+    if swap {
+        swap_red_blue(src, dst);
+    } else {
+        dst.copy_from_slice(src);
+    }
+    let table = &*UNPREMULTIPLY;
+    for pixel in dst.chunks_exact_mut(4) {
+        let alpha = pixel[3];
+        if alpha != 255 {
+            let row = &table[alpha as usize];
+            for color in &mut pixel[..3] {
+                *color = row[*color as usize];
+            }
+        }
+    }
+}
+
+/// Converts the part of `src` at the origin with the given size to I420, which
+/// are planes of Y, U and V with half the width and height for U and V, with
+/// BT.601 coefficients. Pixels outside of `src` are black, and so are
+/// transparent ones. Replaces the contents of `out`.
+pub fn to_i420(src: ImageRef, width: i32, height: i32, out: &mut Vec<u8>) {
+    let (width, height) = (width.max(0), height.max(0));
+    let chroma_width = (width + 1) / 2;
+    let chroma_height = (height + 1) / 2;
+    let luma_len = (width * height) as usize;
+    let chroma_len = (chroma_width * chroma_height) as usize;
+    out.clear();
+    out.resize(luma_len + 2 * chroma_len, 0);
+    let (luma, chroma) = out.split_at_mut(luma_len);
+    let (u_plane, v_plane) = chroma.split_at_mut(chroma_len);
+
+    let covered = width.min(src.width);
+    let (red, blue) = match src.format {
+        PixelFormat::Bgra => (2, 0),
+        PixelFormat::Rgba => (0, 2),
+    };
+    for (y, luma_row) in luma.chunks_exact_mut(width.max(1) as usize).enumerate() {
+        let y = y as i32;
+        let (inside, outside) =
+            luma_row.split_at_mut(if y < src.height { covered as usize } else { 0 });
+        if !inside.is_empty() {
+            let pixels = src.pixels(0, y, covered);
+            let done = simd::luma(pixels, inside, src.format);
+            for (pixel, luma) in pixels[4 * done..].chunks_exact(4).zip(&mut inside[done..]) {
+                let (r, g, b) = (pixel[red] as i32, pixel[1] as i32, pixel[blue] as i32);
+                *luma = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+            }
+        }
+        for luma in outside {
+            *luma = 16;
+        }
+    }
+    // Sums of the channels of each block of 2x2 pixels in a row of blocks, in
+    // the order of `src`.
+    let mut sums = Vec::with_capacity(chroma_width as usize);
+    for cy in 0..chroma_height {
+        sums.clear();
+        sums.resize(chroma_width as usize, [0i32; 3]);
+        let rows = 2 * cy..(2 * cy + 2).min(height);
+        let row_count = rows.len() as i32;
+        for y in rows.filter(|&y| y < src.height) {
+            for (sum, pixels) in sums.iter_mut().zip(src.pixels(0, y, covered).chunks(8)) {
+                for pixel in pixels.chunks_exact(4) {
+                    sum[0] += pixel[0] as i32;
+                    sum[1] += pixel[1] as i32;
+                    sum[2] += pixel[2] as i32;
+                }
+            }
+        }
+        let u_row = &mut u_plane[(cy * chroma_width) as usize..][..chroma_width as usize];
+        let v_row = &mut v_plane[(cy * chroma_width) as usize..][..chroma_width as usize];
+        // Whole blocks are averaged with shifts instead of slow divisions.
+        let whole = if row_count == 2 {
+            width as usize / 2
+        } else {
+            0
+        };
+        for ((sum, u), v) in sums[..whole]
+            .iter()
+            .zip(&mut u_row[..whole])
+            .zip(&mut v_row[..whole])
+        {
+            *u = chroma_u(sum[red] >> 2, sum[1] >> 2, sum[blue] >> 2);
+            *v = chroma_v(sum[red] >> 2, sum[1] >> 2, sum[blue] >> 2);
+        }
+        for cx in whole..chroma_width as usize {
+            let count = (width - 2 * cx as i32).min(2) * row_count;
+            let sum = sums[cx];
+            u_row[cx] = chroma_u(sum[red] / count, sum[1] / count, sum[blue] / count);
+            v_row[cx] = chroma_v(sum[red] / count, sum[1] / count, sum[blue] / count);
+        }
+    }
+}
+
+fn chroma_u(r: i32, g: i32, b: i32) -> u8 {
+    (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8
+}
+
+fn chroma_v(r: i32, g: i32, b: i32) -> u8 {
+    (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8
+}
+
+/// The filter used by [scale].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleFilter {
+    /// Averages all source pixels covered by a destination pixel. Best for
+    /// shrinking by large factors, like thumbnails. Enlarging repeats pixels.
+    Box,
+    /// Interpolates between the 4 nearest source pixels. Smoother when
+    /// enlarging or shrinking by less than half, but skips pixels otherwise.
+    Bilinear,
+}
+
+/// Scales `src` to `dst_width` x `dst_height` pixels in `dst`, whose rows are
+/// `dst_stride` bytes apart. The pixels keep the format of `src`, and since
+/// alpha is premultiplied, transparent pixels don't bleed into their
+/// neighbors. Panics if `dst` is too small.
+pub fn scale(
+    src: ImageRef,
+    dst: &mut [u8],
+    dst_width: i32,
+    dst_height: i32,
+    dst_stride: usize,
+    filter: ScaleFilter,
+) {
+    let (dst_width, dst_height) = (dst_width.max(0) as usize, dst_height.max(0) as usize);
+    assert!(dst_stride >= 4 * dst_width, "stride smaller than a row");
+    if dst_height > 0 {
+        assert!(
+            dst.len() >= dst_stride * (dst_height - 1) + 4 * dst_width,
+            "destination too small"
+        );
+    }
+    if dst_width == 0 || dst_height == 0 {
+        return;
+    }
+    if src.width == 0 || src.height == 0 {
+        for row in dst.chunks_mut(dst_stride).take(dst_height) {
+            for value in &mut row[..4 * dst_width] {
+                *value = 0;
+            }
+        }
+        return;
+    }
+    let (src_width, src_height) = (src.width as usize, src.height as usize);
+    match filter {
+        ScaleFilter::Box => {
+            let columns = box_ranges(src_width, dst_width);
+            let rows = box_ranges(src_height, dst_height);
+            let mut sums = Vec::with_capacity(4 * dst_width);
+            for (row, &(top, bottom)) in dst.chunks_mut(dst_stride).zip(&rows) {
+                sums.clear();
+                sums.resize(4 * dst_width, 0u32);
+                for y in top..bottom {
+                    let src_row = src.pixels(0, y as i32, src.width);
+                    for (sum, &(left, right)) in sums.chunks_exact_mut(4).zip(&columns) {
+                        for pixel in src_row[4 * left..4 * right].chunks_exact(4) {
+                            for (sum, &value) in sum.iter_mut().zip(pixel) {
+                                *sum += value as u32;
+                            }
+                        }
+                    }
+                }
+                for ((pixel, sum), &(left, right)) in row
+                    .chunks_exact_mut(4)
+                    .zip(sums.chunks_exact(4))
+                    .zip(&columns)
+                {
+                    let count = ((right - left) * (bottom - top)) as u32;
+                    for (value, &sum) in pixel.iter_mut().zip(sum) {
+                        *value = ((sum + count / 2) / count) as u8;
+                    }
+                }
+            }
+        }
+        ScaleFilter::Bilinear => {
+            let columns = bilinear_samples(src_width, dst_width);
+            let rows = bilinear_samples(src_height, dst_height);
+            for (row, &(top, bottom, fy)) in dst.chunks_mut(dst_stride).zip(&rows) {
+                let top = src.pixels(0, top as i32, src.width);
+                let bottom = src.pixels(0, bottom as i32, src.width);
+                for (pixel, &(left, right, fx)) in row.chunks_exact_mut(4).zip(&columns) {
+                    for (channel, value) in pixel.iter_mut().enumerate() {
+                        let lerp = |row: &[u8]| {
+                            row[4 * left + channel] as u32 * (256 - fx)
+                                + row[4 * right + channel] as u32 * fx
+                        };
+                        *value =
+                            ((lerp(top) * (256 - fy) + lerp(bottom) * fy + (1 << 15)) >> 16) as u8;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The range of source pixels averaged for each destination pixel.
+fn box_ranges(src_len: usize, dst_len: usize) -> Vec<(usize, usize)> {
+    (0..dst_len)
+        .map(|index| {
+            let start = index * src_len / dst_len;
+            let end = ((index + 1) * src_len / dst_len)
+                .max(start + 1)
+                .min(src_len);
+            (start, end)
+        })
+        .collect()
+}
+
+/// The two source pixels interpolated for each destination pixel, and the
+/// weight of the second one out of 256. Pixel centers are aligned.
+fn bilinear_samples(src_len: usize, dst_len: usize) -> Vec<(usize, usize, u32)> {
+    let ratio = src_len as f64 / dst_len as f64;
+    (0..dst_len)
+        .map(|index| {
+            let position = ((index as f64 + 0.5) * ratio - 0.5)
+                .max(0.0)
+                .min((src_len - 1) as f64);
+            let first = position as usize;
+            let second = (first + 1).min(src_len - 1);
+            (first, second, ((position - first as f64) * 256.0) as u32)
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use super::PixelFormat;
+    use std::arch::x86_64::*;
+    #[cfg(test)]
+    use std::cell::Cell;
+
+    pub(super) const SWAP_RED_BLUE: [i8; 16] =
+        [2, 1, 0, 3, 6, 5, 4, 7, 10, 9, 8, 11, 14, 13, 12, 15];
+    pub(super) const DROP_ALPHA: [i8; 16] =
+        [0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, -1, -1, -1, -1];
+    pub(super) const DROP_ALPHA_SWAPPED: [i8; 16] =
+        [2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, -1, -1, -1, -1];
+
+    #[cfg(test)]
+    thread_local! {
+        /// Lets tests compare the SIMD versions with the portable ones.
+        pub(super) static DISABLED: Cell<bool> = Cell::new(false);
+    }
+
+    fn ssse3() -> bool {
+        #[cfg(test)]
+        {
+            if DISABLED.with(Cell::get) {
+                return false;
+            }
+        }
+        is_x86_feature_detected!("ssse3")
+    }
+
+    /// Rearranges the bytes of groups of 4 pixels of `src` according to `mask`
+    /// and stores them in `dst`. Returns the number of pixels done, the rest
+    /// has to be done by the caller.
+    pub(super) fn shuffle(
+        src: &[u8],
+        dst: &mut [u8],
+        dst_bytes_per_pixel: usize,
+        mask: &[i8; 16],
+    ) -> usize {
+        if ssse3() {
+            unsafe { shuffle_ssse3(src, dst, dst_bytes_per_pixel, mask) }
+        } else {
+            0
+        }
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn shuffle_ssse3(
+        src: &[u8],
+        dst: &mut [u8],
+        dst_bytes_per_pixel: usize,
+        mask: &[i8; 16],
+    ) -> usize {
+        let pixels = (src.len() / 4).min(dst.len() / dst_bytes_per_pixel);
+        let mask = _mm_loadu_si128(mask.as_ptr() as *const __m128i);
+        let mut done = 0;
+        // Every store writes 16 bytes, even if fewer are used.
+        while done + 4 <= pixels && done * dst_bytes_per_pixel + 16 <= dst.len() {
+            let input = _mm_loadu_si128(src.as_ptr().add(4 * done) as *const __m128i);
+            let output = _mm_shuffle_epi8(input, mask);
+            _mm_storeu_si128(
+                dst.as_mut_ptr().add(done * dst_bytes_per_pixel) as *mut __m128i,
+                output,
+            );
+            done += 4;
+        }
+        done
+    }
+
+    /// Computes the luma of groups of 4 pixels of `src` into `dst`. Returns the
+    /// number of pixels done.
+    pub(super) fn luma(src: &[u8], dst: &mut [u8], format: PixelFormat) -> usize {
+        if ssse3() {
+            unsafe { luma_ssse3(src, dst, format) }
+        } else {
+            0
+        }
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn luma_ssse3(src: &[u8], dst: &mut [u8], format: PixelFormat) -> usize {
+        let pixels = (src.len() / 4).min(dst.len());
+        let coefficients = match format {
+            PixelFormat::Bgra => _mm_setr_epi16(25, 129, 66, 0, 25, 129, 66, 0),
+            PixelFormat::Rgba => _mm_setr_epi16(66, 129, 25, 0, 66, 129, 25, 0),
+        };
+        let zero = _mm_setzero_si128();
+        let rounding = _mm_set1_epi32(128);
+        let offset = _mm_set1_epi32(16);
+        let mut done = 0;
+        while done + 4 <= pixels {
+            let input = _mm_loadu_si128(src.as_ptr().add(4 * done) as *const __m128i);
+            // Two pixels per register as 16 bit values, multiplied and summed
+            // pairwise, then the pairs are summed.
+            let low = _mm_madd_epi16(_mm_unpacklo_epi8(input, zero), coefficients);
+            let high = _mm_madd_epi16(_mm_unpackhi_epi8(input, zero), coefficients);
+            let sums = _mm_hadd_epi32(low, high);
+            let luma = _mm_add_epi32(_mm_srai_epi32(_mm_add_epi32(sums, rounding), 8), offset);
+            let luma = _mm_packus_epi16(_mm_packs_epi32(luma, zero), zero);
+            let bytes = (_mm_cvtsi128_si32(luma) as u32).to_le_bytes();
+            dst[done..done + 4].copy_from_slice(&bytes);
+            done += 4;
+        }
+        done
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+mod simd {
+    use super::PixelFormat;
+
+    pub(super) const SWAP_RED_BLUE: [i8; 16] = [0; 16];
+    pub(super) const DROP_ALPHA: [i8; 16] = [0; 16];
+    pub(super) const DROP_ALPHA_SWAPPED: [i8; 16] = [0; 16];
+
+    pub(super) fn shuffle(
+        src: &[u8],
+        dst: &mut [u8],
+        dst_bytes_per_pixel: usize,
+        mask: &[i8; 16],
+    ) -> usize {
+        0
+    }
+
+    pub(super) fn luma(src: &[u8], dst: &mut [u8], format: PixelFormat) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [0, 0, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    fn pixels(pixels: &[[u8; 4]]) -> Vec<u8> {
+        pixels.concat()
+    }
+
+    /// Premultiplied BGRA pixels that are the same for every run.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x1234_5678u32;
+        (0..len / 4)
+            .flat_map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                let [b, g, r, a] = state.to_le_bytes();
+                [b.min(a), g.min(a), r.min(a), a]
+            })
+            .collect()
+    }
+
+    /// Runs `fun` without SIMD instructions.
+    fn portable<T>(fun: impl FnOnce() -> T) -> T {
+        #[cfg(target_arch = "x86_64")]
+        simd::DISABLED.with(|disabled| disabled.set(true));
+        let result = fun();
+        #[cfg(target_arch = "x86_64")]
+        simd::DISABLED.with(|disabled| disabled.set(false));
+        result
+    }
+
+    fn converted(src: ImageRef, format: TargetFormat) -> Vec<u8> {
+        let stride = format.bytes_per_pixel() * src.width() as usize;
+        let mut dst = vec![0; stride * src.height() as usize];
+        convert(src, &mut dst, stride, format);
+        dst
+    }
+
+    fn i420(src: ImageRef, width: i32, height: i32) -> Vec<u8> {
+        let mut out = Vec::new();
+        to_i420(src, width, height, &mut out);
+        out
+    }
+
+    fn scaled(src: ImageRef, width: i32, height: i32, filter: ScaleFilter) -> Vec<u8> {
+        let mut dst = vec![0; 4 * (width * height) as usize];
+        scale(src, &mut dst, width, height, 4 * width as usize, filter);
+        dst
+    }
+
+    #[test]
+    fn rgba_and_rgb() {
+        let src = pixels(&[[10, 20, 30, 40], [1, 2, 3, 255]]);
+        let image = ImageRef::new(&src, 2, 1);
+        assert_eq!(converted(image, TargetFormat::Bgra), src);
+        assert_eq!(
+            converted(image, TargetFormat::Rgba),
+            [30, 20, 10, 40, 3, 2, 1, 255]
+        );
+        assert_eq!(converted(image, TargetFormat::Rgb), [30, 20, 10, 3, 2, 1]);
+
+        let image = image.format(PixelFormat::Rgba);
+        assert_eq!(converted(image, TargetFormat::Rgba), src);
+        assert_eq!(
+            converted(image, TargetFormat::Bgra),
+            [30, 20, 10, 40, 3, 2, 1, 255]
+        );
+        assert_eq!(converted(image, TargetFormat::Rgb), [10, 20, 30, 1, 2, 3]);
+    }
+
+    #[test]
+    fn unpremultiplied_rgba() {
+        let src = pixels(&[
+            // (50 * 255 + 64) / 128 = 100.1 and (100 * 255 + 64) / 128 = 199.7
+            [50, 100, 0, 128],
+            [7, 8, 9, 0],
+            [1, 2, 3, 255],
+            // Colors brighter than alpha are invalid and clamped.
+            [200, 5, 10, 10],
+        ]);
+        assert_eq!(
+            converted(ImageRef::new(&src, 4, 1), TargetFormat::UnpremultipliedRgba),
+            pixels(&[
+                [0, 199, 100, 128],
+                [0, 0, 0, 0],
+                [3, 2, 1, 255],
+                [255, 128, 255, 10],
+            ])
+        );
+    }
+
+    #[test]
+    fn dirty_rects_with_stride() {
+        let src = pixels(&[
+            [1, 2, 3, 4],
+            [5, 6, 7, 8],
+            [9, 10, 11, 12],
+            [13, 14, 15, 16],
+            [17, 18, 19, 20],
+            [21, 22, 23, 24],
+        ]);
+        // 3x2 RGB pixels in rows of 11 bytes, 2 of them padding.
+        let mut dst = vec![0xaa; 22];
+        let rects = [
+            Rect {
+                x: 1,
+                y: 0,
+                width: 1,
+                height: 1,
+            },
+            // Clipped to the pixel in the lower-right corner.
+            Rect {
+                x: 2,
+                y: 1,
+                width: 5,
+                height: 5,
+            },
+            Rect {
+                x: -3,
+                y: 0,
+                width: 2,
+                height: 2,
+            },
+        ];
+        convert_rects(
+            ImageRef::new(&src, 3, 2),
+            &rects,
+            &mut dst,
+            11,
+            TargetFormat::Rgb,
+        );
+        #[rustfmt::skip]
+        let expected = [
+            0xaa, 0xaa, 0xaa, 7, 6, 5, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa,
+            0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 23, 22, 21, 0xaa, 0xaa,
+        ];
+        assert_eq!(dst, expected);
+
+        // The source can have padding as well.
+        let padded = pixels(&[[1, 2, 3, 4], [0xbb; 4], [5, 6, 7, 8], [0xbb; 4]]);
+        let image = ImageRef::with_stride(&padded, 1, 2, 8);
+        assert_eq!(
+            converted(image, TargetFormat::Rgba),
+            [3, 2, 1, 4, 7, 6, 5, 8]
+        );
+    }
+
+    #[test]
+    fn i420_colors() {
+        // BT.601: white is (235, 128, 128), black (16, 128, 128) and red
+        // (82, 90, 240).
+        let src = pixels(&[WHITE, WHITE, WHITE, WHITE]);
+        assert_eq!(
+            i420(ImageRef::new(&src, 2, 2), 2, 2),
+            [235, 235, 235, 235, 128, 128]
+        );
+        let src = pixels(&[RED, RED, RED, RED]);
+        assert_eq!(
+            i420(ImageRef::new(&src, 2, 2), 2, 2),
+            [82, 82, 82, 82, 90, 240]
+        );
+        let src = pixels(&[[0, 0, 0, 0]; 4]);
+        assert_eq!(
+            i420(ImageRef::new(&src, 2, 2), 2, 2),
+            [16, 16, 16, 16, 128, 128]
+        );
+        // The red channel is in a different place.
+        let src = pixels(&[[255, 0, 0, 255]; 4]);
+        let image = ImageRef::new(&src, 2, 2).format(PixelFormat::Rgba);
+        assert_eq!(i420(image, 2, 2), [82, 82, 82, 82, 90, 240]);
+    }
+
+    #[test]
+    fn i420_odd_size() {
+        let src = pixels(&[
+            RED, RED, WHITE, //
+            BLACK, BLACK, WHITE, //
+            RED, BLACK, WHITE,
+        ]);
+        // Half red and half black averages to a red of 510 >> 2 = 127, so U is
+        // ((-38 * 127 + 128) >> 8) + 128 = 109 and V is
+        // ((112 * 127 + 128) >> 8) + 128 = 184. The blocks on the right and at
+        // the bottom only average the pixels they cover.
+        #[rustfmt::skip]
+        let expected = [
+            82, 82, 235,
+            16, 16, 235,
+            82, 16, 235,
+            109, 128,
+            109, 128,
+            184, 128,
+            184, 128,
+        ];
+        assert_eq!(i420(ImageRef::new(&src, 3, 3), 3, 3), expected);
+    }
+
+    #[test]
+    fn i420_padding() {
+        // Outside of the source is black: the red of the block is 255 >> 2 = 63,
+        // which makes U ((-38 * 63 + 128) >> 8) + 128 = 119 and V
+        // ((112 * 63 + 128) >> 8) + 128 = 156.
+        let src = pixels(&[RED]);
+        assert_eq!(
+            i420(ImageRef::new(&src, 1, 1), 2, 2),
+            [82, 16, 16, 16, 119, 156]
+        );
+        // Cropped to the requested size.
+        let src = pixels(&[RED, WHITE, RED, WHITE]);
+        assert_eq!(i420(ImageRef::new(&src, 2, 2), 1, 1), [82, 90, 240]);
+    }
+
+    #[test]
+    fn box_downscale() {
+        #[rustfmt::skip]
+        let src = pixels(&[
+            [10, 0, 0, 255], [20, 0, 0, 255], [100, 4, 0, 255], [100, 5, 0, 255],
+            [30, 0, 0, 255], [41, 0, 0, 255], [100, 5, 0, 255], [100, 5, 0, 255],
+        ]);
+        let image = ImageRef::new(&src, 4, 2);
+        // (10 + 20 + 30 + 41 + 2) / 4 = 25.75 and (4 + 5 + 5 + 5 + 2) / 4 = 5.25
+        assert_eq!(
+            scaled(image, 2, 1, ScaleFilter::Box),
+            [25, 0, 0, 255, 100, 5, 0, 255]
+        );
+        // Uneven ratios: the first pixel covers column 0, the second columns 1
+        // and 2 of 3.
+        let src = pixels(&[[10, 0, 0, 0], [20, 0, 0, 0], [31, 0, 0, 0]]);
+        assert_eq!(
+            scaled(ImageRef::new(&src, 3, 1), 2, 1, ScaleFilter::Box),
+            [10, 0, 0, 0, 26, 0, 0, 0]
+        );
+        // Enlarging repeats pixels.
+        let src = pixels(&[[1, 2, 3, 4]]);
+        assert_eq!(
+            scaled(ImageRef::new(&src, 1, 1), 2, 1, ScaleFilter::Box),
+            [1, 2, 3, 4, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn bilinear_scale() {
+        // Halving interpolates between pairs of pixels with equal weights,
+        // (10 + 21) / 2 = 15.5 is rounded up.
+        let src = pixels(&[[10, 0, 0, 0], [21, 0, 0, 0], [0, 100, 0, 0], [0, 50, 0, 0]]);
+        assert_eq!(
+            scaled(ImageRef::new(&src, 4, 1), 2, 1, ScaleFilter::Bilinear),
+            [16, 0, 0, 0, 0, 75, 0, 0]
+        );
+        // Doubling samples at 0, 0.25, 0.75 and 1 of the way, clamped at the
+        // edges: 200 * 64 / 256 = 50 and 200 * 192 / 256 = 150.
+        let src = pixels(&[[0, 0, 0, 0], [200, 0, 0, 0]]);
+        assert_eq!(
+            scaled(ImageRef::new(&src, 2, 1), 4, 1, ScaleFilter::Bilinear),
+            pixels(&[[0, 0, 0, 0], [50, 0, 0, 0], [150, 0, 0, 0], [200, 0, 0, 0]])
+        );
+        // Vertically as well.
+        let src = pixels(&[[0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 100], [0, 0, 0, 100]]);
+        assert_eq!(
+            scaled(ImageRef::new(&src, 1, 4), 1, 2, ScaleFilter::Bilinear),
+            [0, 0, 0, 0, 0, 0, 0, 100]
+        );
+    }
+
+    #[test]
+    fn simd_matches_portable() {
+        for width in 1..=19 {
+            let src = noise(4 * width * 3);
+            for &format in &[PixelFormat::Bgra, PixelFormat::Rgba] {
+                let image = ImageRef::new(&src, width as i32, 3).format(format);
+                for &target in &[
+                    TargetFormat::Bgra,
+                    TargetFormat::Rgba,
+                    TargetFormat::Rgb,
+                    TargetFormat::UnpremultipliedRgba,
+                ] {
+                    assert_eq!(
+                        converted(image, target),
+                        portable(|| converted(image, target)),
+                        "{}x3 {:?} to {:?}",
+                        width,
+                        format,
+                        target
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simd_i420_matches_portable() {
+        for width in 1..=19 {
+            for height in 1..=5 {
+                let src = noise(4 * width * height);
+                for &format in &[PixelFormat::Bgra, PixelFormat::Rgba] {
+                    let image = ImageRef::new(&src, width as i32, height as i32).format(format);
+                    let (width, height) = (width as i32, height as i32);
+                    assert_eq!(
+                        i420(image, width, height),
+                        portable(|| i420(image, width, height)),
+                        "{}x{} {:?}",
+                        width,
+                        height,
+                        format
+                    );
+                    // Padded to a larger odd size.
+                    assert_eq!(
+                        i420(image, width + 3, height + 2),
+                        portable(|| i420(image, width + 3, height + 2)),
+                        "{}x{} {:?} padded",
+                        width,
+                        height,
+                        format
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,756 @@
+//! Comparison of [ComposedFrame]s with golden PNG images, for UI tests that
+//! render in windowless mode.
+//!
+//! Enabled by the `golden-images` feature. [check_golden] compares a frame with
+//! a golden image and writes the actual frame and a diff image next to the
+//! golden if they don't match. To accept the new rendering instead, run the
+//! tests with the environment variable `CEF_UPDATE_GOLDENS=1`, which makes
+//! [check_golden] overwrite the goldens:
+//!
+//! ```ignore
+//!  let screenshot = capture_element(&compositor, &frame, "#toolbar", ScreenshotOptions::new()).await?;
+//!  let options = CompareOptions::new()
+//!      .tolerance(2)
+//!      .ignore(Rect { x: 0, y: 0, width: 80, height: 20 });
+//!  check_golden(screenshot.frame(), "tests/goldens/toolbar.png", &options)?;
+//! ```
+
+use super::{
+    convert::{convert, TargetFormat},
+    ComposedFrame,
+};
+use crate::values::Rect;
+use std::{
+    env, fmt,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+/// If this environment variable is set to anything but `0`, [check_golden]
+/// writes the frames as new goldens instead of comparing them.
+pub const UPDATE_GOLDENS_VAR: &str = "CEF_UPDATE_GOLDENS";
+
+/// Why a golden image check failed.
+#[derive(Debug)]
+pub enum GoldenError {
+    Io(PathBuf, io::Error),
+    /// The PNG couldn't be decoded or encoded.
+    Png(PathBuf, String),
+    /// The golden image doesn't exist and [UPDATE_GOLDENS_VAR] isn't set.
+    Missing(PathBuf),
+    /// The images have different sizes, `(width, height)`.
+    SizeMismatch {
+        expected: (i32, i32),
+        actual: (i32, i32),
+    },
+    /// Too many pixels differ.
+    Mismatch {
+        golden: PathBuf,
+        differing_pixels: usize,
+        bounds: Option<Rect>,
+        diff: PathBuf,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoldenError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            GoldenError::Png(path, err) => write!(f, "{}: {}", path.display(), err),
+            GoldenError::Missing(path) => write!(
+                f,
+                "golden image {} doesn't exist, set {}=1 to create it",
+                path.display(),
+                UPDATE_GOLDENS_VAR
+            ),
+            GoldenError::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected an image of {}x{}, got {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            GoldenError::Mismatch {
+                golden,
+                differing_pixels,
+                bounds,
+                diff,
+            } => {
+                write!(
+                    f,
+                    "{} pixels differ from {}",
+                    differing_pixels,
+                    golden.display()
+                )?;
+                if let Some(bounds) = bounds {
+                    write!(
+                        f,
+                        " in {}x{} at ({}, {})",
+                        bounds.width, bounds.height, bounds.x, bounds.y
+                    )?;
+                }
+                write!(
+                    f,
+                    ", see {}, or set {}=1 to update the golden",
+                    diff.display(),
+                    UPDATE_GOLDENS_VAR
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GoldenError::Io(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// An image with 8 bit RGBA pixels without premultiplied alpha, the format of
+/// golden images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: i32,
+    height: i32,
+    data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Returns None if `data` isn't `width * height * 4` bytes long.
+    pub fn new(width: i32, height: i32, data: Vec<u8>) -> Option<RgbaImage> {
+        if width < 0 || height < 0 || data.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(RgbaImage {
+            width,
+            height,
+            data,
+        })
+    }
+    /// Converts the premultiplied pixels of `frame`.
+    pub fn from_frame(frame: &ComposedFrame) -> RgbaImage {
+        let stride = 4 * frame.width() as usize;
+        let mut data = vec![0; stride * frame.height() as usize];
+        convert(
+            frame.into(),
+            &mut data,
+            stride,
+            TargetFormat::UnpremultipliedRgba,
+        );
+        RgbaImage {
+            width: frame.width(),
+            height: frame.height(),
+            data,
+        }
+    }
+    /// Decodes a PNG file. Grayscale, palette and 16 bit images are converted.
+    pub fn load_png(path: impl AsRef<Path>) -> Result<RgbaImage, GoldenError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| GoldenError::Io(path.to_owned(), err))?;
+        let png_error =
+            |err: png::DecodingError| GoldenError::Png(path.to_owned(), err.to_string());
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info().map_err(png_error)?;
+        let mut buffer = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buffer).map_err(png_error)?;
+
+        let pixels = info.width as usize * info.height as usize;
+        let mut data = Vec::with_capacity(pixels * 4);
+        for y in 0..info.height as usize {
+            let row = &buffer[y * info.line_size..(y + 1) * info.line_size];
+            match info.color_type {
+                png::ColorType::RGBA => data.extend_from_slice(row),
+                png::ColorType::RGB => {
+                    for rgb in row.chunks_exact(3) {
+                        data.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+                    }
+                }
+                png::ColorType::GrayscaleAlpha => {
+                    for ga in row.chunks_exact(2) {
+                        data.extend_from_slice(&[ga[0], ga[0], ga[0], ga[1]]);
+                    }
+                }
+                png::ColorType::Grayscale => {
+                    for &g in row {
+                        data.extend_from_slice(&[g, g, g, 255]);
+                    }
+                }
+                png::ColorType::Indexed => {
+                    return Err(GoldenError::Png(
+                        path.to_owned(),
+                        "indexed colors were not expanded".to_owned(),
+                    ))
+                }
+            }
+        }
+        Ok(RgbaImage {
+            width: info.width as i32,
+            height: info.height as i32,
+            data,
+        })
+    }
+    /// Encodes the image as PNG, creating the parent directories if necessary.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), GoldenError> {
+        let path = path.as_ref();
+        let io_error = |err| GoldenError::Io(path.to_owned(), err);
+        let png_error =
+            |err: png::EncodingError| GoldenError::Png(path.to_owned(), err.to_string());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let file = File::create(path).map_err(io_error)?;
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.data).map_err(png_error)
+    }
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Returns the RGBA components of a pixel, or None if it's out of bounds.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<[u8; 4]> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.at(x, y))
+    }
+
+    fn at(&self, x: i32, y: i32) -> [u8; 4] {
+        let offset = 4 * (y * self.width + x) as usize;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.data[offset..offset + 4]);
+        pixel
+    }
+    fn set(&mut self, x: i32, y: i32, pixel: [u8; 4]) {
+        let offset = 4 * (y * self.width + x) as usize;
+        self.data[offset..offset + 4].copy_from_slice(&pixel);
+    }
+}
+
+/// How strictly [compare] and [check_golden] compare images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareOptions {
+    tolerance: u8,
+    anti_aliasing: bool,
+    ignore: Vec<Rect>,
+    max_differing_pixels: usize,
+}
+
+impl CompareOptions {
+    pub fn new() -> CompareOptions {
+        CompareOptions::default()
+    }
+    /// Pixels only differ if a channel differs by more than `tolerance`.
+    /// Defaults to 0.
+    pub fn tolerance(self, tolerance: u8) -> CompareOptions {
+        CompareOptions { tolerance, ..self }
+    }
+    /// Whether differences that look like anti-aliased edges are ignored, which
+    /// happens when fonts or shapes are rasterized slightly differently.
+    /// Defaults to true.
+    pub fn anti_aliasing(self, anti_aliasing: bool) -> CompareOptions {
+        CompareOptions {
+            anti_aliasing,
+            ..self
+        }
+    }
+    /// Don't compare the pixels in `rect`, in pixel coordinates. Useful for
+    /// clocks, carets and other content that changes between runs.
+    pub fn ignore(mut self, rect: Rect) -> CompareOptions {
+        self.ignore.push(rect);
+        self
+    }
+    /// How many pixels may differ for the images to match. Defaults to 0.
+    pub fn max_differing_pixels(self, max_differing_pixels: usize) -> CompareOptions {
+        CompareOptions {
+            max_differing_pixels,
+            ..self
+        }
+    }
+}
+
+impl Default for CompareOptions {
+    fn default() -> CompareOptions {
+        CompareOptions {
+            tolerance: 0,
+            anti_aliasing: true,
+            ignore: Vec::new(),
+            max_differing_pixels: 0,
+        }
+    }
+}
+
+/// The result of [compare].
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    differing_pixels: usize,
+    anti_aliased_pixels: usize,
+    bounds: Option<Rect>,
+    matches: bool,
+    diff: RgbaImage,
+}
+
+impl Comparison {
+    /// Whether at most [CompareOptions::max_differing_pixels] pixels differ.
+    pub fn matches(&self) -> bool {
+        self.matches
+    }
+    /// The number of pixels that differ, not counting anti-aliased ones.
+    pub fn differing_pixels(&self) -> usize {
+        self.differing_pixels
+    }
+    /// The number of pixels that differ but were considered anti-aliasing.
+    pub fn anti_aliased_pixels(&self) -> usize {
+        self.anti_aliased_pixels
+    }
+    /// The smallest rectangle containing all differing pixels.
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+    /// An image showing the expected image faded out, with differing pixels in
+    /// red and anti-aliased ones in yellow.
+    pub fn diff(&self) -> &RgbaImage {
+        &self.diff
+    }
+}
+
+/// Compares two images of the same size.
+pub fn compare(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    options: &CompareOptions,
+) -> Result<Comparison, GoldenError> {
+    if actual.width != expected.width || actual.height != expected.height {
+        return Err(GoldenError::SizeMismatch {
+            expected: (expected.width, expected.height),
+            actual: (actual.width, actual.height),
+        });
+    }
+    let mut diff = RgbaImage {
+        width: expected.width,
+        height: expected.height,
+        data: vec![0; expected.data.len()],
+    };
+    let mut differing_pixels = 0;
+    let mut anti_aliased_pixels = 0;
+    let mut bounds: Option<(i32, i32, i32, i32)> = None;
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            let a = actual.at(x, y);
+            let e = expected.at(x, y);
+            let ignored = options.ignore.iter().any(|rect| contains(*rect, x, y));
+            let differs = a
+                .iter()
+                .zip(&e)
+                .any(|(a, e)| (*a as i32 - *e as i32).abs() > options.tolerance as i32);
+            if ignored || !differs {
+                let gray = (255.0 - (255.0 - luma(e)) * 0.1) as u8;
+                diff.set(x, y, [gray, gray, gray, 255]);
+            } else if options.anti_aliasing
+                && (is_anti_aliased(actual, x, y, expected)
+                    || is_anti_aliased(expected, x, y, actual))
+            {
+                anti_aliased_pixels += 1;
+                diff.set(x, y, [255, 255, 0, 255]);
+            } else {
+                differing_pixels += 1;
+                diff.set(x, y, [255, 0, 0, 255]);
+                bounds = Some(match bounds {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    None => (x, y, x, y),
+                });
+            }
+        }
+    }
+    Ok(Comparison {
+        differing_pixels,
+        anti_aliased_pixels,
+        bounds: bounds.map(|(x0, y0, x1, y1)| Rect {
+            x: x0,
+            y: y0,
+            width: x1 - x0 + 1,
+            height: y1 - y0 + 1,
+        }),
+        matches: differing_pixels <= options.max_differing_pixels,
+        diff,
+    })
+}
+
+/// Compares `frame` with the golden PNG at `path`. If they don't match, the
+/// frame and the diff image are written next to the golden with the extensions
+/// `.actual.png` and `.diff.png`. If [UPDATE_GOLDENS_VAR] is set, the frame is
+/// written to `path` instead.
+pub fn check_golden(
+    frame: &ComposedFrame,
+    path: impl AsRef<Path>,
+    options: &CompareOptions,
+) -> Result<(), GoldenError> {
+    check_image(
+        &RgbaImage::from_frame(frame),
+        path.as_ref(),
+        options,
+        update_goldens(),
+    )
+}
+
+fn check_image(
+    actual: &RgbaImage,
+    path: &Path,
+    options: &CompareOptions,
+    update: bool,
+) -> Result<(), GoldenError> {
+    if update {
+        return actual.save_png(path);
+    }
+    if !path.exists() {
+        return Err(GoldenError::Missing(path.to_owned()));
+    }
+    let expected = RgbaImage::load_png(path)?;
+    let actual_path = path.with_extension("actual.png");
+    let comparison = match compare(actual, &expected, options) {
+        Ok(comparison) => comparison,
+        Err(err) => {
+            actual.save_png(&actual_path)?;
+            return Err(err);
+        }
+    };
+    if comparison.matches() {
+        return Ok(());
+    }
+    let diff_path = path.with_extension("diff.png");
+    actual.save_png(&actual_path)?;
+    comparison.diff().save_png(&diff_path)?;
+    Err(GoldenError::Mismatch {
+        golden: path.to_owned(),
+        differing_pixels: comparison.differing_pixels(),
+        bounds: comparison.bounds(),
+        diff: diff_path,
+    })
+}
+
+fn update_goldens() -> bool {
+    env::var_os(UPDATE_GOLDENS_VAR).map_or(false, |value| !value.is_empty() && value != "0")
+}
+
+fn contains(rect: Rect, x: i32, y: i32) -> bool {
+    x >= rect.x && y >= rect.y && x < rect.x + rect.width && y < rect.y + rect.height
+}
+
+/// The brightness of a pixel blended onto white.
+fn luma(pixel: [u8; 4]) -> f64 {
+    let alpha = pixel[3] as f64 / 255.0;
+    let blend = |c: u8| 255.0 + (c as f64 - 255.0) * alpha;
+    blend(pixel[0]) * 0.29889531 + blend(pixel[1]) * 0.58662247 + blend(pixel[2]) * 0.11448223
+}
+
+/// Whether the pixel at `x`, `y` of `image` looks like part of an anti-aliased
+/// edge: it has both darker and brighter neighbors, and the darkest or the
+/// brightest of them is part of a solid area in both images. This is the
+/// heuristic of "Anti-aliased Pixel and Intensity Slope Detector" by
+/// V. Vysniauskas, also used by pixelmatch.
+fn is_anti_aliased(image: &RgbaImage, x: i32, y: i32, other: &RgbaImage) -> bool {
+    let center = image.at(x, y);
+    let mut equal = if on_edge(image, x, y) { 1 } else { 0 };
+    let mut darkest = (0.0, None);
+    let mut brightest = (0.0, None);
+    for (nx, ny) in neighbors(image, x, y) {
+        let pixel = image.at(nx, ny);
+        let delta = luma(pixel) - luma(center);
+        if pixel == center || delta == 0.0 {
+            equal += 1;
+            if equal > 2 {
+                return false;
+            }
+        } else if delta < darkest.0 {
+            darkest = (delta, Some((nx, ny)));
+        } else if delta > brightest.0 {
+            brightest = (delta, Some((nx, ny)));
+        }
+    }
+    let solid = |position: Option<(i32, i32)>| {
+        position.map_or(false, |(nx, ny)| {
+            has_many_siblings(image, nx, ny) && has_many_siblings(other, nx, ny)
+        })
+    };
+    match (darkest.1, brightest.1) {
+        (Some(_), Some(_)) => solid(darkest.1) || solid(brightest.1),
+        _ => false,
+    }
+}
+
+/// Whether at least 3 neighbors of the pixel have the same color.
+fn has_many_siblings(image: &RgbaImage, x: i32, y: i32) -> bool {
+    let center = image.at(x, y);
+    let mut equal = if on_edge(image, x, y) { 1 } else { 0 };
+    for (nx, ny) in neighbors(image, x, y) {
+        if image.at(nx, ny) == center {
+            equal += 1;
+            if equal > 2 {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn on_edge(image: &RgbaImage, x: i32, y: i32) -> bool {
+    x == 0 || y == 0 || x == image.width - 1 || y == image.height - 1
+}
+
+fn neighbors(image: &RgbaImage, x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    let (width, height) = (image.width, image.height);
+    (y - 1..=y + 1)
+        .flat_map(move |ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+        .filter(move |&(nx, ny)| {
+            (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && nx < width && ny < height
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    fn image(width: i32, height: i32, color: impl Fn(i32, i32) -> [u8; 4]) -> RgbaImage {
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&color(x, y));
+            }
+        }
+        RgbaImage::new(width, height, data).unwrap()
+    }
+
+    /// Black on the left, white from column 3 on, with column 2 in `edge`.
+    fn edge(edge: [u8; 4]) -> RgbaImage {
+        image(5, 5, |x, _| match x {
+            0 | 1 => BLACK,
+            2 => edge,
+            _ => WHITE,
+        })
+    }
+
+    #[test]
+    fn identical_images() {
+        let expected = image(4, 3, |x, y| [x as u8 * 60, y as u8 * 80, 10, 255]);
+        let comparison = compare(&expected.clone(), &expected, &CompareOptions::new()).unwrap();
+        assert!(comparison.matches());
+        assert_eq!(comparison.differing_pixels(), 0);
+        assert_eq!(comparison.anti_aliased_pixels(), 0);
+        assert_eq!(comparison.bounds(), None);
+        // The expected image is faded out in the diff: [0, 0, 10] has a luma of
+        // 1.14, which becomes 255 - (255 - 1.14) * 0.1 = 229.6.
+        assert_eq!(comparison.diff().pixel(0, 0), Some([229, 229, 229, 255]));
+    }
+
+    #[test]
+    fn single_differing_pixel() {
+        let expected = image(4, 4, |_, _| WHITE);
+        let actual = image(4, 4, |x, y| {
+            if (x, y) == (2, 1) {
+                [255, 0, 0, 255]
+            } else {
+                WHITE
+            }
+        });
+        let comparison = compare(&actual, &expected, &CompareOptions::new()).unwrap();
+        assert!(!comparison.matches());
+        assert_eq!(comparison.differing_pixels(), 1);
+        assert_eq!(comparison.anti_aliased_pixels(), 0);
+        assert_eq!(
+            comparison.bounds(),
+            Some(Rect {
+                x: 2,
+                y: 1,
+                width: 1,
+                height: 1
+            })
+        );
+        assert_eq!(comparison.diff().pixel(2, 1), Some([255, 0, 0, 255]));
+        assert_eq!(comparison.diff().pixel(0, 0), Some(WHITE));
+
+        let options = CompareOptions::new().max_differing_pixels(1);
+        assert!(compare(&actual, &expected, &options).unwrap().matches());
+        let options = CompareOptions::new().ignore(Rect {
+            x: 2,
+            y: 0,
+            width: 2,
+            height: 2,
+        });
+        assert!(compare(&actual, &expected, &options).unwrap().matches());
+    }
+
+    #[test]
+    fn tolerance() {
+        let expected = image(3, 3, |_, _| [100, 100, 100, 255]);
+        let actual = image(3, 3, |x, _| {
+            if x == 1 {
+                [102, 98, 100, 255]
+            } else {
+                [100, 100, 100, 255]
+            }
+        });
+        let options = CompareOptions::new().anti_aliasing(false);
+        assert_eq!(
+            compare(&actual, &expected, &options)
+                .unwrap()
+                .differing_pixels(),
+            3
+        );
+        let options = options.tolerance(2);
+        assert!(compare(&actual, &expected, &options).unwrap().matches());
+    }
+
+    #[test]
+    fn anti_aliased_edge() {
+        let expected = edge(BLACK);
+        let actual = edge([128, 128, 128, 255]);
+        let comparison = compare(&actual, &expected, &CompareOptions::new()).unwrap();
+        assert!(comparison.matches());
+        assert_eq!(comparison.differing_pixels(), 0);
+        assert_eq!(comparison.anti_aliased_pixels(), 5);
+        assert_eq!(comparison.diff().pixel(2, 2), Some([255, 255, 0, 255]));
+
+        let options = CompareOptions::new().anti_aliasing(false);
+        let comparison = compare(&actual, &expected, &options).unwrap();
+        assert_eq!(comparison.differing_pixels(), 5);
+        assert_eq!(
+            comparison.bounds(),
+            Some(Rect {
+                x: 2,
+                y: 0,
+                width: 1,
+                height: 5
+            })
+        );
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let expected = image(4, 3, |_, _| WHITE);
+        let actual = image(3, 4, |_, _| WHITE);
+        match compare(&actual, &expected, &CompareOptions::new()) {
+            Err(GoldenError::SizeMismatch { expected, actual }) => {
+                assert_eq!(expected, (4, 3));
+                assert_eq!(actual, (3, 4));
+            }
+            other => panic!(
+                "unexpected result {:?}",
+                other.map(|c| c.differing_pixels())
+            ),
+        }
+
+        let dir = TempDir::new("golden");
+        let golden = dir.join("golden.png");
+        expected.save_png(&golden).unwrap();
+        assert!(matches!(
+            check_image(&actual, &golden, &CompareOptions::new(), false),
+            Err(GoldenError::SizeMismatch { .. })
+        ));
+        assert_eq!(
+            RgbaImage::load_png(dir.join("golden.actual.png")).unwrap(),
+            actual
+        );
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let dir = TempDir::new("golden");
+        let path = dir.join("nested").join("image.png");
+        let original = image(3, 2, |x, y| [x as u8 * 100, y as u8 * 200, 50, 128]);
+        original.save_png(&path).unwrap();
+        assert_eq!(RgbaImage::load_png(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn check_writes_actual_and_diff() {
+        let dir = TempDir::new("golden");
+        let golden = dir.join("golden.png");
+        let options = CompareOptions::new();
+        let expected = image(4, 4, |_, _| WHITE);
+        let actual = image(4, 4, |x, y| if (x, y) == (0, 3) { BLACK } else { WHITE });
+
+        assert!(matches!(
+            check_image(&actual, &golden, &options, false),
+            Err(GoldenError::Missing(_))
+        ));
+        expected.save_png(&golden).unwrap();
+        assert!(check_image(&expected, &golden, &options, false).is_ok());
+        assert!(!dir.join("golden.actual.png").exists());
+
+        match check_image(&actual, &golden, &options, false) {
+            Err(GoldenError::Mismatch {
+                differing_pixels,
+                bounds,
+                diff,
+                ..
+            }) => {
+                assert_eq!(differing_pixels, 1);
+                assert_eq!(
+                    bounds,
+                    Some(Rect {
+                        x: 0,
+                        y: 3,
+                        width: 1,
+                        height: 1
+                    })
+                );
+                assert_eq!(diff, dir.join("golden.diff.png"));
+                let diff = RgbaImage::load_png(&diff).unwrap();
+                assert_eq!(diff.pixel(0, 3), Some([255, 0, 0, 255]));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(
+            RgbaImage::load_png(dir.join("golden.actual.png")).unwrap(),
+            actual
+        );
+        // The golden itself is left alone.
+        assert_eq!(RgbaImage::load_png(&golden).unwrap(), expected);
+    }
+
+    #[test]
+    fn update_writes_golden() {
+        let dir = TempDir::new("golden");
+        let golden = dir.join("goldens").join("golden.png");
+        let options = CompareOptions::new();
+        let first = image(2, 2, |_, _| WHITE);
+        let second = image(3, 1, |_, _| BLACK);
+
+        check_image(&first, &golden, &options, true).unwrap();
+        assert_eq!(RgbaImage::load_png(&golden).unwrap(), first);
+        // Updating replaces mismatching goldens, even of a different size.
+        check_image(&second, &golden, &options, true).unwrap();
+        assert_eq!(RgbaImage::load_png(&golden).unwrap(), second);
+        assert!(check_image(&second, &golden, &options, false).is_ok());
+    }
+
+    #[test]
+    fn update_goldens_var() {
+        // This is the only test that reads or writes the variable.
+        env::set_var(UPDATE_GOLDENS_VAR, "1");
+        assert!(update_goldens());
+        env::set_var(UPDATE_GOLDENS_VAR, "yes");
+        assert!(update_goldens());
+        env::set_var(UPDATE_GOLDENS_VAR, "0");
+        assert!(!update_goldens());
+        env::set_var(UPDATE_GOLDENS_VAR, "");
+        assert!(!update_goldens());
+        env::remove_var(UPDATE_GOLDENS_VAR);
+        assert!(!update_goldens());
+    }
+}
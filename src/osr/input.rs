@@ -0,0 +1,340 @@
+//! Translation of the input of remote front ends, which report absolute button
+//! and key states, into the events [BrowserHost] expects.
+
+use crate::{
+    browser_host::BrowserHost,
+    events::{EventFlags, KeyEvent, MouseButtonType, MouseEvent, WindowsKeyCode},
+};
+use std::time::{Duration, Instant};
+
+/// Scroll distance of one wheel notch.
+pub(crate) const WHEEL_DELTA: i32 = 120;
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+const BUTTONS: [(MouseButtonType, EventFlags); 3] = [
+    (MouseButtonType::Left, EventFlags::LEFT_MOUSE_BUTTON),
+    (MouseButtonType::Middle, EventFlags::MIDDLE_MOUSE_BUTTON),
+    (MouseButtonType::Right, EventFlags::RIGHT_MOUSE_BUTTON),
+];
+
+/// Keeps track of pressed buttons and modifier keys, and of the last click for
+/// counting double clicks. Coordinates are view coordinates.
+#[derive(Debug)]
+pub(crate) struct InputTranslator {
+    modifiers: EventFlags,
+    buttons: [bool; 3],
+    position: Option<(i32, i32)>,
+    last_click: Option<(usize, Instant, (i32, i32), i32)>,
+}
+
+impl InputTranslator {
+    pub(crate) fn new() -> InputTranslator {
+        InputTranslator {
+            modifiers: EventFlags::empty(),
+            buttons: [false; 3],
+            position: None,
+            last_click: None,
+        }
+    }
+
+    fn mouse_event(&self, x: i32, y: i32) -> MouseEvent {
+        let mut modifiers = self.modifiers;
+        for (pressed, (_, flag)) in self.buttons.iter().zip(&BUTTONS) {
+            if *pressed {
+                modifiers |= *flag;
+            }
+        }
+        MouseEvent { x, y, modifiers }
+    }
+
+    /// Moves the pointer to `x`, `y` and presses or releases the left, middle
+    /// and right buttons so that they match `buttons`.
+    pub(crate) fn pointer(&mut self, host: &BrowserHost, x: i32, y: i32, buttons: [bool; 3]) {
+        if self.position != Some((x, y)) {
+            self.position = Some((x, y));
+            host.send_mouse_move_event(&self.mouse_event(x, y), false);
+        }
+        for (index, &pressed) in buttons.iter().enumerate() {
+            if pressed == self.buttons[index] {
+                continue;
+            }
+            let click_count = self.click_count(index, pressed, (x, y), Instant::now());
+            self.buttons[index] = pressed;
+            let event = self.mouse_event(x, y);
+            host.send_mouse_click_event(&event, BUTTONS[index].0, !pressed, click_count);
+        }
+    }
+
+    /// Returns the click count for pressing or releasing button `index` at
+    /// `position`. A release has the count of the press before it.
+    fn click_count(
+        &mut self,
+        index: usize,
+        pressed: bool,
+        (x, y): (i32, i32),
+        now: Instant,
+    ) -> i32 {
+        let click_count = match self.last_click {
+            Some((button, time, (last_x, last_y), count))
+                if button == index
+                    && (!pressed || now.duration_since(time) < DOUBLE_CLICK_TIME)
+                    && (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE
+                    && (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE =>
+            {
+                if pressed {
+                    count + 1
+                } else {
+                    count
+                }
+            }
+            _ => 1,
+        };
+        if pressed {
+            self.last_click = Some((index, now, (x, y), click_count));
+        }
+        click_count
+    }
+
+    pub(crate) fn wheel(&mut self, host: &BrowserHost, x: i32, y: i32, delta_x: i32, delta_y: i32) {
+        host.send_mouse_wheel_event(&self.mouse_event(x, y), delta_x, delta_y);
+    }
+
+    /// Tells the browser that the pointer left the view, e.g. because the
+    /// client disconnected.
+    pub(crate) fn leave(&mut self, host: &BrowserHost) {
+        if let Some((x, y)) = self.position {
+            self.pointer(host, x, y, [false; 3]);
+            host.send_mouse_move_event(&self.mouse_event(x, y), true);
+        }
+        self.position = None;
+        self.modifiers = EventFlags::empty();
+    }
+
+    /// Presses or releases a key. Modifier keys update the modifiers of later
+    /// events. If the key produces `character`, a char event follows the key
+    /// down event, unless it's part of a shortcut. `extra_modifiers` are added
+    /// to this event only, for front ends that don't report modifier keys
+    /// separately.
+    pub(crate) fn key(
+        &mut self,
+        host: &BrowserHost,
+        code: WindowsKeyCode,
+        character: Option<char>,
+        down: bool,
+        extra_modifiers: EventFlags,
+    ) {
+        if let Some(flag) = modifier_flag(code) {
+            self.modifiers.set(flag, down);
+        }
+        let modifiers = self.modifiers | extra_modifiers;
+        if down {
+            host.send_key_event(KeyEvent::KeyDown {
+                modifiers,
+                windows_key_code: code,
+                native_key_code: 0,
+                is_system_key: false,
+                focus_on_editable_field: false,
+            });
+            let shortcut =
+                EventFlags::CONTROL_DOWN | EventFlags::ALT_DOWN | EventFlags::COMMAND_DOWN;
+            if let (Some(character), false) = (character, modifiers.intersects(shortcut)) {
+                host.send_key_event(KeyEvent::Char {
+                    modifiers,
+                    char: character,
+                });
+            }
+        } else {
+            host.send_key_event(KeyEvent::KeyUp {
+                modifiers,
+                windows_key_code: code,
+                native_key_code: 0,
+                is_system_key: false,
+                focus_on_editable_field: false,
+            });
+        }
+    }
+
+    /// Presses and releases the key for `character`.
+    pub(crate) fn type_char(
+        &mut self,
+        host: &BrowserHost,
+        character: char,
+        extra_modifiers: EventFlags,
+    ) {
+        let (code, shift) = key_code_for_char(character);
+        let modifiers = if shift {
+            extra_modifiers | EventFlags::SHIFT_DOWN
+        } else {
+            extra_modifiers
+        };
+        // Chromium only expects char events for control characters for Enter.
+        let character = match character {
+            '\r' | '\n' => Some('\r'),
+            _ if character.is_control() => None,
+            _ => Some(character),
+        };
+        self.key(host, code, character, true, modifiers);
+        self.key(host, code, None, false, modifiers);
+    }
+}
+
+fn modifier_flag(code: WindowsKeyCode) -> Option<EventFlags> {
+    match code {
+        WindowsKeyCode::Shift | WindowsKeyCode::LShift | WindowsKeyCode::RShift => {
+            Some(EventFlags::SHIFT_DOWN)
+        }
+        WindowsKeyCode::Control | WindowsKeyCode::LControl | WindowsKeyCode::RControl => {
+            Some(EventFlags::CONTROL_DOWN)
+        }
+        WindowsKeyCode::Menu | WindowsKeyCode::LMenu | WindowsKeyCode::RMenu => {
+            Some(EventFlags::ALT_DOWN)
+        }
+        WindowsKeyCode::LWin | WindowsKeyCode::RWin => Some(EventFlags::COMMAND_DOWN),
+        _ => None,
+    }
+}
+
+/// Returns the key code of the key that produces `character` on a US keyboard
+/// layout, and whether shift has to be pressed for it. Characters that aren't
+/// on that layout get key code 0, which is fine for text input.
+pub(crate) fn key_code_for_char(character: char) -> (WindowsKeyCode, bool) {
+    let (code, shift) = match character {
+        'a'..='z' => (character as i32 - 'a' as i32 + 0x41, false),
+        'A'..='Z' => (character as i32 - 'A' as i32 + 0x41, true),
+        '0'..='9' => (character as i32, false),
+        ' ' => (0x20, false),
+        '\r' | '\n' => (0x0D, false),
+        '\t' => (0x09, false),
+        '\u{8}' | '\u{7f}' => (0x08, false),
+        '\u{1b}' => (0x1B, false),
+        ')' => (0x30, true),
+        '!' => (0x31, true),
+        '@' => (0x32, true),
+        '#' => (0x33, true),
+        '$' => (0x34, true),
+        '%' => (0x35, true),
+        '^' => (0x36, true),
+        '&' => (0x37, true),
+        '*' => (0x38, true),
+        '(' => (0x39, true),
+        ';' => (0xBA, false),
+        ':' => (0xBA, true),
+        '=' => (0xBB, false),
+        '+' => (0xBB, true),
+        ',' => (0xBC, false),
+        '<' => (0xBC, true),
+        '-' => (0xBD, false),
+        '_' => (0xBD, true),
+        '.' => (0xBE, false),
+        '>' => (0xBE, true),
+        '/' => (0xBF, false),
+        '?' => (0xBF, true),
+        '`' => (0xC0, false),
+        '~' => (0xC0, true),
+        '[' => (0xDB, false),
+        '{' => (0xDB, true),
+        '\\' => (0xDC, false),
+        '|' => (0xDC, true),
+        ']' => (0xDD, false),
+        '}' => (0xDD, true),
+        '\'' => (0xDE, false),
+        '"' => (0xDE, true),
+        _ => (0, false),
+    };
+    (WindowsKeyCode(code), shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn click_count() {
+        let mut input = InputTranslator::new();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut click = |pressed, position, ms| input.click_count(0, pressed, position, at(ms));
+
+        assert_eq!(click(true, (10, 10), 0), 1);
+        assert_eq!(click(false, (10, 10), 50), 1);
+        // Within the double click time and distance of the previous press.
+        assert_eq!(click(true, (13, 6), 300), 2);
+        assert_eq!(click(false, (13, 6), 350), 2);
+        assert_eq!(click(true, (13, 6), 700), 3);
+        // A release keeps the count, no matter how late.
+        assert_eq!(click(false, (13, 6), 5000), 3);
+
+        // Too late.
+        assert_eq!(click(true, (13, 6), 5500), 1);
+        assert_eq!(click(false, (13, 6), 5550), 1);
+        // Too far away.
+        assert_eq!(click(true, (18, 6), 5600), 1);
+        assert_eq!(click(false, (18, 6), 5650), 1);
+        assert_eq!(click(true, (18, 11), 5700), 1);
+        // Released somewhere else.
+        assert_eq!(click(false, (30, 11), 5750), 1);
+    }
+
+    #[test]
+    fn click_count_per_button() {
+        let mut input = InputTranslator::new();
+        let now = Instant::now();
+        assert_eq!(input.click_count(0, true, (0, 0), now), 1);
+        assert_eq!(input.click_count(0, false, (0, 0), now), 1);
+        assert_eq!(input.click_count(2, true, (0, 0), now), 1);
+        assert_eq!(input.click_count(2, false, (0, 0), now), 1);
+        // The right click interrupted the double click.
+        assert_eq!(input.click_count(0, true, (0, 0), now), 1);
+        assert_eq!(input.click_count(0, false, (0, 0), now), 1);
+        assert_eq!(input.click_count(0, true, (0, 0), now), 2);
+    }
+
+    #[test]
+    fn key_codes() {
+        assert_eq!(key_code_for_char('a'), (WindowsKeyCode::A, false));
+        assert_eq!(key_code_for_char('A'), (WindowsKeyCode::A, true));
+        assert_eq!(key_code_for_char('z'), (WindowsKeyCode::Z, false));
+        assert_eq!(key_code_for_char('0'), (WindowsKeyCode::Key0, false));
+        assert_eq!(key_code_for_char('9'), (WindowsKeyCode::Key9, false));
+        assert_eq!(key_code_for_char(')'), (WindowsKeyCode::Key0, true));
+        assert_eq!(key_code_for_char('('), (WindowsKeyCode::Key9, true));
+        assert_eq!(key_code_for_char(' '), (WindowsKeyCode::Space, false));
+        assert_eq!(key_code_for_char('\n'), (WindowsKeyCode::Return, false));
+        assert_eq!(key_code_for_char('\r'), (WindowsKeyCode::Return, false));
+        assert_eq!(key_code_for_char('\t'), (WindowsKeyCode::Tab, false));
+        assert_eq!(key_code_for_char('\u{7f}'), (WindowsKeyCode::Back, false));
+        assert_eq!(key_code_for_char('\u{1b}'), (WindowsKeyCode::Escape, false));
+        assert_eq!(key_code_for_char(';'), (WindowsKeyCode::Oem1, false));
+        assert_eq!(key_code_for_char(':'), (WindowsKeyCode::Oem1, true));
+        assert_eq!(key_code_for_char('+'), (WindowsKeyCode::OemPlus, true));
+        assert_eq!(key_code_for_char('_'), (WindowsKeyCode::OemMinus, true));
+        assert_eq!(key_code_for_char('?'), (WindowsKeyCode::Oem2, true));
+        assert_eq!(key_code_for_char('~'), (WindowsKeyCode::Oem3, true));
+        assert_eq!(key_code_for_char('|'), (WindowsKeyCode::Oem5, true));
+        assert_eq!(key_code_for_char('"'), (WindowsKeyCode::Oem7, true));
+        // Not on a US keyboard.
+        assert_eq!(key_code_for_char('é'), (WindowsKeyCode(0), false));
+        assert_eq!(key_code_for_char('€'), (WindowsKeyCode(0), false));
+    }
+
+    #[test]
+    fn modifier_flags() {
+        assert_eq!(
+            modifier_flag(WindowsKeyCode::RShift),
+            Some(EventFlags::SHIFT_DOWN)
+        );
+        assert_eq!(
+            modifier_flag(WindowsKeyCode::LControl),
+            Some(EventFlags::CONTROL_DOWN)
+        );
+        assert_eq!(
+            modifier_flag(WindowsKeyCode::Menu),
+            Some(EventFlags::ALT_DOWN)
+        );
+        assert_eq!(
+            modifier_flag(WindowsKeyCode::LWin),
+            Some(EventFlags::COMMAND_DOWN)
+        );
+        assert_eq!(modifier_flag(WindowsKeyCode::A), None);
+    }
+}
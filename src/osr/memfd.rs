@@ -0,0 +1,909 @@
+//! Sharing the frames of a windowless browser with another process through
+//! `memfd` shared memory.
+//!
+//! A [FramePublisher] in the browser process copies every frame painted into a
+//! [Compositor] into one of a few shared memory slots and sends the frame's
+//! metadata over a Unix socket. The file descriptor of a slot is only sent
+//! along when the slot is created or grown. A [FrameConsumer] on the other end
+//! of the socket maps the slots and reads the frames in place. Each frame
+//! stays valid until the consumer drops it, so a consumer can hold on to up to
+//! [SLOTS] frames at once, e.g. while a GPU upload of the previous one is still
+//! in flight. The publisher skips frames while all slots are in use,
+//! accumulating their damage into the next one.
+//!
+//! The consumer only maps memory that is sealed against shrinking and at least
+//! as large as the publisher claims, so a misbehaving publisher can't make it
+//! crash with `SIGBUS`.
+//!
+//! ```ignore
+//!  // browser process
+//!  let (socket, _) = UnixListener::bind("/run/user/1000/browser.sock")?.accept()?;
+//!  let publisher = FramePublisher::start(&compositor, &browser, socket)?;
+//!
+//!  // compositor process
+//!  let mut consumer = FrameConsumer::connect("/run/user/1000/browser.sock")?;
+//!  loop {
+//!      let frame = consumer.recv()?;
+//!      upload(frame.data(), frame.stride(), frame.damage().rects());
+//!  }
+//! ```
+
+use super::{ComposedFrame, Compositor, Damage, FrameListenerId, PixelFormat};
+use crate::{browser::Browser, values::Rect};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    ffi::CStr,
+    fs::File,
+    io::{self, Read, Write},
+    mem,
+    net::Shutdown,
+    os::{
+        raw::c_void,
+        unix::{
+            io::{AsRawFd, FromRawFd, RawFd},
+            net::UnixStream,
+        },
+    },
+    path::Path,
+    ptr, slice,
+    sync::Arc,
+    thread::JoinHandle,
+};
+
+/// The number of shared memory slots of a [FramePublisher].
+pub const SLOTS: usize = 3;
+const MAGIC: u32 = 0x4652_4643;
+const HEADER_LEN: usize = 48 + 16 * Damage::MAX_RECTS;
+
+/// The metadata sent for each frame.
+#[derive(Debug, Clone, PartialEq)]
+struct Header {
+    slot: u32,
+    sequence: u64,
+    width: i32,
+    height: i32,
+    stride: u32,
+    format: PixelFormat,
+    len: u64,
+    damage: Vec<Rect>,
+}
+
+impl Header {
+    fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        let format: u32 = match self.format {
+            PixelFormat::Bgra => 0,
+            PixelFormat::Rgba => 1,
+        };
+        let mut offset = 0;
+        let mut put = |field: &[u8]| {
+            bytes[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        };
+        put(&MAGIC.to_le_bytes());
+        put(&self.slot.to_le_bytes());
+        put(&self.sequence.to_le_bytes());
+        put(&self.width.to_le_bytes());
+        put(&self.height.to_le_bytes());
+        put(&self.stride.to_le_bytes());
+        put(&format.to_le_bytes());
+        put(&self.len.to_le_bytes());
+        put(&(self.damage.len() as u32).to_le_bytes());
+        put(&0u32.to_le_bytes());
+        for rect in &self.damage {
+            put(&rect.x.to_le_bytes());
+            put(&rect.y.to_le_bytes());
+            put(&rect.width.to_le_bytes());
+            put(&rect.height.to_le_bytes());
+        }
+        bytes
+    }
+    fn decode(bytes: &[u8; HEADER_LEN]) -> io::Result<Header> {
+        let mut offset = 0;
+        let mut take = |len: usize| {
+            let field = &bytes[offset..offset + len];
+            offset += len;
+            field
+        };
+        let mut u32_field = || {
+            let mut field = [0; 4];
+            field.copy_from_slice(take(4));
+            u32::from_le_bytes(field)
+        };
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        if u32_field() != MAGIC {
+            return Err(invalid("not a frame header"));
+        }
+        let slot = u32_field();
+        let sequence = u32_field() as u64 | (u32_field() as u64) << 32;
+        let width = u32_field() as i32;
+        let height = u32_field() as i32;
+        let stride = u32_field();
+        let format = match u32_field() {
+            0 => PixelFormat::Bgra,
+            1 => PixelFormat::Rgba,
+            _ => return Err(invalid("unknown pixel format")),
+        };
+        let len = u32_field() as u64 | (u32_field() as u64) << 32;
+        let rect_count = u32_field() as usize;
+        u32_field();
+        if slot as usize >= SLOTS || rect_count > Damage::MAX_RECTS {
+            return Err(invalid("invalid frame header"));
+        }
+        let damage = (0..rect_count)
+            .map(|_| Rect {
+                x: u32_field() as i32,
+                y: u32_field() as i32,
+                width: u32_field() as i32,
+                height: u32_field() as i32,
+            })
+            .collect();
+        Ok(Header {
+            slot,
+            sequence,
+            width,
+            height,
+            stride,
+            format,
+            len,
+            damage,
+        })
+    }
+}
+
+/// A shared memory mapping of a memfd.
+struct SharedMemory {
+    file: File,
+    ptr: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for SharedMemory {}
+// The memory is only written through `&mut SharedMemory`.
+unsafe impl Sync for SharedMemory {}
+
+impl SharedMemory {
+    /// Creates a memfd of `len` bytes that can't shrink and maps it writable.
+    fn create(len: usize) -> io::Result<SharedMemory> {
+        let name = CStr::from_bytes_with_nul(b"cef-osr-frame\0").unwrap();
+        // Older glibc versions don't have a memfd_create wrapper.
+        let flags = libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING;
+        let fd = unsafe { libc::syscall(libc::SYS_memfd_create, name.as_ptr(), flags) } as RawFd;
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let file = unsafe { File::from_raw_fd(fd) };
+        file.set_len(len as u64)?;
+        // The consumer would get SIGBUS if the memory shrank while it's mapped.
+        if unsafe {
+            libc::fcntl(
+                fd,
+                libc::F_ADD_SEALS,
+                libc::F_SEAL_SHRINK | libc::F_SEAL_SEAL,
+            )
+        } < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        SharedMemory::map(file, len, libc::PROT_READ | libc::PROT_WRITE)
+    }
+    /// Maps a memfd received from a publisher read-only, after checking that
+    /// it's sealed against shrinking and holds at least `len` bytes.
+    fn map_received(file: File, len: usize) -> io::Result<SharedMemory> {
+        let seals = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GET_SEALS) };
+        if seals < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if seals & libc::F_SEAL_SHRINK == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory isn't sealed against shrinking",
+            ));
+        }
+        // Checked after the seals, the size can't change anymore.
+        if file.metadata()?.len() < len as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory is smaller than announced",
+            ));
+        }
+        SharedMemory::map(file, len, libc::PROT_READ)
+    }
+    fn map(file: File, len: usize, protection: libc::c_int) -> io::Result<SharedMemory> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len.max(1),
+                protection,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(SharedMemory {
+            file,
+            ptr: ptr as *mut u8,
+            len,
+        })
+    }
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut c_void, self.len.max(1)) };
+    }
+}
+
+/// Publishes the frames of a browser to a [FrameConsumer], see the
+/// [module documentation](self).
+pub struct FramePublisher {
+    compositor: Compositor,
+    listener: Option<FrameListenerId>,
+    threads: PublisherThreads,
+}
+
+impl FramePublisher {
+    /// Start publishing the frames of `browser` to the consumer connected to
+    /// `socket`. The current frame is sent right away, if there is one.
+    pub fn start(
+        compositor: &Compositor,
+        browser: &Browser,
+        socket: UnixStream,
+    ) -> io::Result<FramePublisher> {
+        let threads = PublisherThreads::spawn(socket)?;
+
+        let browser_id = browser.get_identifier();
+        let listener_shared = threads.shared.clone();
+        let listener = compositor.add_frame_listener(move |browser, frame, damage| {
+            if browser.get_identifier() == browser_id {
+                listener_shared.push(frame.clone(), damage);
+            }
+        });
+        if let Some(frame) = compositor.frame(browser_id) {
+            let damage = Damage::full(frame.width(), frame.height());
+            threads.shared.push(frame, &damage);
+        }
+
+        Ok(FramePublisher {
+            compositor: compositor.clone(),
+            listener: Some(listener),
+            threads,
+        })
+    }
+    /// Stop publishing and close the socket.
+    pub fn stop(mut self) -> io::Result<()> {
+        self.join()
+    }
+
+    fn join(&mut self) -> io::Result<()> {
+        if let Some(listener) = self.listener.take() {
+            self.compositor.remove_frame_listener(listener);
+        }
+        self.threads.join()
+    }
+}
+
+impl Drop for FramePublisher {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+/// The threads of a [FramePublisher], which send the frames pushed into
+/// [Shared] and receive the releases of the consumer.
+struct PublisherThreads {
+    socket: UnixStream,
+    shared: Arc<Shared>,
+    threads: Vec<JoinHandle<io::Result<()>>>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    latest: Option<ComposedFrame>,
+    damage: Damage,
+    in_use: [bool; SLOTS],
+    stopped: bool,
+}
+
+impl Shared {
+    /// Replaces the frame waiting to be sent, adding to its damage.
+    fn push(&self, frame: ComposedFrame, damage: &Damage) {
+        let mut state = self.state.lock();
+        state.latest = Some(frame);
+        state.damage.extend(damage);
+        self.condvar.notify_all();
+    }
+}
+
+impl PublisherThreads {
+    fn spawn(socket: UnixStream) -> io::Result<PublisherThreads> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            condvar: Condvar::new(),
+        });
+
+        let mut threads = Vec::new();
+        let publish_socket = socket.try_clone()?;
+        let publish_shared = shared.clone();
+        threads.push(
+            std::thread::Builder::new()
+                .name("cef-frame-publisher".to_owned())
+                .spawn(move || publish(&publish_shared, publish_socket))?,
+        );
+        let release_socket = socket.try_clone()?;
+        let release_shared = shared.clone();
+        threads.push(
+            std::thread::Builder::new()
+                .name("cef-frame-releases".to_owned())
+                .spawn(move || receive_releases(&release_shared, release_socket))?,
+        );
+        Ok(PublisherThreads {
+            socket,
+            shared,
+            threads,
+        })
+    }
+
+    fn join(&mut self) -> io::Result<()> {
+        self.shared.state.lock().stopped = true;
+        self.shared.condvar.notify_all();
+        let _ = self.socket.shutdown(Shutdown::Both);
+        let mut result = Ok(());
+        for thread in self.threads.drain(..) {
+            let thread_result = thread.join().unwrap_or_else(|_| {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "a publisher thread panicked",
+                ))
+            });
+            if result.is_ok() {
+                result = thread_result;
+            }
+        }
+        result
+    }
+}
+
+impl Drop for PublisherThreads {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+fn publish(shared: &Shared, mut socket: UnixStream) -> io::Result<()> {
+    let mut slots = Slots::default();
+    let mut first = true;
+    loop {
+        let (slot, frame, mut damage) = {
+            let mut state = shared.state.lock();
+            loop {
+                if state.stopped {
+                    return Ok(());
+                }
+                let free = state.in_use.iter().position(|in_use| !in_use);
+                if let (Some(slot), Some(_)) = (free, &state.latest) {
+                    state.in_use[slot] = true;
+                    let frame = state.latest.take().unwrap();
+                    let damage = mem::take(&mut state.damage);
+                    break (slot, frame, damage);
+                }
+                shared.condvar.wait(&mut state);
+            }
+        };
+        if first {
+            // The consumer has nothing to apply the damage to yet.
+            damage = Damage::full(frame.width(), frame.height());
+            first = false;
+        }
+        let result = slots.send(&mut socket, slot, &frame, &damage);
+        if result.is_err() && shared.state.lock().stopped {
+            return Ok(());
+        }
+        result?;
+    }
+}
+
+/// The shared memory of the publisher's slots.
+#[derive(Default)]
+struct Slots {
+    memory: [Option<SharedMemory>; SLOTS],
+}
+
+impl Slots {
+    /// Copies `frame` into `slot` and sends its header, along with the file
+    /// descriptor of the slot if it had to be created or grown.
+    fn send(
+        &mut self,
+        socket: &mut UnixStream,
+        slot: usize,
+        frame: &ComposedFrame,
+        damage: &Damage,
+    ) -> io::Result<()> {
+        let data = frame.data();
+        let mut new_fd = None;
+        if self.memory[slot]
+            .as_ref()
+            .map_or(true, |memory| memory.len < data.len())
+        {
+            let memory = SharedMemory::create(data.len())?;
+            new_fd = Some(memory.file.as_raw_fd());
+            self.memory[slot] = Some(memory);
+        }
+        let memory = self.memory[slot].as_mut().unwrap();
+        memory.as_mut_slice()[..data.len()].copy_from_slice(data);
+
+        let header = Header {
+            slot: slot as u32,
+            sequence: frame.sequence(),
+            width: frame.width(),
+            height: frame.height(),
+            stride: frame.stride() as u32,
+            format: frame.format(),
+            len: memory.len as u64,
+            damage: damage.rects().to_vec(),
+        };
+        send(socket, &header.encode(), new_fd)
+    }
+}
+
+fn receive_releases(shared: &Shared, mut socket: UnixStream) -> io::Result<()> {
+    let mut message = [0; 4];
+    loop {
+        if let Err(err) = socket.read_exact(&mut message) {
+            // The consumer disconnected, or the publisher was stopped.
+            let mut state = shared.state.lock();
+            let stopped = state.stopped;
+            state.stopped = true;
+            shared.condvar.notify_all();
+            return match err.kind() {
+                io::ErrorKind::UnexpectedEof => Ok(()),
+                _ if stopped => Ok(()),
+                _ => Err(err),
+            };
+        }
+        let slot = u32::from_le_bytes(message) as usize;
+        if slot < SLOTS {
+            shared.state.lock().in_use[slot] = false;
+            shared.condvar.notify_all();
+        }
+    }
+}
+
+/// Writes `bytes` to `socket`, with `fd` attached to the first byte.
+fn send(socket: &mut UnixStream, bytes: &[u8], fd: Option<RawFd>) -> io::Result<()> {
+    let fd = match fd {
+        Some(fd) => fd,
+        None => return socket.write_all(bytes),
+    };
+    // u64 for the alignment of cmsghdr.
+    let mut control = [0u64; 8];
+    let mut iov = libc::iovec {
+        iov_base: bytes.as_ptr() as *mut c_void,
+        iov_len: bytes.len(),
+    };
+    let sent = unsafe {
+        let mut message: libc::msghdr = mem::zeroed();
+        message.msg_iov = &mut iov;
+        message.msg_iovlen = 1;
+        message.msg_control = control.as_mut_ptr() as *mut c_void;
+        message.msg_controllen = libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) as _;
+        let cmsg = libc::CMSG_FIRSTHDR(&message);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut RawFd, fd);
+        libc::sendmsg(socket.as_raw_fd(), &message, libc::MSG_NOSIGNAL)
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    socket.write_all(&bytes[sent as usize..])
+}
+
+/// Reads exactly `buffer.len()` bytes from `socket` and returns the file
+/// descriptor that was attached, if any.
+fn recv(mut socket: &UnixStream, buffer: &mut [u8]) -> io::Result<Option<File>> {
+    let mut control = [0u64; 8];
+    let mut iov = libc::iovec {
+        iov_base: buffer.as_mut_ptr() as *mut c_void,
+        iov_len: buffer.len(),
+    };
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr() as *mut c_void;
+    message.msg_controllen = mem::size_of_val(&control) as _;
+    let received = loop {
+        let received =
+            unsafe { libc::recvmsg(socket.as_raw_fd(), &mut message, libc::MSG_CMSG_CLOEXEC) };
+        if received >= 0 {
+            break received as usize;
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    };
+    if received == 0 && !buffer.is_empty() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut file = None;
+    unsafe {
+        let mut cmsg = libc::CMSG_FIRSTHDR(&message);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let fd = ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const RawFd);
+                file = Some(File::from_raw_fd(fd));
+            }
+            cmsg = libc::CMSG_NXTHDR(&message, cmsg);
+        }
+    }
+    socket.read_exact(&mut buffer[received..])?;
+    Ok(file)
+}
+
+/// Receives the frames of a [FramePublisher], usually in another process.
+/// Doesn't need CEF to be initialized.
+pub struct FrameConsumer {
+    socket: Arc<UnixStream>,
+    slots: Vec<Option<Arc<SharedMemory>>>,
+}
+
+impl FrameConsumer {
+    pub fn new(socket: UnixStream) -> FrameConsumer {
+        FrameConsumer {
+            socket: Arc::new(socket),
+            slots: (0..SLOTS).map(|_| None).collect(),
+        }
+    }
+    /// Connect to a publisher that accepts connections on the Unix socket at
+    /// `path`.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<FrameConsumer> {
+        UnixStream::connect(path).map(FrameConsumer::new)
+    }
+    /// Wait for the next frame. Returns an error of kind
+    /// [io::ErrorKind::UnexpectedEof] once the publisher stopped.
+    ///
+    /// Frames that are still held don't keep this from receiving the next one,
+    /// but the publisher only sends one while fewer than [SLOTS] are held.
+    pub fn recv(&mut self) -> io::Result<SharedFrame> {
+        let mut bytes = [0; HEADER_LEN];
+        let file = recv(&self.socket, &mut bytes)?;
+        let header = Header::decode(&bytes)?;
+        let slot = header.slot as usize;
+        if let Some(file) = file {
+            let memory = SharedMemory::map_received(file, header.len as usize)?;
+            self.slots[slot] = Some(Arc::new(memory));
+        }
+        let len = header.stride as usize * header.height.max(0) as usize;
+        let memory = match &self.slots[slot] {
+            Some(memory) if memory.len >= len => memory.clone(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "frame doesn't fit its shared memory",
+                ))
+            }
+        };
+        let mut damage = Damage::default();
+        for rect in &header.damage {
+            damage.add(*rect);
+        }
+        Ok(SharedFrame {
+            memory,
+            socket: self.socket.clone(),
+            header,
+            damage,
+            len,
+        })
+    }
+}
+
+/// A frame in shared memory, received by [FrameConsumer::recv]. The publisher
+/// doesn't reuse its memory until it's dropped.
+pub struct SharedFrame {
+    memory: Arc<SharedMemory>,
+    socket: Arc<UnixStream>,
+    header: Header,
+    damage: Damage,
+    len: usize,
+}
+
+impl SharedFrame {
+    pub fn width(&self) -> i32 {
+        self.header.width
+    }
+    pub fn height(&self) -> i32 {
+        self.header.height
+    }
+    pub fn format(&self) -> PixelFormat {
+        self.header.format
+    }
+    /// Bytes per row.
+    pub fn stride(&self) -> usize {
+        self.header.stride as usize
+    }
+    /// See [ComposedFrame::sequence].
+    pub fn sequence(&self) -> u64 {
+        self.header.sequence
+    }
+    /// What changed since the previous frame received by this consumer.
+    pub fn damage(&self) -> &Damage {
+        &self.damage
+    }
+    /// The premultiplied pixels, `stride() * height()` bytes.
+    pub fn data(&self) -> &[u8] {
+        &self.memory.as_slice()[..self.len]
+    }
+}
+
+impl Drop for SharedFrame {
+    fn drop(&mut self) {
+        let _ = (&*self.socket).write_all(&self.header.slot.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn frame(width: i32, height: i32, sequence: u64) -> ComposedFrame {
+        let data = (0..4 * width * height)
+            .map(|i| (i as u64 + sequence * 7) as u8)
+            .collect();
+        ComposedFrame::from_data(width, height, PixelFormat::Bgra, sequence, data)
+    }
+
+    fn damage(rects: &[Rect]) -> Damage {
+        let mut damage = Damage::default();
+        for rect in rects {
+            damage.add(*rect);
+        }
+        damage
+    }
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Waits until the publisher's state satisfies `condition`, which depends on
+    /// its threads.
+    fn wait_for(shared: &Shared, condition: impl Fn(&State) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut state = shared.state.lock();
+        while !condition(&state) {
+            assert!(Instant::now() < deadline, "timed out");
+            shared
+                .condvar
+                .wait_for(&mut state, Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let header = Header {
+            slot: 2,
+            sequence: 0x1_0000_0005,
+            width: 1920,
+            height: 1080,
+            stride: 7680,
+            format: PixelFormat::Rgba,
+            len: 0x2_0000_0000,
+            damage: vec![rect(0, 0, 10, 20), rect(-5, 300, 1920, 1)],
+        };
+        assert_eq!(Header::decode(&header.encode()).unwrap(), header);
+
+        let mut bytes = header.encode();
+        bytes[0] = 0;
+        assert!(Header::decode(&bytes).is_err());
+        let mut bytes = header.encode();
+        bytes[4] = SLOTS as u8;
+        assert!(Header::decode(&bytes).is_err());
+        let mut bytes = header.encode();
+        bytes[40] = Damage::MAX_RECTS as u8 + 1;
+        assert!(Header::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn frames_arrive_intact() {
+        let (socket, consumer_socket) = UnixStream::pair().unwrap();
+        let threads = PublisherThreads::spawn(socket).unwrap();
+        let mut consumer = FrameConsumer::new(consumer_socket);
+
+        let first = frame(5, 3, 1);
+        threads
+            .shared
+            .push(first.clone(), &damage(&[rect(1, 1, 2, 1)]));
+        {
+            let received = consumer.recv().unwrap();
+            assert_eq!((received.width(), received.height()), (5, 3));
+            assert_eq!(received.stride(), 20);
+            assert_eq!(received.format(), PixelFormat::Bgra);
+            assert_eq!(received.sequence(), 1);
+            assert_eq!(received.data(), first.data());
+            // The consumer has nothing to apply the damage of the first frame to.
+            assert_eq!(received.damage().rects(), &[rect(0, 0, 5, 3)]);
+        }
+
+        let second = frame(5, 3, 2);
+        let second_damage = damage(&[rect(0, 0, 1, 1), rect(3, 2, 2, 1)]);
+        threads.shared.push(second.clone(), &second_damage);
+        let received = consumer.recv().unwrap();
+        assert_eq!(received.sequence(), 2);
+        assert_eq!(received.data(), second.data());
+        assert_eq!(received.damage(), &second_damage);
+    }
+
+    #[test]
+    fn slots_are_reused_after_release() {
+        let (socket, consumer_socket) = UnixStream::pair().unwrap();
+        let threads = PublisherThreads::spawn(socket).unwrap();
+        let shared = threads.shared.clone();
+        let mut consumer = FrameConsumer::new(consumer_socket);
+
+        // Hold on to a frame in every slot.
+        let mut frames = Vec::new();
+        for sequence in 1..=SLOTS as u64 {
+            shared.push(frame(2, 2, sequence), &Damage::full(2, 2));
+            frames.push(consumer.recv().unwrap());
+        }
+        let mut slots = frames
+            .iter()
+            .map(|frame| frame.header.slot)
+            .collect::<Vec<_>>();
+        slots.sort();
+        assert_eq!(slots, (0..SLOTS as u32).collect::<Vec<_>>());
+        assert_eq!(shared.state.lock().in_use, [true; SLOTS]);
+        for (sequence, received) in (1..).zip(&frames) {
+            assert_eq!(received.data(), frame(2, 2, sequence).data());
+        }
+        let held = frames.pop().unwrap();
+        let held_slot = held.header.slot;
+
+        // Without a free slot, newer frames replace the waiting one and their
+        // damage accumulates.
+        let next = SLOTS as u64 + 1;
+        shared.push(frame(2, 2, next), &damage(&[rect(0, 0, 1, 1)]));
+        let larger = frame(4, 3, next + 1);
+        shared.push(larger.clone(), &damage(&[rect(3, 2, 1, 1)]));
+        {
+            let state = shared.state.lock();
+            assert_eq!(state.latest.as_ref().unwrap().sequence(), next + 1);
+            assert_eq!(state.damage.rects(), &[rect(0, 0, 1, 1), rect(3, 2, 1, 1)]);
+        }
+        assert_eq!(held.data(), frame(2, 2, SLOTS as u64).data());
+
+        drop(held);
+        let received = consumer.recv().unwrap();
+        assert_eq!(received.header.slot, held_slot);
+        assert_eq!(received.sequence(), next + 1);
+        assert_eq!((received.width(), received.height()), (4, 3));
+        // The slot grew, so its memory was sent again.
+        assert_eq!(received.data(), larger.data());
+        assert_eq!(
+            received.damage().rects(),
+            &[rect(0, 0, 1, 1), rect(3, 2, 1, 1)]
+        );
+        drop(received);
+        wait_for(&shared, |state| !state.in_use[held_slot as usize]);
+        drop(frames);
+        wait_for(&shared, |state| state.in_use == [false; SLOTS]);
+    }
+
+    /// Sends a header for a 2x2 frame in slot 0 that claims `len` bytes of
+    /// `file`, and returns what the consumer makes of it.
+    fn recv_announced(file: &File, len: u64) -> io::Result<()> {
+        let (mut socket, consumer_socket) = UnixStream::pair().unwrap();
+        let mut consumer = FrameConsumer::new(consumer_socket);
+        let header = Header {
+            slot: 0,
+            sequence: 1,
+            width: 2,
+            height: 2,
+            stride: 8,
+            format: PixelFormat::Bgra,
+            len,
+            damage: vec![rect(0, 0, 2, 2)],
+        };
+        send(&mut socket, &header.encode(), Some(file.as_raw_fd())).unwrap();
+        let frame = consumer.recv()?;
+        assert_eq!(frame.data().len(), 16);
+        Ok(())
+    }
+
+    #[test]
+    fn received_memory_is_checked() {
+        let memory = SharedMemory::create(32).unwrap();
+        recv_announced(&memory.file, 32).unwrap();
+        recv_announced(&memory.file, 16).unwrap();
+
+        // Mapping more than the file holds would crash on access.
+        let err = recv_announced(&memory.file, 4096).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "shared memory is smaller than announced");
+
+        // A memfd without seals could be truncated while it's mapped.
+        let name = CStr::from_bytes_with_nul(b"unsealed\0").unwrap();
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_memfd_create,
+                name.as_ptr(),
+                libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+            )
+        };
+        assert!(fd >= 0);
+        let unsealed = unsafe { File::from_raw_fd(fd as RawFd) };
+        unsealed.set_len(32).unwrap();
+        let err = recv_announced(&unsealed, 32).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "shared memory isn't sealed against shrinking"
+        );
+
+        // Files other than memfds can't be sealed at all.
+        let dir = crate::test_util::TempDir::new("memfd");
+        let path = dir.write("frame", &[0u8; 32][..]);
+        let err = recv_announced(&File::open(path).unwrap(), 32).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    }
+
+    #[test]
+    fn frames_outlive_consumer() {
+        let (socket, consumer_socket) = UnixStream::pair().unwrap();
+        let threads = PublisherThreads::spawn(socket).unwrap();
+        let mut consumer = FrameConsumer::new(consumer_socket);
+        let sent = frame(3, 2, 1);
+        threads.shared.push(sent.clone(), &Damage::full(3, 2));
+        let received = consumer.recv().unwrap();
+        drop(consumer);
+        assert_eq!(received.data(), sent.data());
+        drop(received);
+        wait_for(&threads.shared, |state| state.stopped);
+    }
+
+    #[test]
+    fn recv_fails_after_stop() {
+        let (socket, consumer_socket) = UnixStream::pair().unwrap();
+        let mut threads = PublisherThreads::spawn(socket).unwrap();
+        let mut consumer = FrameConsumer::new(consumer_socket);
+
+        threads.shared.push(frame(1, 1, 1), &Damage::full(1, 1));
+        drop(consumer.recv().unwrap());
+        threads.join().unwrap();
+        match consumer.recv() {
+            Err(err) => assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof),
+            Ok(frame) => panic!("received frame {} after stop", frame.sequence()),
+        };
+    }
+
+    #[test]
+    fn stops_when_consumer_disconnects() {
+        let (socket, consumer_socket) = UnixStream::pair().unwrap();
+        let mut threads = PublisherThreads::spawn(socket).unwrap();
+        drop(consumer_socket);
+        wait_for(&threads.shared, |state| state.stopped);
+        threads.join().unwrap();
+    }
+}
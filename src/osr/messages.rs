@@ -0,0 +1,153 @@
+//! Requests from the browser process to the render process, for things only the
+//! renderer knows, like the size of the document.
+
+use crate::{
+    browser::Browser,
+    error::Error,
+    frame::Frame,
+    process::ProcessMessage,
+    task::{oneshot, Receiver, Sender},
+    values::{ListValue, StoredValue, Value},
+};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicI32, Ordering},
+    task::{Context, Poll},
+};
+
+const REQUEST: &str = "cef-rs.osr.request";
+const RESPONSE: &str = "cef-rs.osr.response";
+
+type Response = Result<Vec<StoredValue>, Error>;
+
+lazy_static! {
+    static ref PENDING: Mutex<HashMap<i32, Sender<Response>>> = Mutex::new(HashMap::new());
+}
+static NEXT_ID: AtomicI32 = AtomicI32::new(0);
+
+/// Handles the process messages used by the [crate::osr] helpers that need the
+/// render process, like full-page screenshots. Call it first from both
+/// [crate::client::ClientCallbacks::on_process_message_received] in the browser
+/// process and [crate::render_process_handler::RenderProcessHandlerCallbacks::on_process_message_received]
+/// in the render process, and return true if it does.
+pub fn on_process_message_received(
+    browser: &Browser,
+    frame: &Frame,
+    message: &ProcessMessage,
+) -> bool {
+    match message.get_name().as_ref().map(String::as_str) {
+        Some(REQUEST) => {
+            handle_request(frame, message.get_argument_list());
+            true
+        }
+        Some(RESPONSE) => {
+            handle_response(message.get_argument_list());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Calls `method` in the render process of `frame`. The arguments of the
+/// request and the response must be simple values.
+pub(crate) fn request(frame: &Frame, method: &str, params: Vec<StoredValue>) -> Reply {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let (sender, receiver) = oneshot();
+    let message = ProcessMessage::new(REQUEST);
+    let args = message.get_argument_list();
+    match args
+        .set_int(0, id)
+        .and_then(|()| args.set_string(1, method))
+        .and_then(|()| set_values(&args, 2, params))
+    {
+        Ok(()) => {
+            PENDING.lock().insert(id, sender);
+            frame.send_process_message(message);
+        }
+        Err(err) => sender.send(Err(err)),
+    }
+    Reply { id, receiver }
+}
+
+/// The response to a [request]. Requests are answered by the
+/// [on_process_message_received] in the render process, so awaiting this never
+/// completes if it's not called there. Use a timeout.
+pub(crate) struct Reply {
+    id: i32,
+    receiver: Receiver<Response>,
+}
+
+impl Future for Reply {
+    type Output = Response;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Response> {
+        Pin::new(&mut self.receiver)
+            .poll(cx)
+            .map(|response| response.unwrap_or(Err(Error::Failed("osr::request"))))
+    }
+}
+
+impl Drop for Reply {
+    fn drop(&mut self) {
+        PENDING.lock().remove(&self.id);
+    }
+}
+
+fn set_values(list: &ListValue, offset: usize, values: Vec<StoredValue>) -> Result<(), Error> {
+    for (index, value) in values.into_iter().enumerate() {
+        let value = Value::try_from(value).map_err(|_| Error::Failed("osr::request"))?;
+        if !list.set_value_inner(offset + index, value) {
+            return Err(Error::Failed("osr::request"));
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(frame: &Frame, args: ListValue) {
+    let id = match args.get_int(0) {
+        Some(id) => id,
+        None => return,
+    };
+    let method = args.get_string(1).unwrap_or_default();
+    let params: Vec<StoredValue> = args.into_iter().skip(2).collect();
+    let result = match method.as_str() {
+        super::screenshot::MEASURE => super::screenshot::measure_document(frame),
+        super::screenshot::ELEMENT_BOUNDS => super::screenshot::scroll_to_element(frame, &params),
+        _ => Err(format!("unknown method {}", method)),
+    };
+
+    let message = ProcessMessage::new(RESPONSE);
+    let args = message.get_argument_list();
+    let written = args.set_int(0, id).and_then(|()| match result {
+        Ok(values) => args
+            .set_bool(1, true)
+            .and_then(|()| set_values(&args, 2, values)),
+        Err(err) => args
+            .set_bool(1, false)
+            .and_then(|()| args.set_string(2, &err)),
+    });
+    if written.is_ok() {
+        frame.send_process_message(message);
+    }
+}
+
+fn handle_response(args: ListValue) {
+    let id = match args.get_int(0) {
+        Some(id) => id,
+        None => return,
+    };
+    let sender = match PENDING.lock().remove(&id) {
+        Some(sender) => sender,
+        None => return,
+    };
+    if args.get_bool(1).unwrap_or(false) {
+        sender.send(Ok(args.into_iter().skip(2).collect()));
+    } else {
+        sender.send(Err(Error::Cef(args.get_string(2).unwrap_or_default())));
+    }
+}
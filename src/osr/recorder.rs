@@ -0,0 +1,430 @@
+use super::{convert::to_i420, ComposedFrame, Compositor, FrameListenerId};
+use crate::{browser::Browser, browser_host::PaintElementType, task::UiThread};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::Arc,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// Options for [Y4mRecorder].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecorderOptions {
+    frame_rate: Option<u32>,
+}
+
+impl RecorderOptions {
+    pub fn new() -> RecorderOptions {
+        RecorderOptions::default()
+    }
+    /// The frame rate of the video. Defaults to the windowless frame rate of the
+    /// browser when recording starts, see
+    /// [crate::browser_host::BrowserHost::set_windowless_frame_rate]. It is read
+    /// only once, later changes of the windowless frame rate don't change the
+    /// frame rate of the video.
+    pub fn frame_rate(self, frame_rate: u32) -> RecorderOptions {
+        RecorderOptions {
+            frame_rate: Some(frame_rate.max(1)),
+        }
+    }
+}
+
+/// Records the frames of a windowless browser to a YUV4MPEG2 (Y4M) video, which
+/// most video tools can read, e.g. `ffmpeg -i recording.y4m recording.mp4`.
+///
+/// Y4M videos have a constant frame rate. The recorder writes the latest frame
+/// painted into the [Compositor] at every tick, so frames are duplicated while
+/// the browser doesn't paint, and paints between two ticks are dropped. The
+/// video starts with the first frame and has the size of that frame. Later
+/// frames of a different size are cropped or padded with black, and transparent
+/// areas are black as well.
+///
+/// Changing the frame rate while recording isn't supported, because the frame
+/// rate is part of the Y4M header. If
+/// [crate::browser_host::BrowserHost::set_windowless_frame_rate] is called
+/// after recording started, the video keeps its frame rate and still plays at
+/// real-time speed: with a lower windowless frame rate, frames are duplicated
+/// more often, with a higher one, more paints are dropped. Stop the recorder and
+/// start a new one to record at the new rate.
+///
+/// Converting and writing the frames happens on a separate thread.
+pub struct Y4mRecorder {
+    compositor: Compositor,
+    listener: Option<FrameListenerId>,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<io::Result<u64>>>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    latest: Option<ComposedFrame>,
+    stopped: bool,
+}
+
+impl Y4mRecorder {
+    /// Start recording `browser` into the file at `path`.
+    pub fn create<P: AsRef<Path>>(
+        ui_thread: UiThread,
+        compositor: &Compositor,
+        browser: &Browser,
+        path: P,
+        options: RecorderOptions,
+    ) -> io::Result<Y4mRecorder> {
+        let file = File::create(path)?;
+        Y4mRecorder::start(
+            ui_thread,
+            compositor,
+            browser,
+            BufWriter::new(file),
+            options,
+        )
+    }
+    /// Start recording `browser` into `output`. The browser is invalidated, so
+    /// static pages are painted at least once.
+    pub fn start<W: Write + Send + 'static>(
+        ui_thread: UiThread,
+        compositor: &Compositor,
+        browser: &Browser,
+        output: W,
+        options: RecorderOptions,
+    ) -> io::Result<Y4mRecorder> {
+        let host = browser.get_host();
+        let frame_rate = options
+            .frame_rate
+            .unwrap_or_else(|| host.get_windowless_frame_rate(ui_thread).max(1) as u32);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            condvar: Condvar::new(),
+        });
+
+        let thread_shared = shared.clone();
+        let thread = std::thread::Builder::new()
+            .name("cef-y4m-recorder".to_owned())
+            .spawn(move || record(&thread_shared, output, frame_rate))?;
+
+        let browser_id = browser.get_identifier();
+        let listener_shared = shared.clone();
+        let listener = compositor.add_frame_listener(move |browser, frame, _| {
+            if browser.get_identifier() == browser_id {
+                listener_shared.state.lock().latest = Some(frame.clone());
+                listener_shared.condvar.notify_one();
+            }
+        });
+        if let Some(frame) = compositor.frame(browser_id) {
+            shared.state.lock().latest = Some(frame);
+            shared.condvar.notify_one();
+        }
+        host.invalidate(PaintElementType::View);
+
+        Ok(Y4mRecorder {
+            compositor: compositor.clone(),
+            listener: Some(listener),
+            shared,
+            thread: Some(thread),
+        })
+    }
+    /// Stop recording and flush the video. Returns the number of frames written.
+    pub fn stop(mut self) -> io::Result<u64> {
+        self.join()
+    }
+
+    fn join(&mut self) -> io::Result<u64> {
+        if let Some(listener) = self.listener.take() {
+            self.compositor.remove_frame_listener(listener);
+        }
+        self.shared.state.lock().stopped = true;
+        self.shared.condvar.notify_one();
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the recorder thread panicked",
+            )),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Drop for Y4mRecorder {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+fn record<W: Write>(shared: &Shared, output: W, frame_rate: u32) -> io::Result<u64> {
+    let interval = Duration::from_secs(1) / frame_rate;
+    let mut output = Some(output);
+    let mut writer = None;
+    let mut start = None;
+    loop {
+        let written = writer.as_ref().map_or(0, Y4mWriter::frames);
+        let frame = {
+            let mut state = shared.state.lock();
+            loop {
+                if state.stopped {
+                    return match writer {
+                        Some(writer) => writer.finish().map(|_| written),
+                        None => Ok(0),
+                    };
+                }
+                match (start, &state.latest) {
+                    (None, None) => shared.condvar.wait(&mut state),
+                    (None, Some(_)) => {
+                        start = Some(Instant::now());
+                        break;
+                    }
+                    (Some(start), _) => {
+                        let due = start + interval * written as u32;
+                        if Instant::now() >= due {
+                            break;
+                        }
+                        shared.condvar.wait_until(&mut state, due);
+                    }
+                }
+            }
+            state.latest.clone().unwrap()
+        };
+        if let Some(output) = output.take() {
+            writer = Some(Y4mWriter::new(
+                output,
+                frame.width(),
+                frame.height(),
+                frame_rate,
+            )?);
+        }
+        writer.as_mut().unwrap().write_frame(&frame)?;
+    }
+}
+
+/// Writes [ComposedFrame]s to a YUV4MPEG2 stream with 4:2:0 chroma subsampling.
+/// Used by [Y4mRecorder], but can also be fed manually.
+pub struct Y4mWriter<W: Write> {
+    output: W,
+    width: i32,
+    height: i32,
+    frames: u64,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Writes the stream header for a video of `width` x `height` pixels.
+    pub fn new(
+        mut output: W,
+        width: i32,
+        height: i32,
+        frame_rate: u32,
+    ) -> io::Result<Y4mWriter<W>> {
+        if width <= 0 || height <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "empty video size",
+            ));
+        }
+        writeln!(
+            output,
+            "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg",
+            width, height, frame_rate
+        )?;
+        Ok(Y4mWriter {
+            output,
+            width,
+            height,
+            frames: 0,
+            buffer: Vec::new(),
+        })
+    }
+    /// Appends a frame. Frames of a different size are cropped or padded.
+    pub fn write_frame(&mut self, frame: &ComposedFrame) -> io::Result<()> {
+        to_i420(frame.into(), self.width, self.height, &mut self.buffer);
+        self.output.write_all(b"FRAME\n")?;
+        self.output.write_all(&self.buffer)?;
+        self.frames += 1;
+        Ok(())
+    }
+    /// The number of frames written.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+    /// Flushes the stream and returns the output.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osr::PixelFormat;
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const RED: [u8; 4] = [0, 0, 255, 255];
+    /// The luma of white and black.
+    const Y_WHITE: u8 = 235;
+    const Y_BLACK: u8 = 16;
+
+    fn solid(width: i32, height: i32, pixel: [u8; 4], sequence: u64) -> ComposedFrame {
+        let data = pixel.repeat((width * height) as usize);
+        ComposedFrame::from_data(width, height, PixelFormat::Bgra, sequence, data)
+    }
+
+    /// Splits a Y4M stream into its header and frames.
+    fn parse(stream: &[u8], frame_len: usize) -> (String, Vec<&[u8]>) {
+        let header_end = stream.iter().position(|&byte| byte == b'\n').unwrap();
+        let header = String::from_utf8(stream[..header_end].to_vec()).unwrap();
+        let mut frames = Vec::new();
+        let mut rest = &stream[header_end + 1..];
+        while !rest.is_empty() {
+            assert!(rest.starts_with(b"FRAME\n"), "missing frame header");
+            assert!(rest.len() >= 6 + frame_len, "truncated frame");
+            frames.push(&rest[6..6 + frame_len]);
+            rest = &rest[6 + frame_len..];
+        }
+        (header, frames)
+    }
+
+    #[test]
+    fn header() {
+        let writer = Y4mWriter::new(Vec::new(), 5, 3, 30).unwrap();
+        assert_eq!(writer.frames(), 0);
+        let stream = writer.finish().unwrap();
+        assert_eq!(stream, b"YUV4MPEG2 W5 H3 F30:1 Ip A1:1 C420jpeg\n");
+    }
+
+    #[test]
+    fn odd_size_frames() {
+        let mut writer = Y4mWriter::new(Vec::new(), 5, 3, 25).unwrap();
+        writer.write_frame(&solid(5, 3, WHITE, 1)).unwrap();
+        writer.write_frame(&solid(5, 3, RED, 2)).unwrap();
+        assert_eq!(writer.frames(), 2);
+        let stream = writer.finish().unwrap();
+        // 5x3 luma and 3x2 for each chroma plane, rounded up.
+        let (header, frames) = parse(&stream, 15 + 2 * 6);
+        assert_eq!(header, "YUV4MPEG2 W5 H3 F25:1 Ip A1:1 C420jpeg");
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0][..15], [Y_WHITE; 15]);
+        assert_eq!(frames[0][15..], [128; 12]);
+        assert_eq!(frames[1][..15], [82; 15]);
+        assert_eq!(frames[1][15..21], [90; 6]);
+        assert_eq!(frames[1][21..], [240; 6]);
+    }
+
+    #[test]
+    fn different_frame_sizes() {
+        let mut writer = Y4mWriter::new(Vec::new(), 4, 2, 30).unwrap();
+        // Padded with black.
+        writer.write_frame(&solid(2, 1, WHITE, 1)).unwrap();
+        // Cropped.
+        writer.write_frame(&solid(6, 4, WHITE, 2)).unwrap();
+        let stream = writer.finish().unwrap();
+        let (_, frames) = parse(&stream, 8 + 2 * 2);
+        #[rustfmt::skip]
+        assert_eq!(frames[0][..8], [
+            Y_WHITE, Y_WHITE, Y_BLACK, Y_BLACK,
+            Y_BLACK, Y_BLACK, Y_BLACK, Y_BLACK,
+        ]);
+        assert_eq!(frames[1][..8], [Y_WHITE; 8]);
+    }
+
+    #[test]
+    fn empty_size() {
+        for &(width, height) in &[(0, 10), (10, 0), (-1, 10)] {
+            let err = Y4mWriter::new(Vec::new(), width, height, 30).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    /// An output that can be inspected while the recorder thread owns it.
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs [record] on a thread, fed by the returned [Shared] like the frame
+    /// listener of [Y4mRecorder] does.
+    fn recorder(frame_rate: u32) -> (Arc<Shared>, Output, JoinHandle<io::Result<u64>>) {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            condvar: Condvar::new(),
+        });
+        let output = Output::default();
+        let thread = std::thread::spawn({
+            let (shared, output) = (shared.clone(), output.clone());
+            move || record(&shared, output, frame_rate)
+        });
+        (shared, output, thread)
+    }
+
+    fn push(shared: &Shared, frame: ComposedFrame) {
+        shared.state.lock().latest = Some(frame);
+        shared.condvar.notify_one();
+    }
+
+    fn stop(shared: &Shared, thread: JoinHandle<io::Result<u64>>) -> u64 {
+        shared.state.lock().stopped = true;
+        shared.condvar.notify_one();
+        thread.join().unwrap().unwrap()
+    }
+
+    #[test]
+    fn nothing_recorded() {
+        let (shared, output, thread) = recorder(30);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(stop(&shared, thread), 0);
+        // Without a frame, the video size is unknown, so not even the header
+        // is written.
+        assert!(output.0.lock().is_empty());
+    }
+
+    #[test]
+    fn frames_are_duplicated() {
+        let (shared, output, thread) = recorder(50);
+        push(&shared, solid(2, 2, WHITE, 1));
+        std::thread::sleep(Duration::from_millis(210));
+        let frames = stop(&shared, thread);
+
+        // A frame every 20 ms, starting with the first one.
+        assert!((5..=12).contains(&frames), "{} frames", frames);
+        let stream = output.0.lock();
+        let (header, written) = parse(&stream, 4 + 2);
+        assert_eq!(header, "YUV4MPEG2 W2 H2 F50:1 Ip A1:1 C420jpeg");
+        assert_eq!(written.len() as u64, frames);
+        assert!(written.iter().all(|frame| frame[..4] == [Y_WHITE; 4]));
+    }
+
+    #[test]
+    fn latest_frame_is_written() {
+        let (shared, output, thread) = recorder(50);
+        push(&shared, solid(2, 2, WHITE, 1));
+        std::thread::sleep(Duration::from_millis(100));
+        push(&shared, solid(4, 4, RED, 2));
+        std::thread::sleep(Duration::from_millis(100));
+        let frames = stop(&shared, thread);
+
+        let stream = output.0.lock();
+        let (_, written) = parse(&stream, 4 + 2);
+        assert_eq!(written.len() as u64, frames);
+        // The larger frame is cropped to the size of the first one.
+        let white = [Y_WHITE, Y_WHITE, Y_WHITE, Y_WHITE, 128, 128];
+        let red = [82, 82, 82, 82, 90, 240];
+        let switch = written.iter().position(|frame| *frame != white).unwrap();
+        assert!(switch > 0);
+        assert!(written[switch..].iter().all(|frame| *frame == red));
+    }
+}
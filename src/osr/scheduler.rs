@@ -0,0 +1,458 @@
+//! Adaptive pacing of the begin frames of windowless browsers.
+//!
+//! Browsers with external begin frames only paint when they get one.
+//! [FrameScheduler] sends them at the maximum rate while the page reacts to
+//! input or animates, and lowers the rate step by step while nothing changes,
+//! so static pages cost next to no CPU:
+//!
+//! ```ignore
+//!  let scheduler = FrameScheduler::start(&compositor, &browser, SchedulerOptions::new().min_frame_rate(5))?;
+//!  // From the input handlers of the host application:
+//!  scheduler.notify_input();
+//! ```
+
+use super::{Compositor, FrameListenerId};
+use crate::browser::Browser;
+use parking_lot::{Condvar, Mutex};
+use std::{
+    io,
+    sync::Arc,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// The frame rate is only lowered after this many frames at the current rate.
+const FRAMES_PER_STEP: u32 = 8;
+
+/// What triggers begin frames in [FrameScheduler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameSource {
+    /// A thread of the scheduler.
+    Timer,
+    /// Calls to [FrameScheduler::on_vsync] at every vertical blank of the
+    /// display the browser is shown on. Frames are skipped to reach lower rates.
+    Vsync,
+}
+
+/// Options for [FrameScheduler].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerOptions {
+    source: FrameSource,
+    max_frame_rate: u32,
+    min_frame_rate: u32,
+    idle_timeout: Duration,
+}
+
+impl SchedulerOptions {
+    pub fn new() -> SchedulerOptions {
+        SchedulerOptions::default()
+    }
+    /// Defaults to [FrameSource::Timer].
+    pub fn source(self, source: FrameSource) -> SchedulerOptions {
+        SchedulerOptions { source, ..self }
+    }
+    /// The frame rate while there is input or animation, at most 60. Defaults
+    /// to 60.
+    pub fn max_frame_rate(self, max_frame_rate: u32) -> SchedulerOptions {
+        SchedulerOptions {
+            max_frame_rate: max_frame_rate.clamp(1, 60),
+            ..self
+        }
+    }
+    /// The frame rate of idle pages. Defaults to 1.
+    pub fn min_frame_rate(self, min_frame_rate: u32) -> SchedulerOptions {
+        SchedulerOptions {
+            min_frame_rate: min_frame_rate.clamp(1, 60),
+            ..self
+        }
+    }
+    /// How long the maximum frame rate is kept after input. Defaults to 1
+    /// second.
+    pub fn idle_timeout(self, idle_timeout: Duration) -> SchedulerOptions {
+        SchedulerOptions {
+            idle_timeout,
+            ..self
+        }
+    }
+}
+
+impl Default for SchedulerOptions {
+    fn default() -> SchedulerOptions {
+        SchedulerOptions {
+            source: FrameSource::Timer,
+            max_frame_rate: 60,
+            min_frame_rate: 1,
+            idle_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Paces the frames of a windowless browser created with
+/// [crate::window::WindowInfo::external_begin_frame_enabled], so static pages
+/// don't use CPU at a fixed frame rate.
+///
+/// The scheduler calls [crate::browser_host::BrowserHost::send_external_begin_frame]
+/// at an adaptive rate, which is also passed to
+/// [crate::browser_host::BrowserHost::set_windowless_frame_rate]:
+///
+/// - After input reported with [FrameScheduler::notify_input], it runs at the
+///   maximum rate until the idle timeout has passed.
+/// - When the last two frames were both painted into the [Compositor], the page
+///   is animating and it goes to the maximum rate as well.
+/// - When none of the last 8 frames was painted, the rate is halved, down to
+///   the minimum rate.
+///
+/// While hidden with [FrameScheduler::set_hidden], no frames are sent at all.
+pub struct FrameScheduler {
+    compositor: Compositor,
+    listener: Option<FrameListenerId>,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    browser: Browser,
+    options: SchedulerOptions,
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+struct State {
+    frame_rate: u32,
+    hidden: bool,
+    stopped: bool,
+    last_frame: Option<Instant>,
+    last_input: Option<Instant>,
+    last_vsync: Option<Instant>,
+    vsync_interval: Duration,
+    /// Whether the browser painted since the last begin frame.
+    painted: bool,
+    /// One bit per recent begin frame, set if the browser painted after it. The
+    /// lowest bit is the last frame.
+    history: u8,
+    frames_at_rate: u32,
+}
+
+impl FrameScheduler {
+    /// Start scheduling the frames of `browser`.
+    pub fn start(
+        compositor: &Compositor,
+        browser: &Browser,
+        options: SchedulerOptions,
+    ) -> io::Result<FrameScheduler> {
+        let frame_rate = options.max_frame_rate;
+        let shared = Arc::new(Shared {
+            browser: browser.clone(),
+            options,
+            state: Mutex::new(State::new(frame_rate)),
+            condvar: Condvar::new(),
+        });
+        browser
+            .get_host()
+            .set_windowless_frame_rate(frame_rate as i32);
+
+        let thread = if shared.options.source == FrameSource::Timer {
+            let thread_shared = shared.clone();
+            Some(
+                std::thread::Builder::new()
+                    .name("cef-frame-scheduler".to_owned())
+                    .spawn(move || run_timer(&thread_shared))?,
+            )
+        } else {
+            None
+        };
+
+        let browser_id = browser.get_identifier();
+        let listener_shared = shared.clone();
+        let listener = compositor.add_frame_listener(move |browser, _, _| {
+            if browser.get_identifier() == browser_id {
+                listener_shared.state.lock().painted = true;
+            }
+        });
+        Ok(FrameScheduler {
+            compositor: compositor.clone(),
+            listener: Some(listener),
+            shared,
+            thread,
+        })
+    }
+    /// Sends a begin frame if one is due. Call this at every vertical blank
+    /// when using [FrameSource::Vsync], it does nothing otherwise.
+    pub fn on_vsync(&self) {
+        if self.shared.options.source != FrameSource::Vsync {
+            return;
+        }
+        let now = Instant::now();
+        let frame = {
+            let mut state = self.shared.state.lock();
+            if let Some(last_vsync) = state.last_vsync {
+                state.vsync_interval = now - last_vsync;
+            }
+            state.last_vsync = Some(now);
+            // Begin the frame at the vertical blank closest to when it's due.
+            let due = state.due().map(|due| due <= now + state.vsync_interval / 2);
+            if state.hidden || due == Some(false) {
+                return;
+            }
+            state.begin_frame(&self.shared.options, now)
+        };
+        self.shared.send(frame);
+    }
+    /// Tells the scheduler about user input, which raises the frame rate to
+    /// the maximum until the idle timeout has passed.
+    pub fn notify_input(&self) {
+        let changed = {
+            let mut state = self.shared.state.lock();
+            state.last_input = Some(Instant::now());
+            state.set_frame_rate(self.shared.options.max_frame_rate)
+        };
+        self.shared.condvar.notify_one();
+        if changed {
+            let host = self.shared.browser.get_host();
+            host.set_windowless_frame_rate(self.shared.options.max_frame_rate as i32);
+        }
+    }
+    /// Hides or shows the browser with
+    /// [crate::browser_host::BrowserHost::was_hidden]. No frames are sent while
+    /// hidden. Showing counts as input.
+    pub fn set_hidden(&self, hidden: bool) {
+        self.shared.state.lock().hidden = hidden;
+        self.shared.browser.get_host().was_hidden(hidden);
+        if hidden {
+            self.shared.condvar.notify_one();
+        } else {
+            self.notify_input();
+        }
+    }
+    /// The current frame rate.
+    pub fn frame_rate(&self) -> u32 {
+        self.shared.state.lock().frame_rate
+    }
+    /// Stop sending begin frames.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            self.compositor.remove_frame_listener(listener);
+        }
+        self.shared.state.lock().stopped = true;
+        self.shared.condvar.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for FrameScheduler {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+impl Shared {
+    /// Sends a begin frame. `new_rate` is the frame rate if it changed.
+    /// Called without the state locked, since CEF may paint synchronously when
+    /// called on the UI thread.
+    fn send(&self, new_rate: Option<u32>) {
+        let host = self.browser.get_host();
+        if let Some(frame_rate) = new_rate {
+            host.set_windowless_frame_rate(frame_rate as i32);
+        }
+        host.send_external_begin_frame();
+    }
+}
+
+impl State {
+    fn new(frame_rate: u32) -> State {
+        State {
+            frame_rate,
+            hidden: false,
+            stopped: false,
+            last_frame: None,
+            last_input: None,
+            last_vsync: None,
+            vsync_interval: Duration::from_secs(1) / 60,
+            painted: false,
+            history: 0,
+            frames_at_rate: 0,
+        }
+    }
+    /// When the next frame is due, or None if no frame was sent yet.
+    fn due(&self) -> Option<Instant> {
+        self.last_frame
+            .map(|last_frame| last_frame + Duration::from_secs(1) / self.frame_rate)
+    }
+    /// Returns whether the rate changed.
+    fn set_frame_rate(&mut self, frame_rate: u32) -> bool {
+        if self.frame_rate == frame_rate {
+            return false;
+        }
+        self.frame_rate = frame_rate;
+        self.frames_at_rate = 0;
+        true
+    }
+    /// Records a begin frame and adapts the frame rate to the paints since the
+    /// last one. Returns the new frame rate if it changed.
+    fn begin_frame(&mut self, options: &SchedulerOptions, now: Instant) -> Option<u32> {
+        self.history = self.history << 1 | self.painted as u8;
+        self.painted = false;
+        self.last_frame = Some(now);
+        self.frames_at_rate += 1;
+        let input = match self.last_input {
+            Some(last_input) => now - last_input < options.idle_timeout,
+            None => false,
+        };
+        let frame_rate = if input || self.history & 0b11 == 0b11 {
+            options.max_frame_rate
+        } else if self.history == 0 && self.frames_at_rate >= FRAMES_PER_STEP {
+            (self.frame_rate / 2).max(options.min_frame_rate.min(options.max_frame_rate))
+        } else {
+            self.frame_rate
+        };
+        if self.set_frame_rate(frame_rate) {
+            Some(frame_rate)
+        } else {
+            None
+        }
+    }
+}
+
+fn run_timer(shared: &Shared) {
+    loop {
+        let frame = {
+            let mut state = shared.state.lock();
+            loop {
+                if state.stopped {
+                    return;
+                }
+                if !state.hidden {
+                    match state.due() {
+                        Some(due) if due > Instant::now() => {
+                            shared.condvar.wait_until(&mut state, due);
+                        }
+                        _ => break,
+                    }
+                } else {
+                    shared.condvar.wait(&mut state);
+                }
+            }
+            state.begin_frame(&shared.options, Instant::now())
+        };
+        shared.send(frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Begin frames at a fixed interval, with simulated paints.
+    struct Frames {
+        state: State,
+        options: SchedulerOptions,
+        now: Instant,
+    }
+
+    impl Frames {
+        fn new(options: SchedulerOptions) -> Frames {
+            Frames {
+                state: State::new(options.max_frame_rate),
+                options,
+                now: Instant::now(),
+            }
+        }
+        /// Begins a frame 1/60 s after the last one. Returns the new frame
+        /// rate if it changed.
+        fn next(&mut self, painted: bool) -> Option<u32> {
+            self.now += Duration::from_secs(1) / 60;
+            let changed = self.state.begin_frame(&self.options, self.now);
+            // The browser paints after the begin frame, before the next one.
+            self.state.painted = painted;
+            changed
+        }
+        /// Begins `count` frames. Returns the frame rate changes.
+        fn run(&mut self, count: usize, painted: bool) -> Vec<u32> {
+            (0..count).filter_map(|_| self.next(painted)).collect()
+        }
+    }
+
+    #[test]
+    fn idle_pages_ramp_down() {
+        let mut frames = Frames::new(SchedulerOptions::new());
+        assert_eq!(frames.run(FRAMES_PER_STEP as usize - 1, false), []);
+        assert_eq!(frames.next(false), Some(30));
+        assert_eq!(frames.state.frame_rate, 30);
+        // Each rate is kept for a few frames.
+        assert_eq!(frames.run(FRAMES_PER_STEP as usize - 1, false), []);
+        assert_eq!(frames.next(false), Some(15));
+        assert_eq!(frames.run(100, false), [7, 3, 1]);
+        assert_eq!(frames.state.frame_rate, 1);
+    }
+
+    #[test]
+    fn paints_delay_ramp_down() {
+        let mut frames = Frames::new(SchedulerOptions::new());
+        frames.run(3, false);
+        frames.next(true);
+        // Only after 8 frames without a paint.
+        assert_eq!(frames.run(FRAMES_PER_STEP as usize, false), []);
+        assert_eq!(frames.next(false), Some(30));
+    }
+
+    #[test]
+    fn animation_ramps_up() {
+        let mut frames = Frames::new(SchedulerOptions::new());
+        frames.run(100, false);
+        assert_eq!(frames.state.frame_rate, 1);
+        // A single paint isn't enough.
+        assert_eq!(frames.next(true), None);
+        assert_eq!(frames.next(false), None);
+        assert_eq!(frames.next(true), None);
+        assert_eq!(frames.next(true), None);
+        assert_eq!(frames.next(false), Some(60));
+        // And the maximum rate is kept while the page keeps painting.
+        assert_eq!(frames.run(100, true), []);
+        assert_eq!(frames.state.frame_rate, 60);
+    }
+
+    #[test]
+    fn input_keeps_max_rate() {
+        let options = SchedulerOptions::new().idle_timeout(Duration::from_millis(500));
+        let mut frames = Frames::new(options);
+        frames.run(100, false);
+        frames.state.last_input = Some(frames.now);
+        assert_eq!(frames.next(false), Some(60));
+        // The idle timeout ends 30 frames after the input.
+        assert_eq!(frames.run(29, false), []);
+        assert_eq!(frames.next(false), Some(30));
+        assert_eq!(
+            frames.state.due(),
+            Some(frames.now + Duration::from_secs(1) / 30)
+        );
+    }
+
+    #[test]
+    fn min_frame_rate() {
+        let options = SchedulerOptions::new().min_frame_rate(5);
+        let mut frames = Frames::new(options);
+        assert_eq!(frames.run(100, false), [30, 15, 7, 5]);
+
+        // A minimum above the maximum is the maximum.
+        let options = SchedulerOptions::new()
+            .max_frame_rate(10)
+            .min_frame_rate(30);
+        let mut frames = Frames::new(options);
+        assert_eq!(frames.run(100, false), []);
+        assert_eq!(frames.state.frame_rate, 10);
+    }
+
+    #[test]
+    fn options_are_clamped() {
+        let options = SchedulerOptions::new()
+            .max_frame_rate(240)
+            .min_frame_rate(0);
+        assert_eq!((options.max_frame_rate, options.min_frame_rate), (60, 1));
+    }
+}
@@ -0,0 +1,689 @@
+use super::{
+    messages,
+    util::{base64, resize},
+    ComposedFrame, Compositor, FrameListenerId, PixelFormat, Viewport,
+};
+use crate::{
+    browser::{Browser, BrowserSettings},
+    browser_host::{BrowserHost, PaintElementType},
+    client::{render_handler::RenderHandler, Client, ClientCallbacks},
+    error::Error,
+    frame::Frame,
+    image::{AlphaType, ColorType, Image},
+    load_handler::{ErrorCode, LoadHandler, LoadHandlerCallbacks},
+    process::ProcessMessage,
+    task::{
+        oneshot, spawn_on, timeout, Cancelled, JoinHandle, Receiver, Sender, ThreadId, UiThread,
+    },
+    v8context::V8Value,
+    values::{Rect, Size, StoredValue},
+    window::WindowInfo,
+    Context,
+};
+use parking_lot::Mutex;
+use std::{future::Future, pin::Pin, task::Poll, time::Duration};
+
+/// Name of the render process method that measures the document.
+pub(crate) const MEASURE: &str = "measure";
+/// Name of the render process method that scrolls to an element and returns its
+/// bounds.
+pub(crate) const ELEMENT_BOUNDS: &str = "element_bounds";
+
+/// Frames larger than this in either dimension are not supported by Chromium.
+const MAX_PIXEL_SIZE: i32 = 16384;
+
+/// How a [Screenshot] is encoded in addition to the [Image].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageEncoding {
+    Png,
+    /// `quality` ranges from 0 (lowest) to 100 (highest).
+    Jpeg {
+        quality: u8,
+    },
+}
+
+/// Options for [capture_screenshot].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenshotOptions {
+    viewport: Option<Viewport>,
+    full_page: bool,
+    clip: Option<Rect>,
+    encoding: Option<ImageEncoding>,
+    timeout: Duration,
+}
+
+impl ScreenshotOptions {
+    pub fn new() -> ScreenshotOptions {
+        ScreenshotOptions::default()
+    }
+    /// Render with this viewport instead of the current one. The browser is
+    /// resized back afterwards.
+    pub fn viewport(self, viewport: Viewport) -> ScreenshotOptions {
+        ScreenshotOptions {
+            viewport: Some(viewport),
+            ..self
+        }
+    }
+    /// Grow the viewport to the height of the document. This needs
+    /// [super::on_process_message_received] to be called in both processes.
+    pub fn full_page(self, full_page: bool) -> ScreenshotOptions {
+        ScreenshotOptions { full_page, ..self }
+    }
+    /// Only capture this part of the view, in view coordinates.
+    pub fn clip(self, clip: Rect) -> ScreenshotOptions {
+        ScreenshotOptions {
+            clip: Some(clip),
+            ..self
+        }
+    }
+    /// Also encode the screenshot, see [Screenshot::encoded].
+    pub fn encoding(self, encoding: ImageEncoding) -> ScreenshotOptions {
+        ScreenshotOptions {
+            encoding: Some(encoding),
+            ..self
+        }
+    }
+    /// How long to wait for CEF to paint or the render process to respond.
+    /// Defaults to 10 seconds.
+    pub fn timeout(self, timeout: Duration) -> ScreenshotOptions {
+        ScreenshotOptions { timeout, ..self }
+    }
+}
+
+impl Default for ScreenshotOptions {
+    fn default() -> ScreenshotOptions {
+        ScreenshotOptions {
+            viewport: None,
+            full_page: false,
+            clip: None,
+            encoding: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// The result of [capture_screenshot].
+pub struct Screenshot {
+    image: Image,
+    frame: ComposedFrame,
+    device_scale_factor: f32,
+    encoded: Option<Vec<u8>>,
+}
+
+impl Screenshot {
+    /// The screenshot as an image with a single representation at
+    /// [Screenshot::device_scale_factor]. Like all [Image] functions, its
+    /// functions must be called on the browser process UI thread.
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+    /// The pixels of the screenshot.
+    pub fn frame(&self) -> &ComposedFrame {
+        &self.frame
+    }
+    pub fn device_scale_factor(&self) -> f32 {
+        self.device_scale_factor
+    }
+    /// The PNG or JPEG data, if an encoding was requested with
+    /// [ScreenshotOptions::encoding].
+    pub fn encoded(&self) -> Option<&[u8]> {
+        self.encoded.as_ref().map(Vec::as_slice)
+    }
+}
+
+/// Returned by [capture_screenshot], [capture_element] and [capture_html].
+pub struct CaptureScreenshot(JoinHandle<Result<Screenshot, Error>>);
+
+impl Future for CaptureScreenshot {
+    type Output = Result<Screenshot, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<Self::Output> {
+        Pin::new(&mut self.0)
+            .poll(cx)
+            .map(|result| result.unwrap_or(Err(Error::Failed("capture_screenshot"))))
+    }
+}
+
+/// Take a screenshot of a windowless browser whose render handler feeds
+/// `compositor`, e.g. because it uses [Compositor::handler]. Can be called on
+/// any thread, the work is done on the browser process UI thread. Example usage:
+///
+/// ```ignore
+///  let options = ScreenshotOptions::new()
+///      .viewport(Viewport::new(1280, 720).device_scale_factor(2.0))
+///      .full_page(true)
+///      .encoding(ImageEncoding::Png);
+///  let screenshot = capture_screenshot(&compositor, &browser, options).await?;
+///  std::fs::write("thumbnail.png", screenshot.encoded().unwrap())?;
+/// ```
+///
+/// The browser is resized if necessary and repainted with
+/// [BrowserHost::invalidate], and the first complete frame painted afterwards is
+/// returned. Hidden browsers don't paint, so this fails with [Error::TimedOut]
+/// for them.
+pub fn capture_screenshot(
+    compositor: &Compositor,
+    browser: &Browser,
+    options: ScreenshotOptions,
+) -> CaptureScreenshot {
+    let compositor = compositor.clone();
+    let browser = browser.clone();
+    CaptureScreenshot(spawn_on(ThreadId::UI, async move {
+        capture(&compositor, &browser, &options).await
+    }))
+}
+
+/// Load `html` into a new windowless browser, take a screenshot once it has
+/// loaded and close the browser again. The viewport defaults to 800x600. For
+/// [ScreenshotOptions::full_page], the render process still has to call
+/// [super::on_process_message_received].
+///
+/// The HTML is loaded from a `data:` URL, so it can't reference relative URLs.
+pub fn capture_html(
+    _ui_thread: UiThread,
+    context: &Context,
+    html: &str,
+    options: ScreenshotOptions,
+) -> CaptureScreenshot {
+    let viewport = options.viewport.unwrap_or_else(|| Viewport::new(800, 600));
+    let compositor = Compositor::new(PixelFormat::Bgra, viewport);
+    let (loaded, load_result) = oneshot();
+    let client = Client::new(CaptureClient {
+        render_handler: RenderHandler::new(compositor.handler()),
+        load_handler: LoadHandler::new(LoadWaiter(Mutex::new(Some(loaded)))),
+    });
+    let window_info = WindowInfo {
+        windowless_rendering_enabled: true,
+        ..WindowInfo::default()
+    };
+    let browser = BrowserHost::create_browser_sync(
+        &window_info,
+        client,
+        &data_url(html),
+        &BrowserSettings::new(),
+        None,
+        None,
+        context,
+    );
+    CaptureScreenshot(spawn_on(ThreadId::UI, async move {
+        let result = match timeout(ThreadId::UI, options.timeout, load_result).await {
+            Some(Ok(Ok(()))) => capture(&compositor, &browser, &options).await,
+            Some(Ok(Err(err))) => Err(err),
+            Some(Err(Cancelled)) => Err(Error::Failed("capture_html")),
+            None => Err(Error::TimedOut),
+        };
+        browser.get_host().close_browser(true);
+        compositor.remove(browser.get_identifier());
+        result
+    }))
+}
+
+/// Take a screenshot of the first element in `frame` that matches the CSS
+/// `selector`. The element is scrolled into view if necessary, and the
+/// screenshot is cropped to its bounds at the device scale factor of the
+/// viewport. Parts of the element that don't fit into the viewport are cut
+/// off, so use [ScreenshotOptions::viewport] for large elements.
+/// [ScreenshotOptions::full_page] and [ScreenshotOptions::clip] are ignored.
+///
+/// Like [ScreenshotOptions::full_page], this needs
+/// [super::on_process_message_received] to be called in both processes.
+/// Example usage:
+///
+/// ```ignore
+///  let frame = browser.get_main_frame();
+///  let screenshot = capture_element(&compositor, &frame, "#date-picker", ScreenshotOptions::new()).await?;
+///  assert_eq!(screenshot.frame().width(), 320);
+/// ```
+pub fn capture_element(
+    compositor: &Compositor,
+    frame: &Frame,
+    selector: &str,
+    options: ScreenshotOptions,
+) -> CaptureScreenshot {
+    let compositor = compositor.clone();
+    let frame = frame.clone();
+    let selector = selector.to_owned();
+    CaptureScreenshot(spawn_on(ThreadId::UI, async move {
+        capture_selector(&compositor, &frame, &selector, &options).await
+    }))
+}
+
+async fn capture(
+    compositor: &Compositor,
+    browser: &Browser,
+    options: &ScreenshotOptions,
+) -> Result<Screenshot, Error> {
+    let browser_id = browser.get_identifier();
+    let original = compositor.viewport(browser_id);
+    let mut viewport = options.viewport.unwrap_or(original);
+    if options.full_page {
+        // Wait for a frame at the new viewport, so the document has been laid
+        // out at its width before measuring it.
+        if viewport != original {
+            if let Err(err) = render(compositor, browser, viewport, options.timeout).await {
+                resize(compositor, browser, original);
+                return Err(err);
+            }
+        }
+        let document = match measure(browser, options.timeout).await {
+            Ok(document) => document,
+            Err(err) => {
+                resize(compositor, browser, original);
+                return Err(err);
+            }
+        };
+        let max_height = (MAX_PIXEL_SIZE as f32 / viewport.device_scale_factor) as i32;
+        viewport.size.height = viewport.size.height.max(document.height).min(max_height);
+    }
+
+    let frame = render(compositor, browser, viewport, options.timeout).await;
+    if viewport != original {
+        resize(compositor, browser, original);
+    }
+    let frame = match options.clip {
+        Some(clip) => {
+            let pixels = to_pixels(
+                [
+                    clip.x as f64,
+                    clip.y as f64,
+                    clip.width as f64,
+                    clip.height as f64,
+                ],
+                viewport.device_scale_factor,
+            );
+            frame?
+                .crop(pixels)
+                .ok_or(Error::Failed("capture_screenshot"))?
+        }
+        None => frame?,
+    };
+    screenshot(frame, viewport.device_scale_factor, options.encoding)
+}
+
+async fn capture_selector(
+    compositor: &Compositor,
+    frame: &Frame,
+    selector: &str,
+    options: &ScreenshotOptions,
+) -> Result<Screenshot, Error> {
+    let browser = frame.get_browser();
+    let browser_id = browser.get_identifier();
+    let original = compositor.viewport(browser_id);
+    let viewport = options.viewport.unwrap_or(original);
+    // Like for full-page screenshots, lay the document out at the new viewport
+    // before measuring.
+    if viewport != original {
+        if let Err(err) = render(compositor, &browser, viewport, options.timeout).await {
+            resize(compositor, &browser, original);
+            return Err(err);
+        }
+    }
+    let bounds = match element_bounds(frame, selector, options.timeout).await {
+        Ok(bounds) => bounds,
+        Err(err) => {
+            resize(compositor, &browser, original);
+            return Err(err);
+        }
+    };
+
+    let composed = render(compositor, &browser, viewport, options.timeout).await;
+    if viewport != original {
+        resize(compositor, &browser, original);
+    }
+    let pixels = to_pixels(bounds, viewport.device_scale_factor);
+    let composed = composed?
+        .crop(pixels)
+        .ok_or(Error::Failed("capture_element"))?;
+    screenshot(composed, viewport.device_scale_factor, options.encoding)
+}
+
+/// Converts `[x, y, width, height]` in view coordinates to the smallest pixel
+/// rectangle containing it.
+fn to_pixels(bounds: [f64; 4], device_scale_factor: f32) -> Rect {
+    let scale = device_scale_factor as f64;
+    let [x, y, width, height] = bounds;
+    let left = (x * scale).floor() as i32;
+    let top = (y * scale).floor() as i32;
+    Rect {
+        x: left,
+        y: top,
+        width: ((x + width) * scale).ceil() as i32 - left,
+        height: ((y + height) * scale).ceil() as i32 - top,
+    }
+}
+
+/// Turns a frame into a [Screenshot]. Must be called on the UI thread.
+pub(crate) fn screenshot(
+    frame: ComposedFrame,
+    device_scale_factor: f32,
+    encoding: Option<ImageEncoding>,
+) -> Result<Screenshot, Error> {
+    let image = Image::new();
+    let color_type = match frame.format() {
+        PixelFormat::Bgra => ColorType::Bgra8888,
+        PixelFormat::Rgba => ColorType::Rgba8888,
+    };
+    if !image.add_bitmap(
+        device_scale_factor,
+        frame.width(),
+        frame.height(),
+        color_type,
+        AlphaType::Premultiplied,
+        frame.data(),
+    ) {
+        return Err(Error::Failed("Image::add_bitmap"));
+    }
+    let encoded = match encoding {
+        Some(ImageEncoding::Png) => Some(
+            image
+                .get_as_png(device_scale_factor, true)
+                .ok_or(Error::Failed("Image::get_as_png"))?
+                .data,
+        ),
+        Some(ImageEncoding::Jpeg { quality }) => Some(
+            image
+                .get_as_jpeg(device_scale_factor, quality)
+                .ok_or(Error::Failed("Image::get_as_jpeg"))?
+                .data,
+        ),
+        None => None,
+    };
+    Ok(Screenshot {
+        image,
+        frame,
+        device_scale_factor,
+        encoded,
+    })
+}
+
+/// Renders the browser at `viewport` and waits for the frame.
+pub(crate) async fn render(
+    compositor: &Compositor,
+    browser: &Browser,
+    viewport: Viewport,
+    duration: Duration,
+) -> Result<ComposedFrame, Error> {
+    let browser_id = browser.get_identifier();
+    let after = compositor
+        .with_frame(browser_id, ComposedFrame::sequence)
+        .unwrap_or(0);
+    let next_frame = NextFrame::new(compositor, browser_id, after, viewport.pixel_size());
+    if compositor.viewport(browser_id) != viewport {
+        resize(compositor, browser, viewport);
+    }
+    browser.get_host().invalidate(PaintElementType::View);
+    match timeout(ThreadId::UI, duration, next_frame).await {
+        Some(Ok(frame)) => Ok(frame),
+        Some(Err(Cancelled)) => Err(Error::Failed("capture_screenshot")),
+        None => Err(Error::TimedOut),
+    }
+}
+
+/// Resolves to the first frame after `after` with the expected size.
+struct NextFrame {
+    compositor: Compositor,
+    listener: FrameListenerId,
+    receiver: Receiver<ComposedFrame>,
+}
+
+impl NextFrame {
+    fn new(compositor: &Compositor, browser_id: i32, after: u64, size: Size) -> NextFrame {
+        let (sender, receiver) = oneshot();
+        let sender = Mutex::new(Some(sender));
+        let listener = compositor.add_frame_listener(move |browser, frame, _| {
+            // Chromium may round the pixel size differently.
+            let complete = browser.get_identifier() == browser_id
+                && frame.sequence() > after
+                && (frame.width() - size.width).abs() <= 1
+                && (frame.height() - size.height).abs() <= 1;
+            if complete {
+                if let Some(sender) = sender.lock().take() {
+                    sender.send(frame.clone());
+                }
+            }
+        });
+        NextFrame {
+            compositor: compositor.clone(),
+            listener,
+            receiver,
+        }
+    }
+}
+
+impl Future for NextFrame {
+    type Output = Result<ComposedFrame, Cancelled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver).poll(cx)
+    }
+}
+
+impl Drop for NextFrame {
+    fn drop(&mut self) {
+        self.compositor.remove_frame_listener(self.listener);
+    }
+}
+
+/// Returns the size of the document of the main frame in view coordinates.
+async fn measure(browser: &Browser, duration: Duration) -> Result<Size, Error> {
+    let reply = messages::request(&browser.get_main_frame(), MEASURE, Vec::new());
+    let values = timeout(ThreadId::UI, duration, reply)
+        .await
+        .ok_or(Error::TimedOut)??;
+    match values.as_slice() {
+        [StoredValue::Int(width), StoredValue::Int(height)] => Ok(Size {
+            width: *width,
+            height: *height,
+        }),
+        _ => Err(Error::Failed("measure")),
+    }
+}
+
+/// Implementation of [MEASURE] in the render process.
+pub(crate) fn measure_document(frame: &Frame) -> Result<Vec<StoredValue>, String> {
+    const SCRIPT: &str = "(function() {
+        var root = document.documentElement, body = document.body || root;
+        return Math.max(root.scrollWidth, body.scrollWidth) + ',' + Math.max(root.scrollHeight, body.scrollHeight);
+    })()";
+    let value = frame
+        .get_v8context()
+        .eval(SCRIPT, "", 0)
+        .map_err(|exception| exception.get_message())?;
+    let size = value.get_string_value().unwrap_or_default();
+    let mut parts = size.split(',').map(str::parse::<i32>);
+    match (parts.next(), parts.next()) {
+        (Some(Ok(width)), Some(Ok(height))) => {
+            Ok(vec![StoredValue::Int(width), StoredValue::Int(height)])
+        }
+        _ => Err(format!("unexpected document size {:?}", size)),
+    }
+}
+
+/// Returns the bounds of the first element matching `selector` in `frame` in
+/// view coordinates, after scrolling it into view.
+async fn element_bounds(
+    frame: &Frame,
+    selector: &str,
+    duration: Duration,
+) -> Result<[f64; 4], Error> {
+    let reply = messages::request(
+        frame,
+        ELEMENT_BOUNDS,
+        vec![StoredValue::String(selector.to_owned())],
+    );
+    let values = timeout(ThreadId::UI, duration, reply)
+        .await
+        .ok_or(Error::TimedOut)??;
+    match values.as_slice() {
+        [StoredValue::Double(x), StoredValue::Double(y), StoredValue::Double(width), StoredValue::Double(height)] => {
+            Ok([*x, *y, *width, *height])
+        }
+        _ => Err(Error::Failed("element_bounds")),
+    }
+}
+
+/// Implementation of [ELEMENT_BOUNDS] in the render process.
+///
+/// CEF's DOM API can't query selectors, so this uses JavaScript. Bounds of
+/// elements in same-origin child frames are offset by the position of the
+/// frame elements, but only the innermost document is scrolled.
+pub(crate) fn scroll_to_element(
+    frame: &Frame,
+    params: &[StoredValue],
+) -> Result<Vec<StoredValue>, String> {
+    const SCRIPT: &str = "(function(selector) {
+        var element = document.querySelector(selector);
+        if (!element) {
+            return '';
+        }
+        element.scrollIntoView({ block: 'nearest', inline: 'nearest' });
+        var rect = element.getBoundingClientRect();
+        var x = rect.left, y = rect.top, view = window;
+        try {
+            while (view.frameElement) {
+                var frame = view.frameElement, bounds = frame.getBoundingClientRect();
+                x += bounds.left + frame.clientLeft;
+                y += bounds.top + frame.clientTop;
+                view = view.parent;
+            }
+        } catch (e) {}
+        return [x, y, rect.width, rect.height].join(',');
+    })";
+    let selector = match params {
+        [StoredValue::String(selector)] => selector,
+        _ => return Err("expected a selector".to_owned()),
+    };
+    let context = frame.get_v8context();
+    let bounds = context
+        .execute_in_context(|| {
+            let function = context
+                .eval(SCRIPT, "", 0)
+                .map_err(|exception| exception.get_message())?;
+            match function.execute_function(None, &[V8Value::from(selector.as_str())]) {
+                Some(value) => Ok(value.get_string_value().unwrap_or_default()),
+                None => Err(function
+                    .get_exception()
+                    .map(|exception| exception.get_message())
+                    .unwrap_or_else(|| format!("invalid selector {:?}", selector))),
+            }
+        })
+        .map_err(|err| err.to_string())??;
+    if bounds.is_empty() {
+        return Err(format!("no element matches {:?}", selector));
+    }
+    let values: Result<Vec<f64>, _> = bounds.split(',').map(str::parse::<f64>).collect();
+    match values {
+        Ok(ref values) if values.len() == 4 => Ok(values
+            .iter()
+            .map(|value| StoredValue::Double(*value))
+            .collect()),
+        _ => Err(format!("unexpected element bounds {:?}", bounds)),
+    }
+}
+
+struct CaptureClient {
+    render_handler: RenderHandler,
+    load_handler: LoadHandler,
+}
+
+impl ClientCallbacks for CaptureClient {
+    fn get_load_handler(&self) -> Option<LoadHandler> {
+        Some(self.load_handler.clone())
+    }
+    fn get_render_handler(&self) -> Option<RenderHandler> {
+        Some(self.render_handler.clone())
+    }
+    fn on_process_message_received(
+        &self,
+        browser: Browser,
+        frame: Frame,
+        message: ProcessMessage,
+    ) -> bool {
+        messages::on_process_message_received(&browser, &frame, &message)
+    }
+}
+
+/// Reports the end of the first load.
+struct LoadWaiter(Mutex<Option<Sender<Result<(), Error>>>>);
+
+impl LoadHandlerCallbacks for LoadWaiter {
+    fn on_loading_state_change(
+        &self,
+        browser: Browser,
+        is_loading: bool,
+        can_go_back: bool,
+        can_go_forward: bool,
+    ) {
+        if !is_loading {
+            if let Some(sender) = self.0.lock().take() {
+                sender.send(Ok(()));
+            }
+        }
+    }
+    fn on_load_error(
+        &self,
+        browser: Browser,
+        frame: Frame,
+        error_code: ErrorCode,
+        error_text: &str,
+        failed_url: &str,
+    ) {
+        if frame.is_main() {
+            if let Some(sender) = self.0.lock().take() {
+                sender.send(Err(Error::Cef(error_text.to_owned())));
+            }
+        }
+    }
+}
+
+fn data_url(html: &str) -> String {
+    let mut url = String::with_capacity(22 + (html.len() + 2) / 3 * 4);
+    url.push_str("data:text/html;base64,");
+    base64(html.as_bytes(), &mut url);
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn whole_pixels() {
+        assert_eq!(
+            to_pixels([10.0, 20.0, 30.0, 40.0], 1.0),
+            rect(10, 20, 30, 40)
+        );
+        assert_eq!(
+            to_pixels([10.0, 20.0, 30.0, 40.0], 2.0),
+            rect(20, 40, 60, 80)
+        );
+    }
+
+    #[test]
+    fn fractional_bounds() {
+        // Rounded outwards, so partially covered pixels are included.
+        assert_eq!(
+            to_pixels([10.5, 20.25, 30.0, 0.5], 1.0),
+            rect(10, 20, 31, 1)
+        );
+        assert_eq!(to_pixels([0.3, 0.3, 0.3, 0.3], 1.0), rect(0, 0, 1, 1));
+        assert_eq!(to_pixels([10.3, 5.0, 3.3, 2.0], 1.5), rect(15, 7, 6, 4));
+        // Scaled after adding the size, so the right edge doesn't drift.
+        assert_eq!(to_pixels([0.25, 0.0, 0.5, 1.0], 2.0), rect(0, 0, 2, 2));
+        assert_eq!(to_pixels([-2.5, -0.5, 5.0, 1.0], 1.0), rect(-3, -1, 6, 2));
+    }
+
+    #[test]
+    fn html_data_url() {
+        assert_eq!(data_url("<p>hi</p>"), "data:text/html;base64,PHA+aGk8L3A+");
+    }
+}
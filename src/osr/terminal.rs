@@ -0,0 +1,1194 @@
+//! Shows windowless browsers in a terminal, for inspecting headless instances
+//! over SSH.
+//!
+//! Enabled by the `terminal` feature, Unix only. [TerminalView] takes over the
+//! controlling terminal of the process: it switches to the alternate screen,
+//! draws the frames painted into a [Compositor] with the kitty graphics
+//! protocol or as sixels, and forwards mouse and keyboard input to the browser.
+//! Only the damaged cells are redrawn. The last row of the terminal is left
+//! empty, so sixel images never scroll the screen.
+//!
+//! All keys, including Ctrl+C, go to the browser. Press Ctrl+] to close the
+//! view and restore the terminal:
+//!
+//! ```ignore
+//!  let view = TerminalView::start(&compositor, &browser, TerminalOptions::new().graphics(TerminalGraphics::Sixel))?;
+//!  view.wait();
+//! ```
+
+use super::{
+    compositor::intersect,
+    convert::{convert, TargetFormat},
+    input::{InputTranslator, WHEEL_DELTA},
+    util::{base64, resize},
+    ComposedFrame, Compositor, Damage, FrameListenerId, Viewport,
+};
+use crate::{
+    browser::Browser,
+    browser_host::BrowserHost,
+    events::{EventFlags, WindowsKeyCode},
+    values::Rect,
+};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::unix::io::AsRawFd,
+    sync::Arc,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// How often the terminal size is checked.
+const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long to wait for the rest of an escape sequence before treating a lone
+/// escape byte as the Escape key.
+const ESCAPE_TIMEOUT: i32 = 50;
+/// Ctrl+], which closes the view.
+const QUIT_BYTE: u8 = 0x1d;
+const KITTY_IMAGE_ID: u32 = 1;
+/// Maximum size of the base64 payload of a kitty graphics escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+/// The sixel palette has 6 levels of red and blue, and 7 levels of green.
+const SIXEL_LEVELS: [u32; 3] = [6, 7, 6];
+const BAYER: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// The image protocol used to draw frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalGraphics {
+    /// The kitty graphics protocol, supported by kitty, WezTerm and Konsole.
+    /// Frames are sent with full color.
+    Kitty,
+    /// Sixel graphics, supported by xterm (`-ti vt340`), foot, mlterm and
+    /// WezTerm. Frames are dithered to 252 colors.
+    Sixel,
+}
+
+/// Options for [TerminalView].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalOptions {
+    graphics: TerminalGraphics,
+    frame_rate: u32,
+    fit_browser: bool,
+}
+
+impl TerminalOptions {
+    pub fn new() -> TerminalOptions {
+        TerminalOptions::default()
+    }
+    /// The image protocol to use. Defaults to [TerminalGraphics::Kitty].
+    pub fn graphics(self, graphics: TerminalGraphics) -> TerminalOptions {
+        TerminalOptions { graphics, ..self }
+    }
+    /// The maximum number of updates written per second. Defaults to 30, since
+    /// terminals are slow to decode images.
+    pub fn frame_rate(self, frame_rate: u32) -> TerminalOptions {
+        TerminalOptions {
+            frame_rate: frame_rate.max(1),
+            ..self
+        }
+    }
+    /// Whether to resize the browser to the terminal, keeping its device scale
+    /// factor. Otherwise frames are drawn at their size and cropped. Defaults to
+    /// true.
+    pub fn fit_browser(self, fit_browser: bool) -> TerminalOptions {
+        TerminalOptions {
+            fit_browser,
+            ..self
+        }
+    }
+}
+
+impl Default for TerminalOptions {
+    fn default() -> TerminalOptions {
+        TerminalOptions {
+            graphics: TerminalGraphics::Kitty,
+            frame_rate: 30,
+            fit_browser: true,
+        }
+    }
+}
+
+/// Shows a windowless browser in the controlling terminal, see the
+/// [module documentation](self).
+pub struct TerminalView {
+    compositor: Compositor,
+    listener: Option<FrameListenerId>,
+    shared: Arc<Shared>,
+    output_thread: Option<JoinHandle<()>>,
+    input_thread: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    compositor: Compositor,
+    browser: Browser,
+    options: TerminalOptions,
+    /// The terminal settings before switching to raw mode.
+    termios: libc::termios,
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    damage: Damage,
+    geometry: Geometry,
+    /// Whether the terminal reports mouse positions in pixels instead of cells.
+    pixel_mouse: bool,
+    stopped: bool,
+}
+
+impl TerminalView {
+    /// Take over the terminal and start showing `browser`.
+    pub fn start(
+        compositor: &Compositor,
+        browser: &Browser,
+        options: TerminalOptions,
+    ) -> io::Result<TerminalView> {
+        let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let mut output = tty.try_clone()?;
+        let input = tty.try_clone()?;
+        let fd = tty.as_raw_fd();
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(fd, &mut termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = termios;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Alternate screen, hidden cursor, all mouse motion in SGR format, in
+        // pixels if the terminal supports it, which the DECRQM query at the
+        // end tells.
+        let setup = b"\x1b[?1049h\x1b[?25l\x1b[2J\x1b[?1003h\x1b[?1006h\x1b[?1016h\x1b[?1016$p";
+        if let Err(err) = tty.write_all(setup) {
+            restore(&mut tty, &termios, options.graphics);
+            return Err(err);
+        }
+
+        let shared = Arc::new(Shared {
+            compositor: compositor.clone(),
+            browser: browser.clone(),
+            options,
+            termios,
+            state: Mutex::new(State::default()),
+            condvar: Condvar::new(),
+        });
+        let browser_id = browser.get_identifier();
+        let listener_shared = shared.clone();
+        let listener = compositor.add_frame_listener(move |browser, _, damage| {
+            if browser.get_identifier() == browser_id {
+                listener_shared.state.lock().damage.extend(damage);
+                listener_shared.condvar.notify_all();
+            }
+        });
+
+        let mut view = TerminalView {
+            compositor: compositor.clone(),
+            listener: Some(listener),
+            shared: shared.clone(),
+            output_thread: None,
+            input_thread: None,
+        };
+        let output_shared = shared.clone();
+        let spawned = std::thread::Builder::new()
+            .name("cef-terminal-output".to_owned())
+            .spawn(move || {
+                if let Err(err) = draw(&output_shared, &mut output) {
+                    log::warn!("Terminal: drawing failed: {}", err);
+                }
+                output_shared.stop();
+                restore(
+                    &mut output,
+                    &output_shared.termios,
+                    output_shared.options.graphics,
+                );
+            });
+        match spawned {
+            Ok(thread) => view.output_thread = Some(thread),
+            Err(err) => {
+                restore(&mut tty, &termios, shared.options.graphics);
+                return Err(err);
+            }
+        }
+        // From here on the output thread restores the terminal, also when
+        // `view` is dropped because the input thread couldn't be started.
+        view.input_thread = Some(
+            std::thread::Builder::new()
+                .name("cef-terminal-input".to_owned())
+                .spawn(move || {
+                    if let Err(err) = forward_input(&shared, input) {
+                        log::warn!("Terminal: reading input failed: {}", err);
+                    }
+                    shared.stop();
+                })?,
+        );
+        Ok(view)
+    }
+    /// Block until the user closes the view with Ctrl+], then restore the
+    /// terminal. Must not be called on the UI thread, which has to keep running
+    /// for the browser to paint.
+    pub fn wait(mut self) {
+        {
+            let mut state = self.shared.state.lock();
+            while !state.stopped {
+                self.shared.condvar.wait(&mut state);
+            }
+        }
+        self.join();
+    }
+    /// Close the view and restore the terminal.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            self.compositor.remove_frame_listener(listener);
+        }
+        self.shared.stop();
+        for thread in self
+            .output_thread
+            .take()
+            .into_iter()
+            .chain(self.input_thread.take())
+        {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for TerminalView {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+impl Shared {
+    fn stop(&self) {
+        self.state.lock().stopped = true;
+        self.condvar.notify_all();
+    }
+}
+
+fn restore(tty: &mut File, termios: &libc::termios, graphics: TerminalGraphics) {
+    if graphics == TerminalGraphics::Kitty {
+        let _ = write!(tty, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID);
+    }
+    let _ = tty.write_all(b"\x1b[?1016l\x1b[?1006l\x1b[?1003l\x1b[?25h\x1b[?1049l");
+    unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSADRAIN, termios) };
+}
+
+/// The size of the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Geometry {
+    columns: i32,
+    rows: i32,
+    cell_width: i32,
+    cell_height: i32,
+}
+
+impl Geometry {
+    fn query(tty: &File) -> io::Result<Geometry> {
+        let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+        if unsafe { libc::ioctl(tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let columns = (size.ws_col as i32).max(1);
+        let rows = (size.ws_row as i32).max(1);
+        // Some terminals don't report their size in pixels.
+        let (cell_width, cell_height) = match (
+            size.ws_xpixel as i32 / columns,
+            size.ws_ypixel as i32 / rows,
+        ) {
+            (width, height) if width > 0 && height > 0 => (width, height),
+            _ => (8, 16),
+        };
+        Ok(Geometry {
+            columns,
+            rows,
+            cell_width,
+            cell_height,
+        })
+    }
+    /// The area used for drawing in pixels, which leaves out the last row.
+    fn area(&self) -> Rect {
+        Rect {
+            x: 0,
+            y: 0,
+            width: self.columns * self.cell_width,
+            height: (self.rows - 1).max(1) * self.cell_height,
+        }
+    }
+    /// Extends `rect` to whole cells, since images can only be placed at cells.
+    fn align(&self, rect: Rect) -> Rect {
+        let x = rect.x.div_euclid(self.cell_width) * self.cell_width;
+        let y = rect.y.div_euclid(self.cell_height) * self.cell_height;
+        let right = rect.x + rect.width + self.cell_width - 1;
+        let bottom = rect.y + rect.height + self.cell_height - 1;
+        Rect {
+            x,
+            y,
+            width: right.div_euclid(self.cell_width) * self.cell_width - x,
+            height: bottom.div_euclid(self.cell_height) * self.cell_height - y,
+        }
+    }
+}
+
+/// Draws damaged areas until the view is stopped.
+fn draw(shared: &Shared, tty: &mut File) -> io::Result<()> {
+    let browser_id = shared.browser.get_identifier();
+    let interval = Duration::from_secs(1) / shared.options.frame_rate;
+    let mut geometry = None;
+    let mut shown = None;
+    let mut redraw = true;
+    let mut next_update = Instant::now();
+    let mut output = Vec::new();
+    loop {
+        let damage = {
+            let mut state = shared.state.lock();
+            let poll = Instant::now() + RESIZE_POLL_INTERVAL;
+            loop {
+                if state.stopped {
+                    return Ok(());
+                }
+                let now = Instant::now();
+                if now >= poll || (!state.damage.is_empty() && now >= next_update) {
+                    break;
+                }
+                let until = if state.damage.is_empty() {
+                    poll
+                } else {
+                    next_update.min(poll)
+                };
+                shared.condvar.wait_until(&mut state, until);
+            }
+            std::mem::take(&mut state.damage)
+        };
+
+        let current = Geometry::query(tty)?;
+        if geometry != Some(current) {
+            geometry = Some(current);
+            redraw = true;
+            shared.state.lock().geometry = current;
+            if shared.options.fit_browser {
+                let area = current.area();
+                let scale = shared.compositor.viewport(browser_id).device_scale_factor;
+                let viewport = Viewport::new(
+                    (area.width as f32 / scale) as i32,
+                    (area.height as f32 / scale) as i32,
+                )
+                .device_scale_factor(scale);
+                resize(&shared.compositor, &shared.browser, viewport);
+            }
+        }
+
+        // Copy the damaged areas, so the compositor isn't blocked while they
+        // are encoded.
+        let tiles = shared.compositor.with_frame(browser_id, |frame| {
+            let size = (frame.width(), frame.height());
+            if shown != Some(size) {
+                shown = Some(size);
+                redraw = true;
+            }
+            let bounds = Rect {
+                x: 0,
+                y: 0,
+                width: frame.width(),
+                height: frame.height(),
+            };
+            let bounds = intersect(bounds, current.area()).unwrap_or(bounds);
+            let rects: Vec<Rect> = if redraw {
+                vec![bounds]
+            } else {
+                damage
+                    .rects()
+                    .iter()
+                    .filter_map(|&rect| intersect(current.align(rect), bounds))
+                    .collect()
+            };
+            rects
+                .into_iter()
+                .filter_map(|rect| frame.crop(rect).map(|tile| (rect, tile)))
+                .collect::<Vec<_>>()
+        });
+        let tiles = match tiles {
+            Some(tiles) if !tiles.is_empty() => tiles,
+            _ => continue,
+        };
+
+        output.clear();
+        // Synchronized output, to avoid tearing.
+        output.extend_from_slice(b"\x1b[?2026h");
+        if redraw {
+            if shared.options.graphics == TerminalGraphics::Kitty {
+                write!(output, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID)?;
+            }
+            output.extend_from_slice(b"\x1b[2J");
+        }
+        for (rect, tile) in &tiles {
+            match shared.options.graphics {
+                TerminalGraphics::Kitty => put_kitty(&mut output, *rect, tile, redraw),
+                TerminalGraphics::Sixel => put_sixel(&mut output, current, *rect, tile),
+            }
+        }
+        output.extend_from_slice(b"\x1b[?2026l");
+        tty.write_all(&output)?;
+        tty.flush()?;
+        redraw = false;
+        next_update = Instant::now() + interval;
+    }
+}
+
+/// Returns the colors of `frame` as 8 bit RGB. Frames have premultiplied
+/// alpha, so transparent areas become black.
+fn rgb(frame: &ComposedFrame) -> Vec<u8> {
+    let stride = 3 * frame.width() as usize;
+    let mut rgb = vec![0; stride * frame.height() as usize];
+    convert(frame.into(), &mut rgb, stride, TargetFormat::Rgb);
+    rgb
+}
+
+/// Writes `tile` with the kitty graphics protocol. A redraw transmits a new
+/// image at the upper-left corner, later tiles replace parts of it.
+fn put_kitty(output: &mut Vec<u8>, rect: Rect, tile: &ComposedFrame, redraw: bool) {
+    let control = if redraw {
+        format!(
+            "a=T,i={},f=24,s={},v={},C=1,q=2",
+            KITTY_IMAGE_ID, rect.width, rect.height
+        )
+    } else {
+        format!(
+            "a=f,r=1,i={},f=24,x={},y={},s={},v={},q=2",
+            KITTY_IMAGE_ID, rect.x, rect.y, rect.width, rect.height
+        )
+    };
+    if redraw {
+        output.extend_from_slice(b"\x1b[H");
+    }
+    let mut payload = String::new();
+    base64(&rgb(tile), &mut payload);
+    let chunks = payload.as_bytes().chunks(KITTY_CHUNK_SIZE);
+    let count = chunks.len();
+    for (index, chunk) in chunks.enumerate() {
+        let more = (index + 1 < count) as u8;
+        output.extend_from_slice(b"\x1b_G");
+        if index == 0 {
+            output.extend_from_slice(control.as_bytes());
+            output.push(b',');
+        }
+        output.extend_from_slice(format!("m={};", more).as_bytes());
+        output.extend_from_slice(chunk);
+        output.extend_from_slice(b"\x1b\\");
+    }
+}
+
+/// Writes `tile` as a sixel image at the cell of the upper-left corner of
+/// `rect`. Colors are reduced to a fixed palette with ordered dithering, based
+/// on the position in the frame so the tiles match.
+fn put_sixel(output: &mut Vec<u8>, geometry: Geometry, rect: Rect, tile: &ComposedFrame) {
+    let width = rect.width as usize;
+    let rgb = rgb(tile);
+    let colors: Vec<u8> = rgb
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(index, pixel)| {
+            let x = rect.x as usize + index % width;
+            let y = rect.y as usize + index / width;
+            let threshold = 2 * BAYER[y % 4][x % 4] + 1;
+            let level = |channel: usize| {
+                let levels = SIXEL_LEVELS[channel];
+                (pixel[channel] as u32 * (levels - 1) * 32 + threshold * 255) / (255 * 32)
+            };
+            (level(0) * 42 + level(1) * 6 + level(2)) as u8
+        })
+        .collect();
+
+    let _ = write!(
+        output,
+        "\x1b[{};{}H\x1bP9;1q\"1;1;{};{}",
+        rect.y / geometry.cell_height + 1,
+        rect.x / geometry.cell_width + 1,
+        rect.width,
+        rect.height
+    );
+    let mut used = [false; 252];
+    for &color in &colors {
+        used[color as usize] = true;
+    }
+    for (color, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let (red, green, blue) = (color / 42, color / 6 % 7, color % 6);
+        let _ = write!(
+            output,
+            "#{};2;{};{};{}",
+            color,
+            red * 20,
+            green * 100 / 6,
+            blue * 20
+        );
+    }
+    for band in colors.chunks(width * 6) {
+        let mut used = [false; 252];
+        for &color in band {
+            used[color as usize] = true;
+        }
+        let mut first = true;
+        for (color, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            if !first {
+                output.push(b'$');
+            }
+            first = false;
+            let _ = write!(output, "#{}", color);
+            let mut run = (0, 0);
+            for x in 0..width {
+                let mut sixel = 0;
+                for (row, line) in band.chunks(width).enumerate() {
+                    if line[x] as usize == color {
+                        sixel |= 1 << row;
+                    }
+                }
+                if sixel == run.0 {
+                    run.1 += 1;
+                } else {
+                    put_sixel_run(output, run);
+                    run = (sixel, 1);
+                }
+            }
+            // Trailing empty sixels can be left out.
+            if run.0 != 0 {
+                put_sixel_run(output, run);
+            }
+        }
+        output.push(b'-');
+    }
+    output.extend_from_slice(b"\x1b\\");
+}
+
+fn put_sixel_run(output: &mut Vec<u8>, (sixel, count): (u8, usize)) {
+    let sixel = 63 + sixel;
+    match count {
+        0 => {}
+        1..=3 => output.resize(output.len() + count, sixel),
+        _ => {
+            let _ = write!(output, "!{}", count);
+            output.push(sixel);
+        }
+    }
+}
+
+/// Input decoded from the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TerminalInput {
+    Key {
+        code: WindowsKeyCode,
+        modifiers: EventFlags,
+    },
+    Char {
+        character: char,
+        modifiers: EventFlags,
+    },
+    /// An SGR mouse report with the button code, 1-based cell or 0-based pixel
+    /// coordinates and whether it's a press.
+    Mouse {
+        button: u32,
+        x: i32,
+        y: i32,
+        pressed: bool,
+    },
+    /// The answer to the query whether pixel mouse positions are supported.
+    PixelMouse(bool),
+    Quit,
+}
+
+fn forward_input(shared: &Shared, mut tty: File) -> io::Result<()> {
+    let browser_id = shared.browser.get_identifier();
+    let host = shared.browser.get_host();
+    host.send_focus_event(true);
+    let mut input = InputTranslator::new();
+    let mut buttons = [false; 3];
+    let mut pending = Vec::new();
+    let mut buffer = [0; 4096];
+    let result = loop {
+        if shared.state.lock().stopped {
+            break Ok(());
+        }
+        let mut poll = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = if pending.is_empty() {
+            100
+        } else {
+            ESCAPE_TIMEOUT
+        };
+        match unsafe { libc::poll(&mut poll, 1, timeout) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                break Err(err);
+            }
+            0 => {
+                // Nothing followed, so a pending escape byte was the Escape key.
+                if pending.first() == Some(&0x1b) {
+                    pending.remove(0);
+                    let escape = TerminalInput::Key {
+                        code: WindowsKeyCode::Escape,
+                        modifiers: EventFlags::empty(),
+                    };
+                    if !dispatch(shared, &host, &mut input, &mut buttons, escape) {
+                        break Ok(());
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        let len = tty.read(&mut buffer)?;
+        if len == 0 {
+            break Ok(());
+        }
+        pending.extend_from_slice(&buffer[..len]);
+        let mut consumed = 0;
+        let mut quit = false;
+        while let Some((event, len)) = parse_input(&pending[consumed..]) {
+            consumed += len;
+            if let Some(event) = event {
+                if !dispatch(shared, &host, &mut input, &mut buttons, event) {
+                    quit = true;
+                    break;
+                }
+            }
+        }
+        pending.drain(..consumed);
+        if quit {
+            break Ok(());
+        }
+    };
+    input.leave(&host);
+    result
+}
+
+/// Sends an input event to the browser. Returns false if the user asked to
+/// close the view.
+fn dispatch(
+    shared: &Shared,
+    host: &BrowserHost,
+    input: &mut InputTranslator,
+    buttons: &mut [bool; 3],
+    event: TerminalInput,
+) -> bool {
+    match event {
+        TerminalInput::Key { code, modifiers } => {
+            input.key(host, code, None, true, modifiers);
+            input.key(host, code, None, false, modifiers);
+        }
+        TerminalInput::Char {
+            character,
+            modifiers,
+        } => input.type_char(host, character, modifiers),
+        TerminalInput::Mouse {
+            button,
+            x,
+            y,
+            pressed,
+        } => {
+            let (geometry, pixel_mouse) = {
+                let state = shared.state.lock();
+                (state.geometry, state.pixel_mouse)
+            };
+            let (x, y) = if pixel_mouse {
+                (x, y)
+            } else {
+                (
+                    (x - 1) * geometry.cell_width + geometry.cell_width / 2,
+                    (y - 1) * geometry.cell_height + geometry.cell_height / 2,
+                )
+            };
+            let scale = shared
+                .compositor
+                .viewport(shared.browser.get_identifier())
+                .device_scale_factor;
+            let (x, y) = ((x as f32 / scale) as i32, (y as f32 / scale) as i32);
+            if button & 64 != 0 {
+                if pressed {
+                    let (delta_x, delta_y) = match button & 3 {
+                        0 => (0, WHEEL_DELTA),
+                        1 => (0, -WHEEL_DELTA),
+                        2 => (WHEEL_DELTA, 0),
+                        _ => (-WHEEL_DELTA, 0),
+                    };
+                    input.wheel(host, x, y, delta_x, delta_y);
+                }
+            } else {
+                let index = (button & 3) as usize;
+                if button & 32 == 0 && index < 3 {
+                    buttons[index] = pressed;
+                }
+                input.pointer(host, x, y, *buttons);
+            }
+        }
+        TerminalInput::PixelMouse(enabled) => shared.state.lock().pixel_mouse = enabled,
+        TerminalInput::Quit => return false,
+    }
+    true
+}
+
+/// Parses the event at the start of `bytes`. Returns the event, if the bytes
+/// are a known one, and the number of bytes used, or None if the sequence is
+/// incomplete.
+fn parse_input(bytes: &[u8]) -> Option<(Option<TerminalInput>, usize)> {
+    let key = |code: WindowsKeyCode| {
+        Some(TerminalInput::Key {
+            code,
+            modifiers: EventFlags::empty(),
+        })
+    };
+    let char = |character: char, modifiers: EventFlags| {
+        Some(TerminalInput::Char {
+            character,
+            modifiers,
+        })
+    };
+    let first = *bytes.first()?;
+    match first {
+        0x1b => match bytes.get(1)? {
+            b'[' => parse_csi(&bytes[2..]).map(|(event, len)| (event, len + 2)),
+            b'O' => {
+                let code = match bytes.get(2)? {
+                    b'A' => WindowsKeyCode::Up,
+                    b'B' => WindowsKeyCode::Down,
+                    b'C' => WindowsKeyCode::Right,
+                    b'D' => WindowsKeyCode::Left,
+                    b'H' => WindowsKeyCode::Home,
+                    b'F' => WindowsKeyCode::End,
+                    b'P' => WindowsKeyCode::F1,
+                    b'Q' => WindowsKeyCode::F2,
+                    b'R' => WindowsKeyCode::F3,
+                    b'S' => WindowsKeyCode::F4,
+                    _ => return Some((None, 3)),
+                };
+                Some((key(code), 3))
+            }
+            0x1b => Some((key(WindowsKeyCode::Escape), 1)),
+            // Alt sends an escape byte before the key.
+            _ => {
+                let (event, len) = parse_input(&bytes[1..])?;
+                let event = match event {
+                    Some(TerminalInput::Key { code, modifiers }) => Some(TerminalInput::Key {
+                        code,
+                        modifiers: modifiers | EventFlags::ALT_DOWN,
+                    }),
+                    Some(TerminalInput::Char {
+                        character,
+                        modifiers,
+                    }) => char(character, modifiers | EventFlags::ALT_DOWN),
+                    event => event,
+                };
+                Some((event, len + 1))
+            }
+        },
+        QUIT_BYTE => Some((Some(TerminalInput::Quit), 1)),
+        b'\r' | b'\n' => Some((char('\r', EventFlags::empty()), 1)),
+        b'\t' => Some((char('\t', EventFlags::empty()), 1)),
+        0x08 | 0x7f => Some((key(WindowsKeyCode::Back), 1)),
+        0x00 => Some((char(' ', EventFlags::CONTROL_DOWN), 1)),
+        0x01..=0x1a => Some((
+            char((b'a' + first - 1) as char, EventFlags::CONTROL_DOWN),
+            1,
+        )),
+        0x1c..=0x1f => Some((None, 1)),
+        _ => {
+            let len = match first {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Some((None, 1)),
+            };
+            let sequence = bytes.get(..len)?;
+            match std::str::from_utf8(sequence)
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(character) => Some((char(character, EventFlags::empty()), len)),
+                None => Some((None, 1)),
+            }
+        }
+    }
+}
+
+/// Parses a control sequence after the "ESC [" introducer.
+fn parse_csi(bytes: &[u8]) -> Option<(Option<TerminalInput>, usize)> {
+    let end = match bytes.iter().position(|byte| !(0x20..=0x3f).contains(byte)) {
+        Some(end) => end,
+        // Give up on overlong garbage.
+        None if bytes.len() > 64 => return Some((None, bytes.len())),
+        None => return None,
+    };
+    let len = end + 1;
+    let params = std::str::from_utf8(&bytes[..end]).unwrap_or("");
+    let numbers = |params: &str| -> Vec<i32> {
+        params
+            .split(';')
+            .map(|param| param.parse().unwrap_or(0))
+            .collect()
+    };
+    let final_byte = bytes[end];
+    if let (Some(params), b'M') | (Some(params), b'm') = (params.strip_prefix('<'), final_byte) {
+        let numbers = numbers(params);
+        if numbers.len() != 3 {
+            return Some((None, len));
+        }
+        let event = TerminalInput::Mouse {
+            button: numbers[0] as u32,
+            x: numbers[1],
+            y: numbers[2],
+            pressed: final_byte == b'M',
+        };
+        return Some((Some(event), len));
+    }
+    if let (Some(params), b'y') = (params.strip_prefix('?'), final_byte) {
+        // DECRQM answer, the mode is set (1) or permanently set (3).
+        let numbers = numbers(params.trim_end_matches('$'));
+        let event = match numbers.as_slice() {
+            [1016, state] => Some(TerminalInput::PixelMouse(*state == 1 || *state == 3)),
+            _ => None,
+        };
+        return Some((event, len));
+    }
+    let numbers = numbers(params);
+    // The second parameter is 1 plus a bit set of shift, alt and control.
+    let modifier_bits = numbers.get(1).map_or(0, |bits| (bits - 1).max(0));
+    let mut modifiers = EventFlags::empty();
+    for &(bit, flag) in &[
+        (1, EventFlags::SHIFT_DOWN),
+        (2, EventFlags::ALT_DOWN),
+        (4, EventFlags::CONTROL_DOWN),
+        (8, EventFlags::COMMAND_DOWN),
+    ] {
+        if modifier_bits & bit != 0 {
+            modifiers |= flag;
+        }
+    }
+    let code = match final_byte {
+        b'A' => WindowsKeyCode::Up,
+        b'B' => WindowsKeyCode::Down,
+        b'C' => WindowsKeyCode::Right,
+        b'D' => WindowsKeyCode::Left,
+        b'H' => WindowsKeyCode::Home,
+        b'F' => WindowsKeyCode::End,
+        b'P' => WindowsKeyCode::F1,
+        b'Q' => WindowsKeyCode::F2,
+        b'R' => WindowsKeyCode::F3,
+        b'S' => WindowsKeyCode::F4,
+        b'Z' => {
+            modifiers |= EventFlags::SHIFT_DOWN;
+            WindowsKeyCode::Tab
+        }
+        b'~' => match numbers[0] {
+            1 | 7 => WindowsKeyCode::Home,
+            2 => WindowsKeyCode::Insert,
+            3 => WindowsKeyCode::Delete,
+            4 | 8 => WindowsKeyCode::End,
+            5 => WindowsKeyCode::Prior,
+            6 => WindowsKeyCode::Next,
+            11..=15 => WindowsKeyCode(WindowsKeyCode::F1.0 + numbers[0] - 11),
+            17..=21 => WindowsKeyCode(WindowsKeyCode::F6.0 + numbers[0] - 17),
+            23 | 24 => WindowsKeyCode(WindowsKeyCode::F11.0 + numbers[0] - 23),
+            _ => return Some((None, len)),
+        },
+        _ => return Some((None, len)),
+    };
+    Some((Some(TerminalInput::Key { code, modifiers }), len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osr::PixelFormat;
+
+    const CELLS: Geometry = Geometry {
+        columns: 80,
+        rows: 24,
+        cell_width: 8,
+        cell_height: 16,
+    };
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn parsed(bytes: &[u8]) -> (Option<TerminalInput>, usize) {
+        parse_input(bytes).unwrap()
+    }
+
+    fn key(code: WindowsKeyCode, modifiers: EventFlags) -> Option<TerminalInput> {
+        Some(TerminalInput::Key { code, modifiers })
+    }
+
+    fn char(character: char, modifiers: EventFlags) -> Option<TerminalInput> {
+        Some(TerminalInput::Char {
+            character,
+            modifiers,
+        })
+    }
+
+    fn mouse(button: u32, x: i32, y: i32, pressed: bool) -> Option<TerminalInput> {
+        Some(TerminalInput::Mouse {
+            button,
+            x,
+            y,
+            pressed,
+        })
+    }
+
+    /// A tile of `width` x `height` pixels with the given BGRA colors.
+    fn tile(width: i32, height: i32, pixels: &[[u8; 4]]) -> ComposedFrame {
+        let data = pixels.iter().flatten().copied().collect();
+        ComposedFrame::from_data(width, height, PixelFormat::Bgra, 1, data)
+    }
+
+    fn sixel(geometry: Geometry, rect: Rect, tile: &ComposedFrame) -> String {
+        let mut output = Vec::new();
+        put_sixel(&mut output, geometry, rect, tile);
+        String::from_utf8(output).unwrap()
+    }
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    #[test]
+    fn characters() {
+        let none = EventFlags::empty();
+        assert_eq!(parsed(b"ab"), (char('a', none), 1));
+        assert_eq!(parsed("é!".as_bytes()), (char('é', none), 2));
+        assert_eq!(parsed("€".as_bytes()), (char('€', none), 3));
+        assert_eq!(parse_input(&"€".as_bytes()[..2]), None);
+        assert_eq!(parsed(b"\xc3("), (None, 1));
+        assert_eq!(parsed(b"\xff"), (None, 1));
+        assert_eq!(parse_input(b""), None);
+    }
+
+    #[test]
+    fn control_characters() {
+        let none = EventFlags::empty();
+        let control = EventFlags::CONTROL_DOWN;
+        assert_eq!(parsed(b"\r"), (char('\r', none), 1));
+        assert_eq!(parsed(b"\n"), (char('\r', none), 1));
+        assert_eq!(parsed(b"\t"), (char('\t', none), 1));
+        assert_eq!(parsed(b"\x7f"), (key(WindowsKeyCode::Back, none), 1));
+        assert_eq!(parsed(b"\x08"), (key(WindowsKeyCode::Back, none), 1));
+        assert_eq!(parsed(b"\x00"), (char(' ', control), 1));
+        assert_eq!(parsed(b"\x01"), (char('a', control), 1));
+        assert_eq!(parsed(b"\x03"), (char('c', control), 1));
+        assert_eq!(parsed(b"\x1a"), (char('z', control), 1));
+        assert_eq!(parsed(&[QUIT_BYTE]), (Some(TerminalInput::Quit), 1));
+        assert_eq!(parsed(b"\x1c"), (None, 1));
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let none = EventFlags::empty();
+        let alt = EventFlags::ALT_DOWN;
+        // A lone escape byte may be the start of a sequence.
+        assert_eq!(parse_input(b"\x1b"), None);
+        assert_eq!(
+            parsed(b"\x1b\x1b[A"),
+            (key(WindowsKeyCode::Escape, none), 1)
+        );
+        assert_eq!(parsed(b"\x1bx"), (char('x', alt), 2));
+        assert_eq!(parsed(b"\x1b\x7f"), (key(WindowsKeyCode::Back, alt), 2));
+        assert_eq!(parsed(b"\x1b\x1c"), (None, 2));
+        assert_eq!(parsed(b"\x1bOA"), (key(WindowsKeyCode::Up, none), 3));
+        assert_eq!(parsed(b"\x1bOF"), (key(WindowsKeyCode::End, none), 3));
+        assert_eq!(parsed(b"\x1bOS"), (key(WindowsKeyCode::F4, none), 3));
+        assert_eq!(parsed(b"\x1bOx"), (None, 3));
+        assert_eq!(parse_input(b"\x1bO"), None);
+    }
+
+    #[test]
+    fn csi_keys() {
+        let none = EventFlags::empty();
+        assert_eq!(parsed(b"\x1b[Ax"), (key(WindowsKeyCode::Up, none), 3));
+        assert_eq!(parsed(b"\x1b[H"), (key(WindowsKeyCode::Home, none), 3));
+        assert_eq!(
+            parsed(b"\x1b[Z"),
+            (key(WindowsKeyCode::Tab, EventFlags::SHIFT_DOWN), 3)
+        );
+        assert_eq!(parsed(b"\x1b[X"), (None, 3));
+        assert_eq!(parse_input(b"\x1b[1;5"), None);
+        // Overlong garbage is dropped.
+        let mut garbage = b"\x1b[".to_vec();
+        garbage.resize(67, b'0');
+        assert_eq!(parsed(&garbage), (None, 67));
+    }
+
+    #[test]
+    fn csi_modifiers() {
+        let shift = EventFlags::SHIFT_DOWN;
+        let alt = EventFlags::ALT_DOWN;
+        let control = EventFlags::CONTROL_DOWN;
+        assert_eq!(parsed(b"\x1b[1;2A"), (key(WindowsKeyCode::Up, shift), 6));
+        assert_eq!(
+            parsed(b"\x1b[1;5C"),
+            (key(WindowsKeyCode::Right, control), 6)
+        );
+        assert_eq!(
+            parsed(b"\x1b[1;4D"),
+            (key(WindowsKeyCode::Left, shift | alt), 6)
+        );
+        assert_eq!(
+            parsed(b"\x1b[1;8P"),
+            (key(WindowsKeyCode::F1, shift | alt | control), 6)
+        );
+        assert_eq!(
+            parsed(b"\x1b[1;9F"),
+            (key(WindowsKeyCode::End, EventFlags::COMMAND_DOWN), 6)
+        );
+        assert_eq!(
+            parsed(b"\x1b[1;1B"),
+            (key(WindowsKeyCode::Down, EventFlags::empty()), 6)
+        );
+        assert_eq!(parsed(b"\x1b[1;2Z"), (key(WindowsKeyCode::Tab, shift), 6));
+    }
+
+    #[test]
+    fn csi_tilde_keys() {
+        let none = EventFlags::empty();
+        let keys = [
+            (&b"\x1b[1~"[..], WindowsKeyCode::Home),
+            (b"\x1b[2~", WindowsKeyCode::Insert),
+            (b"\x1b[3~", WindowsKeyCode::Delete),
+            (b"\x1b[4~", WindowsKeyCode::End),
+            (b"\x1b[5~", WindowsKeyCode::Prior),
+            (b"\x1b[6~", WindowsKeyCode::Next),
+            (b"\x1b[7~", WindowsKeyCode::Home),
+            (b"\x1b[8~", WindowsKeyCode::End),
+            (b"\x1b[11~", WindowsKeyCode::F1),
+            (b"\x1b[15~", WindowsKeyCode::F5),
+            (b"\x1b[17~", WindowsKeyCode::F6),
+            (b"\x1b[21~", WindowsKeyCode::F10),
+            (b"\x1b[23~", WindowsKeyCode::F11),
+            (b"\x1b[24~", WindowsKeyCode::F12),
+        ];
+        for &(bytes, code) in &keys {
+            assert_eq!(parsed(bytes), (key(code, none), bytes.len()), "{:?}", bytes);
+        }
+        assert_eq!(
+            parsed(b"\x1b[3;5~"),
+            (key(WindowsKeyCode::Delete, EventFlags::CONTROL_DOWN), 6)
+        );
+        // F5 and F6 aren't next to each other, and neither are F10 and F11.
+        assert_eq!(parsed(b"\x1b[16~"), (None, 5));
+        assert_eq!(parsed(b"\x1b[22~"), (None, 5));
+        // Start of a bracketed paste.
+        assert_eq!(parsed(b"\x1b[200~"), (None, 6));
+    }
+
+    #[test]
+    fn sgr_mouse() {
+        assert_eq!(parsed(b"\x1b[<0;10;5M"), (mouse(0, 10, 5, true), 10));
+        assert_eq!(parsed(b"\x1b[<0;10;5m"), (mouse(0, 10, 5, false), 10));
+        assert_eq!(
+            parsed(b"\x1b[<35;1200;640M"),
+            (mouse(35, 1200, 640, true), 15)
+        );
+        assert_eq!(parsed(b"\x1b[<65;1;1M"), (mouse(65, 1, 1, true), 10));
+        assert_eq!(parse_input(b"\x1b[<0;10;5"), None);
+        assert_eq!(parsed(b"\x1b[<0;10M"), (None, 8));
+        assert_eq!(parse_csi(b"<2;3;4Mx"), Some((mouse(2, 3, 4, true), 7)));
+    }
+
+    #[test]
+    fn decrqm_answer() {
+        let pixel_mouse = |enabled| Some(TerminalInput::PixelMouse(enabled));
+        assert_eq!(parsed(b"\x1b[?1016;1$y"), (pixel_mouse(true), 11));
+        assert_eq!(parsed(b"\x1b[?1016;2$y"), (pixel_mouse(false), 11));
+        assert_eq!(parsed(b"\x1b[?1016;3$y"), (pixel_mouse(true), 11));
+        // Not recognized.
+        assert_eq!(parsed(b"\x1b[?1016;0$y"), (pixel_mouse(false), 11));
+        assert_eq!(parsed(b"\x1b[?2026;1$y"), (None, 11));
+    }
+
+    #[test]
+    fn geometry_area() {
+        assert_eq!(CELLS.area(), rect(0, 0, 640, 368));
+        let single_row = Geometry { rows: 1, ..CELLS };
+        assert_eq!(single_row.area(), rect(0, 0, 640, 16));
+    }
+
+    #[test]
+    fn geometry_align() {
+        assert_eq!(CELLS.align(rect(8, 16, 8, 16)), rect(8, 16, 8, 16));
+        assert_eq!(CELLS.align(rect(9, 17, 2, 2)), rect(8, 16, 8, 16));
+        assert_eq!(CELLS.align(rect(7, 15, 2, 2)), rect(0, 0, 16, 32));
+        assert_eq!(CELLS.align(rect(1, 1, 100, 1)), rect(0, 0, 104, 16));
+        assert_eq!(CELLS.align(rect(-3, -1, 2, 2)), rect(-8, -16, 8, 32));
+        assert_eq!(CELLS.align(rect(8, 16, 0, 0)), rect(8, 16, 0, 0));
+    }
+
+    #[test]
+    fn sixel_image() {
+        // Placed at the cell of the upper-left corner, with a palette of the
+        // used colors.
+        assert_eq!(
+            sixel(CELLS, rect(16, 32, 2, 1), &tile(2, 1, &[WHITE, WHITE])),
+            "\x1b[3;3H\x1bP9;1q\"1;1;2;1#251;2;100;100;100#251@@-\x1b\\"
+        );
+        // One color per pass, the second row is the second bit.
+        assert_eq!(
+            sixel(CELLS, rect(0, 0, 1, 2), &tile(1, 2, &[WHITE, BLACK])),
+            "\x1b[1;1H\x1bP9;1q\"1;1;1;2#0;2;0;0;0#251;2;100;100;100#0A$#251@-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_bands() {
+        // Six rows per band.
+        assert_eq!(
+            sixel(CELLS, rect(0, 0, 1, 7), &tile(1, 7, &[WHITE; 7])),
+            "\x1b[1;1H\x1bP9;1q\"1;1;1;7#251;2;100;100;100#251~-#251@-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_runs() {
+        let row = [WHITE, BLACK, BLACK, WHITE, WHITE, WHITE, WHITE];
+        assert_eq!(
+            sixel(CELLS, rect(0, 0, 7, 1), &tile(7, 1, &row)),
+            "\x1b[1;1H\x1bP9;1q\"1;1;7;1#0;2;0;0;0#251;2;100;100;100#0?@@$#251@??!4@-\x1b\\"
+        );
+        // Trailing empty sixels are left out.
+        assert_eq!(
+            sixel(CELLS, rect(0, 0, 3, 1), &tile(3, 1, &[BLACK, WHITE, WHITE])),
+            "\x1b[1;1H\x1bP9;1q\"1;1;3;1#0;2;0;0;0#251;2;100;100;100#0@$#251?@@-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_dithering() {
+        // Mid gray is dithered into two levels of each channel.
+        let gray = tile(4, 4, &[[128, 128, 128, 255]; 16]);
+        let output = sixel(CELLS, rect(0, 0, 4, 4), &gray);
+        let palette: Vec<&str> = output
+            .split('#')
+            .skip(1)
+            .take_while(|color| color.contains(";2;"))
+            .collect();
+        assert!(palette.len() > 1, "{}", output);
+        // The pattern depends on the position in the frame, so tiles that are
+        // drawn separately match. It repeats every 4 pixels.
+        let shifted = sixel(CELLS, rect(8, 16, 4, 4), &gray);
+        assert_eq!(output[6..], shifted[6..]);
+        assert_ne!(output, sixel(CELLS, rect(1, 0, 4, 4), &gray));
+    }
+}
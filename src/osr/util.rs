@@ -0,0 +1,76 @@
+//! Helpers shared by the windowless rendering modules.
+
+use super::{Compositor, Viewport};
+use crate::browser::Browser;
+
+/// Changes the viewport of `browser` and tells CEF about it, so that it paints
+/// a frame of the new size.
+pub(crate) fn resize(compositor: &Compositor, browser: &Browser, viewport: Viewport) {
+    let browser_id = browser.get_identifier();
+    let previous = compositor.viewport(browser_id);
+    compositor.set_viewport(browser_id, viewport);
+    let host = browser.get_host();
+    if previous.device_scale_factor != viewport.device_scale_factor {
+        host.notify_screen_info_changed();
+    }
+    host.was_resized();
+}
+
+/// Appends the standard base64 encoding of `bytes` to `out`, with padding.
+pub(crate) fn base64(bytes: &[u8], out: &mut String) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for chunk in bytes.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(bytes: &[u8]) -> String {
+        let mut out = String::new();
+        base64(bytes, &mut out);
+        out
+    }
+
+    #[test]
+    fn base64_rfc4648_vectors() {
+        // RFC 4648, section 10.
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg==");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(b"foob"), "Zm9vYg==");
+        assert_eq!(encode(b"fooba"), "Zm9vYmE=");
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn base64_alphabet() {
+        // Uses the last two characters of the standard alphabet, not the URL
+        // safe ones.
+        assert_eq!(encode(&[0xfb, 0xff, 0xbf]), "+/+/");
+        assert_eq!(encode(&[0, 0x10, 0x83]), "ABCD");
+        assert_eq!(encode(&[0xff]), "/w==");
+    }
+
+    #[test]
+    fn base64_appends() {
+        let mut out = "data:,".to_owned();
+        base64(b"hi", &mut out);
+        assert_eq!(out, "data:,aGk=");
+    }
+}
@@ -0,0 +1,1045 @@
+//! A VNC server for windowless browsers.
+//!
+//! Enabled by the `vnc` feature. [VncServer] serves the frames painted into a
+//! [Compositor] over the RFB 3.8 protocol (RFC 6143), so any VNC client can
+//! show a headless browser and send it pointer and keyboard input. Only the
+//! changed regions of a frame are sent, with the raw encoding, and clients that
+//! support the DesktopSize pseudo-encoding are told when the browser is resized.
+//!
+//! There is no authentication and no encryption. Bind the server to a
+//! loopback address and use an SSH tunnel for remote access:
+//!
+//! ```ignore
+//!  let server = VncServer::start(&compositor, &browser, "127.0.0.1:5900", VncOptions::new().name("kiosk"))?;
+//! ```
+
+use super::{
+    compositor::intersect,
+    convert::swap_red_blue,
+    input::{key_code_for_char, InputTranslator, WHEEL_DELTA},
+    ComposedFrame, Compositor, Damage, FrameListenerId, PixelFormat,
+};
+use crate::{
+    browser::Browser,
+    browser_host::BrowserHost,
+    events::{EventFlags, WindowsKeyCode},
+    values::Rect,
+};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    io::{self, BufWriter, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+const ENCODING_RAW: i32 = 0;
+const ENCODING_DESKTOP_SIZE: i32 = -223;
+
+/// Options for [VncServer].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VncOptions {
+    name: String,
+}
+
+impl VncOptions {
+    pub fn new() -> VncOptions {
+        VncOptions::default()
+    }
+    /// The desktop name shown by clients. Defaults to "cef".
+    pub fn name(self, name: &str) -> VncOptions {
+        VncOptions {
+            name: name.to_owned(),
+        }
+    }
+}
+
+impl Default for VncOptions {
+    fn default() -> VncOptions {
+        VncOptions {
+            name: "cef".to_owned(),
+        }
+    }
+}
+
+/// Serves a windowless browser to VNC clients, see the
+/// [module documentation](self).
+pub struct VncServer {
+    compositor: Compositor,
+    listener: Option<FrameListenerId>,
+    local_addr: SocketAddr,
+    shared: Arc<ServerShared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct ServerShared {
+    compositor: Compositor,
+    browser: Browser,
+    name: String,
+    stopped: AtomicBool,
+    clients: Mutex<Vec<Arc<Client>>>,
+}
+
+struct Client {
+    socket: TcpStream,
+    state: Mutex<ClientState>,
+    condvar: Condvar,
+}
+
+struct ClientState {
+    request: Option<UpdateRequest>,
+    damage: Damage,
+    /// Whether the browser has painted a frame yet.
+    painted: bool,
+    format: ClientFormat,
+    desktop_size: bool,
+    closed: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UpdateRequest {
+    incremental: bool,
+    rect: Rect,
+}
+
+impl VncServer {
+    /// Start accepting clients on `addr`. Use port 0 to let the OS pick a free
+    /// port, see [VncServer::local_addr].
+    pub fn start<A: ToSocketAddrs>(
+        compositor: &Compositor,
+        browser: &Browser,
+        addr: A,
+        options: VncOptions,
+    ) -> io::Result<VncServer> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let shared = Arc::new(ServerShared {
+            compositor: compositor.clone(),
+            browser: browser.clone(),
+            name: options.name,
+            stopped: AtomicBool::new(false),
+            clients: Mutex::new(Vec::new()),
+        });
+
+        let browser_id = browser.get_identifier();
+        let listener_shared = shared.clone();
+        let frame_listener = compositor.add_frame_listener(move |browser, _, damage| {
+            if browser.get_identifier() == browser_id {
+                for client in listener_shared.clients.lock().iter() {
+                    let mut state = client.state.lock();
+                    state.painted = true;
+                    state.damage.extend(damage);
+                    client.condvar.notify_one();
+                }
+            }
+        });
+
+        let thread_shared = shared.clone();
+        let thread = std::thread::Builder::new()
+            .name("cef-vnc".to_owned())
+            .spawn(move || accept(&thread_shared, listener))?;
+        Ok(VncServer {
+            compositor: compositor.clone(),
+            listener: Some(frame_listener),
+            local_addr,
+            shared,
+            thread: Some(thread),
+        })
+    }
+    /// The address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+    /// Disconnect all clients and stop listening.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            self.compositor.remove_frame_listener(listener);
+        }
+        self.shared.stopped.store(true, Ordering::SeqCst);
+        // Wake up the accept call.
+        let _ = TcpStream::connect(self.local_addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        for client in self.shared.clients.lock().drain(..) {
+            let _ = client.socket.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for VncServer {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+fn accept(shared: &Arc<ServerShared>, listener: TcpListener) {
+    for socket in listener.incoming() {
+        if shared.stopped.load(Ordering::SeqCst) {
+            return;
+        }
+        let socket = match socket {
+            Ok(socket) => socket,
+            Err(err) => {
+                log::warn!("VNC: accepting a connection failed: {}", err);
+                continue;
+            }
+        };
+        let shared = shared.clone();
+        let spawned = std::thread::Builder::new()
+            .name("cef-vnc-client".to_owned())
+            .spawn(move || {
+                let peer = socket.peer_addr().ok();
+                if let Err(err) = serve(&shared, socket) {
+                    log::info!("VNC: client {:?} disconnected: {}", peer, err);
+                }
+            });
+        if let Err(err) = spawned {
+            log::warn!("VNC: starting a client thread failed: {}", err);
+        }
+    }
+}
+
+/// Runs the handshake and then handles the messages of a client until it
+/// disconnects. Framebuffer updates are sent by a second thread.
+fn serve(shared: &Arc<ServerShared>, mut socket: TcpStream) -> io::Result<()> {
+    socket.set_nodelay(true)?;
+    let browser_id = shared.browser.get_identifier();
+    handshake(&mut socket)?;
+    let frame_size = shared
+        .compositor
+        .with_frame(browser_id, |frame| (frame.width(), frame.height()));
+    let painted = frame_size.is_some();
+    let size = frame_size.unwrap_or_else(|| {
+        let size = shared.compositor.viewport(browser_id).pixel_size();
+        (size.width, size.height)
+    });
+    let size = (size.0.min(u16::MAX as i32), size.1.min(u16::MAX as i32));
+    let mut init = Vec::new();
+    init.extend_from_slice(&(size.0 as u16).to_be_bytes());
+    init.extend_from_slice(&(size.1 as u16).to_be_bytes());
+    init.extend_from_slice(&ClientFormat::default().encode());
+    init.extend_from_slice(&(shared.name.len() as u32).to_be_bytes());
+    init.extend_from_slice(shared.name.as_bytes());
+    socket.write_all(&init)?;
+
+    let client = Arc::new(Client {
+        socket: socket.try_clone()?,
+        state: Mutex::new(ClientState {
+            request: None,
+            damage: Damage::default(),
+            painted,
+            format: ClientFormat::default(),
+            desktop_size: false,
+            closed: false,
+        }),
+        condvar: Condvar::new(),
+    });
+    shared.clients.lock().push(client.clone());
+    // The first paint may have happened since the frame size was read.
+    if !painted && shared.compositor.with_frame(browser_id, |_| ()).is_some() {
+        client.state.lock().painted = true;
+    }
+    let writer_shared = shared.clone();
+    let writer_client = client.clone();
+    let writer = std::thread::Builder::new()
+        .name("cef-vnc-updates".to_owned())
+        .spawn(move || send_updates(&writer_shared, &writer_client, size))?;
+
+    let host = shared.browser.get_host();
+    host.send_focus_event(true);
+    let mut input = InputTranslator::new();
+    let result = handle_messages(shared, &client, &mut socket, &host, &mut input);
+
+    input.leave(&host);
+    client.state.lock().closed = true;
+    client.condvar.notify_one();
+    let _ = client.socket.shutdown(Shutdown::Both);
+    shared
+        .clients
+        .lock()
+        .retain(|other| !Arc::ptr_eq(other, &client));
+    let _ = writer.join();
+    match result {
+        Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(()),
+        result => result,
+    }
+}
+
+fn handshake(socket: &mut (impl Read + Write)) -> io::Result<()> {
+    socket.write_all(b"RFB 003.008\n")?;
+    let mut version = [0; 12];
+    socket.read_exact(&mut version)?;
+    let minor = match &version {
+        b"RFB 003.003\n" => 3,
+        b"RFB 003.007\n" => 7,
+        _ if version.starts_with(b"RFB 003.") => 8,
+        _ => return Err(protocol_error("unsupported protocol version")),
+    };
+    // Only the "None" security type is supported.
+    if minor == 3 {
+        socket.write_all(&1u32.to_be_bytes())?;
+    } else {
+        socket.write_all(&[1, 1])?;
+        let mut security_type = [0];
+        socket.read_exact(&mut security_type)?;
+        if security_type[0] != 1 {
+            return Err(protocol_error("unsupported security type"));
+        }
+        if minor == 8 {
+            socket.write_all(&0u32.to_be_bytes())?;
+        }
+    }
+    // ClientInit, whether to share the desktop, which is always the case.
+    let mut shared_flag = [0];
+    socket.read_exact(&mut shared_flag)
+}
+
+fn handle_messages(
+    shared: &ServerShared,
+    client: &Client,
+    socket: &mut TcpStream,
+    host: &BrowserHost,
+    input: &mut InputTranslator,
+) -> io::Result<()> {
+    let browser_id = shared.browser.get_identifier();
+    let mut buttons = 0u8;
+    loop {
+        let mut message_type = [0];
+        socket.read_exact(&mut message_type)?;
+        match message_type[0] {
+            // SetPixelFormat
+            0 => {
+                let mut message = [0; 19];
+                socket.read_exact(&mut message)?;
+                let format = ClientFormat::decode(&message[3..])?;
+                client.state.lock().format = format;
+            }
+            // SetEncodings
+            2 => {
+                let mut header = [0; 3];
+                socket.read_exact(&mut header)?;
+                let count = u16::from_be_bytes([header[1], header[2]]) as usize;
+                let mut encodings = vec![0; count * 4];
+                socket.read_exact(&mut encodings)?;
+                let desktop_size = encodings.chunks_exact(4).any(|encoding| {
+                    i32::from_be_bytes([encoding[0], encoding[1], encoding[2], encoding[3]])
+                        == ENCODING_DESKTOP_SIZE
+                });
+                client.state.lock().desktop_size = desktop_size;
+            }
+            // FramebufferUpdateRequest
+            3 => {
+                let mut message = [0; 9];
+                socket.read_exact(&mut message)?;
+                let field =
+                    |index: usize| u16::from_be_bytes([message[index], message[index + 1]]) as i32;
+                let request = UpdateRequest {
+                    incremental: message[0] != 0,
+                    rect: Rect {
+                        x: field(1),
+                        y: field(3),
+                        width: field(5),
+                        height: field(7),
+                    },
+                };
+                let mut state = client.state.lock();
+                state.request = Some(request);
+                client.condvar.notify_one();
+            }
+            // KeyEvent
+            4 => {
+                let mut message = [0; 7];
+                socket.read_exact(&mut message)?;
+                let keysym = u32::from_be_bytes([message[3], message[4], message[5], message[6]]);
+                if let Some((code, character)) = keysym_to_key(keysym) {
+                    input.key(host, code, character, message[0] != 0, EventFlags::empty());
+                }
+            }
+            // PointerEvent
+            5 => {
+                let mut message = [0; 5];
+                socket.read_exact(&mut message)?;
+                let scale = shared.compositor.viewport(browser_id).device_scale_factor;
+                let x = (u16::from_be_bytes([message[1], message[2]]) as f32 / scale) as i32;
+                let y = (u16::from_be_bytes([message[3], message[4]]) as f32 / scale) as i32;
+                let mask = message[0];
+                input.pointer(host, x, y, [mask & 1 != 0, mask & 2 != 0, mask & 4 != 0]);
+                // Buttons 4 to 7 are the scroll wheel, scrolling once per press.
+                let pressed = mask & !buttons;
+                let wheel = [
+                    (8, 0, WHEEL_DELTA),
+                    (16, 0, -WHEEL_DELTA),
+                    (32, WHEEL_DELTA, 0),
+                    (64, -WHEEL_DELTA, 0),
+                ];
+                for &(bit, delta_x, delta_y) in &wheel {
+                    if pressed & bit != 0 {
+                        input.wheel(host, x, y, delta_x, delta_y);
+                    }
+                }
+                buttons = mask;
+            }
+            // ClientCutText
+            6 => {
+                let mut header = [0; 7];
+                socket.read_exact(&mut header)?;
+                let len = u32::from_be_bytes([header[3], header[4], header[5], header[6]]);
+                io::copy(&mut (&*socket).take(len as u64), &mut io::sink())?;
+            }
+            _ => return Err(protocol_error("unsupported message type")),
+        }
+    }
+}
+
+/// Sends framebuffer updates when the client asked for one and there is
+/// something to send. `size` is the framebuffer size the client knows about.
+fn send_updates(shared: &ServerShared, client: &Client, mut size: (i32, i32)) -> io::Result<()> {
+    let browser_id = shared.browser.get_identifier();
+    let mut socket = BufWriter::new(&client.socket);
+    let mut message = Vec::new();
+    loop {
+        // The frame listener locks the client state on the UI thread, so don't
+        // keep it locked while the frame is encoded.
+        let (request, damage, format, desktop_size) = {
+            let mut state = client.state.lock();
+            loop {
+                if state.closed {
+                    return Ok(());
+                }
+                if let (Some(request), true) = (state.request, state.painted) {
+                    if !request.incremental || !state.damage.is_empty() {
+                        break;
+                    }
+                }
+                client.condvar.wait(&mut state);
+            }
+            let damage = std::mem::take(&mut state.damage);
+            (
+                state.request.take().unwrap(),
+                damage,
+                state.format,
+                state.desktop_size,
+            )
+        };
+        let encoded = shared.compositor.with_frame(browser_id, |frame| {
+            encode_update(
+                &mut message,
+                frame,
+                request,
+                &damage,
+                format,
+                desktop_size,
+                &mut size,
+            )
+        });
+        if encoded.is_some() {
+            socket.write_all(&message)?;
+            socket.flush()?;
+        } else {
+            // The browser was removed from the compositor, wait for it to paint again.
+            let mut state = client.state.lock();
+            state.painted = false;
+            state.request.get_or_insert(request);
+            state.damage.extend(&damage);
+        }
+    }
+}
+
+/// Writes a FramebufferUpdate message for `request` to `message`. `size` is the
+/// framebuffer size the client knows about and is updated when the client is
+/// told about a new size.
+fn encode_update(
+    message: &mut Vec<u8>,
+    frame: &ComposedFrame,
+    request: UpdateRequest,
+    damage: &Damage,
+    format: ClientFormat,
+    desktop_size: bool,
+    size: &mut (i32, i32),
+) {
+    let frame_size = (
+        frame.width().min(u16::MAX as i32),
+        frame.height().min(u16::MAX as i32),
+    );
+    let resized = desktop_size && *size != frame_size;
+    let mut rects = Vec::new();
+    if resized {
+        *size = frame_size;
+        rects.push(Rect {
+            x: 0,
+            y: 0,
+            width: frame_size.0,
+            height: frame_size.1,
+        });
+    } else if request.incremental {
+        rects.extend_from_slice(damage.rects());
+    } else {
+        rects.push(request.rect);
+    }
+    let bounds = Rect {
+        x: 0,
+        y: 0,
+        width: size.0.min(frame.width()),
+        height: size.1.min(frame.height()),
+    };
+    let rects: Vec<Rect> = rects
+        .into_iter()
+        .filter_map(|rect| intersect(rect, bounds))
+        .collect();
+
+    message.clear();
+    message.extend_from_slice(&[0, 0]);
+    message.extend_from_slice(&((rects.len() + resized as usize) as u16).to_be_bytes());
+    if resized {
+        put_rect_header(
+            message,
+            Rect {
+                x: 0,
+                y: 0,
+                width: frame_size.0,
+                height: frame_size.1,
+            },
+            ENCODING_DESKTOP_SIZE,
+        );
+    }
+    for rect in rects {
+        put_rect_header(message, rect, ENCODING_RAW);
+        format.put_pixels(message, frame, rect);
+    }
+}
+
+fn put_rect_header(message: &mut Vec<u8>, rect: Rect, encoding: i32) {
+    message.extend_from_slice(&(rect.x as u16).to_be_bytes());
+    message.extend_from_slice(&(rect.y as u16).to_be_bytes());
+    message.extend_from_slice(&(rect.width as u16).to_be_bytes());
+    message.extend_from_slice(&(rect.height as u16).to_be_bytes());
+    message.extend_from_slice(&encoding.to_be_bytes());
+}
+
+/// A true color pixel format requested by a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClientFormat {
+    bytes_per_pixel: usize,
+    big_endian: bool,
+    max: [u32; 3],
+    shift: [u32; 3],
+}
+
+impl Default for ClientFormat {
+    /// 32 bit little endian BGRX, which matches the frames.
+    fn default() -> ClientFormat {
+        ClientFormat {
+            bytes_per_pixel: 4,
+            big_endian: false,
+            max: [255, 255, 255],
+            shift: [16, 8, 0],
+        }
+    }
+}
+
+impl ClientFormat {
+    fn encode(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[0] = (self.bytes_per_pixel * 8) as u8;
+        bytes[1] = if self.bytes_per_pixel == 4 {
+            24
+        } else {
+            (self.bytes_per_pixel * 8) as u8
+        };
+        bytes[2] = self.big_endian as u8;
+        bytes[3] = 1;
+        for i in 0..3 {
+            bytes[4 + 2 * i..6 + 2 * i].copy_from_slice(&(self.max[i] as u16).to_be_bytes());
+            bytes[10 + i] = self.shift[i] as u8;
+        }
+        bytes
+    }
+    fn decode(bytes: &[u8]) -> io::Result<ClientFormat> {
+        let bytes_per_pixel = match bytes[0] {
+            8 => 1,
+            16 => 2,
+            32 => 4,
+            _ => return Err(protocol_error("unsupported bits per pixel")),
+        };
+        if bytes[3] == 0 {
+            return Err(protocol_error("color maps are not supported"));
+        }
+        let mut format = ClientFormat {
+            bytes_per_pixel,
+            big_endian: bytes[2] != 0,
+            max: [0; 3],
+            shift: [0; 3],
+        };
+        for i in 0..3 {
+            format.max[i] = u16::from_be_bytes([bytes[4 + 2 * i], bytes[5 + 2 * i]]) as u32;
+            format.shift[i] = bytes[10 + i] as u32 % 32;
+        }
+        Ok(format)
+    }
+    fn put_pixels(&self, message: &mut Vec<u8>, frame: &ComposedFrame, rect: Rect) {
+        let (red, blue) = match frame.format() {
+            PixelFormat::Bgra => (2, 0),
+            PixelFormat::Rgba => (0, 2),
+        };
+        let data = frame.data();
+        let stride = frame.stride();
+        message.reserve(rect.width as usize * rect.height as usize * self.bytes_per_pixel);
+        for y in rect.y..rect.y + rect.height {
+            let row = &data[y as usize * stride + rect.x as usize * 4..][..rect.width as usize * 4];
+            if *self == ClientFormat::default() {
+                if red == 2 {
+                    message.extend_from_slice(row);
+                } else {
+                    let start = message.len();
+                    message.resize(start + row.len(), 0);
+                    swap_red_blue(row, &mut message[start..]);
+                }
+                continue;
+            }
+            for pixel in row.chunks_exact(4) {
+                let mut value = 0;
+                for (i, &c) in [pixel[red], pixel[1], pixel[blue]].iter().enumerate() {
+                    value |= ((c as u32 * self.max[i] + 127) / 255) << self.shift[i];
+                }
+                let bytes = if self.big_endian {
+                    value.to_be_bytes()
+                } else {
+                    value.to_le_bytes()
+                };
+                match (self.bytes_per_pixel, self.big_endian) {
+                    (4, _) => message.extend_from_slice(&bytes),
+                    (n, true) => message.extend_from_slice(&bytes[4 - n..]),
+                    (n, false) => message.extend_from_slice(&bytes[..n]),
+                }
+            }
+        }
+    }
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Maps an X11 keysym, which VNC uses for keys, to a key code and the
+/// character it produces.
+fn keysym_to_key(keysym: u32) -> Option<(WindowsKeyCode, Option<char>)> {
+    let code = |code: WindowsKeyCode| Some((code, None));
+    match keysym {
+        0xff08 => code(WindowsKeyCode::Back),
+        0xff09 => code(WindowsKeyCode::Tab),
+        0xff0d | 0xff8d => Some((WindowsKeyCode::Return, Some('\r'))),
+        0xff13 => code(WindowsKeyCode::Pause),
+        0xff14 => code(WindowsKeyCode::Scroll),
+        0xff1b => code(WindowsKeyCode::Escape),
+        0xff50 => code(WindowsKeyCode::Home),
+        0xff51 => code(WindowsKeyCode::Left),
+        0xff52 => code(WindowsKeyCode::Up),
+        0xff53 => code(WindowsKeyCode::Right),
+        0xff54 => code(WindowsKeyCode::Down),
+        0xff55 => code(WindowsKeyCode::Prior),
+        0xff56 => code(WindowsKeyCode::Next),
+        0xff57 => code(WindowsKeyCode::End),
+        0xff61 => code(WindowsKeyCode::Snapshot),
+        0xff63 => code(WindowsKeyCode::Insert),
+        0xff67 => code(WindowsKeyCode::Apps),
+        0xff7f => code(WindowsKeyCode::Numlock),
+        0xffff => code(WindowsKeyCode::Delete),
+        0xffaa..=0xffaf => {
+            let character = b"*+,-./"[(keysym - 0xffaa) as usize] as char;
+            Some((
+                WindowsKeyCode(0x6a + (keysym - 0xffaa) as i32),
+                Some(character),
+            ))
+        }
+        0xffb0..=0xffb9 => {
+            let digit = keysym - 0xffb0;
+            Some((
+                WindowsKeyCode(0x60 + digit as i32),
+                std::char::from_digit(digit, 10),
+            ))
+        }
+        0xffbe..=0xffd5 => code(WindowsKeyCode(0x70 + (keysym - 0xffbe) as i32)),
+        0xffe1 => code(WindowsKeyCode::LShift),
+        0xffe2 => code(WindowsKeyCode::RShift),
+        0xffe3 => code(WindowsKeyCode::LControl),
+        0xffe4 => code(WindowsKeyCode::RControl),
+        0xffe5 => code(WindowsKeyCode::Capital),
+        0xffe7 | 0xffe9 => code(WindowsKeyCode::LMenu),
+        0xffe8 | 0xffea => code(WindowsKeyCode::RMenu),
+        0xffeb => code(WindowsKeyCode::LWin),
+        0xffec => code(WindowsKeyCode::RWin),
+        0x20..=0x7e | 0xa0..=0xff => {
+            let character = std::char::from_u32(keysym)?;
+            Some((key_code_for_char(character).0, Some(character)))
+        }
+        0x0100_0000..=0x0110_ffff => {
+            let character = std::char::from_u32(keysym - 0x0100_0000)?;
+            Some((key_code_for_char(character).0, Some(character)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+
+    /// Runs the handshake against a client that sends `client`, and returns
+    /// the result and everything the server sent.
+    fn handshake_with(client: &[u8]) -> (io::Result<()>, Vec<u8>) {
+        let (mut server, mut peer) = UnixStream::pair().unwrap();
+        peer.write_all(client).unwrap();
+        peer.shutdown(Shutdown::Write).unwrap();
+        let result = handshake(&mut server);
+        drop(server);
+        let mut sent = Vec::new();
+        peer.read_to_end(&mut sent).unwrap();
+        (result, sent)
+    }
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// A frame whose bytes count up from 0.
+    fn numbered(width: i32, height: i32, format: PixelFormat) -> ComposedFrame {
+        let data = (0..4 * width * height).map(|i| i as u8).collect();
+        ComposedFrame::from_data(width, height, format, 1, data)
+    }
+
+    fn rect_header(rect: Rect, encoding: i32) -> Vec<u8> {
+        let mut header = Vec::new();
+        put_rect_header(&mut header, rect, encoding);
+        header
+    }
+
+    /// The raw pixels of `rect` in the default format.
+    fn raw(frame: &ComposedFrame, rect: Rect) -> Vec<u8> {
+        let mut pixels = Vec::new();
+        for y in rect.y..rect.y + rect.height {
+            let start = y as usize * frame.stride() + rect.x as usize * 4;
+            pixels.extend_from_slice(&frame.data()[start..start + rect.width as usize * 4]);
+        }
+        pixels
+    }
+
+    fn update(
+        frame: &ComposedFrame,
+        request: UpdateRequest,
+        damage: &Damage,
+        desktop_size: bool,
+        size: &mut (i32, i32),
+    ) -> Vec<u8> {
+        // Leftovers of a previous update.
+        let mut message = vec![0xff; 3];
+        encode_update(
+            &mut message,
+            frame,
+            request,
+            damage,
+            ClientFormat::default(),
+            desktop_size,
+            size,
+        );
+        message
+    }
+
+    #[test]
+    fn handshake_3_8() {
+        let (result, sent) = handshake_with(b"RFB 003.008\n\x01\x01");
+        result.unwrap();
+        // Security types [None], then SecurityResult OK.
+        assert_eq!(sent, b"RFB 003.008\n\x01\x01\0\0\0\0");
+    }
+
+    #[test]
+    fn handshake_3_7() {
+        let (result, sent) = handshake_with(b"RFB 003.007\n\x01\x01");
+        result.unwrap();
+        // No SecurityResult for the None type.
+        assert_eq!(sent, b"RFB 003.008\n\x01\x01");
+    }
+
+    #[test]
+    fn handshake_3_3() {
+        let (result, sent) = handshake_with(b"RFB 003.003\n\x01");
+        result.unwrap();
+        // The server picks the security type.
+        assert_eq!(sent, b"RFB 003.008\n\0\0\0\x01");
+    }
+
+    #[test]
+    fn handshake_other_minor_versions() {
+        // Apple's clients claim 3.889, and unknown ones are treated as 3.8.
+        let (result, sent) = handshake_with(b"RFB 003.889\n\x01\x01");
+        result.unwrap();
+        assert_eq!(sent, b"RFB 003.008\n\x01\x01\0\0\0\0");
+    }
+
+    #[test]
+    fn handshake_errors() {
+        let (result, sent) = handshake_with(b"RFB 004.000\n");
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "unsupported protocol version");
+        assert_eq!(sent, b"RFB 003.008\n");
+
+        // VNC authentication.
+        let (result, sent) = handshake_with(b"RFB 003.008\n\x02");
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "unsupported security type");
+        assert_eq!(sent, b"RFB 003.008\n\x01\x01");
+
+        // No ClientInit.
+        let (result, _) = handshake_with(b"RFB 003.008\n\x01");
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn client_format_encoding() {
+        let default = ClientFormat::default();
+        assert_eq!(
+            default.encode(),
+            [32, 24, 0, 1, 0, 255, 0, 255, 0, 255, 16, 8, 0, 0, 0, 0]
+        );
+        assert_eq!(ClientFormat::decode(&default.encode()).unwrap(), default);
+
+        let rgb565 = ClientFormat {
+            bytes_per_pixel: 2,
+            big_endian: true,
+            max: [31, 63, 31],
+            shift: [11, 5, 0],
+        };
+        assert_eq!(
+            rgb565.encode(),
+            [16, 16, 1, 1, 0, 31, 0, 63, 0, 31, 11, 5, 0, 0, 0, 0]
+        );
+        assert_eq!(ClientFormat::decode(&rgb565.encode()).unwrap(), rgb565);
+
+        let mut bytes = default.encode();
+        bytes[0] = 24;
+        let err = ClientFormat::decode(&bytes).unwrap_err();
+        assert_eq!(err.to_string(), "unsupported bits per pixel");
+        let mut bytes = default.encode();
+        bytes[3] = 0;
+        let err = ClientFormat::decode(&bytes).unwrap_err();
+        assert_eq!(err.to_string(), "color maps are not supported");
+    }
+
+    #[test]
+    fn put_pixels_default_format() {
+        let format = ClientFormat::default();
+        let frame = numbered(3, 2, PixelFormat::Bgra);
+        let mut message = vec![0xff];
+        format.put_pixels(&mut message, &frame, rect(1, 1, 2, 1));
+        assert_eq!(message, [&[0xff][..], &frame.data()[16..24]].concat());
+
+        // Red and blue are swapped for RGBA frames.
+        let frame = numbered(1, 2, PixelFormat::Rgba);
+        let mut message = Vec::new();
+        format.put_pixels(&mut message, &frame, rect(0, 0, 1, 2));
+        assert_eq!(message, [2, 1, 0, 3, 6, 5, 4, 7]);
+    }
+
+    #[test]
+    fn put_pixels_converted() {
+        // Red, half green and white in BGRA.
+        let data = vec![0, 0, 255, 255, 0, 128, 0, 255, 255, 255, 255, 255];
+        let frame = ComposedFrame::from_data(3, 1, PixelFormat::Bgra, 1, data);
+        let pixels = |format: ClientFormat| {
+            let mut message = Vec::new();
+            format.put_pixels(&mut message, &frame, rect(0, 0, 3, 1));
+            message
+        };
+        let rgb565 = ClientFormat {
+            bytes_per_pixel: 2,
+            big_endian: true,
+            max: [31, 63, 31],
+            shift: [11, 5, 0],
+        };
+        assert_eq!(pixels(rgb565), [0xf8, 0x00, 0x04, 0x00, 0xff, 0xff]);
+        let rgb565_le = ClientFormat {
+            big_endian: false,
+            ..rgb565
+        };
+        assert_eq!(pixels(rgb565_le), [0x00, 0xf8, 0x00, 0x04, 0xff, 0xff]);
+        let bgr233 = ClientFormat {
+            bytes_per_pixel: 1,
+            big_endian: false,
+            max: [7, 7, 3],
+            shift: [0, 3, 6],
+        };
+        assert_eq!(pixels(bgr233), [0x07, 0x20, 0xff]);
+        let rgbx_be = ClientFormat {
+            bytes_per_pixel: 4,
+            big_endian: true,
+            max: [255, 255, 255],
+            shift: [24, 16, 8],
+        };
+        assert_eq!(
+            pixels(rgbx_be),
+            [255, 0, 0, 0, 0, 128, 0, 0, 255, 255, 255, 0]
+        );
+    }
+
+    #[test]
+    fn full_update_is_clipped() {
+        let frame = numbered(4, 3, PixelFormat::Bgra);
+        let request = UpdateRequest {
+            incremental: false,
+            rect: rect(2, 1, 100, 100),
+        };
+        let mut size = (4, 3);
+        let message = update(&frame, request, &Damage::default(), true, &mut size);
+        let expected = [
+            &[0, 0, 0, 1][..],
+            &rect_header(rect(2, 1, 2, 2), ENCODING_RAW),
+            &raw(&frame, rect(2, 1, 2, 2)),
+        ]
+        .concat();
+        assert_eq!(message, expected);
+        assert_eq!(size, (4, 3));
+    }
+
+    #[test]
+    fn incremental_update() {
+        let frame = numbered(4, 3, PixelFormat::Bgra);
+        let request = UpdateRequest {
+            incremental: true,
+            rect: rect(0, 0, 4, 3),
+        };
+        let mut damage = Damage::default();
+        damage.add(rect(0, 0, 1, 1));
+        damage.add(rect(3, 2, 5, 5));
+        damage.add(rect(10, 10, 2, 2));
+        let mut size = (4, 3);
+        let message = update(&frame, request, &damage, false, &mut size);
+        // Only the parts of the damage inside the frame.
+        let expected = [
+            &[0, 0, 0, 2][..],
+            &rect_header(rect(0, 0, 1, 1), ENCODING_RAW),
+            &raw(&frame, rect(0, 0, 1, 1)),
+            &rect_header(rect(3, 2, 1, 1), ENCODING_RAW),
+            &raw(&frame, rect(3, 2, 1, 1)),
+        ]
+        .concat();
+        assert_eq!(message, expected);
+
+        let message = update(&frame, request, &Damage::default(), false, &mut size);
+        assert_eq!(message, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn desktop_size_update() {
+        let frame = numbered(4, 3, PixelFormat::Bgra);
+        let request = UpdateRequest {
+            incremental: true,
+            rect: rect(0, 0, 2, 2),
+        };
+        let mut damage = Damage::default();
+        damage.add(rect(0, 0, 1, 1));
+        let mut size = (2, 2);
+        let message = update(&frame, request, &damage, true, &mut size);
+        // The whole frame follows the new size.
+        let expected = [
+            &[0, 0, 0, 2][..],
+            &rect_header(rect(0, 0, 4, 3), ENCODING_DESKTOP_SIZE),
+            &rect_header(rect(0, 0, 4, 3), ENCODING_RAW),
+            &raw(&frame, rect(0, 0, 4, 3)),
+        ]
+        .concat();
+        assert_eq!(message, expected);
+        assert_eq!(size, (4, 3));
+    }
+
+    #[test]
+    fn resize_without_desktop_size() {
+        let frame = numbered(4, 3, PixelFormat::Bgra);
+        let request = UpdateRequest {
+            incremental: false,
+            rect: rect(0, 0, 2, 2),
+        };
+        // The client only gets the part of a larger frame that it knows about.
+        let mut size = (2, 2);
+        let message = update(&frame, request, &Damage::full(4, 3), false, &mut size);
+        let expected = [
+            &[0, 0, 0, 1][..],
+            &rect_header(rect(0, 0, 2, 2), ENCODING_RAW),
+            &raw(&frame, rect(0, 0, 2, 2)),
+        ]
+        .concat();
+        assert_eq!(message, expected);
+        assert_eq!(size, (2, 2));
+
+        // And the part of a smaller frame that exists.
+        let mut size = (8, 8);
+        let request = UpdateRequest {
+            incremental: false,
+            rect: rect(0, 0, 8, 8),
+        };
+        let message = update(&frame, request, &Damage::default(), false, &mut size);
+        assert_eq!(
+            &message[4..16],
+            &rect_header(rect(0, 0, 4, 3), ENCODING_RAW)[..]
+        );
+        assert_eq!(size, (8, 8));
+    }
+
+    #[test]
+    fn keysyms() {
+        let key = |code, character| Some((code, character));
+        assert_eq!(keysym_to_key(0x61), key(WindowsKeyCode::A, Some('a')));
+        assert_eq!(keysym_to_key(0x41), key(WindowsKeyCode::A, Some('A')));
+        assert_eq!(keysym_to_key(0x3f), key(WindowsKeyCode::Oem2, Some('?')));
+        assert_eq!(keysym_to_key(0xe9), key(WindowsKeyCode(0), Some('é')));
+        assert_eq!(
+            keysym_to_key(0x0100_20ac),
+            key(WindowsKeyCode(0), Some('€'))
+        );
+        assert_eq!(
+            keysym_to_key(0xff0d),
+            key(WindowsKeyCode::Return, Some('\r'))
+        );
+        assert_eq!(
+            keysym_to_key(0xff8d),
+            key(WindowsKeyCode::Return, Some('\r'))
+        );
+        assert_eq!(keysym_to_key(0xff08), key(WindowsKeyCode::Back, None));
+        assert_eq!(keysym_to_key(0xff51), key(WindowsKeyCode::Left, None));
+        assert_eq!(keysym_to_key(0xffff), key(WindowsKeyCode::Delete, None));
+        assert_eq!(
+            keysym_to_key(0xffaa),
+            key(WindowsKeyCode::Multiply, Some('*'))
+        );
+        assert_eq!(
+            keysym_to_key(0xffaf),
+            key(WindowsKeyCode::Divide, Some('/'))
+        );
+        assert_eq!(
+            keysym_to_key(0xffb5),
+            key(WindowsKeyCode::Numpad5, Some('5'))
+        );
+        assert_eq!(keysym_to_key(0xffbe), key(WindowsKeyCode::F1, None));
+        assert_eq!(keysym_to_key(0xffd5), key(WindowsKeyCode::F24, None));
+        assert_eq!(keysym_to_key(0xffe1), key(WindowsKeyCode::LShift, None));
+        assert_eq!(keysym_to_key(0xffea), key(WindowsKeyCode::RMenu, None));
+        assert_eq!(keysym_to_key(0xffeb), key(WindowsKeyCode::LWin, None));
+        // ISO_Level3_Shift, and a surrogate.
+        assert_eq!(keysym_to_key(0xfe03), None);
+        assert_eq!(keysym_to_key(0x0100_d800), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! What happens when a callback panics.
+//!
+//! Unwinding from a Rust callback into CEF is undefined behavior, so every
+//! callback catches panics at the FFI boundary and then applies the global
+//! [PanicPolicy]. Example usage:
+//!
+//! ```no_run
+//!  use cef::panic_policy::{set_panic_policy, PanicPolicy};
+//!
+//!  set_panic_policy(PanicPolicy::hook(|panic| {
+//!      eprintln!("{} panicked for browser {:?}: {}", panic.callback, panic.browser_id, panic.message);
+//!  }));
+//! ```
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Describes a panic caught at the FFI boundary.
+#[derive(Debug, Clone)]
+pub struct CallbackPanic {
+    /// The callback that panicked, e.g. `RenderHandlerWrapper::on_paint`.
+    pub callback: &'static str,
+    /// The identifier of the browser the callback was called for, if it received one.
+    /// None if the panic happened while converting the arguments.
+    pub browser_id: Option<i32>,
+    /// The panic message, if it was a string.
+    pub message: String,
+}
+
+/// Decides what happens after a callback panicked.
+#[derive(Clone)]
+pub enum PanicPolicy {
+    /// Log the panic and abort the process. This is the default.
+    Abort,
+    /// Log the panic and return a default value to CEF: false, 0 or null,
+    /// depending on the callback. Note that for some callbacks this value has a
+    /// meaning, like allowing a navigation.
+    LogAndDefault,
+    /// Call the hook and return the same default value as [PanicPolicy::LogAndDefault].
+    /// The hook may abort the process itself.
+    Hook(Arc<dyn Fn(&CallbackPanic) + Send + Sync>),
+}
+
+impl PanicPolicy {
+    pub fn hook(hook: impl Fn(&CallbackPanic) + Send + Sync + 'static) -> PanicPolicy {
+        PanicPolicy::Hook(Arc::new(hook))
+    }
+}
+
+lazy_static! {
+    static ref POLICY: RwLock<PanicPolicy> = RwLock::new(PanicPolicy::Abort);
+}
+
+/// Only look up browser identifiers if somebody is interested in them.
+static WANTS_BROWSER_ID: AtomicBool = AtomicBool::new(false);
+
+/// Set the policy for all callbacks.
+pub fn set_panic_policy(policy: PanicPolicy) {
+    let wants_browser_id = match policy {
+        PanicPolicy::Hook(_) => true,
+        _ => false,
+    };
+    *POLICY.write() = policy;
+    WANTS_BROWSER_ID.store(wants_browser_id, Ordering::SeqCst);
+}
+
+/// Returns the current policy.
+pub fn panic_policy() -> PanicPolicy {
+    POLICY.read().clone()
+}
+
+#[doc(hidden)]
+pub fn wants_browser_id() -> bool {
+    WANTS_BROWSER_ID.load(Ordering::Relaxed)
+}
+
+#[doc(hidden)]
+pub fn handle_panic(callback: &'static str, browser_id: Option<i32>, payload: Box<dyn Any + Send>) {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<Any>".to_owned()
+    };
+    let panic = CallbackPanic {
+        callback,
+        browser_id,
+        message,
+    };
+    match panic_policy() {
+        PanicPolicy::Abort => {
+            log::error!("{} panicked: {}, aborting", panic.callback, panic.message);
+            std::process::abort();
+        }
+        PanicPolicy::LogAndDefault => {
+            log::error!("{} panicked: {}", panic.callback, panic.message);
+        }
+        PanicPolicy::Hook(hook) => {
+            // The hook itself must not unwind into CEF either.
+            if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| hook(&panic))).is_err() {
+                std::process::abort();
+            }
+        }
+    }
+}
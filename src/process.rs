@@ -1,4 +1,5 @@
-use cef_sys::{cef_process_id_t, cef_process_message_t, cef_string_userfree_utf16_free, cef_process_message_create};
+use cef_sys::{cef_process_id_t, cef_process_message_t};
+use crate::capi::{cef_string_userfree_utf16_free, cef_process_message_create};
 
 use crate::{
     string::CefString,
@@ -28,6 +29,7 @@ ref_counted_ptr! {
 /// Structure representing a message. Can be used on any process and thread.
 impl ProcessMessage {
     pub fn new(name: &str) -> Self {
+        ffi_event!("ProcessMessage::new");
         unsafe {
             Self::from_ptr_unchecked(cef_process_message_create(CefString::from(name).as_ptr()))
         }
@@ -36,6 +38,7 @@ impl ProcessMessage {
     /// Returns true (1) if this object is valid. Do not call any other functions
     /// if this function returns false (0).
     pub fn is_valid(&self) -> bool {
+        ffi_event!("ProcessMessage::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) } != 0)
@@ -44,6 +47,7 @@ impl ProcessMessage {
     /// Returns true if the values of this object are read-only. Some APIs may
     /// expose read-only objects.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("ProcessMessage::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.as_ptr()) } != 0)
@@ -51,6 +55,7 @@ impl ProcessMessage {
     }
     /// Returns the message name.
     pub fn get_name(&self) -> Option<String> {
+        ffi_event!("ProcessMessage::get_name");
         self.0
             .get_name
             .and_then(|get_name| unsafe { get_name(self.as_ptr()).as_mut() })
@@ -62,6 +67,7 @@ impl ProcessMessage {
     }
     /// Returns the list of arguments.
     pub fn get_argument_list(&self) -> ListValue {
+        ffi_event!("ProcessMessage::get_argument_list");
         unsafe { ListValue::from_ptr_unchecked(self.0.get_argument_list.unwrap()(self.as_ptr())) }
     }
 }
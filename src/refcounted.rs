@@ -16,6 +16,8 @@ use lazy_static::lazy_static;
 /// behavior.
 pub(crate) unsafe trait RefCounter: Sized {
     const POISONABLE: bool;
+    /// The name of the Rust type holding handles to this, used for leak reports.
+    const TYPE_NAME: &'static str;
     fn base(&self) -> &cef_base_ref_counted_t;
     fn base_mut(&mut self) -> &mut cef_base_ref_counted_t;
 }
@@ -26,12 +28,13 @@ pub(crate) trait Wrapper: Sized + Send + Sync {
 }
 
 macro_rules! ref_counter {
-    ($cef:ty) => {
-        ref_counter!($cef, false);
+    (@named $name:expr, $cef:ty) => {
+        ref_counter!(@named $name, $cef, false);
     };
-    ($cef:ty, $poisonable:expr) => {
+    (@named $name:expr, $cef:ty, $poisonable:expr) => {
         unsafe impl crate::refcounted::RefCounter for $cef {
             const POISONABLE: bool = $poisonable;
+            const TYPE_NAME: &'static str = $name;
             fn base(&self) -> &cef_sys::cef_base_ref_counted_t {
                 &self.base
             }
@@ -40,6 +43,12 @@ macro_rules! ref_counter {
             }
         }
     };
+    ($cef:ty) => {
+        ref_counter!(@named stringify!($cef), $cef, false);
+    };
+    ($cef:ty, $poisonable:expr) => {
+        ref_counter!(@named stringify!($cef), $cef, $poisonable);
+    };
 }
 
 #[repr(transparent)]
@@ -59,17 +68,23 @@ impl<C: RefCounter> RefCountedPtr<C> {
         let mut cef = NonNull::new(ptr)?;
         let add_ref = cef.as_ref().base().add_ref.unwrap();
         (add_ref)(cef.as_mut().base_mut());
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::handle_acquired::<C>();
         Some(RefCountedPtr { cef })
     }
 
     pub(crate) unsafe fn from_ptr(ptr: *mut C) -> Option<RefCountedPtr<C>> {
         let cef = NonNull::new(ptr)?;
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::handle_acquired::<C>();
         Some(RefCountedPtr { cef })
     }
 
     pub(crate) unsafe fn from_ptr_unchecked(ptr: *mut C) -> RefCountedPtr<C> {
         debug_assert!(ptr != std::ptr::null_mut());
         let cef = NonNull::new_unchecked(ptr);
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::handle_acquired::<C>();
         RefCountedPtr { cef }
     }
 
@@ -79,6 +94,8 @@ impl<C: RefCounter> RefCountedPtr<C> {
 
     pub(crate) fn into_raw(self) -> *mut C {
         let ptr = self.cef.as_ptr();
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::handle_released::<C>();
         std::mem::forget(self);
         ptr
     }
@@ -117,7 +134,7 @@ macro_rules! ref_counted_ptr {
         unsafe impl$(<$($generic $(: $bound)?),+>)? Send for $Struct$(<$($generic),+>)? {}
         unsafe impl$(<$($generic $(: $bound)?),+>)? Sync for $Struct$(<$($generic),+>)? {}
 
-        ref_counter!($cef $(, $poisonable)?);
+        ref_counter!(@named stringify!($Struct), $cef $(, $poisonable)?);
 
         impl$(<$($generic $(: $bound)?),+>)? $Struct$(<$($generic),+>)? {
             pub(crate) unsafe fn from_ptr_add_ref(ptr: *mut $cef) -> Option<Self> {
@@ -175,6 +192,8 @@ impl<C: RefCounter> DerefMut for RefCountedPtr<C> {
 
 impl<C: RefCounter> Drop for RefCountedPtr<C> {
     fn drop(&mut self) {
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::handle_released::<C>();
         unsafe {
             if !self.check_poisoned() {
                 let release = self.cef.as_ref().base().release.unwrap();
@@ -193,6 +212,8 @@ impl<C: RefCounter> Clone for RefCountedPtr<C> {
             let mut new = RefCountedPtr { cef: self.cef };
             let add_ref = new.cef.as_ref().base().add_ref.unwrap();
             (add_ref)(new.cef.as_mut().base_mut());
+            #[cfg(feature = "leak-detection")]
+            crate::leak_detector::handle_acquired::<C>();
             new
         }
     }
@@ -237,11 +258,14 @@ impl<W: Wrapper> RefCounted<W> {
 
         // TODO: SHOULD WE GIT RID OF THE POINTER CAST? THIS IS BEING SHARED ACROSS THREADS AFTER
         // ALL
-        Box::into_raw(Box::new(Self {
+        let ptr = Box::into_raw(Box::new(Self {
             cefobj,
             ref_count: AtomicUsize::new(1),
             object,
-        }))
+        }));
+        #[cfg(feature = "leak-detection")]
+        crate::leak_detector::object_created::<W>(ptr as usize);
+        ptr
     }
 
     pub(crate) extern "C" fn add_ref(ref_counted: *mut cef_base_ref_counted_t) {
@@ -257,6 +281,8 @@ impl<W: Wrapper> RefCounted<W> {
         atomic::fence(Ordering::Acquire);
 
         if strong_count == 1 {
+            #[cfg(feature = "leak-detection")]
+            crate::leak_detector::object_destroyed::<W>(ref_counted as usize);
             unsafe{ Box::from_raw(ref_counted as *mut Self); }
             1
         } else {
@@ -8,6 +8,7 @@ use crate::{
     v8context::{V8Context, V8Exception, V8StackFrame, V8StackTrace},
     values::{DictionaryValue, ListValue},
     send_protector::SendProtector,
+    task::RendererThread,
 };
 use cef_sys::{
     cef_browser_t, cef_dictionary_value_t, cef_domnode_t, cef_frame_t, cef_list_value_t,
@@ -32,18 +33,18 @@ pub trait RenderProcessHandlerCallbacks: 'static + Send {
     /// Called after the render process main thread has been created. `extra_info`
     /// is originating from
     /// [BrowserProcessHandlerCallbacks::on_render_process_thread_created].
-    fn on_render_thread_created(&self, extra_info: ListValue) {}
+    fn on_render_thread_created(&self, renderer_thread: RendererThread, extra_info: ListValue) {}
     /// Called after WebKit has been initialized.
-    fn on_web_kit_initialized(&self) {}
+    fn on_web_kit_initialized(&self, renderer_thread: RendererThread) {}
     /// Called after a browser has been created. When browsing cross-origin a new
     /// browser will be created before the old browser with the same identifier is
     /// destroyed. |extra_info| is originating from
     /// [BrowserHost::create_browser()],
     /// [BrowserHost::create_browser_sync()],
     /// [LifeSpanHandler::on_before_popup()] or [BrowserView::create()].
-    fn on_browser_created(&self, browser: Browser, extra_info: DictionaryValue) {}
+    fn on_browser_created(&self, renderer_thread: RendererThread, browser: Browser, extra_info: DictionaryValue) {}
     /// Called before a browser is destroyed.
-    fn on_browser_destroyed(&self, browser: Browser) {}
+    fn on_browser_destroyed(&self, renderer_thread: RendererThread, browser: Browser) {}
     /// Return the handler for browser load status events.
     fn get_load_handler(&self) -> Option<LoadHandler> {
         None
@@ -54,14 +55,15 @@ pub trait RenderProcessHandlerCallbacks: 'static + Send {
     /// from the thread on which they are created. A task runner for posting tasks
     /// on the associated thread can be retrieved via the
     /// [V8Context::get_task_runner()] function.
-    fn on_context_created(&self, browser: Browser, frame: Frame, context: V8Context) {}
+    fn on_context_created(&self, renderer_thread: RendererThread, browser: Browser, frame: Frame, context: V8Context) {}
     /// Called immediately before the V8 context for a frame is released.
-    fn on_context_released(&self, browser: Browser, frame: Frame, context: V8Context) {}
+    fn on_context_released(&self, renderer_thread: RendererThread, browser: Browser, frame: Frame, context: V8Context) {}
     /// Called for global uncaught exceptions in a frame. Execution of this
     /// callback is disabled by default. To enable set
     /// [CefSettings.uncaught_exception_stack_size] > 0.
     fn on_uncaught_exception(
         &self,
+        renderer_thread: RendererThread,
         browser: Browser,
         frame: Frame,
         context: V8Context,
@@ -73,11 +75,12 @@ pub trait RenderProcessHandlerCallbacks: 'static + Send {
     /// be None if no specific node has gained focus. The node object passed to
     /// this function represents a snapshot of the DOM at the time this function is
     /// executed.
-    fn on_focused_node_changed(&self, browser: Browser, frame: Frame, node: Option<DOMNode>) {}
+    fn on_focused_node_changed(&self, renderer_thread: RendererThread, browser: Browser, frame: Frame, node: Option<DOMNode>) {}
     /// Called when a new message is received from a different process. Return true
     /// if the message was handled or false otherwise.
     fn on_process_message_received(
         &self,
+        renderer_thread: RendererThread,
         browser: Browser,
         frame: Frame,
         message: ProcessMessage,
@@ -126,11 +129,11 @@ cef_callback_impl! {
             &self,
             extra_info: ListValue: *mut cef_list_value_t,
         ) {
-            unsafe{ self.0.get() }.on_render_thread_created(extra_info);
+            unsafe{ self.0.get() }.on_render_thread_created(unsafe { RendererThread::new_unchecked() }, extra_info);
         }
 
         fn web_kit_initialized(&self) {
-            unsafe{ self.0.get() }.on_web_kit_initialized();
+            unsafe{ self.0.get() }.on_web_kit_initialized(unsafe { RendererThread::new_unchecked() });
         }
 
         fn browser_created(
@@ -138,14 +141,14 @@ cef_callback_impl! {
             browser: Browser: *mut cef_browser_t,
             extra_info: DictionaryValue: *mut cef_dictionary_value_t,
         ) {
-            unsafe{ self.0.get() }.on_browser_created(browser, extra_info);
+            unsafe{ self.0.get() }.on_browser_created(unsafe { RendererThread::new_unchecked() }, browser, extra_info);
         }
 
         fn browser_destroyed(
             &self,
             browser: Browser: *mut cef_browser_t,
         ) {
-            unsafe{ self.0.get() }.on_browser_destroyed(browser);
+            unsafe{ self.0.get() }.on_browser_destroyed(unsafe { RendererThread::new_unchecked() }, browser);
         }
 
         fn get_load_handler(
@@ -161,6 +164,7 @@ cef_callback_impl! {
             context: V8Context: *mut cef_v8context_t,
         ) {
             unsafe{ self.0.get() }.on_context_created(
+                unsafe { RendererThread::new_unchecked() },
                 browser,
                 frame,
                 context,
@@ -174,6 +178,7 @@ cef_callback_impl! {
             context: V8Context: *mut cef_v8context_t,
         ) {
             unsafe{ self.0.get() }.on_context_created(
+                unsafe { RendererThread::new_unchecked() },
                 browser,
                 frame,
                 context,
@@ -189,6 +194,7 @@ cef_callback_impl! {
             stack_trace: V8StackTrace: *mut cef_v8stack_trace_t,
         ) {
             unsafe{ self.0.get() }.on_uncaught_exception(
+                unsafe { RendererThread::new_unchecked() },
                 browser,
                 frame,
                 context,
@@ -204,6 +210,7 @@ cef_callback_impl! {
             node: Option<DOMNode>: *mut cef_domnode_t,
         ) {
             unsafe{ self.0.get() }.on_focused_node_changed(
+                unsafe { RendererThread::new_unchecked() },
                 browser,
                 frame,
                 node
@@ -219,6 +226,7 @@ cef_callback_impl! {
         ) -> std::os::raw::c_int {
             assert_eq!(ProcessId::Browser, source_process);
             unsafe{ self.0.get() }.on_process_message_received(
+                unsafe { RendererThread::new_unchecked() },
                 browser,
                 frame,
                 message
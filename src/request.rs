@@ -1,7 +1,10 @@
 use cef_sys::{
-    cef_post_data_create, cef_post_data_element_create, cef_post_data_element_t, cef_post_data_t,
-    cef_postdataelement_type_t, cef_referrer_policy_t, cef_request_create, cef_request_t,
-    cef_resource_type_t, cef_string_userfree_utf16_free,
+    cef_post_data_element_t, cef_post_data_t, cef_postdataelement_type_t, cef_referrer_policy_t,
+    cef_request_t, cef_resource_type_t,
+};
+use crate::capi::{
+    cef_post_data_create, cef_post_data_element_create, cef_request_create,
+    cef_string_userfree_utf16_free,
 };
 use std::{collections::HashMap, convert::TryFrom, ptr::null_mut};
 
@@ -178,11 +181,13 @@ ref_counted_ptr! {
 impl Request {
     /// Create a new Request object.
     pub fn new() -> Self {
+        ffi_event!("Request::new");
         unsafe { Self::from_ptr_unchecked(cef_request_create()) }
     }
 
     /// Returns true if this object is read-only.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("Request::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.0.as_ptr()) != 0 })
@@ -190,6 +195,7 @@ impl Request {
     }
     /// Get the fully qualified URL.
     pub fn get_url(&self) -> String {
+        ffi_event!("Request::get_url");
         self.0
             .get_url
             .and_then(|get_url| unsafe { get_url(self.as_ptr()).as_mut() })
@@ -202,6 +208,7 @@ impl Request {
     }
     /// Set the fully qualified URL.
     pub fn set_url(&self, url: &str) {
+        ffi_event!("Request::set_url");
         if let Some(set_url) = self.0.set_url {
             unsafe {
                 set_url(self.0.as_ptr(), CefString::new(url).as_ptr());
@@ -211,6 +218,7 @@ impl Request {
     /// Get the request function type. The value will default to POST if post data
     /// is provided and GET otherwise.
     pub fn get_method(&self) -> String {
+        ffi_event!("Request::get_method");
         self.0
             .get_method
             .and_then(|get_method| unsafe { get_method(self.as_ptr()).as_mut() })
@@ -223,6 +231,7 @@ impl Request {
     }
     /// Set the request function type.
     pub fn set_method(&self, method: &str) {
+        ffi_event!("Request::set_method");
         if let Some(set_method) = self.0.set_method {
             unsafe {
                 set_method(self.0.as_ptr(), CefString::new(method).as_ptr());
@@ -233,6 +242,7 @@ impl Request {
     /// qualified with an HTTP or HTTPS scheme component. Any username, password or
     /// ref component will be removed.
     pub fn set_referrer(&self, referrer_url: Option<&str>, policy: ReferrerPolicy) {
+        ffi_event!("Request::set_referrer");
         if let Some(set_referrer) = self.0.set_referrer {
             if let Some(referrer_url) = referrer_url {
                 unsafe {
@@ -247,6 +257,7 @@ impl Request {
     }
     /// Get the referrer URL.
     pub fn get_referrer_url(&self) -> String {
+        ffi_event!("Request::get_referrer_url");
         self.0
             .get_referrer_url
             .and_then(|get_referrer_url| unsafe { get_referrer_url(self.as_ptr()).as_mut() })
@@ -259,6 +270,7 @@ impl Request {
     }
     /// Get the referrer policy.
     pub fn get_referrer_policy(&self) -> ReferrerPolicy {
+        ffi_event!("Request::get_referrer_policy");
         self.0
             .get_referrer_policy
             .map(|get_referrer_policy| unsafe {
@@ -268,11 +280,13 @@ impl Request {
     }
     /// Get the post data.
     pub fn get_post_data(&self) -> PostData {
+        ffi_event!("Request::get_post_data");
         let get_post_data = self.0.get_post_data.unwrap();
         unsafe { PostData::from_ptr_unchecked(get_post_data(self.0.as_ptr())) }
     }
     /// Set the post data.
     pub fn set_post_data(&self, post_data: PostData) {
+        ffi_event!("Request::set_post_data");
         if let Some(set_post_data) = self.0.set_post_data {
             unsafe {
                 set_post_data(self.0.as_ptr(), post_data.into_raw());
@@ -281,6 +295,7 @@ impl Request {
     }
     /// Get the header values. Will not include the Referer value if any.
     pub fn get_header_map(&self) -> HashMap<String, Vec<String>> {
+        ffi_event!("Request::get_header_map");
         if let Some(get_header_map) = self.0.get_header_map {
             let map = MultiMap::new();
             unsafe { get_header_map(self.0.as_ptr(), map.as_ptr()) };
@@ -293,6 +308,7 @@ impl Request {
     /// Will not return the Referer value if any. Use [Request::get_header_map] instead if
     /// `name` might have multiple values.
     pub fn get_header_by_name(&self, name: &str) -> Option<String> {
+        ffi_event!("Request::get_header_by_name");
         self.0
             .get_header_by_name
             .and_then(|get_header_by_name| unsafe {
@@ -309,6 +325,7 @@ impl Request {
     /// existing values will not be overwritten. The Referer value cannot be set
     /// using this function.
     pub fn set_header_by_name(&self, name: &str, value: &str, overwrite: bool) {
+        ffi_event!("Request::set_header_by_name");
         if let Some(set_header_by_name) = self.0.set_header_by_name {
             unsafe {
                 set_header_by_name(
@@ -328,6 +345,7 @@ impl Request {
         post_data: PostData,
         header_map: HashMap<String, Vec<String>>,
     ) {
+        ffi_event!("Request::set");
         if let Some(set) = self.0.set {
             let url = CefString::new(url);
             let method = CefString::new(method);
@@ -347,6 +365,7 @@ impl Request {
     /// Get the flags used in combination with [URLRequest]. See
     /// [URLRequestFlags] for supported values.
     pub fn get_flags(&self) -> Vec<URLRequestFlags> {
+        ffi_event!("Request::get_flags");
         if let Some(get_flags) = self.0.get_flags {
             URLRequestFlags::from_bitfield(unsafe { get_flags(self.0.as_ptr()) })
         } else {
@@ -356,6 +375,7 @@ impl Request {
     /// Set the flags used in combination with [URLRequest]. See
     /// [URLRequestFlags] for supported values.
     pub fn set_flags(&self, flags: &[URLRequestFlags]) {
+        ffi_event!("Request::set_flags");
         if let Some(set_flags) = self.0.set_flags {
             unsafe {
                 set_flags(self.0.as_ptr(), URLRequestFlags::to_bitfield(flags));
@@ -365,6 +385,7 @@ impl Request {
     /// Get the URL to the first party for cookies used in combination with
     /// [URLRequest].
     pub fn get_first_party_for_cookies(&self) -> String {
+        ffi_event!("Request::get_first_party_for_cookies");
         self.0
             .get_first_party_for_cookies
             .and_then(|get_first_party_for_cookies| unsafe {
@@ -380,6 +401,7 @@ impl Request {
     /// Set the URL to the first party for cookies used in combination with
     /// [URLRequest].
     pub fn set_first_party_for_cookies(&self, url: &str) {
+        ffi_event!("Request::set_first_party_for_cookies");
         if let Some(set_first_party_for_cookies) = self.0.set_first_party_for_cookies {
             unsafe {
                 set_first_party_for_cookies(self.0.as_ptr(), CefString::new(url).as_ptr());
@@ -389,6 +411,7 @@ impl Request {
     /// Get the resource type for this request. Only available in the browser
     /// process.
     pub fn get_resource_type(&self) -> ResourceType {
+        ffi_event!("Request::get_resource_type");
         unsafe {
             ResourceType::from_unchecked(((*self.0.as_ptr()).get_resource_type).unwrap()(
                 self.0.as_ptr(),
@@ -399,6 +422,7 @@ impl Request {
     /// process and only applies to requests that represent a main frame or sub-
     /// frame navigation.
     pub fn get_transition_type(&self) -> TransitionType {
+        ffi_event!("Request::get_transition_type");
         TransitionType::try_from(unsafe {
             (*self.0.as_ptr()).get_transition_type.unwrap()(self.0.as_ptr()).0
         })
@@ -408,6 +432,7 @@ impl Request {
     /// specified. Can be used by [ResourceRequestHandlerCallbacks] implementations in
     /// the browser process to track a single request across multiple callbacks.
     pub fn get_identifier(&self) -> u64 {
+        ffi_event!("Request::get_identifier");
         if let Some(get_identifier) = self.0.get_identifier {
             unsafe { get_identifier(self.0.as_ptr()) }
         } else {
@@ -430,11 +455,13 @@ ref_counted_ptr! {
 
 impl PostData {
     pub fn new() -> Self {
+        ffi_event!("PostData::new");
         unsafe { Self::from_ptr_unchecked(cef_post_data_create()) }
     }
 
     /// Returns true if this object is read-only.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("PostData::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.as_ptr()) != 0 })
@@ -445,6 +472,7 @@ impl PostData {
     /// upload data). Modifying [PostData] objects with excluded elements may
     /// result in the request failing.
     pub fn has_excluded_elements(&self) -> bool {
+        ffi_event!("PostData::has_excluded_elements");
         self.0
             .has_excluded_elements
             .map(|has_excluded_elements| unsafe { has_excluded_elements(self.as_ptr()) != 0 })
@@ -452,6 +480,7 @@ impl PostData {
     }
     /// Returns the number of existing post data elements.
     pub fn get_element_count(&self) -> usize {
+        ffi_event!("PostData::get_element_count");
         self.0
             .get_element_count
             .map(|get_element_count| unsafe { get_element_count(self.as_ptr()) })
@@ -459,6 +488,7 @@ impl PostData {
     }
     /// Retrieve the post data elements.
     pub fn get_elements(&self) -> Vec<PostDataElement> {
+        ffi_event!("PostData::get_elements");
         let mut count = self.get_element_count();
         if count > 0 {
             if let Some(get_elements) = self.0.get_elements {
@@ -480,6 +510,7 @@ impl PostData {
     /// Remove the specified post data element. Returns true if the removal
     /// succeeds.
     pub fn remove_element(&self, element: &PostDataElement) -> bool {
+        ffi_event!("PostData::remove_element");
         if let Some(remove_element) = self.0.remove_element {
             unsafe { remove_element(self.as_ptr(), element.as_ptr()) != 0 }
         } else {
@@ -488,6 +519,7 @@ impl PostData {
     }
     /// Add the specified post data element. Returns true if the add succeeds.
     pub fn add_element(&self, element: &PostDataElement) -> bool {
+        ffi_event!("PostData::add_element");
         if let Some(add_element) = self.0.add_element {
             unsafe { add_element(self.as_ptr(), element.as_ptr()) != 0 }
         } else {
@@ -496,6 +528,7 @@ impl PostData {
     }
     /// Remove all existing post data elements.
     pub fn remove_elements(&self) {
+        ffi_event!("PostData::remove_elements");
         if let Some(remove_elements) = self.0.remove_elements {
             unsafe {
                 remove_elements(self.as_ptr());
@@ -519,11 +552,13 @@ ref_counted_ptr! {
 impl PostDataElement {
     /// Create a new [PostDataElement] object.
     pub fn new() -> Self {
+        ffi_event!("PostDataElement::new");
         unsafe { Self::from_ptr_unchecked(cef_post_data_element_create()) }
     }
 
     /// Returns true if this object is read-only.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("PostDataElement::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.as_ptr()) != 0 })
@@ -531,6 +566,7 @@ impl PostDataElement {
     }
     /// Remove all contents from the post data element.
     pub fn set_to_empty(&self) {
+        ffi_event!("PostDataElement::set_to_empty");
         if let Some(set_to_empty) = self.0.set_to_empty {
             unsafe {
                 set_to_empty(self.as_ptr());
@@ -539,6 +575,7 @@ impl PostDataElement {
     }
     /// The post data element will represent a file.
     pub fn set_to_file(&self, file_name: &str) {
+        ffi_event!("PostDataElement::set_to_file");
         if let Some(set_to_file) = self.0.set_to_file {
             unsafe {
                 set_to_file(self.0.as_ptr(), CefString::new(file_name).as_ptr());
@@ -548,6 +585,7 @@ impl PostDataElement {
     /// The post data element will represent bytes.  The bytes passed in will be
     /// copied.
     pub fn set_to_bytes(&self, bytes: &[u8]) {
+        ffi_event!("PostDataElement::set_to_bytes");
         if let Some(set_to_bytes) = self.0.set_to_bytes {
             unsafe {
                 set_to_bytes(
@@ -560,6 +598,7 @@ impl PostDataElement {
     }
     /// Return the type of this post data element.
     pub fn get_type(&self) -> PostDataElementType {
+        ffi_event!("PostDataElement::get_type");
         if let Some(get_type) = self.0.get_type {
             unsafe { PostDataElementType::from_unchecked(get_type(self.as_ptr()) as crate::CEnumType) }
         } else {
@@ -568,6 +607,7 @@ impl PostDataElement {
     }
     /// Return the file name.
     pub fn get_file(&self) -> String {
+        ffi_event!("PostDataElement::get_file");
         self.0
             .get_file
             .and_then(|get_file| unsafe { get_file(self.as_ptr()).as_mut() })
@@ -580,6 +620,7 @@ impl PostDataElement {
     }
     /// Return the number of bytes.
     pub fn get_bytes_count(&self) -> usize {
+        ffi_event!("PostDataElement::get_bytes_count");
         if let Some(get_bytes_count) = self.0.get_bytes_count {
             unsafe { get_bytes_count(self.as_ptr()) }
         } else {
@@ -588,6 +629,7 @@ impl PostDataElement {
     }
     /// Return the bytes.
     pub fn get_bytes(&self) -> Vec<u8> {
+        ffi_event!("PostDataElement::get_bytes");
         let size = self.get_bytes_count();
         if size > 0 {
             if let Some(get_bytes) = self.0.get_bytes {
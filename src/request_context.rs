@@ -13,11 +13,13 @@ use crate::extension::ExtensionHandler;
 use crate::extension::Extension;
 use crate::callback::CompletionCallback;
 use cef_sys::{
-    cef_browser_t, cef_create_context_shared, cef_frame_t, cef_plugin_policy_t,
-    cef_request_context_create_context, cef_request_context_get_global_context,
-    cef_request_context_handler_t, cef_request_context_settings_t, cef_request_context_t,
-    cef_request_t, cef_resource_request_handler_t, cef_string_t, cef_string_utf8_to_utf16,
-    cef_web_plugin_info_t, cef_errorcode_t,
+    cef_browser_t, cef_frame_t, cef_plugin_policy_t, cef_request_context_handler_t,
+    cef_request_context_settings_t, cef_request_context_t, cef_request_t,
+    cef_resource_request_handler_t, cef_string_t, cef_web_plugin_info_t, cef_errorcode_t,
+};
+use crate::capi::{
+    cef_create_context_shared, cef_request_context_create_context,
+    cef_request_context_get_global_context, cef_string_utf8_to_utf16,
 };
 
 use std::{
@@ -28,11 +30,13 @@ use std::{
 
 use crate::{
     browser::Browser,
+    error::{check, check_thread, Error},
     frame::Frame,
     refcounted::{RefCountedPtr, Wrapper},
     request::Request,
     resource_request_handler::{ResourceRequestHandler},
     string::CefString,
+    task::ThreadId,
     web_plugin::WebPluginInfo,
 };
 
@@ -275,6 +279,7 @@ cef_callback_impl!{
 impl RequestContext {
     /// Returns the global context object.
     pub fn global() -> Self {
+        ffi_event!("RequestContext::global");
         unsafe { Self::from_ptr_unchecked(cef_request_context_get_global_context()) }
     }
     /// Creates a new context object that shares storage with `other` and uses an
@@ -283,6 +288,7 @@ impl RequestContext {
         other: RequestContext,
         handler: Option<Box<dyn RequestContextHandlerCallbacks>>,
     ) -> Self {
+        ffi_event!("RequestContext::new_shared");
         let handler_ptr = if let Some(handler) = handler {
             RequestContextHandlerWrapper::new(handler).wrap().into_raw()
         } else {
@@ -296,32 +302,38 @@ impl RequestContext {
     /// Returns `true` if this object is pointing to the same context as `that`
     /// object.
     pub fn is_same(&self, other: RequestContext) -> bool {
+        ffi_event!("RequestContext::is_same");
         unsafe{ self.0.is_same.unwrap()(self.as_ptr(), other.into_raw()) != 0 }
     }
     /// Returns `true` if this object is sharing the same storage as `that`
     /// object.
     pub fn is_sharing_with(&self, other: RequestContext) -> bool {
+        ffi_event!("RequestContext::is_sharing_with");
         unsafe{ self.0.is_sharing_with.unwrap()(self.as_ptr(), other.into_raw()) != 0 }
     }
     /// Returns `true` if this object is the global context. The global context
     /// is used by default when creating a browser or URL request with a `None`
     /// context argument.
     pub fn is_global(&self) -> bool {
+        ffi_event!("RequestContext::is_global");
         unsafe{ self.0.is_global.unwrap()(self.as_ptr()) != 0 }
     }
     /// Returns the handler for this context if any.
     pub fn get_handler(&self) -> Option<RequestContextHandler> {
+        ffi_event!("RequestContext::get_handler");
         unsafe{ RequestContextHandler::from_ptr(self.0.get_handler.unwrap()(self.as_ptr())) }
     }
     /// Returns the cache path for this object. If `None` an "incognito mode" in-
     /// memory cache is being used.
     pub fn get_cache_path(&self) -> Option<String> {
+        ffi_event!("RequestContext::get_cache_path");
         unsafe{ CefString::from_userfree(self.0.get_cache_path.unwrap()(self.as_ptr())).map(String::from) }
     }
     /// Returns the cookie manager for this object. `callback`
     /// will be executed asnychronously on the IO thread after the manager's
     /// storage has been initialized.
     pub fn get_cookie_manager(&self, callback: impl 'static + Send + FnOnce()) -> CookieManager {
+        ffi_event!("RequestContext::get_cookie_manager");
         unsafe{ CookieManager::from_ptr_unchecked(self.0.get_cookie_manager.unwrap()(self.as_ptr(), CompletionCallback::new(callback).into_raw())) }
     }
     /// Register a scheme handler factory for the specified `scheme_name` and
@@ -336,6 +348,7 @@ impl RequestContext {
     /// optional `domain_name`. Returns `false` if an error occurs. This function
     /// may be called on any thread in the browser process.
     pub fn register_scheme_handler_factory(&self, scheme_name: &str, domain_name: Option<&str>, factory: SchemeHandlerFactory) -> bool {
+        ffi_event!("RequestContext::register_scheme_handler_factory");
         unsafe{
             self.0.register_scheme_handler_factory.unwrap()(
                 self.as_ptr(),
@@ -348,6 +361,7 @@ impl RequestContext {
     /// Clear all registered scheme handler factories. Returns `false` on error.
     /// This function may be called on any thread in the browser process.
     pub fn clear_scheme_handler_factories(&self) -> bool {
+        ffi_event!("RequestContext::clear_scheme_handler_factories");
         unsafe{ self.0.clear_scheme_handler_factories.unwrap()(self.as_ptr()) != 0 }
     }
     /// Tells all renderer processes associated with this context to throw away
@@ -356,63 +370,76 @@ impl RequestContext {
     /// cef_request_tContextHandler::OnBeforePluginLoad may be called to rebuild
     /// the plugin list cache.
     pub fn purge_plugin_list_cache(&self, reload_pages: bool) {
+        ffi_event!("RequestContext::purge_plugin_list_cache");
         unsafe{ self.0.purge_plugin_list_cache.unwrap()(self.as_ptr(), reload_pages as c_int) }
     }
     /// Returns `true` if a preference with the specified `name` exists. This
-    /// function must be called on the browser process UI thread.
-    pub fn has_preference(&self, name: &str) -> bool {
-        unsafe{
+    /// function must be called on the browser process UI thread, otherwise
+    /// [Error::WrongThread] is returned.
+    pub fn has_preference(&self, name: &str) -> Result<bool, Error> {
+        ffi_event!("RequestContext::has_preference");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             self.0.has_preference.unwrap()(
                 self.as_ptr(),
                 CefString::new(name).as_ptr(),
             ) != 0
-        }
+        })
     }
     /// Returns the value for the preference with the specified `name`. Returns
     /// `None` if the preference does not exist. The returned object contains a copy
     /// of the underlying preference value and modifications to the returned object
     /// will not modify the underlying preference value. This function must be
-    /// called on the browser process UI thread.
-    pub fn get_preference(&self, name: &str) -> Option<StoredValue> {
-        unsafe{
+    /// called on the browser process UI thread, otherwise [Error::WrongThread] is
+    /// returned.
+    pub fn get_preference(&self, name: &str) -> Result<Option<StoredValue>, Error> {
+        ffi_event!("RequestContext::get_preference");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             Value::from_ptr(self.0.get_preference.unwrap()(
                 self.as_ptr(),
                 CefString::new(name).as_ptr(),
             )).map(StoredValue::from)
-        }
+        })
     }
     /// Returns all preferences as a dictionary. If `include_defaults` is `true`
     /// then preferences currently at their default value will be included. The
     /// returned object contains a copy of the underlying preference values and
     /// modifications to the returned object will not modify the underlying
     /// preference values. This function must be called on the browser process UI
-    /// thread.
-    pub fn get_all_preferences(&self, include_defaults: bool) -> DictionaryValue {
-        unsafe{
+    /// thread, otherwise [Error::WrongThread] is returned.
+    pub fn get_all_preferences(&self, include_defaults: bool) -> Result<DictionaryValue, Error> {
+        ffi_event!("RequestContext::get_all_preferences");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             DictionaryValue::from_ptr_unchecked(self.0.get_all_preferences.unwrap()(
                 self.as_ptr(),
                 include_defaults as c_int,
             ))
-        }
+        })
     }
     /// Returns `true` if the preference with the specified `name` can be
     /// modified using SetPreference. As one example preferences set via the
     /// command-line usually cannot be modified. This function must be called on
-    /// the browser process UI thread.
-    pub fn can_set_preference(&self, name: &str) -> bool {
-        unsafe{
+    /// the browser process UI thread, otherwise [Error::WrongThread] is returned.
+    pub fn can_set_preference(&self, name: &str) -> Result<bool, Error> {
+        ffi_event!("RequestContext::can_set_preference");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             self.0.can_set_preference.unwrap()(
                 self.as_ptr(),
                 CefString::new(name).as_ptr(),
             ) != 0
-        }
+        })
     }
-    /// Set the `value` associated with preference `name`. Returns `true` if the
-    /// value is set successfully and `false` otherwise. If `value` is `None` the
+    /// Set the `value` associated with preference `name`. If `value` is `None` the
     /// preference will be restored to its default value. If setting the preference
-    /// fails then `error` will be populated with a detailed description of the
-    /// problem. This function must be called on the browser process UI thread.
-    pub fn set_preference(&self, name: &str, value: Option<StoredValue>) -> Result<(), String> {
+    /// fails then [Error::Cef] contains a detailed description of the problem.
+    /// This function must be called on the browser process UI thread, otherwise
+    /// [Error::WrongThread] is returned.
+    pub fn set_preference(&self, name: &str, value: Option<StoredValue>) -> Result<(), Error> {
+        ffi_event!("RequestContext::set_preference");
+        check_thread(ThreadId::UI)?;
         let mut error = CefString::null();
         let success = unsafe {
             self.0.set_preference.unwrap()(
@@ -423,9 +450,11 @@ impl RequestContext {
             ) != 0
         };
         if success {
-            Ok(())
-        } else {
-            Err(String::from(error))
+            return Ok(());
+        }
+        match String::from(error) {
+            error if error.is_empty() => Err(Error::Failed("RequestContext::set_preference")),
+            error => Err(Error::Cef(error)),
         }
     }
     /// Clears all certificate exceptions that were added as part of handling
@@ -434,6 +463,7 @@ impl RequestContext {
     /// being prompted again for server certificates if you reconnect quickly.
     /// `callback` will be executed on the UI thread after completion.
     pub fn clear_certificate_exceptions(&self, callback: impl 'static + Send + FnOnce()) {
+        ffi_event!("RequestContext::clear_certificate_exceptions");
         unsafe {
             self.0.clear_certificate_exceptions.unwrap()(
                 self.as_ptr(),
@@ -445,6 +475,7 @@ impl RequestContext {
     /// handling GetAuthCredentials. `callback` will be executed
     /// on the UI thread after completion.
     pub fn clear_http_auth_credentials(&self, callback: impl 'static + Send + FnOnce()) {
+        ffi_event!("RequestContext::clear_http_auth_credentials");
         unsafe {
             self.0.clear_http_auth_credentials.unwrap()(
                 self.as_ptr(),
@@ -457,6 +488,7 @@ impl RequestContext {
     /// want to call Cefshutdown(). `callback` will be executed
     /// on the UI thread after completion.
     pub fn close_all_connections(&self, callback: impl 'static + Send + FnOnce()) {
+        ffi_event!("RequestContext::close_all_connections");
         unsafe {
             self.0.close_all_connections.unwrap()(
                 self.as_ptr(),
@@ -467,6 +499,7 @@ impl RequestContext {
     /// Attempts to resolve `origin` to a list of associated IP addresses.
     /// `callback` will be executed on the UI thread after completion.
     pub fn resolve_host(&self, origin: &str, callback: impl 'static + Send + FnOnce(ErrorCode, &[IpAddr])) {
+        ffi_event!("RequestContext::resolve_host");
         unsafe {
             self.0.resolve_host.unwrap()(
                 self.as_ptr(),
@@ -522,6 +555,7 @@ impl RequestContext {
     /// See https://developer.chrome.com/extensions for extension implementation
     /// and usage documentation.
     pub fn load_extension(&self, root_directory: &str, manifest: Option<DictionaryValue>, handler: ExtensionHandler) {
+        ffi_event!("RequestContext::load_extension");
         unsafe {
             self.0.load_extension.unwrap()(
                 self.as_ptr(),
@@ -534,55 +568,60 @@ impl RequestContext {
     /// Returns `true` if this context was used to load the extension identified
     /// by `extension_id`. Other contexts sharing the same storage will also have
     /// access to the extension (see HasExtension). This function must be called on
-    /// the browser process UI thread.
-    pub fn did_load_extension(&self, extension_id: &str) -> bool {
-        unsafe{
+    /// the browser process UI thread, otherwise [Error::WrongThread] is returned.
+    pub fn did_load_extension(&self, extension_id: &str) -> Result<bool, Error> {
+        ffi_event!("RequestContext::did_load_extension");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             self.0.did_load_extension.unwrap()(
                 self.as_ptr(),
                 CefString::new(extension_id).as_ptr(),
             ) != 0
-        }
+        })
     }
     /// Returns `true` if this context has access to the extension identified by
     /// `extension_id`. This may not be the context that was used to load the
     /// extension (see DidLoadExtension). This function must be called on the
-    /// browser process UI thread.
-    pub fn has_extension(&self, extension_id: &str) -> bool {
-        unsafe{
+    /// browser process UI thread, otherwise [Error::WrongThread] is returned.
+    pub fn has_extension(&self, extension_id: &str) -> Result<bool, Error> {
+        ffi_event!("RequestContext::has_extension");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             self.0.has_extension.unwrap()(
                 self.as_ptr(),
                 CefString::new(extension_id).as_ptr(),
             ) != 0
-        }
+        })
     }
     /// Retrieve the list of all extensions that this context has access to (see
-    /// HasExtension). `extension_ids` will be populated with the list of extension
-    /// ID values. Returns `true` on success. This function must be called on the
-    /// browser process UI thread.
-    pub fn get_extensions(&self) -> Option<Vec<String>> {
-        unsafe {
-            let mut string_list = CefStringList::new();
-            let success = self.0.get_extensions.unwrap()(
+    /// HasExtension) by their ID. This function must be called on the browser
+    /// process UI thread, otherwise [Error::WrongThread] is returned.
+    pub fn get_extensions(&self) -> Result<Vec<String>, Error> {
+        ffi_event!("RequestContext::get_extensions");
+        check_thread(ThreadId::UI)?;
+        let mut string_list = CefStringList::new();
+        let success = unsafe {
+            self.0.get_extensions.unwrap()(
                 self.as_ptr(),
                 string_list.as_mut_ptr()
-            ) != 0;
-            if success {
-                Some(string_list.into_iter().map(String::from).collect())
-            } else {
-                None
-            }
-        }
+            ) != 0
+        };
+        check(success, "RequestContext::get_extensions")?;
+        Ok(string_list.into_iter().map(String::from).collect())
     }
     /// Returns the extension matching `extension_id` or `None` if no matching
     /// extension is accessible in this context (see HasExtension). This function
-    /// must be called on the browser process UI thread.
-    pub fn get_extension(&self, extension_id: &str) -> Option<Extension> {
-        unsafe{
+    /// must be called on the browser process UI thread, otherwise
+    /// [Error::WrongThread] is returned.
+    pub fn get_extension(&self, extension_id: &str) -> Result<Option<Extension>, Error> {
+        ffi_event!("RequestContext::get_extension");
+        check_thread(ThreadId::UI)?;
+        Ok(unsafe{
             Extension::from_ptr(self.0.get_extension.unwrap()(
                 self.as_ptr(),
                 CefString::new(extension_id).as_ptr(),
             ))
-        }
+        })
     }
 }
 
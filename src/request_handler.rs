@@ -403,6 +403,7 @@ impl SelectClientCertificateCallback {
     /// Chooses the specified certificate for client certificate authentication.
     /// None value means that no client certificate should be used.
     pub fn select(&self, cert: Option<X509Certificate>) {
+        ffi_event!("SelectClientCertificateCallback::select");
         unsafe {
             self.0.select.unwrap()(
                 self.0.as_ptr(),
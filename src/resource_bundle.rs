@@ -1,7 +1,8 @@
 use core::ffi::c_void;
 use crate::resource_bundle_handler::ScaleFactor;
 use crate::string::CefString;
-use cef_sys::{cef_resource_bundle_t, cef_resource_bundle_get_global};
+use cef_sys::cef_resource_bundle_t;
+use crate::capi::cef_resource_bundle_get_global;
 use std::{slice, ptr};
 
 ref_counted_ptr!{
@@ -16,6 +17,7 @@ ref_counted_ptr!{
 impl ResourceBundle {
     /// Returns the global resource bundle instance.
     pub fn global() -> ResourceBundle {
+        ffi_event!("ResourceBundle::global");
         unsafe {
             ResourceBundle::from_ptr_unchecked(cef_resource_bundle_get_global())
         }
@@ -25,6 +27,7 @@ impl ResourceBundle {
     /// string if the value is not found. Include cef_pack_strings.h for a listing
     /// of valid string ID values.
     pub fn get_localized_string(&self, string_id: i32) -> Option<String> {
+        ffi_event!("ResourceBundle::get_localized_string");
         unsafe {
             CefString::from_userfree(self.0.get_localized_string.unwrap()(self.as_ptr(), string_id as _)).map(String::from)
         }
@@ -33,6 +36,7 @@ impl ResourceBundle {
     /// the value is found then this function will return `Some(data)`. If the value is not found then this function
     /// will return `None`.
     pub fn get_data_resource(&self, resource_id: i32) -> Option<&'static [u8]> {
+        ffi_event!("ResourceBundle::get_data_resource");
         unsafe {
             let mut data_ptr = ptr::null_mut();
             let mut size = 0;
@@ -50,6 +54,7 @@ impl ResourceBundle {
     /// is found then this function will return `Some(data)`. If the value is not found then this function will
     /// return `None`.
     pub fn get_data_resource_for_scale(&self, resource_id: i32, scale_factor: Option<ScaleFactor>) -> Option<&'static [u8]> {
+        ffi_event!("ResourceBundle::get_data_resource_for_scale");
         unsafe {
             let mut data_ptr = ptr::null_mut();
             let mut size = 0;
@@ -4,6 +4,7 @@ use crate::{
     refcounted::{RefCountedPtr, Wrapper},
     request::Request,
     string::CefString,
+    task::IoThread,
     url_request::{
         CookieAccessFilter, RequestCallback, ResourceHandler,
         ResponseFilter, URLRequestStatus,
@@ -40,6 +41,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// callback.
     fn get_cookie_access_filter(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -56,6 +58,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// request asynchronously. Return [ReturnValue::Cancel] to cancel the request immediately.
     fn on_before_resource_load(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -70,6 +73,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// handler for the resource return a [ResourceHandlerCallbacks] object.
     fn get_resource_handler(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -85,6 +89,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// and can be changed if desired.
     fn on_resource_redirect(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -98,6 +103,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// object cannot be modified in this callback.
     fn on_resource_response(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -110,6 +116,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// and `response` canoot be modified in this callback.
     fn get_resource_response_filter(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -132,6 +139,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// frame is invalid.
     fn on_resource_load_complete(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -151,6 +159,7 @@ pub trait ResourceRequestHandlerCallbacks: 'static + Sync + Send {
     /// ANALYSIS BEFORE ALLOWING OS EXECUTION.
     fn on_protocol_execution(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -196,7 +205,7 @@ cef_callback_impl! {
             request: Request        : *mut cef_request_t,
         ) -> *mut cef_cookie_access_filter_t
         {
-            self.0.get_cookie_access_filter(browser, frame, request).map(|cef| cef.into_raw()).unwrap_or(null_mut())
+            self.0.get_cookie_access_filter(unsafe { IoThread::new_unchecked() }, browser, frame, request).map(|cef| cef.into_raw()).unwrap_or(null_mut())
         }
         fn before_resource_load(
             &self,
@@ -206,6 +215,7 @@ cef_callback_impl! {
             callback: RequestCallback: *mut cef_request_callback_t,
         ) -> cef_return_value_t::Type {
             self.0.on_before_resource_load(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -220,6 +230,7 @@ cef_callback_impl! {
             request: Request: *mut cef_request_t,
         ) -> *mut cef_resource_handler_t {
             self.0.get_resource_handler(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -236,6 +247,7 @@ cef_callback_impl! {
         ) {
             let mut new_url_rust = String::from(&*new_url);
             self.0.on_resource_redirect(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -253,6 +265,7 @@ cef_callback_impl! {
             response: Response: *mut cef_response_t,
         ) -> std::os::raw::c_int {
             self.0.on_resource_response(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -269,6 +282,7 @@ cef_callback_impl! {
             response: Response: *mut cef_response_t,
         ) -> *mut cef_response_filter_t {
             self.0.get_resource_response_filter(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -286,6 +300,7 @@ cef_callback_impl! {
             received_content_length: i64: i64,
         ) {
             self.0.on_resource_load_complete(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -303,6 +318,7 @@ cef_callback_impl! {
             allow_os_execution: Option<&mut std::os::raw::c_int>: *mut std::os::raw::c_int,
         ) {
             if self.0.on_protocol_execution(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
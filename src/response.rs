@@ -1,4 +1,5 @@
-use cef_sys::{cef_response_t, cef_response_create, cef_string_userfree_utf16_free};
+use cef_sys::cef_response_t;
+use crate::capi::{cef_response_create, cef_string_userfree_utf16_free};
 use std::collections::HashMap;
 
 use crate::{
@@ -15,18 +16,21 @@ ref_counted_ptr! {
 
 impl Response {
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("Response::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.0.as_ptr()) != 0 })
             .unwrap_or(true)
         }
     pub fn get_error(&self) -> ErrorCode {
+        ffi_event!("Response::get_error");
         self.0
             .get_error
             .map(|get_error| unsafe { ErrorCode::from_unchecked(get_error(self.0.as_ptr())) })
             .unwrap_or(ErrorCode::Failed)
     }
     pub fn set_error(&self, error: ErrorCode) {
+        ffi_event!("Response::set_error");
         if let Some(set_error) = self.0.set_error {
             unsafe {
                 set_error(self.0.as_ptr(), error as _);
@@ -34,12 +38,14 @@ impl Response {
         }
     }
     pub fn get_status(&self) -> i32 {
+        ffi_event!("Response::get_status");
         self.0
             .get_status
             .map(|get_status| unsafe { get_status(self.0.as_ptr()) } as _)
             .unwrap_or(0)
     }
     pub fn set_status(&self, status: i32) {
+        ffi_event!("Response::set_status");
         if let Some(set_status) = self.0.set_status {
             unsafe {
                 set_status(self.0.as_ptr(), status as _);
@@ -47,6 +53,7 @@ impl Response {
         }
     }
     pub fn get_status_text(&self) -> String {
+        ffi_event!("Response::get_status_text");
         self.0
             .get_status_text
             .and_then(|get_status_text| unsafe { get_status_text(self.as_ptr()).as_mut() })
@@ -58,6 +65,7 @@ impl Response {
             .unwrap_or_default()
     }
     pub fn set_status_text(&self, status_text: &str) {
+        ffi_event!("Response::set_status_text");
         if let Some(set_status_text) = self.0.set_status_text {
             unsafe {
                 set_status_text(self.0.as_ptr(), CefString::new(status_text).as_ptr());
@@ -65,6 +73,7 @@ impl Response {
         }
     }
     pub fn get_mime_type(&self) -> String {
+        ffi_event!("Response::get_mime_type");
         self.0
             .get_mime_type
             .and_then(|get_mime_type| unsafe { get_mime_type(self.as_ptr()).as_mut() })
@@ -76,6 +85,7 @@ impl Response {
             .unwrap_or_default()
     }
     pub fn set_mime_type(&self, mime_type: &str) {
+        ffi_event!("Response::set_mime_type");
         if let Some(set_mime_type) = self.0.set_mime_type {
             unsafe {
                 set_mime_type(self.0.as_ptr(), CefString::new(mime_type).as_ptr());
@@ -83,6 +93,7 @@ impl Response {
         }
     }
     pub fn get_charset(&self) -> String {
+        ffi_event!("Response::get_charset");
         self.0
             .get_charset
             .and_then(|get_charset| unsafe { get_charset(self.as_ptr()).as_mut() })
@@ -94,6 +105,7 @@ impl Response {
             .unwrap_or_default()
     }
     pub fn set_charset(&self, charset: &str) {
+        ffi_event!("Response::set_charset");
         if let Some(set_charset) = self.0.set_charset {
             unsafe {
                 set_charset(self.0.as_ptr(), CefString::new(charset).as_ptr());
@@ -101,6 +113,7 @@ impl Response {
         }
     }
     pub fn get_header_by_name(&self, name: &str) -> String {
+        ffi_event!("Response::get_header_by_name");
         self.0
             .get_header_by_name
             .and_then(|get_header_by_name| unsafe { get_header_by_name(self.as_ptr(), CefString::new(name).as_ptr()).as_mut() })
@@ -112,6 +125,7 @@ impl Response {
             .unwrap_or_default()
     }
     pub fn set_header_by_name(&self, name: &str, value: &str, overwrite: bool) {
+        ffi_event!("Response::set_header_by_name");
         if let Some(set_header_by_name) = self.0.set_header_by_name {
             unsafe {
                 set_header_by_name(self.0.as_ptr(), CefString::new(name).as_ptr(), CefString::new(value).as_ptr(), overwrite as _);
@@ -119,6 +133,7 @@ impl Response {
         }
     }
     pub fn get_header_map(&self) -> HashMap<String, Vec<String>> {
+        ffi_event!("Response::get_header_map");
         self.0
             .get_header_map
             .map(|get_header_map| {
@@ -129,12 +144,14 @@ impl Response {
             .unwrap_or_else(HashMap::new)
     }
     pub fn set_header_map(&self, header_map: &HashMap<String, Vec<String>>) {
+        ffi_event!("Response::set_header_map");
         let map: MultiMap = header_map.into();
         self.0
             .set_header_map
             .map(|set_header_map| unsafe { set_header_map(self.as_ptr(), map.as_ptr()); });
     }
     pub fn get_url(&self) -> String {
+        ffi_event!("Response::get_url");
         self.0
             .get_url
             .and_then(|get_url| unsafe { get_url(self.as_ptr()).as_mut() })
@@ -146,6 +163,7 @@ impl Response {
             .unwrap_or_default()
     }
     pub fn set_url(&self, url: &str) {
+        ffi_event!("Response::set_url");
         if let Some(set_url) = self.0.set_url {
             unsafe {
                 set_url(self.0.as_ptr(), CefString::new(url).as_ptr());
@@ -0,0 +1,295 @@
+//! A single entry point that runs the right kind of process.
+//!
+//! CEF starts the same executable again for its renderer, GPU and utility
+//! processes. Every one of them has to call [crate::execute_process] with an
+//! [App] that provides the handlers for that process type, and the browser
+//! process then has to initialize the context, run the message loop and shut
+//! down in the right order. [run] does all of that. Example usage:
+//!
+//! ```ignore
+//!  fn main() {
+//!      let settings = cef::settings::Settings::new();
+//!      let exit_code = cef::run(|| MyBrowserApp::new(), || MyRendererApp::new(), settings).unwrap();
+//!      std::process::exit(exit_code);
+//!  }
+//! ```
+//!
+//! The factories are only called in the process that needs them, so the
+//! renderer never constructs browser process state and vice versa.
+
+use crate::{
+    app::{App, AppCallbacks},
+    browser_process_handler::BrowserProcessHandler,
+    command_line::CommandLine,
+    misc_fns::{execute_process, process_type, Context, ProcessType},
+    render_process_handler::RenderProcessHandler,
+    resource_bundle_handler::ResourceBundleHandler,
+    sandbox::SandboxInfo,
+    scheme_registrar::SchemeRegistrar,
+    settings::Settings,
+    version::VersionMismatch,
+};
+use std::{fmt, sync::Arc};
+
+/// Application callbacks for the browser process.
+pub trait BrowserApp: 'static + Send + Sync {
+    /// See [AppCallbacks::on_before_command_line_processing].
+    fn on_before_command_line_processing(&self, process_type: Option<&str>, command_line: CommandLine) {}
+    /// See [AppCallbacks::get_resource_bundle_handler].
+    fn get_resource_bundle_handler(&self) -> Option<ResourceBundleHandler> {
+        None
+    }
+    /// See [AppCallbacks::get_browser_process_handler].
+    fn get_browser_process_handler(&self) -> Option<BrowserProcessHandler> {
+        None
+    }
+    /// Called on the main thread after the context has been initialized and right
+    /// before the message loop starts. This is the place to create the first
+    /// browser.
+    fn on_initialized(&self, context: &Context) {}
+    /// Called on the main thread after the message loop has quit and before the
+    /// context is shut down. All browsers have to be closed at this point.
+    fn on_shutdown(&self, context: &Context) {}
+}
+
+/// Application callbacks for the render process.
+pub trait RendererApp: 'static + Send + Sync {
+    /// See [AppCallbacks::on_before_command_line_processing].
+    fn on_before_command_line_processing(&self, process_type: Option<&str>, command_line: CommandLine) {}
+    /// See [AppCallbacks::get_resource_bundle_handler].
+    fn get_resource_bundle_handler(&self) -> Option<ResourceBundleHandler> {
+        None
+    }
+    /// See [AppCallbacks::get_render_process_handler].
+    fn get_render_process_handler(&self) -> Option<RenderProcessHandler> {
+        None
+    }
+}
+
+/// Errors returned by [run], [Runtime::run] and [crate::execute_process].
+#[derive(Debug)]
+pub enum RunError {
+    /// The loaded libcef is incompatible with this crate.
+    Version(VersionMismatch),
+    /// libcef couldn't be loaded.
+    #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+    Library(crate::LibraryError),
+    /// [Context::initialize] failed.
+    Initialize(std::io::Error),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Version(err) => err.fmt(f),
+            #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+            RunError::Library(err) => err.fmt(f),
+            RunError::Initialize(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RunError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RunError::Version(err) => Some(err),
+            #[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+            RunError::Library(err) => Some(err),
+            RunError::Initialize(err) => Some(err),
+        }
+    }
+}
+
+impl From<VersionMismatch> for RunError {
+    fn from(err: VersionMismatch) -> RunError {
+        RunError::Version(err)
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "dynamic-loading"))]
+impl From<crate::LibraryError> for RunError {
+    fn from(err: crate::LibraryError) -> RunError {
+        RunError::Library(err)
+    }
+}
+
+/// Run the current process as whatever CEF started it as and return its exit
+/// code. Shorthand for [Runtime::new] with the browser and renderer factories set.
+pub fn run<B, R, FB, FR>(browser: FB, renderer: FR, settings: Settings) -> Result<i32, RunError>
+where
+    B: BrowserApp,
+    R: RendererApp,
+    FB: FnOnce() -> B,
+    FR: FnOnce() -> R,
+{
+    Runtime::new(settings).browser(browser).renderer(renderer).run()
+}
+
+type SchemeRegistration = Arc<dyn Fn(&SchemeRegistrar) + Send + Sync>;
+
+/// Builder for running a process with additional options.
+pub struct Runtime<FB, FR> {
+    settings: Settings,
+    browser: FB,
+    renderer: FR,
+    custom_schemes: Option<SchemeRegistration>,
+}
+
+impl Runtime<fn() -> NoApp, fn() -> NoApp> {
+    pub fn new(settings: Settings) -> Self {
+        Runtime {
+            settings,
+            browser: || NoApp,
+            renderer: || NoApp,
+            custom_schemes: None,
+        }
+    }
+}
+
+impl<B, R, FB, FR> Runtime<FB, FR>
+where
+    B: BrowserApp,
+    R: RendererApp,
+    FB: FnOnce() -> B,
+    FR: FnOnce() -> R,
+{
+    /// Set the factory for the browser process callbacks.
+    pub fn browser<B2: BrowserApp, FB2: FnOnce() -> B2>(self, browser: FB2) -> Runtime<FB2, FR> {
+        Runtime {
+            settings: self.settings,
+            browser,
+            renderer: self.renderer,
+            custom_schemes: self.custom_schemes,
+        }
+    }
+    /// Set the factory for the render process callbacks.
+    pub fn renderer<R2: RendererApp, FR2: FnOnce() -> R2>(self, renderer: FR2) -> Runtime<FB, FR2> {
+        Runtime {
+            settings: self.settings,
+            browser: self.browser,
+            renderer,
+            custom_schemes: self.custom_schemes,
+        }
+    }
+    /// Register custom schemes. The function is called in every process, which
+    /// ensures that all of them register the same schemes.
+    pub fn custom_schemes<F: Fn(&SchemeRegistrar) + Send + Sync + 'static>(mut self, register: F) -> Self {
+        self.custom_schemes = Some(Arc::new(register));
+        self
+    }
+
+    /// Run the current process. For sub-processes this blocks until CEF is done
+    /// with them. For the browser process this initializes the context, calls
+    /// [BrowserApp::on_initialized], runs the message loop until
+    /// [crate::quit_message_loop] is called, calls [BrowserApp::on_shutdown] and
+    /// shuts CEF down again.
+    ///
+    /// [Settings::browser_subprocess_path] is ignored, all sub-processes are
+    /// started from the current executable.
+    pub fn run(self) -> Result<i32, RunError> {
+        #[cfg(feature = "sandbox")]
+        let sandbox_info = SandboxInfo::new();
+        #[cfg(feature = "sandbox")]
+        let sandbox_info = Some(&sandbox_info);
+        #[cfg(not(feature = "sandbox"))]
+        let sandbox_info: Option<&SandboxInfo> = None;
+
+        let custom_schemes = self.custom_schemes;
+        match process_type() {
+            ProcessType::Browser => {
+                let browser = Arc::new((self.browser)());
+                let app = App::new(BrowserAppWrapper {
+                    delegate: browser.clone(),
+                    custom_schemes,
+                });
+                let exit_code = execute_process(Some(app.clone()), sandbox_info)?;
+                if exit_code >= 0 {
+                    return Ok(exit_code);
+                }
+
+                let mut settings = self.settings;
+                settings.browser_subprocess_path = None;
+                let context = Context::initialize(&settings, Some(app), sandbox_info)
+                    .map_err(RunError::Initialize)?;
+                browser.on_initialized(&context);
+                context.run_message_loop();
+                browser.on_shutdown(&context);
+                drop(context);
+                Ok(0)
+            }
+            ProcessType::Renderer => {
+                let app = App::new(RendererAppWrapper {
+                    delegate: (self.renderer)(),
+                    custom_schemes,
+                });
+                Ok(execute_process(Some(app), sandbox_info)?)
+            }
+            ProcessType::Gpu | ProcessType::Utility | ProcessType::Other => {
+                let app = App::new(SubprocessAppWrapper { custom_schemes });
+                Ok(execute_process(Some(app), sandbox_info)?)
+            }
+        }
+    }
+}
+
+/// Placeholder for processes that don't need any callbacks.
+pub struct NoApp;
+
+impl BrowserApp for NoApp {}
+impl RendererApp for NoApp {}
+
+fn register_custom_schemes(custom_schemes: &Option<SchemeRegistration>, registrar: SchemeRegistrar) {
+    if let Some(register) = custom_schemes {
+        register(&registrar);
+    }
+}
+
+struct BrowserAppWrapper<B: BrowserApp> {
+    delegate: Arc<B>,
+    custom_schemes: Option<SchemeRegistration>,
+}
+
+impl<B: BrowserApp> AppCallbacks for BrowserAppWrapper<B> {
+    fn on_before_command_line_processing(&self, process_type: Option<&str>, command_line: CommandLine) {
+        self.delegate.on_before_command_line_processing(process_type, command_line);
+    }
+    fn on_register_custom_schemes(&self, registrar: SchemeRegistrar) {
+        register_custom_schemes(&self.custom_schemes, registrar);
+    }
+    fn get_resource_bundle_handler(&self) -> Option<ResourceBundleHandler> {
+        self.delegate.get_resource_bundle_handler()
+    }
+    fn get_browser_process_handler(&self) -> Option<BrowserProcessHandler> {
+        self.delegate.get_browser_process_handler()
+    }
+}
+
+struct RendererAppWrapper<R: RendererApp> {
+    delegate: R,
+    custom_schemes: Option<SchemeRegistration>,
+}
+
+impl<R: RendererApp> AppCallbacks for RendererAppWrapper<R> {
+    fn on_before_command_line_processing(&self, process_type: Option<&str>, command_line: CommandLine) {
+        self.delegate.on_before_command_line_processing(process_type, command_line);
+    }
+    fn on_register_custom_schemes(&self, registrar: SchemeRegistrar) {
+        register_custom_schemes(&self.custom_schemes, registrar);
+    }
+    fn get_resource_bundle_handler(&self) -> Option<ResourceBundleHandler> {
+        self.delegate.get_resource_bundle_handler()
+    }
+    fn get_render_process_handler(&self) -> Option<RenderProcessHandler> {
+        self.delegate.get_render_process_handler()
+    }
+}
+
+struct SubprocessAppWrapper {
+    custom_schemes: Option<SchemeRegistration>,
+}
+
+impl AppCallbacks for SubprocessAppWrapper {
+    fn on_register_custom_schemes(&self, registrar: SchemeRegistrar) {
+        register_custom_schemes(&self.custom_schemes, registrar);
+    }
+}
@@ -21,7 +21,7 @@ impl SandboxInfo {
     pub fn new() -> Self {
         #[cfg(target_os = "windows")]
         {
-            Self(unsafe { cef_sys::cef_sandbox_info_create() })
+            Self(unsafe { crate::capi::cef_sandbox_info_create() })
         }
         #[cfg(not(target_os = "windows"))]
         {
@@ -38,7 +38,7 @@ impl Drop for SandboxInfo {
     fn drop(&mut self) {
         #[cfg(all(target_os = "windows", feature = "sandbox"))]
         unsafe {
-            cef_sys::cef_sandbox_info_destroy(self.0);
+            crate::capi::cef_sandbox_info_destroy(self.0);
         }
     }
 }
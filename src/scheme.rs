@@ -1,12 +1,18 @@
 use parking_lot::Mutex;
-use cef_sys::{cef_scheme_handler_factory_t, cef_browser_t, cef_frame_t, cef_string_t, cef_request_t, cef_resource_handler_t, cef_register_scheme_handler_factory, cef_clear_scheme_handler_factories};
+use cef_sys::{
+    cef_scheme_handler_factory_t, cef_browser_t, cef_frame_t, cef_string_t, cef_request_t,
+    cef_resource_handler_t,
+};
+use crate::capi::{cef_register_scheme_handler_factory, cef_clear_scheme_handler_factories};
 use crate::{
     browser::Browser,
+    error::{check, Error},
     frame::Frame,
     request::Request,
     refcounted::{RefCountedPtr, Wrapper},
     url_request::ResourceHandler,
     string::CefString,
+    task::IoThread,
 };
 use std::ptr;
 
@@ -37,11 +43,12 @@ impl SchemeHandlerFactory {
     /// domain names. If `scheme_name` is [SchemeName::Custom] then you must also implement the
     /// [App::on_register_custom_schemes] function in all processes. This
     /// function may be called multiple times to change or remove the factory that
-    /// matches the specified `scheme_name` and optional `scheme_name.domain`. Returns false
-    /// if an error occurs. This function may be called on any thread in the
+    /// matches the specified `scheme_name` and optional `scheme_name.domain`. This
+    /// function may be called on any thread in the
     /// browser process. Using this function is equivalent to calling
     /// [RequestContext::request_context_get_global_context]().register_scheme_handler_factory()
-    pub fn register<'a>(&self, scheme_name: SchemeName<'a>) -> bool {
+    pub fn register<'a>(&self, scheme_name: SchemeName<'a>) -> Result<(), Error> {
+        ffi_event!("SchemeHandlerFactory::register");
         let (scheme_name, domain_name) = match scheme_name {
             SchemeName::Custom { name }   => (name, None),
             SchemeName::HTTP   { domain } => ("http",  domain),
@@ -53,18 +60,21 @@ impl SchemeHandlerFactory {
         };
         let scheme_name = CefString::from(scheme_name);
         let domain_name = domain_name.map(CefString::from);
-        unsafe {
+        let success = unsafe {
             cef_register_scheme_handler_factory(scheme_name.as_ptr(), domain_name.map(|s| s.as_ptr()).unwrap_or_else(ptr::null), self.as_ptr()) != 0
-        }
+        };
+        check(success, "SchemeHandlerFactory::register")
     }
     /// Clear all scheme handler factories registered with the global request
-    /// context. Returns false on error. This function may be called on any
-    /// thread in the browser process. Using this function is equivalent to calling
+    /// context. This function may be called on any thread in the browser
+    /// process. Using this function is equivalent to calling
     /// [RequestContext::request_context_get_global_context]()->clear_scheme_handler_factories().
-    pub fn clear() -> bool {
-        unsafe {
+    pub fn clear() -> Result<(), Error> {
+        ffi_event!("SchemeHandlerFactory::clear");
+        let success = unsafe {
             cef_clear_scheme_handler_factories() != 0
-        }
+        };
+        check(success, "SchemeHandlerFactory::clear")
     }
 }
 
@@ -77,6 +87,7 @@ pub trait SchemeHandlerFactoryCallbacks: 'static + Send {
     /// passed to this function cannot be modified.
     fn create(
         &self,
+        io_thread: IoThread,
         browser: Browser,
         frame: Frame,
         scheme_name: &str,
@@ -108,6 +119,7 @@ cef_callback_impl!{
             request: Request: *mut cef_request_t
         ) -> *mut cef_resource_handler_t {
             self.0.lock().create(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 &scheme_name,
@@ -7,6 +7,9 @@ use cef_sys::{cef_log_severity_t, cef_settings_t};
 use std::path::PathBuf;
 use uuid::Uuid;
 
+mod validation;
+pub use validation::*;
+
 /// Log severity levels.
 #[repr(C)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -34,6 +37,24 @@ impl LogSeverity {
     }
 }
 
+impl std::str::FromStr for LogSeverity {
+    type Err = String;
+    /// Parses the same values that are accepted by the "log-severity" command-line
+    /// switch, plus "default".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_ascii_lowercase() {
+            "default" => Ok(LogSeverity::Default),
+            "verbose" | "debug" => Ok(LogSeverity::Verbose),
+            "info" => Ok(LogSeverity::Info),
+            "warning" => Ok(LogSeverity::Warning),
+            "error" => Ok(LogSeverity::Error),
+            "fatal" => Ok(LogSeverity::Fatal),
+            "disable" => Ok(LogSeverity::Disable),
+            _ => Err(format!("unknown log severity `{}`", s)),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Settings {
     /// Set the path to a separate executable that will be launched for sub-processes.
@@ -251,8 +272,8 @@ impl Settings {
             size: std::mem::size_of::<cef_settings_t>(),
             no_sandbox: !use_sandbox as c_int,
             browser_subprocess_path: path_to_cef(self.browser_subprocess_path.as_ref())?,
-            framework_dir_path: path_to_cef(self.framework_dir_path.as_ref())?,
-            main_bundle_path: path_to_cef(self.main_bundle_path.as_ref())?,
+            framework_dir_path: path_to_cef(self.framework_dir_path.as_ref().filter(|_| cfg!(target_os = "macos")))?,
+            main_bundle_path: path_to_cef(self.main_bundle_path.as_ref().filter(|_| cfg!(target_os = "macos")))?,
             multi_threaded_message_loop: self.multi_threaded_message_loop as c_int,
             external_message_pump: self.external_message_pump as c_int,
             windowless_rendering_enabled: self.windowless_rendering_enabled as c_int,
@@ -266,7 +287,7 @@ impl Settings {
             product_version: string_to_cef(self.product_version.as_ref()),
             locale: string_to_cef(self.locale.as_ref()),
             log_file: path_to_cef(self.log_file.as_ref())?,
-            log_severity: cef_log_severity_t::LOGSEVERITY_DEFAULT,
+            log_severity: self.log_severity as _,
             javascript_flags: string_to_cef(self.javascript_flags.as_ref()),
             resources_dir_path: path_to_cef(self.resources_dir_path.as_ref())?,
             locales_dir_path: path_to_cef(self.locales_dir_path.as_ref())?,
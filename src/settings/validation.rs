@@ -0,0 +1,410 @@
+use super::Settings;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// A problem with a [Settings] value that would make [crate::Context::initialize]
+/// fail or make CEF misbehave later on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsError {
+    /// The path configured for `field` doesn't exist. All paths are canonicalized
+    /// before they're passed to CEF, so they have to exist at initialization time.
+    PathNotFound { field: &'static str, path: PathBuf },
+    /// The path configured for `field` exists, but has the wrong type (file
+    /// instead of directory or vice versa).
+    WrongPathType {
+        field: &'static str,
+        path: PathBuf,
+        expected_directory: bool,
+    },
+    /// [Settings::cache_path] is neither equal to nor a child directory of
+    /// [Settings::root_cache_path].
+    CachePathOutsideRoot {
+        cache_path: PathBuf,
+        root_cache_path: PathBuf,
+    },
+    /// Windowless browsers are planned, but [Settings::windowless_rendering_enabled]
+    /// is not set.
+    WindowlessRenderingDisabled,
+    /// `field` is set, but not supported on the current platform.
+    UnsupportedOnPlatform { field: &'static str },
+    /// `field` is set, but only has an effect in combination with `requires`.
+    MissingRequirement {
+        field: &'static str,
+        requires: &'static str,
+    },
+    /// Both fields are set, but they can't be used together.
+    Conflict {
+        field: &'static str,
+        conflicts_with: &'static str,
+    },
+    /// The value of `field` is outside of the range accepted by CEF.
+    InvalidValue {
+        field: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::PathNotFound { field, path } => {
+                write!(f, "{}: {} does not exist", field, path.display())
+            }
+            SettingsError::WrongPathType {
+                field,
+                path,
+                expected_directory: true,
+            } => write!(f, "{}: {} is not a directory", field, path.display()),
+            SettingsError::WrongPathType {
+                field,
+                path,
+                expected_directory: false,
+            } => write!(f, "{}: {} is not a file", field, path.display()),
+            SettingsError::CachePathOutsideRoot {
+                cache_path,
+                root_cache_path,
+            } => write!(
+                f,
+                "cache_path: {} is not inside root_cache_path {}",
+                cache_path.display(),
+                root_cache_path.display()
+            ),
+            SettingsError::WindowlessRenderingDisabled => write!(
+                f,
+                "windowless_rendering_enabled must be set to create windowless browsers"
+            ),
+            SettingsError::UnsupportedOnPlatform { field } => {
+                write!(f, "{} is not supported on this platform", field)
+            }
+            SettingsError::MissingRequirement { field, requires } => {
+                write!(f, "{} requires {} to be set", field, requires)
+            }
+            SettingsError::Conflict {
+                field,
+                conflicts_with,
+            } => write!(f, "{} cannot be combined with {}", field, conflicts_with),
+            SettingsError::InvalidValue { field, reason } => write!(f, "{}: {}", field, reason),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// All problems found by [Settings::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsErrors(pub Vec<SettingsError>);
+
+impl fmt::Display for SettingsErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid settings")?;
+        for (i, error) in self.0.iter().enumerate() {
+            write!(f, "{} {}", if i == 0 { ":" } else { ";" }, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SettingsErrors {}
+
+/// Describes how the application is going to use CEF, so that [Settings::validate]
+/// can check the settings against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ValidationOptions {
+    /// Set if windowless (off-screen rendered) browsers are going to be created.
+    pub windowless_browsers: bool,
+}
+
+impl ValidationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn windowless_browsers(mut self, windowless_browsers: bool) -> Self {
+        self.windowless_browsers = windowless_browsers;
+        self
+    }
+}
+
+impl Settings {
+    /// Check the settings for problems that CEF would otherwise only report by
+    /// failing to initialize or by crashing later on.
+    ///
+    /// This is called by [crate::Context::initialize] with the default
+    /// [ValidationOptions]. Call it yourself beforehand to describe your planned
+    /// usage and to get the structured errors.
+    pub fn validate(&self, options: &ValidationOptions) -> Result<(), SettingsErrors> {
+        let mut errors = Vec::new();
+
+        let mut check_path =
+            |field: &'static str, path: Option<&PathBuf>, expected_directory: bool| {
+                if let Some(path) = path {
+                    match std::fs::metadata(path) {
+                        Err(_) => errors.push(SettingsError::PathNotFound {
+                            field,
+                            path: path.clone(),
+                        }),
+                        Ok(metadata) if metadata.is_dir() != expected_directory => {
+                            errors.push(SettingsError::WrongPathType {
+                                field,
+                                path: path.clone(),
+                                expected_directory,
+                            })
+                        }
+                        Ok(_) => {}
+                    }
+                }
+            };
+        check_path(
+            "browser_subprocess_path",
+            self.browser_subprocess_path.as_ref(),
+            false,
+        );
+        check_path("cache_path", self.cache_path.as_ref(), true);
+        check_path("root_cache_path", self.root_cache_path.as_ref(), true);
+        check_path("user_data_path", self.user_data_path.as_ref(), true);
+        check_path("log_file", self.log_file.as_ref(), false);
+        check_path("resources_dir_path", self.resources_dir_path.as_ref(), true);
+        check_path("locales_dir_path", self.locales_dir_path.as_ref(), true);
+        // CEF ignores these fields on other platforms, so configurations shared
+        // between platforms may contain them.
+        if cfg!(target_os = "macos") {
+            check_path("framework_dir_path", self.framework_dir_path.as_ref(), true);
+            check_path("main_bundle_path", self.main_bundle_path.as_ref(), true);
+        } else {
+            if self.framework_dir_path.is_some() {
+                log::warn!("framework_dir_path is ignored on this platform");
+            }
+            if self.main_bundle_path.is_some() {
+                log::warn!("main_bundle_path is ignored on this platform");
+            }
+        }
+
+        if let (Some(cache_path), Some(root_cache_path)) = (&self.cache_path, &self.root_cache_path)
+        {
+            let canonical =
+                |path: &Path| dunce::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+            if !canonical(cache_path).starts_with(canonical(root_cache_path)) {
+                errors.push(SettingsError::CachePathOutsideRoot {
+                    cache_path: cache_path.clone(),
+                    root_cache_path: root_cache_path.clone(),
+                });
+            }
+        }
+
+        if options.windowless_browsers && !self.windowless_rendering_enabled {
+            errors.push(SettingsError::WindowlessRenderingDisabled);
+        }
+
+        if self.multi_threaded_message_loop {
+            if cfg!(any(target_os = "linux", target_os = "macos")) {
+                errors.push(SettingsError::UnsupportedOnPlatform {
+                    field: "multi_threaded_message_loop",
+                });
+            }
+            if self.external_message_pump {
+                errors.push(SettingsError::Conflict {
+                    field: "multi_threaded_message_loop",
+                    conflicts_with: "external_message_pump",
+                });
+            }
+        }
+
+        if self.cache_path.is_none() {
+            if self.persist_session_cookies {
+                errors.push(SettingsError::MissingRequirement {
+                    field: "persist_session_cookies",
+                    requires: "cache_path",
+                });
+            }
+            if self.persist_user_preferences {
+                errors.push(SettingsError::MissingRequirement {
+                    field: "persist_user_preferences",
+                    requires: "cache_path",
+                });
+            }
+        }
+
+        if self.remote_debugging_port != 0 && self.remote_debugging_port < 1024 {
+            errors.push(SettingsError::InvalidValue {
+                field: "remote_debugging_port",
+                reason: "must be 0 or between 1024 and 65535",
+            });
+        }
+        let alpha = self.background_color.a();
+        if alpha != 0x00 && alpha != 0xFF {
+            errors.push(SettingsError::InvalidValue {
+                field: "background_color",
+                reason: "alpha must be either fully opaque or fully transparent",
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(SettingsErrors(errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, test_util::TempDir};
+
+    fn validate(settings: Settings) -> Result<(), SettingsErrors> {
+        settings.validate(&ValidationOptions::new())
+    }
+
+    fn missing_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cef-validation-missing-{}", name))
+    }
+
+    #[test]
+    fn default_settings_are_valid() {
+        assert_eq!(validate(Settings::new()), Ok(()));
+    }
+
+    #[test]
+    fn missing_paths() {
+        let settings = Settings::new()
+            .browser_subprocess_path(missing_path("helper"))
+            .resources_dir_path(missing_path("resources"));
+        assert_eq!(
+            validate(settings),
+            Err(SettingsErrors(vec![
+                SettingsError::PathNotFound {
+                    field: "browser_subprocess_path",
+                    path: missing_path("helper")
+                },
+                SettingsError::PathNotFound {
+                    field: "resources_dir_path",
+                    path: missing_path("resources")
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn wrong_path_type() {
+        let dir = TempDir::new("validation");
+        assert_eq!(
+            validate(Settings::new().log_file(dir.path())),
+            Err(SettingsErrors(vec![SettingsError::WrongPathType {
+                field: "log_file",
+                path: dir.path().to_owned(),
+                expected_directory: false
+            }]))
+        );
+        assert_eq!(
+            validate(
+                Settings::new()
+                    .cache_path(dir.path())
+                    .root_cache_path(dir.path())
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn cache_path_outside_root() {
+        let dir = TempDir::new("validation");
+        let root = dir.create_dir("root");
+        let inside = dir.create_dir("root/profile");
+        let outside = dir.create_dir("other");
+        assert_eq!(
+            validate(Settings::new().cache_path(&inside).root_cache_path(&root)),
+            Ok(())
+        );
+        assert_eq!(
+            validate(Settings::new().cache_path(&outside).root_cache_path(&root)),
+            Err(SettingsErrors(vec![SettingsError::CachePathOutsideRoot {
+                cache_path: outside,
+                root_cache_path: root
+            }]))
+        );
+    }
+
+    #[test]
+    fn remote_debugging_port_range() {
+        for &port in &[0, 1024, 9222, 65535] {
+            assert_eq!(
+                validate(Settings::new().remote_debugging_port(port)),
+                Ok(()),
+                "port {}",
+                port
+            );
+        }
+        for &port in &[1, 80, 1023] {
+            assert_eq!(
+                validate(Settings::new().remote_debugging_port(port)),
+                Err(SettingsErrors(vec![SettingsError::InvalidValue {
+                    field: "remote_debugging_port",
+                    reason: "must be 0 or between 1024 and 65535"
+                }])),
+                "port {}",
+                port
+            );
+        }
+    }
+
+    #[test]
+    fn macos_only_fields() {
+        let settings = Settings::new()
+            .framework_dir_path(missing_path("framework"))
+            .main_bundle_path(missing_path("bundle"));
+        if cfg!(target_os = "macos") {
+            assert_eq!(
+                validate(settings),
+                Err(SettingsErrors(vec![
+                    SettingsError::PathNotFound {
+                        field: "framework_dir_path",
+                        path: missing_path("framework")
+                    },
+                    SettingsError::PathNotFound {
+                        field: "main_bundle_path",
+                        path: missing_path("bundle")
+                    },
+                ]))
+            );
+        } else {
+            assert_eq!(validate(settings), Ok(()));
+        }
+    }
+
+    #[test]
+    fn errors_are_aggregated() {
+        let settings = Settings::new()
+            .persist_session_cookies(true)
+            .remote_debugging_port(80)
+            .background_color(Color::rgba(1.0, 1.0, 1.0, 0.5));
+        let errors = settings
+            .validate(&ValidationOptions::new().windowless_browsers(true))
+            .unwrap_err();
+        assert_eq!(
+            errors.0,
+            vec![
+                SettingsError::WindowlessRenderingDisabled,
+                SettingsError::MissingRequirement {
+                    field: "persist_session_cookies",
+                    requires: "cache_path"
+                },
+                SettingsError::InvalidValue {
+                    field: "remote_debugging_port",
+                    reason: "must be 0 or between 1024 and 65535"
+                },
+                SettingsError::InvalidValue {
+                    field: "background_color",
+                    reason: "alpha must be either fully opaque or fully transparent"
+                },
+            ]
+        );
+        assert_eq!(
+            errors.to_string(),
+            "invalid settings: windowless_rendering_enabled must be set to create windowless browsers; \
+             persist_session_cookies requires cache_path to be set; \
+             remote_debugging_port: must be 0 or between 1024 and 65535; \
+             background_color: alpha must be either fully opaque or fully transparent"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use cef_sys::cef_sslstatus_t;
-use cef_sys::{_cef_sslinfo_t, cef_cert_status_t, cef_is_cert_status_error, cef_ssl_version_t, cef_ssl_content_status_t};
+use cef_sys::{_cef_sslinfo_t, cef_cert_status_t, cef_ssl_version_t, cef_ssl_content_status_t};
+use crate::capi::cef_is_cert_status_error;
 use bitflags::bitflags;
 use crate::x509_certificate::X509Certificate;
 
@@ -80,6 +81,7 @@ impl SSLInfo {
     /// Returns a set containing any and all problems verifying the server
     /// certificate.
     pub fn get_cert_status(&self) -> CertStatus {
+        ffi_event!("SSLInfo::get_cert_status");
         self.0
             .get_cert_status
             .map(|get_cert_status| CertStatus::from_bits_truncate(unsafe { get_cert_status(self.0.as_ptr()).0 }))
@@ -87,6 +89,7 @@ impl SSLInfo {
     }
     /// Returns the X.509 certificate.
     pub fn get_x509certificate(&self) -> X509Certificate {
+        ffi_event!("SSLInfo::get_x509certificate");
         let get_x509certificate = self.0.get_x509certificate.unwrap();
         unsafe { X509Certificate::from_ptr_unchecked(get_x509certificate(self.0.as_ptr())) }
     }
@@ -94,18 +97,23 @@ impl SSLInfo {
 
 impl SSLStatus {
     pub fn is_secure_connection(&self) -> bool {
+        ffi_event!("SSLStatus::is_secure_connection");
         unsafe{ self.0.is_secure_connection.unwrap()(self.as_ptr()) != 0 }
     }
     pub fn get_cert_status(&self) -> CertStatus {
+        ffi_event!("SSLStatus::get_cert_status");
         unsafe{ CertStatus::from_bits_truncate(self.0.get_cert_status.unwrap()(self.as_ptr()).0) }
     }
     pub fn get_ssl_version(&self) -> SSLVersion {
+        ffi_event!("SSLStatus::get_ssl_version");
         unsafe{ SSLVersion::from_unchecked(self.0.get_sslversion.unwrap()(self.as_ptr())) }
     }
     pub fn get_content_status(&self) -> ContentStatus {
+        ffi_event!("SSLStatus::get_content_status");
         unsafe{ ContentStatus::from_bits_truncate(self.0.get_content_status.unwrap()(self.as_ptr()).0) }
     }
     pub fn get_x509certificate(&self) -> X509Certificate {
+        ffi_event!("SSLStatus::get_x509certificate");
         unsafe{ X509Certificate::from_ptr_unchecked(self.0.get_x509certificate.unwrap()(self.as_ptr())) }
     }
 }
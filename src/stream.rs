@@ -17,11 +17,13 @@ ref_counted_ptr!{
 impl StreamReader {
     /// Read raw binary data, returning how many bytes were read.
     pub fn read(&self, data: &mut [u8]) -> usize {
+        ffi_event!("StreamReader::read");
         unsafe{ (self.0.read.unwrap())(self.as_ptr(), data.as_mut_ptr() as *mut _, 1, data.len()) }
     }
 
     /// Seek to the specified position.
     pub fn seek(&self, whence: SeekFrom) -> io::Result<()> {
+        ffi_event!("StreamReader::seek");
         let (whence, offset) = match whence {
             SeekFrom::Start(o) => (libc::SEEK_SET, o as i64),
             SeekFrom::End(o) => (libc::SEEK_END, o),
@@ -36,11 +38,13 @@ impl StreamReader {
 
     /// Return the current offset position.
     pub fn tell(&self) -> usize {
+        ffi_event!("StreamReader::tell");
         unsafe{ (self.0.tell.unwrap())(self.as_ptr()) as usize }
     }
 
     /// Returns `true` if the reader is at the end of the file.
     pub fn eof(&self) -> bool {
+        ffi_event!("StreamReader::eof");
         unsafe{ (self.0.eof.unwrap())(self.as_ptr()) != 0 }
     }
 
@@ -48,6 +52,7 @@ impl StreamReader {
     /// which may block. Used as a hint for determining the thread to access the
     /// reader from.
     pub fn may_block(&self) -> bool {
+        ffi_event!("StreamReader::may_block");
         unsafe{ (self.0.may_block.unwrap())(self.as_ptr()) != 0 }
     }
 }
@@ -55,11 +60,13 @@ impl StreamReader {
 impl StreamWriter {
     /// Write raw binary data, returning how many bytes were written.
     pub fn write(&self, data: &[u8]) -> usize {
+        ffi_event!("StreamWriter::write");
         unsafe{ (self.0.write.unwrap())(self.as_ptr(), data.as_ptr() as *mut _, 1, data.len()) }
     }
 
     /// Seek to the specified position.
     pub fn seek(&self, whence: SeekFrom) -> io::Result<()> {
+        ffi_event!("StreamWriter::seek");
         let (whence, offset) = match whence {
             SeekFrom::Start(o) => (libc::SEEK_SET, o as i64),
             SeekFrom::End(o) => (libc::SEEK_END, o),
@@ -74,11 +81,13 @@ impl StreamWriter {
 
     /// Return the current offset position.
     pub fn tell(&self) -> usize {
+        ffi_event!("StreamWriter::tell");
         unsafe{ (self.0.tell.unwrap())(self.as_ptr()) as usize }
     }
 
     /// Returns `true` if the writer is at the end of the file.
     pub fn flush(&self) -> io::Result<()> {
+        ffi_event!("StreamWriter::flush");
         let result = unsafe{ (self.0.flush.unwrap())(self.as_ptr()) };
         match result {
             0 => Ok(()),
@@ -90,6 +99,7 @@ impl StreamWriter {
     /// which may block. Used as a hint for determining the thread to access the
     /// writer from.
     pub fn may_block(&self) -> bool {
+        ffi_event!("StreamWriter::may_block");
         unsafe{ (self.0.may_block.unwrap())(self.as_ptr()) != 0 }
     }
 }
@@ -1,16 +1,16 @@
-use cef_sys::cef_string_map_clear;
-use cef_sys::cef_string_map_find;
-use cef_sys::cef_string_map_key;
+use crate::capi::cef_string_map_clear;
+use crate::capi::cef_string_map_find;
+use crate::capi::cef_string_map_key;
 use cef_sys::cef_string_map_t;
-use cef_sys::cef_string_map_alloc;
-use cef_sys::cef_string_map_free;
-use cef_sys::cef_string_map_size;
-use cef_sys::cef_string_map_value;
-use cef_sys::cef_string_map_append;
-use cef_sys::{
+use crate::capi::cef_string_map_alloc;
+use crate::capi::cef_string_map_free;
+use crate::capi::cef_string_map_size;
+use crate::capi::cef_string_map_value;
+use crate::capi::cef_string_map_append;
+use cef_sys::{cef_string_list_t, cef_string_t, cef_string_visitor_t, cef_string_userfree_t};
+use crate::capi::{
     cef_string_list_alloc, cef_string_list_append, cef_string_list_free, cef_string_list_size,
-    cef_string_list_t, cef_string_list_value, cef_string_t, cef_string_utf8_to_utf16,
-    cef_string_visitor_t, cef_string_userfree_t, cef_string_userfree_utf16_free,
+    cef_string_list_value, cef_string_utf8_to_utf16, cef_string_userfree_utf16_free,
     cef_string_userfree_utf16_alloc,
 };
 use std::ptr::null_mut;
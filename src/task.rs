@@ -1,10 +1,17 @@
 use crate::refcounted::{RefCountedPtr, Wrapper};
-use cef_sys::{
-    _cef_task_runner_t, _cef_task_t, cef_currently_on, cef_post_delayed_task, cef_post_task,
-    cef_task_runner_get_for_current_thread, cef_task_runner_get_for_thread, cef_thread_id_t,
+use cef_sys::{_cef_task_runner_t, _cef_task_t, cef_thread_id_t};
+use crate::capi::{
+    cef_currently_on, cef_post_delayed_task, cef_post_task, cef_task_runner_get_for_current_thread,
+    cef_task_runner_get_for_thread,
 };
 use parking_lot::Mutex;
 
+mod affinity;
+mod executor;
+pub(crate) use affinity::debug_assert_on;
+pub use affinity::{IoThread, RendererThread, UiThread};
+pub use executor::*;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ThreadId {
@@ -33,20 +40,24 @@ impl TaskRunner {
     /// task runners. None will be returned if this function is called
     /// on an invalid thread.
     pub fn get_for_current_thread() -> Option<Self> {
+        ffi_event!("TaskRunner::get_for_current_thread");
         unsafe { Self::from_ptr(cef_task_runner_get_for_current_thread()) }
     }
     /// Returns the task runner for the specified CEF thread.
     pub fn get_for_thread(thread_id: ThreadId) -> Option<Self> {
+        ffi_event!("TaskRunner::get_for_thread");
         unsafe { Self::from_ptr(cef_task_runner_get_for_thread(thread_id as crate::CEnumType)) }
     }
     /// Returns true if called on the specified thread. Equivalent to using
     /// `TaskRunner::get_for_thread(thread_id).belongs_to_current_thread()`.
     pub fn currently_on(thread_id: ThreadId) -> bool {
+        ffi_event!("TaskRunner::currently_on");
         unsafe { cef_currently_on(thread_id as cef_thread_id_t::Type) != 0 }
     }
     /// Post a task for execution on the specified thread. Equivalent to using
     /// `TaskRunner::get_for_thread(thread_id).post_task(task)`.
     pub fn post_task_on(thread_id: ThreadId, task: impl FnOnce() + Send + 'static) -> bool {
+        ffi_event!("TaskRunner::post_task_on");
         unsafe {
             cef_post_task(
                 thread_id as cef_thread_id_t::Type,
@@ -61,6 +72,7 @@ impl TaskRunner {
         task: impl FnOnce() + Send + 'static,
         delay_ms: i64,
     ) -> bool {
+        ffi_event!("TaskRunner::post_delayed_task_on");
         unsafe {
             cef_post_delayed_task(
                 thread_id as cef_thread_id_t::Type,
@@ -73,6 +85,7 @@ impl TaskRunner {
     /// Returns true if this object is pointing to the same task runner as
     /// `that` object.
     pub fn is_same(&self, that: &Self) -> bool {
+        ffi_event!("TaskRunner::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.as_ptr()) != 0 })
@@ -80,6 +93,7 @@ impl TaskRunner {
     }
     /// Returns true if this task runner belongs to the current thread.
     pub fn belongs_to_current_thread(&self) -> bool {
+        ffi_event!("TaskRunner::belongs_to_current_thread");
         self.0
             .belongs_to_current_thread
             .map(|belongs_to_current_thread| unsafe {
@@ -89,6 +103,7 @@ impl TaskRunner {
     }
     /// Returns true if this task runner is for the specified CEF thread.
     pub fn belongs_to_thread(&self, thread_id: ThreadId) -> bool {
+        ffi_event!("TaskRunner::belongs_to_thread");
         self.0
             .belongs_to_thread
             .map(|belongs_to_thread| unsafe {
@@ -99,6 +114,7 @@ impl TaskRunner {
     /// Post a task for execution on the thread associated with this task runner.
     /// Execution will occur asynchronously.
     pub fn post_task(&self, task: impl FnOnce() + Send + 'static) -> bool {
+        ffi_event!("TaskRunner::post_task");
         self.0
             .post_task
             .map(|post_task| unsafe {
@@ -115,6 +131,7 @@ impl TaskRunner {
         task: impl FnOnce() + Send + 'static,
         delay_ms: i64,
     ) -> bool {
+        ffi_event!("TaskRunner::post_delayed_task");
         self.0
             .post_delayed_task
             .map(|post_delayed_task| unsafe {
@@ -0,0 +1,67 @@
+use super::{TaskRunner, ThreadId};
+use std::marker::PhantomData;
+
+/// Panics in debug builds if not called on `thread_id`.
+pub(crate) fn debug_assert_on(thread_id: ThreadId) {
+    if cfg!(debug_assertions) && !TaskRunner::currently_on(thread_id) {
+        panic!("must be called on the {:?} thread", thread_id);
+    }
+}
+
+macro_rules! thread_token {
+    ($(#[$meta:meta])* pub struct $Token:ident => $thread_id:expr;) => {
+        $(#[$meta])*
+        ///
+        /// Callbacks that CEF always calls on this thread get the token as an
+        /// argument, elsewhere use [Self::current].
+        ///
+        /// The token is zero-sized and neither `Send` nor `Sync`, so it can't leave
+        /// the thread it was created on.
+        #[derive(Debug, Clone, Copy)]
+        pub struct $Token(PhantomData<*const ()>);
+
+        impl $Token {
+            /// The thread this token stands for.
+            pub const THREAD_ID: ThreadId = $thread_id;
+
+            /// Returns the token if called on the right thread.
+            pub fn current() -> Option<Self> {
+                if TaskRunner::currently_on($thread_id) {
+                    Some($Token(PhantomData))
+                } else {
+                    None
+                }
+            }
+            /// Returns the token without checking the current thread in release
+            /// builds. Debug builds check it and panic on the wrong thread.
+            pub unsafe fn new_unchecked() -> Self {
+                debug_assert_on($thread_id);
+                $Token(PhantomData)
+            }
+        }
+    };
+}
+
+thread_token! {
+    /// Proof that the code is running on the browser process UI thread, which is
+    /// required by functions like [crate::browser_host::BrowserHost::try_close_browser].
+    /// Passed to [crate::client::life_span_handler::LifeSpanHandlerCallbacks].
+    pub struct UiThread => ThreadId::UI;
+}
+
+thread_token! {
+    /// Proof that the code is running on the browser process IO thread, where
+    /// network related callbacks like those of
+    /// [crate::resource_request_handler::ResourceRequestHandlerCallbacks] and
+    /// [crate::url_request::ResourceHandlerCallbacks] are called.
+    pub struct IoThread => ThreadId::IO;
+}
+
+thread_token! {
+    /// Proof that the code is running on the main thread of a render process,
+    /// which is required by [crate::v8context::V8Context::register_extension].
+    /// Passed to [crate::render_process_handler::RenderProcessHandlerCallbacks].
+    /// See also [crate::v8context::V8Scope] for functions that additionally
+    /// require a V8 context.
+    pub struct RendererThread => ThreadId::Renderer;
+}
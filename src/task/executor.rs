@@ -0,0 +1,665 @@
+use super::{TaskRunner, ThreadId};
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    future::Future,
+    mem::ManuallyDrop,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    time::Duration,
+};
+
+/// Run `future` to completion on the specified CEF thread.
+///
+/// The future is only ever polled on that thread, so it's free to call
+/// functions with thread-affinity requirements between its await points.
+/// Waking it posts a task to the thread, multiple wake-ups before the next
+/// poll are coalesced into a single task.
+pub fn spawn_on<T: Send + 'static>(
+    thread_id: ThreadId,
+    future: impl Future<Output = T> + Send + 'static,
+) -> JoinHandle<T> {
+    spawn(Target::Thread(thread_id), future)
+}
+
+impl TaskRunner {
+    /// Run `future` to completion on the thread associated with this task runner.
+    /// See [spawn_on] for details.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> JoinHandle<T> {
+        spawn(Target::Runner(self.clone()), future)
+    }
+}
+
+enum Target {
+    Thread(ThreadId),
+    Runner(TaskRunner),
+    #[cfg(test)]
+    Fake(Arc<tests::FakeThread>),
+}
+
+impl Target {
+    fn post(&self, task: impl FnOnce() + Send + 'static) -> bool {
+        match self {
+            Target::Thread(thread_id) => TaskRunner::post_task_on(*thread_id, task),
+            Target::Runner(runner) => runner.post_task(task),
+            #[cfg(test)]
+            Target::Fake(thread) => thread.post(Box::new(task), 0),
+        }
+    }
+    fn post_delayed(&self, task: impl FnOnce() + Send + 'static, delay_ms: i64) -> bool {
+        match self {
+            Target::Thread(thread_id) => TaskRunner::post_delayed_task_on(*thread_id, task, delay_ms),
+            Target::Runner(runner) => runner.post_delayed_task(task, delay_ms),
+            #[cfg(test)]
+            Target::Fake(thread) => thread.post(Box::new(task), delay_ms),
+        }
+    }
+}
+
+fn spawn<T: Send + 'static>(
+    target: Target,
+    future: impl Future<Output = T> + Send + 'static,
+) -> JoinHandle<T> {
+    let output = Arc::new(Mutex::new(JoinState::Pending(None)));
+    let completer = Completer(output.clone());
+    let task = Arc::new(SpawnedTask {
+        future: Mutex::new(Some(Box::pin(async move {
+            let result = future.await;
+            completer.complete(Ok(result));
+        }))),
+        target,
+        scheduled: AtomicBool::new(false),
+        cancelled: AtomicBool::new(false),
+    });
+    task.schedule();
+    JoinHandle { task, output }
+}
+
+struct SpawnedTask {
+    future: Mutex<Option<Pin<Box<dyn Future<Output = ()> + Send>>>>,
+    target: Target,
+    scheduled: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl SpawnedTask {
+    fn schedule(self: &Arc<Self>) {
+        if self.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let task = self.clone();
+        if !self.target.post(move || task.run()) {
+            // The thread is gone, so the future can never complete. The posted
+            // closure has been dropped already, drop the future as well to
+            // resolve the JoinHandle. If it's being polled right now, which is
+            // the case when it wakes itself, [SpawnedTask::run] drops it
+            // afterwards.
+            self.cancelled.store(true, Ordering::SeqCst);
+            self.scheduled.store(false, Ordering::SeqCst);
+            self.drop_if_cancelled();
+        }
+    }
+
+    fn run(self: Arc<Self>) {
+        self.scheduled.store(false, Ordering::SeqCst);
+        {
+            let mut future = self.future.lock();
+            if let (false, Some(pending)) = (self.cancelled.load(Ordering::SeqCst), future.as_mut()) {
+                let waker = waker(self.clone());
+                let mut cx = Context::from_waker(&waker);
+                if let Poll::Ready(()) = pending.as_mut().poll(&mut cx) {
+                    future.take();
+                }
+            }
+        }
+        self.drop_if_cancelled();
+    }
+
+    /// Drops the future if the task was cancelled, unless someone else holds the
+    /// lock. Both [SpawnedTask::schedule] and [SpawnedTask::run] call this after
+    /// setting the flag or releasing the lock respectively, so one of them
+    /// always gets to drop it.
+    fn drop_if_cancelled(&self) {
+        if self.cancelled.load(Ordering::SeqCst) {
+            if let Some(mut future) = self.future.try_lock() {
+                future.take();
+            }
+        }
+    }
+}
+
+fn waker(task: Arc<SpawnedTask>) -> Waker {
+    unsafe { Waker::from_raw(raw_waker(task)) }
+}
+
+fn raw_waker(task: Arc<SpawnedTask>) -> RawWaker {
+    RawWaker::new(Arc::into_raw(task) as *const (), &VTABLE)
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
+
+unsafe fn clone_waker(data: *const ()) -> RawWaker {
+    let task = ManuallyDrop::new(Arc::from_raw(data as *const SpawnedTask));
+    raw_waker(Arc::clone(&task))
+}
+
+unsafe fn wake(data: *const ()) {
+    Arc::from_raw(data as *const SpawnedTask).schedule();
+}
+
+unsafe fn wake_by_ref(data: *const ()) {
+    ManuallyDrop::new(Arc::from_raw(data as *const SpawnedTask)).schedule();
+}
+
+unsafe fn drop_waker(data: *const ()) {
+    drop(Arc::from_raw(data as *const SpawnedTask));
+}
+
+/// Returned by awaiting a [JoinHandle] whose task was cancelled, either
+/// explicitly or because its thread shut down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the task was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+enum JoinState<T> {
+    Pending(Option<Waker>),
+    Done(Result<T, Cancelled>),
+    Taken,
+}
+
+/// Resolves the [JoinHandle]. If the future is dropped before completing, the
+/// handle resolves to [Cancelled].
+struct Completer<T>(Arc<Mutex<JoinState<T>>>);
+
+impl<T> Completer<T> {
+    fn complete(&self, result: Result<T, Cancelled>) {
+        let waker = {
+            let mut state = self.0.lock();
+            match &mut *state {
+                JoinState::Pending(waker) => {
+                    let waker = waker.take();
+                    *state = JoinState::Done(result);
+                    waker
+                }
+                _ => return,
+            }
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        self.complete(Err(Cancelled));
+    }
+}
+
+/// A handle to a future spawned with [spawn_on] or [TaskRunner::spawn]. Awaiting
+/// it returns the output of the future. Dropping it detaches the future, which
+/// then keeps running in the background.
+pub struct JoinHandle<T> {
+    task: Arc<SpawnedTask>,
+    output: Arc<Mutex<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Cancel the future. It's dropped on its own thread before it's polled the
+    /// next time, and the handle resolves to [Cancelled] unless the future has
+    /// completed already.
+    pub fn cancel(&self) {
+        self.task.cancelled.store(true, Ordering::SeqCst);
+        self.task.schedule();
+    }
+    /// Returns true if the future has completed or has been dropped.
+    pub fn is_finished(&self) -> bool {
+        !matches!(*self.output.lock(), JoinState::Pending(_))
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.output.lock();
+        match std::mem::replace(&mut *state, JoinState::Taken) {
+            JoinState::Done(result) => Poll::Ready(result),
+            JoinState::Pending(_) => {
+                *state = JoinState::Pending(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+            JoinState::Taken => panic!("JoinHandle polled after completion"),
+        }
+    }
+}
+
+/// A future that completes after a delay, using
+/// [TaskRunner::post_delayed_task_on] for the timer.
+pub struct Delay {
+    target: Target,
+    delay: Duration,
+    state: Option<Arc<Mutex<DelayState>>>,
+}
+
+struct DelayState {
+    fired: bool,
+    waker: Option<Waker>,
+}
+
+impl Delay {
+    /// Create a future that completes after `delay`. The timer task is posted to
+    /// `thread_id` when the future is first polled, which should be the thread
+    /// the future runs on.
+    pub fn new(thread_id: ThreadId, delay: Duration) -> Delay {
+        Delay {
+            target: Target::Thread(thread_id),
+            delay,
+            state: None,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if let Some(state) = &self.state {
+            let mut state = state.lock();
+            if state.fired {
+                return Poll::Ready(());
+            }
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let state = Arc::new(Mutex::new(DelayState {
+            fired: false,
+            waker: Some(cx.waker().clone()),
+        }));
+        let timer_state = state.clone();
+        let posted = self.target.post_delayed(
+            move || {
+                let waker = {
+                    let mut state = timer_state.lock();
+                    state.fired = true;
+                    state.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            },
+            self.delay.as_millis() as i64,
+        );
+        if !posted {
+            // There's nothing left to wait for if the thread is gone.
+            return Poll::Ready(());
+        }
+        self.state = Some(state);
+        Poll::Pending
+    }
+}
+
+/// Creates a channel for a single value, for turning callbacks into futures.
+/// The receiver resolves to [Cancelled] if the sender is dropped without
+/// sending.
+pub(crate) fn oneshot<T>() -> (Sender<T>, Receiver<T>) {
+    let state = Arc::new(Mutex::new(JoinState::Pending(None)));
+    (Sender(Completer(state.clone())), Receiver(state))
+}
+
+pub(crate) struct Sender<T>(Completer<T>);
+
+impl<T> Sender<T> {
+    pub(crate) fn send(self, value: T) {
+        self.0.complete(Ok(value));
+    }
+}
+
+pub(crate) struct Receiver<T>(Arc<Mutex<JoinState<T>>>);
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.0.lock();
+        match std::mem::replace(&mut *state, JoinState::Taken) {
+            JoinState::Done(result) => Poll::Ready(result),
+            JoinState::Pending(_) => {
+                *state = JoinState::Pending(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+            JoinState::Taken => panic!("Receiver polled after completion"),
+        }
+    }
+}
+
+/// Resolves to None if `future` doesn't complete within `duration`. The timer
+/// runs on `thread_id`, see [Delay::new].
+pub(crate) fn timeout<F: Future>(thread_id: ThreadId, duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        delay: Delay::new(thread_id, duration),
+    }
+}
+
+pub(crate) struct Timeout<F> {
+    future: Pin<Box<F>>,
+    delay: Delay,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        match Pin::new(&mut self.delay).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::counting_waker;
+    use std::sync::atomic::AtomicUsize;
+
+    type Task = Box<dyn FnOnce() + Send>;
+
+    /// Stands in for a CEF thread. Tasks only run when the test calls
+    /// [FakeThread::run_until_idle], and time only passes with
+    /// [FakeThread::advance].
+    #[derive(Default)]
+    pub(super) struct FakeThread {
+        state: Mutex<FakeState>,
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        now: i64,
+        tasks: Vec<(i64, Task)>,
+        stopped: bool,
+    }
+
+    impl FakeThread {
+        pub(super) fn post(&self, task: Task, delay_ms: i64) -> bool {
+            let mut state = self.state.lock();
+            if state.stopped {
+                return false;
+            }
+            let due = state.now + delay_ms;
+            state.tasks.push((due, task));
+            true
+        }
+        /// Runs all due tasks in the order they were posted, including the ones
+        /// posted while running. Returns the number of tasks that ran.
+        fn run_until_idle(&self) -> usize {
+            let mut count = 0;
+            loop {
+                let task = {
+                    let mut state = self.state.lock();
+                    let now = state.now;
+                    match state.tasks.iter().position(|(due, _)| *due <= now) {
+                        Some(index) => state.tasks.remove(index).1,
+                        None => return count,
+                    }
+                };
+                task();
+                count += 1;
+            }
+        }
+        fn advance(&self, ms: i64) {
+            self.state.lock().now += ms;
+        }
+        fn pending(&self) -> usize {
+            self.state.lock().tasks.len()
+        }
+        /// Shuts the thread down like CEF does: pending tasks are dropped without
+        /// running and posting fails from now on.
+        fn stop(&self) {
+            let tasks = {
+                let mut state = self.state.lock();
+                state.stopped = true;
+                std::mem::take(&mut state.tasks)
+            };
+            drop(tasks);
+        }
+    }
+
+    fn spawn_fake<T: Send + 'static>(
+        thread: &Arc<FakeThread>,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> JoinHandle<T> {
+        spawn(Target::Fake(thread.clone()), future)
+    }
+
+    fn delay_on(thread: &Arc<FakeThread>, delay_ms: u64) -> Delay {
+        Delay {
+            target: Target::Fake(thread.clone()),
+            delay: Duration::from_millis(delay_ms),
+            state: None,
+        }
+    }
+
+    fn poll<F: Future + Unpin>(future: &mut F, waker: &Waker) -> Poll<F::Output> {
+        Pin::new(future).poll(&mut Context::from_waker(waker))
+    }
+
+    /// A future that calls the closure whenever it's polled.
+    struct PollFn<F>(F);
+
+    impl<T, F: FnMut(&mut Context) -> Poll<T> + Unpin> Future for PollFn<F> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+            (self.0)(cx)
+        }
+    }
+
+    /// Sets the flag when dropped.
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn runs_on_target_thread() {
+        let thread = Arc::new(FakeThread::default());
+        let (waker, wakes) = counting_waker();
+        let (sender, receiver) = oneshot();
+        let mut handle = spawn_fake(&thread, async move { receiver.await.unwrap() * 2 });
+
+        assert!(poll(&mut handle, &waker).is_pending());
+        assert_eq!(thread.run_until_idle(), 1);
+        assert!(poll(&mut handle, &waker).is_pending());
+
+        // Waking the future only posts it, it runs when the thread gets to it.
+        sender.send(21);
+        assert_eq!(thread.pending(), 1);
+        assert_eq!(wakes.load(Ordering::SeqCst), 0);
+        assert!(!handle.is_finished());
+
+        assert_eq!(thread.run_until_idle(), 1);
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert!(handle.is_finished());
+        assert_eq!(poll(&mut handle, &waker), Poll::Ready(Ok(42)));
+    }
+
+    #[test]
+    fn wake_ups_are_coalesced() {
+        let thread = Arc::new(FakeThread::default());
+        let polls = Arc::new(AtomicUsize::new(0));
+        let future_polls = polls.clone();
+        let handle = spawn_fake(
+            &thread,
+            PollFn(move |cx: &mut Context| {
+                if future_polls.fetch_add(1, Ordering::SeqCst) == 2 {
+                    return Poll::Ready(());
+                }
+                // Both ways of waking, the second one consuming a clone.
+                cx.waker().wake_by_ref();
+                let waker = cx.waker().clone();
+                waker.wake();
+                Poll::Pending
+            }),
+        );
+
+        assert_eq!(thread.pending(), 1);
+        assert_eq!(thread.run_until_idle(), 3);
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+        assert!(handle.is_finished());
+    }
+
+    #[test]
+    fn dropped_handle_detaches() {
+        let thread = Arc::new(FakeThread::default());
+        let done = Arc::new(AtomicBool::new(false));
+        let future_done = done.clone();
+        let (sender, receiver) = oneshot::<()>();
+        drop(spawn_fake(&thread, async move {
+            receiver.await.unwrap();
+            future_done.store(true, Ordering::SeqCst);
+        }));
+
+        thread.run_until_idle();
+        sender.send(());
+        thread.run_until_idle();
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn cancel_drops_future_on_thread() {
+        let thread = Arc::new(FakeThread::default());
+        let (waker, wakes) = counting_waker();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        let (_sender, receiver) = oneshot::<()>();
+        let mut handle = spawn_fake(&thread, async move {
+            let _flag = flag;
+            receiver.await
+        });
+        thread.run_until_idle();
+        assert!(poll(&mut handle, &waker).is_pending());
+
+        handle.cancel();
+        assert!(!dropped.load(Ordering::SeqCst));
+        assert_eq!(thread.run_until_idle(), 1);
+        assert!(dropped.load(Ordering::SeqCst));
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert_eq!(poll(&mut handle, &waker), Poll::Ready(Err(Cancelled)));
+    }
+
+    #[test]
+    fn cancel_after_completion_keeps_output() {
+        let thread = Arc::new(FakeThread::default());
+        let (waker, _) = counting_waker();
+        let mut handle = spawn_fake(&thread, async { 7 });
+        thread.run_until_idle();
+
+        handle.cancel();
+        thread.run_until_idle();
+        assert_eq!(poll(&mut handle, &waker), Poll::Ready(Ok(7)));
+    }
+
+    #[test]
+    fn failed_post_cancels() {
+        let thread = Arc::new(FakeThread::default());
+        let (waker, _) = counting_waker();
+        let (sender, receiver) = oneshot::<i32>();
+        let mut handle = spawn_fake(&thread, receiver);
+        thread.run_until_idle();
+
+        thread.stop();
+        sender.send(1);
+        assert!(handle.is_finished());
+        assert_eq!(poll(&mut handle, &waker), Poll::Ready(Err(Cancelled)));
+    }
+
+    #[test]
+    fn failed_post_while_polling_drops_future() {
+        let thread = Arc::new(FakeThread::default());
+        let (waker, _) = counting_waker();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        let future_thread = thread.clone();
+        let mut handle = spawn_fake(
+            &thread,
+            PollFn(move |cx: &mut Context| -> Poll<()> {
+                let _ = &flag;
+                // The thread shuts down while the future runs, then it wakes
+                // itself. The future is locked at this point, so it can only be
+                // dropped after the poll.
+                future_thread.stop();
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }),
+        );
+
+        assert_eq!(thread.run_until_idle(), 1);
+        assert!(dropped.load(Ordering::SeqCst));
+        assert_eq!(poll(&mut handle, &waker), Poll::Ready(Err(Cancelled)));
+    }
+
+    #[test]
+    fn delay_fires_after_timer() {
+        let thread = Arc::new(FakeThread::default());
+        let handle = spawn_fake(&thread, delay_on(&thread, 50));
+
+        // The first poll posts the timer.
+        assert_eq!(thread.run_until_idle(), 1);
+        assert_eq!(thread.pending(), 1);
+
+        thread.advance(49);
+        assert_eq!(thread.run_until_idle(), 0);
+        assert!(!handle.is_finished());
+
+        // The timer wakes the task, which then completes.
+        thread.advance(1);
+        assert_eq!(thread.run_until_idle(), 2);
+        assert!(handle.is_finished());
+    }
+
+    #[test]
+    fn delay_completes_if_thread_is_gone() {
+        let thread = Arc::new(FakeThread::default());
+        let (waker, _) = counting_waker();
+        let mut delay = delay_on(&thread, 50);
+        thread.stop();
+        assert_eq!(poll(&mut delay, &waker), Poll::Ready(()));
+    }
+
+    #[test]
+    fn oneshot_send_and_drop() {
+        let (waker, wakes) = counting_waker();
+        let (sender, mut receiver) = oneshot();
+        assert!(poll(&mut receiver, &waker).is_pending());
+        sender.send("value");
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert_eq!(poll(&mut receiver, &waker), Poll::Ready(Ok("value")));
+
+        let (sender, mut receiver) = oneshot::<()>();
+        drop(sender);
+        assert_eq!(poll(&mut receiver, &waker), Poll::Ready(Err(Cancelled)));
+    }
+}
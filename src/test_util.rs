@@ -0,0 +1,82 @@
+//! Fixtures shared by the unit tests.
+
+use std::{
+    fs,
+    mem::ManuallyDrop,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{RawWaker, RawWakerVTable, Waker},
+};
+
+/// A directory in the temporary directory that is removed with its contents
+/// when dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory with a unique name starting with `prefix`.
+    pub fn new(prefix: &str) -> TempDir {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "cef-{}-{}-{}",
+            prefix,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.0.join(path)
+    }
+    /// Creates the directory `path` inside this one, with its parents.
+    pub fn create_dir<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let path = self.join(path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+    /// Writes `contents` to the file `path` inside this directory, creating
+    /// the parent directories.
+    pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(&self, path: P, contents: C) -> PathBuf {
+        let path = self.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Returns a waker together with the number of times it was woken.
+pub(crate) fn counting_waker() -> (Waker, Arc<AtomicUsize>) {
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop_waker);
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        let count = ManuallyDrop::new(Arc::from_raw(data as *const AtomicUsize));
+        RawWaker::new(Arc::into_raw(Arc::clone(&count)) as *const (), &VTABLE)
+    }
+    unsafe fn wake(data: *const ()) {
+        Arc::from_raw(data as *const AtomicUsize).fetch_add(1, Ordering::SeqCst);
+    }
+    unsafe fn wake_by_ref(data: *const ()) {
+        (*(data as *const AtomicUsize)).fetch_add(1, Ordering::SeqCst);
+    }
+    unsafe fn drop_waker(data: *const ()) {
+        drop(Arc::from_raw(data as *const AtomicUsize));
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let raw = RawWaker::new(Arc::into_raw(count.clone()) as *const (), &VTABLE);
+    (unsafe { Waker::from_raw(raw) }, count)
+}
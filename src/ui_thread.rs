@@ -0,0 +1,375 @@
+//! Running CEF on a dedicated UI thread.
+//!
+//! [Settings::multi_threaded_message_loop] is only supported on Windows. On
+//! Linux, CEF can still live on a thread other than the main thread, as long as
+//! initialization, the message loop and shutdown all happen on that same thread.
+//! [UiThreadRunner::start] does exactly that, which leaves the main thread free for
+//! another GUI toolkit. The returned [UiThreadHandle] can be sent to any thread
+//! and runs code on the CEF UI thread. Example usage:
+//!
+//! ```ignore
+//!  let ui_thread = cef::ui_thread::UiThreadRunner::start(settings, Some(app)).unwrap();
+//!  let handle = ui_thread.handle();
+//!  let browser = handle
+//!      .call(move || BrowserHost::create_browser_sync(&window_info, client, "https://www.google.com", &browser_settings, None, None, &context))
+//!      .wait()
+//!      .unwrap();
+//!  // ...
+//!  ui_thread.join();
+//! ```
+//!
+//! Sub-processes still have to be handled by calling [crate::execute_process]
+//! at the start of `main`, before the UI thread is started.
+
+use crate::{
+    app::App,
+    misc_fns::Context,
+    settings::Settings,
+    task::{TaskRunner, ThreadId},
+};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    task::{Context as TaskContext, Poll, Waker},
+    thread::JoinHandle,
+};
+
+/// Errors returned when marshaling calls onto the UI thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiThreadError {
+    /// The message loop isn't running (anymore).
+    Stopped,
+    /// CEF shut down before running the task.
+    Cancelled,
+}
+
+impl fmt::Display for UiThreadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UiThreadError::Stopped => write!(f, "the CEF message loop is not running"),
+            UiThreadError::Cancelled => write!(f, "CEF shut down before the task was run"),
+        }
+    }
+}
+
+impl std::error::Error for UiThreadError {}
+
+/// Owns the thread CEF runs on. Dropping it quits the message loop, shuts CEF
+/// down and waits for the thread to exit.
+pub struct UiThreadRunner {
+    handle: UiThreadHandle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl UiThreadRunner {
+    /// Spawn the UI thread, initialize CEF on it and start the message loop.
+    /// Returns once initialization is done, with the error of
+    /// [Context::initialize] if it failed.
+    pub fn start(settings: Settings, application: Option<App>) -> Result<UiThreadRunner, io::Error> {
+        let handle = UiThreadHandle {
+            running: Arc::new(AtomicBool::new(false)),
+        };
+        let running = handle.running.clone();
+        let (sender, receiver) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("cef-ui".to_owned())
+            .spawn(move || {
+                let context = match Context::initialize(&settings, application, None) {
+                    Ok(context) => context,
+                    Err(err) => {
+                        let _ = sender.send(Err(err));
+                        return;
+                    }
+                };
+                running.store(true, Ordering::SeqCst);
+                let _ = sender.send(Ok(()));
+                context.run_message_loop();
+                running.store(false, Ordering::SeqCst);
+                drop(context);
+            })?;
+        match receiver.recv() {
+            Ok(Ok(())) => Ok(UiThreadRunner {
+                handle,
+                thread: Some(thread),
+            }),
+            Ok(Err(err)) => {
+                let _ = thread.join();
+                Err(err)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(io::Error::new(io::ErrorKind::Other, "the CEF UI thread panicked during initialization"))
+            }
+        }
+    }
+    /// Returns a handle for running code on the UI thread.
+    pub fn handle(&self) -> UiThreadHandle {
+        self.handle.clone()
+    }
+    /// Quit the message loop, shut CEF down and wait for the thread to exit.
+    ///
+    /// All browsers have to be closed before calling this, otherwise CEF will
+    /// complain about them at shutdown.
+    pub fn join(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = self.handle.quit();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for UiThreadRunner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A handle for running code on the CEF UI thread. It's cheap to clone and can
+/// be sent to other threads.
+#[derive(Clone)]
+pub struct UiThreadHandle {
+    running: Arc<AtomicBool>,
+}
+
+impl UiThreadHandle {
+    /// Returns true while the message loop is running.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+    /// Run `task` on the UI thread without waiting for it.
+    pub fn post(&self, task: impl FnOnce() + Send + 'static) -> Result<(), UiThreadError> {
+        if self.is_running() && TaskRunner::post_task_on(ThreadId::UI, task) {
+            Ok(())
+        } else {
+            Err(UiThreadError::Stopped)
+        }
+    }
+    /// Run `task` on the UI thread and return its result. The result can either be
+    /// awaited or, outside of async code, waited for with [UiCall::wait].
+    ///
+    /// If this is called on the UI thread itself, `task` is run immediately.
+    pub fn call<R: Send + 'static>(&self, task: impl FnOnce() -> R + Send + 'static) -> UiCall<R> {
+        if !self.is_running() {
+            return UiCall::ready(Err(UiThreadError::Stopped));
+        }
+        if TaskRunner::currently_on(ThreadId::UI) {
+            return UiCall::ready(Ok(task()));
+        }
+        let (call, completer) = UiCall::new();
+        if TaskRunner::post_task_on(ThreadId::UI, move || completer.complete(Ok(task()))) {
+            call
+        } else {
+            UiCall::ready(Err(UiThreadError::Stopped))
+        }
+    }
+    /// Quit the message loop. [UiThreadRunner::join] has to be called afterwards to
+    /// shut CEF down.
+    pub fn quit(&self) -> Result<(), UiThreadError> {
+        self.post(|| {
+            let _ = crate::quit_message_loop();
+        })
+    }
+}
+
+enum CallState<R> {
+    Pending(Option<Waker>),
+    Done(Result<R, UiThreadError>),
+    Taken,
+}
+
+struct CallShared<R> {
+    state: Mutex<CallState<R>>,
+    condvar: Condvar,
+}
+
+/// The result of [UiThreadHandle::call].
+pub struct UiCall<R> {
+    shared: Arc<CallShared<R>>,
+}
+
+impl<R> UiCall<R> {
+    fn new() -> (UiCall<R>, Completer<R>) {
+        let shared = Arc::new(CallShared {
+            state: Mutex::new(CallState::Pending(None)),
+            condvar: Condvar::new(),
+        });
+        (UiCall { shared: shared.clone() }, Completer(shared))
+    }
+    fn ready(result: Result<R, UiThreadError>) -> UiCall<R> {
+        UiCall {
+            shared: Arc::new(CallShared {
+                state: Mutex::new(CallState::Done(result)),
+                condvar: Condvar::new(),
+            }),
+        }
+    }
+    /// Block the current thread until the task has been run.
+    ///
+    /// Never call this on the UI thread with a call made from another thread, as
+    /// that call can't run while the UI thread is blocked.
+    pub fn wait(self) -> Result<R, UiThreadError> {
+        let mut state = self.shared.state.lock();
+        loop {
+            match std::mem::replace(&mut *state, CallState::Taken) {
+                CallState::Done(result) => return result,
+                pending @ CallState::Pending(_) => {
+                    *state = pending;
+                    self.shared.condvar.wait(&mut state);
+                }
+                CallState::Taken => unreachable!(),
+            }
+        }
+    }
+}
+
+impl<R> Future for UiCall<R> {
+    type Output = Result<R, UiThreadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock();
+        match std::mem::replace(&mut *state, CallState::Taken) {
+            CallState::Done(result) => Poll::Ready(result),
+            CallState::Pending(_) => {
+                *state = CallState::Pending(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+            CallState::Taken => panic!("UiCall polled after completion"),
+        }
+    }
+}
+
+/// Moved into the posted task. If CEF drops the task without running it, the
+/// call completes with [UiThreadError::Cancelled].
+struct Completer<R>(Arc<CallShared<R>>);
+
+impl<R> Completer<R> {
+    fn complete(&self, result: Result<R, UiThreadError>) {
+        let waker = {
+            let mut state = self.0.state.lock();
+            match &mut *state {
+                CallState::Pending(waker) => {
+                    let waker = waker.take();
+                    *state = CallState::Done(result);
+                    waker
+                }
+                // already completed, keep the first result
+                _ => return,
+            }
+        };
+        self.0.condvar.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<R> Drop for Completer<R> {
+    fn drop(&mut self) {
+        self.complete(Err(UiThreadError::Cancelled));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::counting_waker;
+    use std::time::Duration;
+
+    type Task = Box<dyn FnOnce() + Send>;
+
+    /// Posts the completion of a new call the way [UiThreadHandle::call] does,
+    /// to a queue standing in for the UI thread.
+    fn post_call<R: Send + 'static>(queue: &mut Vec<Task>, task: impl FnOnce() -> R + Send + 'static) -> UiCall<R> {
+        let (call, completer) = UiCall::new();
+        queue.push(Box::new(move || completer.complete(Ok(task()))));
+        call
+    }
+
+    fn poll<R>(call: &mut UiCall<R>, waker: &Waker) -> Poll<Result<R, UiThreadError>> {
+        Pin::new(call).poll(&mut TaskContext::from_waker(waker))
+    }
+
+    #[test]
+    fn wait_for_other_thread() {
+        let mut queue = Vec::new();
+        let call = post_call(&mut queue, || 21 * 2);
+        let thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            for task in queue {
+                task();
+            }
+        });
+        assert_eq!(call.wait(), Ok(42));
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn await_result() {
+        let (waker, wakes) = counting_waker();
+        let mut queue = Vec::new();
+        let mut call = post_call(&mut queue, || "done");
+
+        assert_eq!(poll(&mut call, &waker), Poll::Pending);
+        assert_eq!(poll(&mut call, &waker), Poll::Pending);
+        assert_eq!(wakes.load(Ordering::SeqCst), 0);
+
+        queue.pop().unwrap()();
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert_eq!(poll(&mut call, &waker), Poll::Ready(Ok("done")));
+    }
+
+    #[test]
+    fn dropped_task_cancels() {
+        let (waker, wakes) = counting_waker();
+        let mut queue = Vec::new();
+        let mut awaited = post_call(&mut queue, || 1);
+        let waited = post_call(&mut queue, || 2);
+        assert_eq!(poll(&mut awaited, &waker), Poll::Pending);
+
+        // CEF drops pending tasks without running them when it shuts down.
+        drop(queue);
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert_eq!(poll(&mut awaited, &waker), Poll::Ready(Err(UiThreadError::Cancelled)));
+        assert_eq!(waited.wait(), Err(UiThreadError::Cancelled));
+    }
+
+    #[test]
+    fn first_result_wins() {
+        let (call, completer) = UiCall::new();
+        completer.complete(Ok(1));
+        completer.complete(Ok(2));
+        drop(completer);
+        assert_eq!(call.wait(), Ok(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "UiCall polled after completion")]
+    fn poll_after_completion() {
+        let (waker, _) = counting_waker();
+        let mut call = UiCall::ready(Ok(()));
+        assert_eq!(poll(&mut call, &waker), Poll::Ready(Ok(())));
+        let _ = poll(&mut call, &waker);
+    }
+
+    #[test]
+    fn stopped() {
+        let handle = UiThreadHandle {
+            running: Arc::new(AtomicBool::new(false)),
+        };
+        assert!(!handle.is_running());
+        assert_eq!(handle.post(|| unreachable!()), Err(UiThreadError::Stopped));
+        assert_eq!(handle.call(|| unreachable!()).wait(), Err::<(), _>(UiThreadError::Stopped));
+        assert_eq!(handle.quit(), Err(UiThreadError::Stopped));
+    }
+}
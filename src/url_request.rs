@@ -4,8 +4,9 @@ use cef_sys::{
     cef_frame_t, cef_request_callback_t, cef_request_t, cef_resource_handler_t,
     cef_resource_read_callback_t, cef_resource_skip_callback_t, cef_response_filter_status_t,
     cef_response_filter_t, cef_response_t, cef_string_t, cef_urlrequest_client_t,
-    cef_urlrequest_create, cef_urlrequest_status_t, cef_urlrequest_t,
+    cef_urlrequest_status_t, cef_urlrequest_t,
 };
+use crate::capi::cef_urlrequest_create;
 use std::{
     convert::TryInto,
     ptr::null_mut,
@@ -24,6 +25,7 @@ use crate::{
     response::Response,
     request_context::RequestContext,
     string::CefString,
+    task::IoThread,
 };
 
 /// Flags that represent [URLRequest] status.
@@ -83,6 +85,7 @@ impl URLRequest {
         client: URLRequestClient,
         request_context: Option<&RequestContext>,
     ) -> Self {
+        ffi_event!("URLRequest::new");
         unsafe {
             Self::from_ptr_unchecked(cef_urlrequest_create(
                 request.as_ptr(),
@@ -96,10 +99,12 @@ impl URLRequest {
     /// Returns the request object used to create this URL request. The returned
     /// object is read-only and should not be modified.
     pub fn get_request(&self) -> Request {
+        ffi_event!("URLRequest::get_request");
         unsafe { Request::from_ptr_unchecked(self.0.get_request.unwrap()(self.as_ptr())) }
     }
     /// Returns the request status.
     pub fn get_request_status(&self) -> URLRequestStatus {
+        ffi_event!("URLRequest::get_request_status");
         unsafe {
             URLRequestStatus::from_unchecked(
                 self.0.get_request_status.unwrap()(self.as_ptr()) as crate::CEnumType
@@ -109,6 +114,7 @@ impl URLRequest {
     /// Returns the request error if status is [URLRequestStatus::Canceled] or [URLRequestStatus::Failed], or [ErrorCode::None]
     /// otherwise.
     pub fn get_request_error(&self) -> ErrorCode {
+        ffi_event!("URLRequest::get_request_error");
         unsafe {
             ErrorCode::from_unchecked(self.0.get_request_error.unwrap()(self.as_ptr()) as i32)
         }
@@ -117,15 +123,18 @@ impl URLRequest {
     /// Response information will only be available after the upload has completed.
     /// The returned object is read-only and should not be modified.
     pub fn get_response(&self) -> Option<Response> {
+        ffi_event!("URLRequest::get_response");
         unsafe { Response::from_ptr(self.0.get_response.unwrap()(self.as_ptr())) }
     }
     /// Returns true if the response body was served from the cache. This
     /// includes responses for which revalidation was required.
     pub fn response_was_cached(&self) -> bool {
+        ffi_event!("URLRequest::response_was_cached");
         unsafe { self.0.response_was_cached.unwrap()(self.as_ptr()) != 0 }
     }
     /// Cancel the request.
     pub fn cancel(&self) {
+        ffi_event!("URLRequest::cancel");
         unsafe { self.0.cancel.unwrap()(self.as_ptr()) }
     }
 }
@@ -139,6 +148,7 @@ ref_counted_ptr! {
 impl AuthCallback {
     /// Continue the authentication request.
     pub fn cont(&self, username: &str, password: &str) {
+        ffi_event!("AuthCallback::cont");
         if let Some(cont) = self.0.cont {
             unsafe {
                 cont(
@@ -151,6 +161,7 @@ impl AuthCallback {
     }
     /// Cancel the authentication request.
     pub fn cancel(&self) {
+        ffi_event!("AuthCallback::cancel");
         if let Some(cancel) = self.0.cancel {
             unsafe {
                 cancel(self.as_ptr());
@@ -312,12 +323,14 @@ impl RequestCallback {
     /// Continue the url request. If `allow` is true the request will be
     /// continued. Otherwise, the request will be canceled.
     pub fn cont(&self, allow: bool) {
+        ffi_event!("RequestCallback::cont");
         unsafe {
             self.0.cont.unwrap()(self.0.as_ptr(), allow as i32);
         }
     }
     /// Cancel the url request.
     pub fn cancel(&self) {
+        ffi_event!("RequestCallback::cancel");
         unsafe {
             self.0.cancel.unwrap()(self.0.as_ptr());
         }
@@ -344,6 +357,7 @@ pub trait CookieAccessFilterCallbacks: 'static + Sync + Send {
     /// Return true if the specified  cookie can be sent with the request or false otherwise.
     fn can_send_cookie(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -359,6 +373,7 @@ pub trait CookieAccessFilterCallbacks: 'static + Sync + Send {
     /// otherwise.
     fn can_save_cookie(
         &self,
+        io_thread: IoThread,
         browser: Option<Browser>,
         frame: Option<Frame>,
         request: Request,
@@ -403,6 +418,7 @@ cef_callback_impl! {
             cookie: Cookie: *const cef_cookie_t,
         ) -> std::os::raw::c_int {
             self.delegate.can_send_cookie(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -419,6 +435,7 @@ cef_callback_impl! {
             cookie: Cookie: *const cef_cookie_t,
         ) -> std::os::raw::c_int {
             self.delegate.can_save_cookie(
+                unsafe { IoThread::new_unchecked() },
                 browser,
                 frame,
                 request,
@@ -592,6 +609,7 @@ pub trait ResourceHandlerCallbacks: 'static + Send + Sync {
     /// `response` to indicate the error condition.
     fn get_response_headers(
         &self,
+        io_thread: IoThread,
         response: Response,
         response_length: &mut Option<u64>,
         redirect_url: &mut String,
@@ -614,7 +632,7 @@ pub trait ResourceHandlerCallbacks: 'static + Send + Sync {
     /// This function will be called in sequence but not from a dedicated thread.
     fn read(&mut self, handler: ResourceReadHandler) -> Option<ResourceReadHandler>;
     /// Request processing has been canceled.
-    fn cancel(&mut self) {}
+    fn cancel(&mut self, io_thread: IoThread) {}
 }
 
 pub(crate) struct ResourceHandlerWrapper {
@@ -669,6 +687,7 @@ cef_callback_impl!{
             let mut response_length_rs = None;
             let mut redirect_url_rs = String::new();
             self.delegate.lock().borrow().get_response_headers(
+                unsafe { IoThread::new_unchecked() },
                 response,
                 &mut response_length_rs,
                 &mut redirect_url_rs
@@ -715,7 +734,7 @@ cef_callback_impl!{
             }
         }
         fn cancel(&self) {
-            self.delegate.lock().borrow_mut().cancel();
+            self.delegate.lock().borrow_mut().cancel(unsafe { IoThread::new_unchecked() });
         }
     }
 }
@@ -726,6 +745,7 @@ impl ResourceSkipCallback {
     }
 
     pub fn cont(&self, bytes_skipped: i64) {
+        ffi_event!("ResourceSkipCallback::cont");
         unsafe{ self.0.cont.unwrap()(self.as_ptr(), bytes_skipped) }
     }
 }
@@ -762,6 +782,7 @@ impl ResourceReadCallback {
     }
 
     pub fn cont(&self, bytes_read: u32) {
+        ffi_event!("ResourceReadCallback::cont");
         unsafe{ self.0.cont.unwrap()(self.as_ptr(), bytes_read.try_into().unwrap()) }
     }
 }
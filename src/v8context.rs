@@ -1,20 +1,22 @@
 use cef_sys::{
-    cef_base_ref_counted_t, cef_register_extension, cef_string_t, cef_time_from_doublet,
-    cef_time_to_doublet, cef_v8_accesscontrol_t, cef_v8_propertyattribute_t, cef_v8accessor_t,
-    cef_v8array_buffer_release_callback_t, cef_v8context_get_current_context,
-    cef_v8context_get_entered_context, cef_v8context_in_context, cef_v8context_t,
-    cef_v8exception_t, cef_v8handler_t, cef_v8interceptor_t, cef_v8stack_frame_t,
-    cef_v8stack_trace_get_current, cef_v8stack_trace_t, cef_v8value_create_array,
-    cef_v8value_create_array_buffer, cef_v8value_create_bool, cef_v8value_create_date,
-    cef_v8value_create_double, cef_v8value_create_function, cef_v8value_create_int,
-    cef_v8value_create_null, cef_v8value_create_object, cef_v8value_create_string,
-    cef_v8value_create_uint, cef_v8value_create_undefined, cef_v8value_t,
+    cef_base_ref_counted_t, cef_string_t, cef_v8_accesscontrol_t, cef_v8_propertyattribute_t,
+    cef_v8accessor_t, cef_v8array_buffer_release_callback_t, cef_v8context_t, cef_v8exception_t,
+    cef_v8handler_t, cef_v8interceptor_t, cef_v8stack_frame_t, cef_v8stack_trace_t, cef_v8value_t,
+};
+use crate::capi::{
+    cef_register_extension, cef_time_from_doublet, cef_time_to_doublet,
+    cef_v8context_get_current_context, cef_v8context_get_entered_context, cef_v8context_in_context,
+    cef_v8stack_trace_get_current, cef_v8value_create_array, cef_v8value_create_array_buffer,
+    cef_v8value_create_bool, cef_v8value_create_date, cef_v8value_create_double,
+    cef_v8value_create_function, cef_v8value_create_int, cef_v8value_create_null,
+    cef_v8value_create_object, cef_v8value_create_string, cef_v8value_create_uint,
+    cef_v8value_create_undefined,
 };
 use parking_lot::Mutex;
 use std::{
     any::Any,
     cell::RefCell,
-    convert::TryFrom,
+    marker::PhantomData,
     ptr::null_mut,
     mem::ManuallyDrop,
     time::{Duration, SystemTime, SystemTimeError},
@@ -22,11 +24,12 @@ use std::{
 
 use crate::{
     browser::Browser,
+    error::{check, Error},
     frame::Frame,
     refcounted::{RefCountedPtr, Wrapper},
     send_protector::SendProtector,
     string::{CefString, CefStringList},
-    task::TaskRunner,
+    task::{RendererThread, TaskRunner},
 };
 use bitflags::bitflags;
 
@@ -42,21 +45,25 @@ ref_counted_ptr! {
 impl V8Context {
     /// Returns the current (top) context object in the V8 context stack.
     pub fn get_current() -> Option<Self> {
+        ffi_event!("V8Context::get_current");
         unsafe { Self::from_ptr(cef_v8context_get_current_context()) }
     }
     /// Returns the entered (bottom) context object in the V8 context stack.
     pub fn get_entered() -> Option<Self> {
+        ffi_event!("V8Context::get_entered");
         unsafe { Self::from_ptr(cef_v8context_get_entered_context()) }
     }
     /// Returns true if V8 is currently inside a context.
     pub fn in_context() -> bool {
+        ffi_event!("V8Context::in_context");
         unsafe { cef_v8context_in_context() != 0 }
     }
     /// Register a new V8 extension with the specified JavaScript extension code and
     /// handler. Functions implemented by the handler are prototyped using the
     /// keyword 'native'. The calling of a native function is restricted to the scope
     /// in which the prototype of the native function is defined. This function may
-    /// only be called on the render process main thread.
+    /// only be called on the render process main thread, usually from
+    /// [RenderProcessHandlerCallbacks::on_web_kit_initialized].
     ///
     /// Example JavaScript extension code:
     /// ```js
@@ -110,10 +117,12 @@ impl V8Context {
     ///   example.test.increment();
     /// ```
     pub fn register_extension(
+        _renderer_thread: RendererThread,
         extension_name: &str,
         javascript_code: &str,
         handler: impl Fn(&str, V8Value, &[V8Value]) -> Result<V8Value, String> + Sync + Send + 'static,
     ) {
+        ffi_event!("V8Context::register_extension");
         let name = CefString::new(extension_name);
         let js = CefString::new(javascript_code);
         unsafe {
@@ -128,6 +137,7 @@ impl V8Context {
     /// be accessed from the thread on which they are created. This function can be
     /// called on any render process thread.
     pub fn get_task_runner(&self) -> TaskRunner {
+        ffi_event!("V8Context::get_task_runner");
         let get_task_runner = self.0.get_task_runner.unwrap();
         unsafe { TaskRunner::from_ptr_unchecked(get_task_runner(self.as_ptr())) }
     }
@@ -135,6 +145,7 @@ impl V8Context {
     /// on the current thread. Do not call any other functions if this function
     /// returns false.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("V8Context::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) != 0 })
@@ -143,6 +154,7 @@ impl V8Context {
     /// Returns the browser for this context. This function will return None
     /// for WebWorker contexts.
     pub fn get_browser(&self) -> Option<Browser> {
+        ffi_event!("V8Context::get_browser");
         self.0
             .get_browser
             .and_then(|get_browser| unsafe { Browser::from_ptr(get_browser(self.as_ptr())) })
@@ -150,6 +162,7 @@ impl V8Context {
     /// Returns the frame for this context. This function will return None
     /// for WebWorker contexts.
     pub fn get_frame(&self) -> Option<Frame> {
+        ffi_event!("V8Context::get_frame");
         self.0
             .get_frame
             .and_then(|get_frame| unsafe { Frame::from_ptr(get_frame(self.as_ptr())) })
@@ -157,6 +170,7 @@ impl V8Context {
     /// Returns the global object for this context or None if scripting is disabled.
     /// The context must be entered before calling this function.
     pub fn get_global(&self) -> Option<V8Value> {
+        ffi_event!("V8Context::get_global");
         self.0
             .get_global
             .and_then(|get_global| unsafe { V8Value::from_ptr(get_global(self.as_ptr())) })
@@ -164,36 +178,56 @@ impl V8Context {
     /// Enter this context. A context must be explicitly entered before creating a
     /// V8 Object, Array, Function or Date asynchronously. [exit] must be called
     /// the same number of times as [enter] before releasing this context. V8
-    /// objects belong to the context in which they are created. Returns true
-    /// if the scope was entered successfully.
-    pub fn enter(&self) -> bool {
-        self.0
+    /// objects belong to the context in which they are created. Returns
+    /// [Error::InvalidHandle] if the context can't be accessed on this thread.
+    pub fn enter(&self) -> Result<(), Error> {
+        ffi_event!("V8Context::enter");
+        if !self.is_valid() {
+            return Err(Error::InvalidHandle);
+        }
+        let success = self
+            .0
             .enter
             .map(|enter| unsafe { enter(self.as_ptr()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "V8Context::enter")
     }
-    /// Exit this context. Call this function only after calling [enter]. Returns
-    /// true if the scope was exited successfully.
-    pub fn exit(&self) -> bool {
-        self.0
+    /// Exit this context. Call this function only after calling [enter].
+    pub fn exit(&self) -> Result<(), Error> {
+        ffi_event!("V8Context::exit");
+        let success = self
+            .0
             .exit
             .map(|exit| unsafe { exit(self.as_ptr()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "V8Context::exit")
     }
     /// Convenience function to wrap a closure in an [enter] and [exit] call.
-    /// If enter fails, the closure is not executed and None is returned. If exit fails, None is returned.
-    pub fn execute_in_context<T>(&self, fun: impl FnOnce() -> T) -> Option<T> {
-        if self.enter() {
-            let result = fun();
-            if self.exit() {
-                return Some(result);
-            }
-        }
-        None
+    /// If enter fails, the closure is not executed. If exit fails, the result of
+    /// the closure is dropped and the error is returned.
+    pub fn execute_in_context<T>(&self, fun: impl FnOnce() -> T) -> Result<T, Error> {
+        ffi_event!("V8Context::execute_in_context");
+        self.enter()?;
+        let result = fun();
+        self.exit()?;
+        Ok(result)
+    }
+    /// Like [execute_in_context], but passes a [V8Scope] to the closure for creating
+    /// V8 values.
+    pub fn with_scope<T>(&self, fun: impl FnOnce(&V8Scope) -> T) -> Result<T, Error> {
+        ffi_event!("V8Context::with_scope");
+        let context = self.clone();
+        self.execute_in_context(move || {
+            fun(&V8Scope {
+                context,
+                marker: PhantomData,
+            })
+        })
     }
     /// Returns true if this object is pointing to the same handle as `that`
     /// object.
     pub fn is_same(&self, that: &Self) -> bool {
+        ffi_event!("V8Context::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.as_ptr()) != 0 })
@@ -208,6 +242,7 @@ impl V8Context {
         script_url: &str,
         start_line: i32,
     ) -> Result<V8Value, V8Exception> {
+        ffi_event!("V8Context::eval");
         let eval = self.0.eval.unwrap();
         let mut retval = null_mut();
         let mut exception = null_mut();
@@ -230,12 +265,44 @@ impl V8Context {
     /// Returns the stack trace for the currently active context. `frame_limit` is
     /// the maximum number of frames that will be captured.
     pub fn get_current_stacktrace(frame_limit: i32) -> Vec<V8StackFrame> {
+        ffi_event!("V8Context::get_current_stacktrace");
         let frames =
             unsafe { V8StackTrace::from_ptr_unchecked(cef_v8stack_trace_get_current(frame_limit)) };
         frames.into()
     }
 }
 
+/// Proof that V8 is currently inside a context on this thread. Creating V8
+/// objects, arrays, array buffers and functions outside of a context crashes the
+/// render process, so the functions doing that require a reference to this.
+///
+/// A scope is only ever handed out by reference for the duration of a closure,
+/// so it can't outlive the context.
+pub struct V8Scope<'a> {
+    context: V8Context,
+    marker: PhantomData<&'a *const ()>,
+}
+
+impl<'a> V8Scope<'a> {
+    /// Run `fun` with the current scope. Returns None if V8 is not inside a
+    /// context, which is the case outside of [RenderProcessHandlerCallbacks],
+    /// [V8Handler] and [V8AccessorCallbacks] callbacks.
+    pub fn with_current<T>(fun: impl FnOnce(&V8Scope) -> T) -> Option<T> {
+        if !V8Context::in_context() {
+            return None;
+        }
+        let context = V8Context::get_current()?;
+        Some(fun(&V8Scope {
+            context,
+            marker: PhantomData,
+        }))
+    }
+    /// Returns the context this scope is in.
+    pub fn context(&self) -> &V8Context {
+        &self.context
+    }
+}
+
 ref_counted_ptr! {
     /// Structure representing a V8 exception. The functions of this structure may be
     /// called on any render process thread.
@@ -245,6 +312,7 @@ ref_counted_ptr! {
 impl V8Exception {
     /// Returns the exception message.
     pub fn get_message(&self) -> String {
+        ffi_event!("V8Exception::get_message");
         self.0
             .get_message
             .and_then(|get_message| {
@@ -255,6 +323,7 @@ impl V8Exception {
     }
     /// Returns the line of source code that the exception occurred within.
     pub fn get_source_line(&self) -> String {
+        ffi_event!("V8Exception::get_source_line");
         self.0
             .get_source_line
             .and_then(|get_source_line| {
@@ -266,6 +335,7 @@ impl V8Exception {
     /// Returns the resource name for the script from where the function causing
     /// the error originates.
     pub fn get_script_resource_name(&self) -> String {
+        ffi_event!("V8Exception::get_script_resource_name");
         self.0
             .get_script_resource_name
             .and_then(|get_script_resource_name| {
@@ -277,6 +347,7 @@ impl V8Exception {
     /// Returns the 1-based number of the line where the error occurred or 0 if the
     /// line number is unknown.
     pub fn get_line_number(&self) -> i32 {
+        ffi_event!("V8Exception::get_line_number");
         self.0
             .get_line_number
             .map(|get_line_number| unsafe { get_line_number(self.as_ptr()) })
@@ -285,6 +356,7 @@ impl V8Exception {
     /// Returns the index within the script of the first character where the error
     /// occurred.
     pub fn get_start_position(&self) -> i32 {
+        ffi_event!("V8Exception::get_start_position");
         self.0
             .get_start_position
             .map(|get_start_position| unsafe { get_start_position(self.as_ptr()) })
@@ -293,6 +365,7 @@ impl V8Exception {
     /// Returns the index within the script of the last character where the error
     /// occurred.
     pub fn get_end_position(&self) -> i32 {
+        ffi_event!("V8Exception::get_end_position");
         self.0
             .get_end_position
             .map(|get_end_position| unsafe { get_end_position(self.as_ptr()) })
@@ -301,6 +374,7 @@ impl V8Exception {
     /// Returns the index within the line of the first character where the error
     /// occurred.
     pub fn get_start_column(&self) -> i32 {
+        ffi_event!("V8Exception::get_start_column");
         self.0
             .get_start_column
             .map(|get_start_column| unsafe { get_start_column(self.as_ptr()) })
@@ -309,6 +383,7 @@ impl V8Exception {
     /// Returns the index within the line of the last character where the error
     /// occurred.
     pub fn get_end_column(&self) -> i32 {
+        ffi_event!("V8Exception::get_end_column");
         self.0
             .get_end_column
             .map(|get_end_column| unsafe { get_end_column(self.as_ptr()) })
@@ -330,6 +405,7 @@ impl V8StackFrame {
     /// on the current thread. Do not call any other functions if this function
     /// returns false.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("V8StackFrame::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) != 0 })
@@ -337,6 +413,7 @@ impl V8StackFrame {
     }
     /// Returns the name of the resource script that contains the function.
     pub fn get_script_name(&self) -> String {
+        ffi_event!("V8StackFrame::get_script_name");
         self.0
             .get_script_name
             .and_then(|get_script_name| {
@@ -349,6 +426,7 @@ impl V8StackFrame {
     /// sourceURL value if the script name is undefined and its source ends with a
     /// `"//@ sourceURL=..."` string.
     pub fn get_script_name_or_source_url(&self) -> String {
+        ffi_event!("V8StackFrame::get_script_name_or_source_url");
         self.0
             .get_script_name_or_source_url
             .and_then(|get_script_name_or_source_url| {
@@ -359,6 +437,7 @@ impl V8StackFrame {
     }
     /// Returns the name of the function.
     pub fn get_function_name(&self) -> String {
+        ffi_event!("V8StackFrame::get_function_name");
         self.0
             .get_function_name
             .and_then(|get_function_name| {
@@ -369,6 +448,7 @@ impl V8StackFrame {
     }
     /// Returns the 1-based line number for the function call or 0 if unknown.
     pub fn get_line_number(&self) -> i32 {
+        ffi_event!("V8StackFrame::get_line_number");
         self.0
             .get_line_number
             .map(|get_line_number| unsafe { get_line_number(self.as_ptr()) })
@@ -377,6 +457,7 @@ impl V8StackFrame {
     /// Returns the 1-based column offset on the line for the function call or 0 if
     /// unknown.
     pub fn get_column(&self) -> i32 {
+        ffi_event!("V8StackFrame::get_column");
         self.0
             .get_column
             .map(|get_column| unsafe { get_column(self.as_ptr()) })
@@ -384,6 +465,7 @@ impl V8StackFrame {
     }
     /// Returns true if the function was compiled using eval().
     pub fn is_eval(&self) -> bool {
+        ffi_event!("V8StackFrame::is_eval");
         self.0
             .is_eval
             .map(|is_eval| unsafe { is_eval(self.as_ptr()) != 0 })
@@ -391,6 +473,7 @@ impl V8StackFrame {
     }
     /// Returns true if the function was called as a constructor via "new".
     pub fn is_constructor(&self) -> bool {
+        ffi_event!("V8StackFrame::is_constructor");
         self.0
             .is_constructor
             .map(|is_constructor| unsafe { is_constructor(self.as_ptr()) != 0 })
@@ -454,21 +537,22 @@ ref_counted_ptr! {
 impl V8Value {
     /// Create a new V8Value object of type undefined.
     pub fn undefined() -> Self {
+        ffi_event!("V8Value::undefined");
         unsafe { V8Value::from_ptr_unchecked(cef_v8value_create_undefined()) }
     }
     /// Create a new V8Value object of type null.
     pub fn null() -> Self {
+        ffi_event!("V8Value::null");
         unsafe { V8Value::from_ptr_unchecked(cef_v8value_create_null()) }
     }
     /// Create a new V8Value object of type object with optional accessor
-    /// and/or interceptor. This function should only be called from within the scope
-    /// of a [RenderProcessHandlerCallbacks], [V8Handler] or [V8AccessorCallbacks]
-    /// callback, or in combination with calling [V8Context::enter] and [V8Context::exit] on a stored
-    /// [V8Context] reference.
+    /// and/or interceptor. See [V8Scope] for how to get a `scope`.
     pub fn new_object(
+        _scope: &V8Scope,
         accessor: Option<V8Accessor>,
         interceptor: Option<V8Interceptor>,
     ) -> Self {
+        ffi_event!("V8Value::new_object");
         unsafe {
             V8Value::from_ptr_unchecked(cef_v8value_create_object(
                 accessor
@@ -481,21 +565,17 @@ impl V8Value {
         }
     }
     /// Create a new V8Value object of type array with the specified `length`.
-    /// If `length` is negative the returned array will have length 0. This function
-    /// should only be called from within the scope of a
-    /// [RenderProcessHandlerCallbacks], [V8Handler] or [V8AccessorCallbacks] callback,
-    /// or in combination with calling [V8Context::enter] and [V8Context::exit] on a stored V8Context
-    /// reference.
-    pub fn new_array(length: i32) -> Self {
+    /// If `length` is negative the returned array will have length 0. See [V8Scope]
+    /// for how to get a `scope`.
+    pub fn new_array(_scope: &V8Scope, length: i32) -> Self {
+        ffi_event!("V8Value::new_array");
         unsafe { V8Value::from_ptr_unchecked(cef_v8value_create_array(length)) }
     }
     /// Create a new V8Value object of type ArrayBuffer which wraps the
-    /// provided `buffer` (without copying it). This function should only
-    /// be called from within the scope of a [RenderProcessHandlerCallbacks], [V8Handler]
-    /// or [V8AccessorCallbacks] callback, or in combination with calling
-    /// [V8Context::enter] and [V8Context::exit] on a stored [V8Context]
-    /// reference.
-    pub fn new_array_buffer(mut buffer: Box<[u8]>) -> Self {
+    /// provided `buffer` (without copying it). See [V8Scope] for how to get a
+    /// `scope`.
+    pub fn new_array_buffer(_scope: &V8Scope, mut buffer: Box<[u8]>) -> Self {
+        ffi_event!("V8Value::new_array_buffer");
         let length = buffer.len();
         let ptr = buffer.as_mut_ptr();
         let mut buffer_md = ManuallyDrop::new(buffer);
@@ -511,15 +591,14 @@ impl V8Value {
             ))
         }
     }
-    /// Create a new V8Value object of type function. This function
-    /// should only be called from within the scope of a
-    /// [RenderProcessHandlerCallbacks], [V8Handler] or [V8AccessorCallbacks] callback,
-    /// or in combination with calling [V8Context::enter] and [V8Context::exit] on a stored [V8Context]
-    /// reference.
+    /// Create a new V8Value object of type function. See [V8Scope] for how to get
+    /// a `scope`.
     pub fn new_function(
+        _scope: &V8Scope,
         name: &str,
         handler: impl Fn(&str, V8Value, &[V8Value]) -> Result<V8Value, String> + Send + 'static,
     ) -> Self {
+        ffi_event!("V8Value::new_function");
         let name = CefString::new(name);
         unsafe {
             V8Value::from_ptr_unchecked(cef_v8value_create_function(
@@ -529,17 +608,50 @@ impl V8Value {
         }
     }
 
+    /// Create a new V8Value object of type Date. See [V8Scope] for how to get a
+    /// `scope`.
+    pub fn new_date(_scope: &V8Scope, value: SystemTime) -> Result<Self, SystemTimeError> {
+        ffi_event!("V8Value::new_date");
+        let duration = value.duration_since(SystemTime::UNIX_EPOCH)?;
+        let mut result = unsafe { std::mem::zeroed() };
+
+        unsafe {
+            cef_time_from_doublet(duration.as_secs_f64(), &mut result);
+        } // This could fail in theory, but the actual implementation only returns 0 if the result is NULL
+        Ok(unsafe { V8Value::from_ptr(cef_v8value_create_date(&result)) }.unwrap())
+    }
+
     /// Returns true if the underlying handle is valid and it can be accessed
     /// on the current thread. Do not call any other functions if this function
     /// returns false.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("V8Value::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) != 0 })
             .unwrap_or(false)
     }
+    /// Returns [Error::InvalidHandle] if this value can't be accessed.
+    fn check_valid(&self) -> Result<(), Error> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(Error::InvalidHandle)
+        }
+    }
+    /// Like [check], but reports the message of the pending exception, if any.
+    fn check_exception(&self, success: bool, operation: &'static str) -> Result<(), Error> {
+        if success {
+            return Ok(());
+        }
+        match self.get_exception() {
+            Some(exception) => Err(Error::Cef(exception.get_message())),
+            None => Err(Error::Failed(operation)),
+        }
+    }
     /// True if the value type is undefined.
     pub fn is_undefined(&self) -> bool {
+        ffi_event!("V8Value::is_undefined");
         self.0
             .is_undefined
             .map(|is_undefined| unsafe { is_undefined(self.as_ptr()) != 0 })
@@ -547,6 +659,7 @@ impl V8Value {
     }
     /// True if the value type is null.
     pub fn is_null(&self) -> bool {
+        ffi_event!("V8Value::is_null");
         self.0
             .is_null
             .map(|is_null| unsafe { is_null(self.as_ptr()) != 0 })
@@ -554,6 +667,7 @@ impl V8Value {
     }
     /// True if the value type is bool.
     pub fn is_bool(&self) -> bool {
+        ffi_event!("V8Value::is_bool");
         self.0
             .is_bool
             .map(|is_bool| unsafe { is_bool(self.as_ptr()) != 0 })
@@ -561,6 +675,7 @@ impl V8Value {
     }
     /// True if the value type is int.
     pub fn is_int(&self) -> bool {
+        ffi_event!("V8Value::is_int");
         self.0
             .is_int
             .map(|is_int| unsafe { is_int(self.as_ptr()) != 0 })
@@ -568,6 +683,7 @@ impl V8Value {
     }
     /// True if the value type is unsigned int.
     pub fn is_uint(&self) -> bool {
+        ffi_event!("V8Value::is_uint");
         self.0
             .is_uint
             .map(|is_uint| unsafe { is_uint(self.as_ptr()) != 0 })
@@ -575,6 +691,7 @@ impl V8Value {
     }
     /// True if the value type is double.
     pub fn is_double(&self) -> bool {
+        ffi_event!("V8Value::is_double");
         self.0
             .is_double
             .map(|is_double| unsafe { is_double(self.as_ptr()) != 0 })
@@ -582,6 +699,7 @@ impl V8Value {
     }
     /// True if the value type is Date.
     pub fn is_date(&self) -> bool {
+        ffi_event!("V8Value::is_date");
         self.0
             .is_date
             .map(|is_date| unsafe { is_date(self.as_ptr()) != 0 })
@@ -589,6 +707,7 @@ impl V8Value {
     }
     /// True if the value type is string.
     pub fn is_string(&self) -> bool {
+        ffi_event!("V8Value::is_string");
         self.0
             .is_string
             .map(|is_string| unsafe { is_string(self.as_ptr()) != 0 })
@@ -596,6 +715,7 @@ impl V8Value {
     }
     /// True if the value type is object.
     pub fn is_object(&self) -> bool {
+        ffi_event!("V8Value::is_object");
         self.0
             .is_object
             .map(|is_object| unsafe { is_object(self.as_ptr()) != 0 })
@@ -603,6 +723,7 @@ impl V8Value {
     }
     /// True if the value type is array.
     pub fn is_array(&self) -> bool {
+        ffi_event!("V8Value::is_array");
         self.0
             .is_array
             .map(|is_array| unsafe { is_array(self.as_ptr()) != 0 })
@@ -610,6 +731,7 @@ impl V8Value {
     }
     /// True if the value type is an ArrayBuffer.
     pub fn is_array_buffer(&self) -> bool {
+        ffi_event!("V8Value::is_array_buffer");
         self.0
             .is_array_buffer
             .map(|is_array_buffer| unsafe { is_array_buffer(self.as_ptr()) != 0 })
@@ -617,6 +739,7 @@ impl V8Value {
     }
     /// True if the value type is function.
     pub fn is_function(&self) -> bool {
+        ffi_event!("V8Value::is_function");
         self.0
             .is_function
             .map(|is_function| unsafe { is_function(self.as_ptr()) != 0 })
@@ -625,6 +748,7 @@ impl V8Value {
     /// Returns true if this object is pointing to the same handle as `that`
     /// object.
     pub fn is_same(&self, that: &Self) -> bool {
+        ffi_event!("V8Value::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.as_ptr()) != 0 })
@@ -632,6 +756,7 @@ impl V8Value {
     }
     /// Return a bool value.
     pub fn get_bool_value(&self) -> Option<bool> {
+        ffi_event!("V8Value::get_bool_value");
         if self.is_bool() {
             self.0
                 .get_bool_value
@@ -642,6 +767,7 @@ impl V8Value {
     }
     /// Return an int value.
     pub fn get_int_value(&self) -> Option<i32> {
+        ffi_event!("V8Value::get_int_value");
         if self.is_int() {
             self.0
                 .get_int_value
@@ -652,6 +778,7 @@ impl V8Value {
     }
     /// Return an unsigned int value.
     pub fn get_uint_value(&self) -> Option<u32> {
+        ffi_event!("V8Value::get_uint_value");
         if self.is_uint() {
             self.0
                 .get_uint_value
@@ -662,6 +789,7 @@ impl V8Value {
     }
     /// Return a double value.
     pub fn get_double_value(&self) -> Option<f64> {
+        ffi_event!("V8Value::get_double_value");
         if self.is_double() {
             self.0
                 .get_double_value
@@ -672,6 +800,7 @@ impl V8Value {
     }
     /// Return a Date value.
     pub fn get_date_value(&self) -> Option<SystemTime> {
+        ffi_event!("V8Value::get_date_value");
         if self.is_date() {
             self.0.get_date_value.map(|get_date_value| {
                 let value = unsafe { get_date_value(self.as_ptr()) };
@@ -687,6 +816,7 @@ impl V8Value {
     }
     /// Return a string value.
     pub fn get_string_value(&self) -> Option<String> {
+        ffi_event!("V8Value::get_string_value");
         if self.is_string() {
             self.0.get_string_value.and_then(|get_string_value| {
                 unsafe { CefString::from_mut_ptr(get_string_value(self.as_ptr())) }
@@ -701,6 +831,7 @@ impl V8Value {
     /// Only available on objects. Arrays and
     /// functions are also objects.
     pub fn is_user_created(&self) -> bool {
+        ffi_event!("V8Value::is_user_created");
         self.0
             .is_user_created
             .map(|is_user_created| unsafe { is_user_created(self.as_ptr()) != 0 })
@@ -712,6 +843,7 @@ impl V8Value {
     /// Only available on objects. Arrays and
     /// functions are also objects.
     pub fn has_exception(&self) -> bool {
+        ffi_event!("V8Value::has_exception");
         self.0
             .has_exception
             .map(|has_exception| unsafe { has_exception(self.as_ptr()) != 0 })
@@ -723,19 +855,24 @@ impl V8Value {
     /// Only available on objects. Arrays and
     /// functions are also objects.
     pub fn get_exception(&self) -> Option<V8Exception> {
+        ffi_event!("V8Value::get_exception");
         self.0.get_exception.and_then(|get_exception| unsafe {
             V8Exception::from_ptr(get_exception(self.as_ptr()))
         })
     }
-    /// Clears the last exception and returns true on success.
+    /// Clears the last exception.
     ///
     /// Only available on objects. Arrays and
     /// functions are also objects.
-    pub fn clear_exception(&self) -> bool {
-        self.0
+    pub fn clear_exception(&self) -> Result<(), Error> {
+        ffi_event!("V8Value::clear_exception");
+        self.check_valid()?;
+        let success = self
+            .0
             .clear_exception
             .map(|clear_exception| unsafe { clear_exception(self.as_ptr()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "V8Value::clear_exception")
     }
     /// Returns true if this object will re-throw future exceptions. This
     /// attribute exists only in the scope of the current CEF value object.
@@ -743,6 +880,7 @@ impl V8Value {
     /// Only available on objects. Arrays and
     /// functions are also objects.
     pub fn will_rethrow_exceptions(&self) -> bool {
+        ffi_event!("V8Value::will_rethrow_exceptions");
         self.0
             .will_rethrow_exceptions
             .map(|will_rethrow_exceptions| unsafe { will_rethrow_exceptions(self.as_ptr()) != 0 })
@@ -751,18 +889,22 @@ impl V8Value {
     /// Set whether this object will re-throw future exceptions. By default
     /// exceptions are not re-thrown. If a exception is re-thrown the current
     /// context should not be accessed again until after the exception has been
-    /// caught and not re-thrown. Returns true on success. This attribute
-    /// exists only in the scope of the current CEF value object.
+    /// caught and not re-thrown. This attribute exists only in the scope of the
+    /// current CEF value object.
     ///
     /// Only available on objects. Arrays and
     /// functions are also objects.
-    pub fn set_rethrow_exceptions(&self, rethrow: bool) -> bool {
-        self.0
+    pub fn set_rethrow_exceptions(&self, rethrow: bool) -> Result<(), Error> {
+        ffi_event!("V8Value::set_rethrow_exceptions");
+        self.check_valid()?;
+        let success = self
+            .0
             .set_rethrow_exceptions
             .map(|set_rethrow_exceptions| unsafe {
                 set_rethrow_exceptions(self.as_ptr(), rethrow as i32) != 0
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "V8Value::set_rethrow_exceptions")
     }
     /// Returns true if the object has a value with the specified identifier.
     ///
@@ -770,6 +912,7 @@ impl V8Value {
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
     pub fn has_value_bykey(&self, key: &str) -> bool {
+        ffi_event!("V8Value::has_value_bykey");
         self.0
             .has_value_bykey
             .map(|has_value_bykey| unsafe {
@@ -783,40 +926,49 @@ impl V8Value {
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
     pub fn has_value_byindex(&self, index: i32) -> bool {
+        ffi_event!("V8Value::has_value_byindex");
         self.0
             .has_value_byindex
             .map(|has_value_byindex| unsafe { has_value_byindex(self.as_ptr(), index) != 0 })
             .unwrap_or(false)
     }
-    /// Deletes the value with the specified identifier and returns true on
-    /// success. Returns false if this function is called incorrectly or an
-    /// exception is thrown. For read-only and don't-delete values this function
-    /// will return true even though deletion failed.
+    /// Deletes the value with the specified identifier. Returns [Error::Cef] with
+    /// the exception message if an exception is thrown. For read-only and
+    /// don't-delete values this function will return Ok even though deletion
+    /// failed.
     ///
     /// Only available on objects. Arrays and functions are also objects.
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
-    pub fn delete_value_bykey(&self, key: &str) -> bool {
-        self.0
+    pub fn delete_value_bykey(&self, key: &str) -> Result<(), Error> {
+        ffi_event!("V8Value::delete_value_bykey");
+        self.check_valid()?;
+        let success = self
+            .0
             .delete_value_bykey
             .map(|delete_value_bykey| unsafe {
                 delete_value_bykey(self.as_ptr(), CefString::new(key).as_ptr()) != 0
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        self.check_exception(success, "V8Value::delete_value_bykey")
     }
-    /// Deletes the value with the specified identifier and returns true on
-    /// success. Returns false if this function is called incorrectly or an
-    /// exception is thrown. For read-only and don't-delete values this function
-    /// will return true even though deletion failed.
+    /// Deletes the value with the specified identifier. Returns [Error::Cef] with
+    /// the exception message if an exception is thrown. For read-only and
+    /// don't-delete values this function will return Ok even though deletion
+    /// failed.
     ///
     /// Only available on objects. Arrays and functions are also objects.
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
-    pub fn delete_value_byindex(&self, index: i32) -> bool {
-        self.0
+    pub fn delete_value_byindex(&self, index: i32) -> Result<(), Error> {
+        ffi_event!("V8Value::delete_value_byindex");
+        self.check_valid()?;
+        let success = self
+            .0
             .delete_value_byindex
             .map(|delete_value_byindex| unsafe { delete_value_byindex(self.as_ptr(), index) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        self.check_exception(success, "V8Value::delete_value_byindex")
     }
     /// Returns the value with the specified identifier on success. Returns None if
     /// this function is called incorrectly or an exception is thrown.
@@ -825,6 +977,7 @@ impl V8Value {
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
     pub fn get_value_bykey(&self, key: &str) -> Option<V8Value> {
+        ffi_event!("V8Value::get_value_bykey");
         self.0.get_value_bykey.and_then(|get_value_bykey| unsafe {
             V8Value::from_ptr(get_value_bykey(self.as_ptr(), CefString::new(key).as_ptr()))
         })
@@ -836,16 +989,16 @@ impl V8Value {
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
     pub fn get_value_byindex(&self, index: i32) -> Option<V8Value> {
+        ffi_event!("V8Value::get_value_byindex");
         self.0
             .get_value_byindex
             .and_then(|get_value_byindex| unsafe {
                 V8Value::from_ptr(get_value_byindex(self.as_ptr(), index))
             })
     }
-    // Associates a value with the specified identifier and returns true on
-    // success. Returns false if this function is called incorrectly or an
-    // exception is thrown. For read-only values this function will return true
-    // even though assignment failed.
+    /// Associates a value with the specified identifier. Returns [Error::Cef] with
+    /// the exception message if an exception is thrown. For read-only values this
+    /// function will return Ok even though assignment failed.
     ///
     /// Only available on objects. Arrays and functions are also objects.
     /// String- and integer-based keys can be used interchangably with the
@@ -855,8 +1008,11 @@ impl V8Value {
         key: &str,
         value: V8Value,
         attributes: V8PropertyAttribute,
-    ) -> bool {
-        self.0
+    ) -> Result<(), Error> {
+        ffi_event!("V8Value::set_value_bykey");
+        self.check_valid()?;
+        let success = self
+            .0
             .set_value_bykey
             .map(|set_value_bykey| unsafe {
                 set_value_bykey(
@@ -866,29 +1022,32 @@ impl V8Value {
                     cef_v8_propertyattribute_t(attributes.bits),
                 ) != 0
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        self.check_exception(success, "V8Value::set_value_bykey")
     }
-    // Associates a value with the specified identifier and returns true on
-    // success. Returns false if this function is called incorrectly or an
-    // exception is thrown. For read-only values this function will return true
-    // even though assignment failed.
+    /// Associates a value with the specified identifier. Returns [Error::Cef] with
+    /// the exception message if an exception is thrown. For read-only values this
+    /// function will return Ok even though assignment failed.
     ///
     /// Only available on objects. Arrays and functions are also objects.
     /// String- and integer-based keys can be used interchangably with the
     /// framework converting between them as necessary.
-    pub fn set_value_byindex(&self, index: i32, value: V8Value) -> bool {
-        self.0
+    pub fn set_value_byindex(&self, index: i32, value: V8Value) -> Result<(), Error> {
+        ffi_event!("V8Value::set_value_byindex");
+        self.check_valid()?;
+        let success = self
+            .0
             .set_value_byindex
             .map(|set_value_byindex| unsafe {
                 set_value_byindex(self.as_ptr(), index, value.as_ptr()) != 0
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        self.check_exception(success, "V8Value::set_value_byindex")
     }
-    /// Registers an identifier and returns true on success. Access to the
-    /// identifier will be forwarded to the [V8AccessorCallbacks] instance passed to
-    /// [V8Value::new_object]. Returns false if this
-    /// function is called incorrectly or an exception is thrown. For read-only
-    /// values this function will return true even though assignment failed.
+    /// Registers an identifier. Access to the identifier will be forwarded to the
+    /// [V8AccessorCallbacks] instance passed to [V8Value::new_object]. Returns
+    /// [Error::Cef] with the exception message if an exception is thrown. For
+    /// read-only values this function will return Ok even though assignment failed.
     ///
     /// Only available on objects. Arrays and functions are also objects.
     /// String- and integer-based keys can be used interchangably with the
@@ -898,8 +1057,11 @@ impl V8Value {
         key: &str,
         settings: V8AccessControl,
         attributes: V8PropertyAttribute,
-    ) -> bool {
-        self.0
+    ) -> Result<(), Error> {
+        ffi_event!("V8Value::set_value_byaccessor");
+        self.check_valid()?;
+        let success = self
+            .0
             .set_value_byaccessor
             .map(|set_value_byaccessor| {
                 unsafe {
@@ -911,13 +1073,15 @@ impl V8Value {
                     ) != 0
                 }
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        self.check_exception(success, "V8Value::set_value_byaccessor")
     }
     /// Read the keys for the object's values into the specified vector. Integer-
     /// based keys will also be returned as strings.
     ///
     /// Only available on objects. Arrays and functions are also objects.
     pub fn get_keys(&self) -> Option<Vec<String>> {
+        ffi_event!("V8Value::get_keys");
         self.0.get_keys.and_then(|get_keys| {
             let mut list = CefStringList::new();
             if unsafe { get_keys(self.as_ptr(), list.as_mut_ptr()) } == 0 {
@@ -931,19 +1095,23 @@ impl V8Value {
             }
         })
     }
-    /// Sets the user data for this object and returns true on success. Returns
-    /// false if this function is called incorrectly. This function can only be
-    /// called on user created objects.
-    pub fn set_user_data(&self, user_data: impl Any + Sync + Send) -> bool {
-        self.0
+    /// Sets the user data for this object. This function can only be called on
+    /// user created objects.
+    pub fn set_user_data(&self, user_data: impl Any + Sync + Send) -> Result<(), Error> {
+        ffi_event!("V8Value::set_user_data");
+        self.check_valid()?;
+        let success = self
+            .0
             .set_user_data
             .map(|set_user_data| unsafe {
                 set_user_data(self.as_ptr(), UserData::new(user_data).into_raw() as _) != 0
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "V8Value::set_user_data")
     }
     /// Returns the user data, if any and of the right type, assigned to this object.
     pub fn get_user_data(&self) -> Option<UserData> {
+        ffi_event!("V8Value::get_user_data");
         self.0.get_user_data.and_then(|get_user_data| {
             let ptr = unsafe { get_user_data(self.as_ptr()) };
             unsafe { UserData::from_ptr(ptr as _) }
@@ -952,6 +1120,7 @@ impl V8Value {
     /// Returns the amount of externally allocated memory registered for the
     /// object.
     pub fn get_externally_allocated_memory(&self) -> i32 {
+        ffi_event!("V8Value::get_externally_allocated_memory");
         self.0
             .get_externally_allocated_memory
             .map(|get_externally_allocated_memory| unsafe {
@@ -969,6 +1138,7 @@ impl V8Value {
     /// returns the number of bytes associated with the object after the
     /// adjustment. This function can only be called on user created objects.
     pub fn adjust_externally_allocated_memory(&self, change_in_bytes: i32) -> i32 {
+        ffi_event!("V8Value::adjust_externally_allocated_memory");
         self.0
             .adjust_externally_allocated_memory
             .map(|adjust_externally_allocated_memory| unsafe {
@@ -980,6 +1150,7 @@ impl V8Value {
     ///
     /// This function is only available on arrays.
     pub fn get_array_length(&self) -> i32 {
+        ffi_event!("V8Value::get_array_length");
         self.0
             .get_array_length
             .map(|get_array_length| unsafe { get_array_length(self.as_ptr()) })
@@ -991,6 +1162,7 @@ impl V8Value {
     ///
     /// This function is only available on ArrayBuffers.
     pub fn neuter_array_buffer(&self) -> bool {
+        ffi_event!("V8Value::neuter_array_buffer");
         self.0
             .neuter_array_buffer
             .map(|neuter_array_buffer| unsafe { neuter_array_buffer(self.as_ptr()) != 0 })
@@ -1000,6 +1172,7 @@ impl V8Value {
     ///
     /// This function is only available on functions.
     pub fn get_function_name(&self) -> Option<String> {
+        ffi_event!("V8Value::get_function_name");
         self.0.get_function_name.and_then(|get_function_name| {
             unsafe { CefString::from_mut_ptr(get_function_name(self.as_ptr())) }
                 .map(|s| <&CefString as Into<_>>::into(s))
@@ -1009,6 +1182,7 @@ impl V8Value {
     ///
     /// This function is only available on functions.
     pub fn get_function_handler(&self) -> Option<V8Handler> {
+        ffi_event!("V8Value::get_function_handler");
         self.0
             .get_function_handler
             .map(|get_function_handler| unsafe {
@@ -1032,6 +1206,7 @@ impl V8Value {
         object: Option<V8Value>,
         arguments: &[V8Value],
     ) -> Option<V8Value> {
+        ffi_event!("V8Value::execute_function");
         self.0.execute_function.and_then(|execute_function| {
             let count = arguments.len();
             unsafe {
@@ -1063,6 +1238,7 @@ impl V8Value {
         object: Option<V8Value>,
         arguments: &[V8Value],
     ) -> Option<V8Value> {
+        ffi_event!("V8Value::execute_function_with_context");
         self.0
             .execute_function_with_context
             .and_then(|execute_function_with_context| {
@@ -1113,23 +1289,6 @@ impl From<f64> for V8Value {
     }
 }
 
-impl TryFrom<SystemTime> for V8Value {
-    type Error = SystemTimeError;
-    /// Create a new V8Value object of type Date. This function should only be
-    /// called from within the scope of a [RenderProcessHandlerCallbacks],
-    /// [V8Handler] or [V8AccessorHandler] callback, or in combination with calling
-    /// [V8Context::enter] and [V8Context::exit] on a stored [V8Context] reference.
-    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
-        let duration = value.duration_since(SystemTime::UNIX_EPOCH)?;
-        let mut result = unsafe { std::mem::zeroed() };
-
-        unsafe {
-            cef_time_from_doublet(duration.as_secs_f64(), &mut result);
-        } // This could fail in theory, but the actual implementation only returns 0 if the result is NULL
-        Ok(unsafe { V8Value::from_ptr(cef_v8value_create_date(&result)) }.unwrap())
-    }
-}
-
 impl From<&str> for V8Value {
     /// Create a new V8Value object of type string.
     fn from(value: &str) -> Self {
@@ -1472,6 +1631,7 @@ impl V8Handler {
         object: V8Value,
         arguments: &[V8Value],
     ) -> Result<V8Value, String> {
+        ffi_event!("V8Handler::execute");
         self.0
             .execute
             .ok_or_else(|| "Execute is null".to_owned())
@@ -1,10 +1,12 @@
 pub mod iter;
 use self::iter::DictionaryValueKeysIter;
 use cef_sys::{
-    cef_binary_value_create, cef_binary_value_t, cef_dictionary_value_create,
-    cef_dictionary_value_t, cef_list_value_create, cef_list_value_t, cef_point_t, cef_range_t,
-    cef_size_t, cef_string_userfree_utf16_free, cef_value_create, cef_value_t, cef_value_type_t,
-    cef_rect_t,
+    cef_binary_value_t, cef_dictionary_value_t, cef_list_value_t, cef_point_t, cef_range_t,
+    cef_size_t, cef_value_t, cef_value_type_t, cef_rect_t,
+};
+use crate::capi::{
+    cef_binary_value_create, cef_dictionary_value_create, cef_list_value_create,
+    cef_string_userfree_utf16_free, cef_value_create,
 };
 use std::{
     collections::HashMap,
@@ -13,7 +15,10 @@ use std::{
     marker::PhantomData,
 };
 
-use crate::string::{CefString, CefStringList, CefStringListIntoIter};
+use crate::{
+    error::{check, check_writable, Error},
+    string::{CefString, CefStringList, CefStringListIntoIter},
+};
 
 #[derive(Debug, Eq, PartialEq)]
 #[repr(C)]
@@ -316,6 +321,7 @@ impl BinaryValue {
     /// Creates a new object that is not owned by any other object. The specified
     /// `data` will be copied.
     pub fn new(data: &[u8]) -> Self {
+        ffi_event!("BinaryValue::new");
         unsafe {
             Self::from_ptr_unchecked(cef_binary_value_create(
                 data.as_ptr() as *const std::os::raw::c_void,
@@ -328,6 +334,7 @@ impl BinaryValue {
     /// and that other object is then modified or destroyed. Do not call any other
     /// functions if this function returns false.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("BinaryValue::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) != 0 })
@@ -335,6 +342,7 @@ impl BinaryValue {
     }
     /// Returns true if the underlying data is owned by another object.
     pub fn is_owned(&self) -> bool {
+        ffi_event!("BinaryValue::is_owned");
         self.0
             .is_owned
             .map(|is_owned| unsafe { is_owned(self.as_ptr()) != 0 })
@@ -343,6 +351,7 @@ impl BinaryValue {
     /// Returns true if this object and `that` object have the same underlying
     /// data.
     pub fn is_same(&self, that: BinaryValue) -> bool {
+        ffi_event!("BinaryValue::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.into_raw()) != 0 })
@@ -350,6 +359,7 @@ impl BinaryValue {
     }
     /// Returns the data size.
     pub fn len(&self) -> usize {
+        ffi_event!("BinaryValue::len");
         self.0
             .get_size
             .map(|get_size| unsafe { get_size(self.as_ptr()) })
@@ -357,6 +367,7 @@ impl BinaryValue {
     }
 
     pub fn write_to_slice(&self, offset: usize, slice: &mut [u8]) -> Result<(), usize> {
+        ffi_event!("BinaryValue::write_to_slice");
         let get_data = self.0.get_data.unwrap();
         let bytes_read = unsafe {
             get_data(
@@ -375,6 +386,7 @@ impl BinaryValue {
     }
 
     pub fn push_to_vec(&self, vec: &mut Vec<u8>) {
+        ffi_event!("BinaryValue::push_to_vec");
         let len = self.len();
         let target_vec_len = vec.len() + len;
         let reserve_additional = target_vec_len.checked_sub(vec.capacity());
@@ -504,6 +516,7 @@ ref_counted_ptr! {
 
 impl DictionaryValue {
     pub fn new() -> Self {
+        ffi_event!("DictionaryValue::new");
         unsafe { Self::from_ptr_unchecked(cef_dictionary_value_create()) }
     }
     /// Returns true if this object is valid. This object may become invalid if
@@ -511,6 +524,7 @@ impl DictionaryValue {
     /// and that other object is then modified or destroyed. Do not call any other
     /// functions if this function returns false.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("DictionaryValue::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) != 0 })
@@ -518,6 +532,7 @@ impl DictionaryValue {
     }
     /// Returns true if the underlying data is owned by another object.
     pub fn is_owned(&self) -> bool {
+        ffi_event!("DictionaryValue::is_owned");
         self.0
             .is_owned
             .map(|is_owned| unsafe { is_owned(self.as_ptr()) != 0 })
@@ -526,6 +541,7 @@ impl DictionaryValue {
     /// Returns true if the underlying data is read-only. Some APIs may expose
     /// read-only objects.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("DictionaryValue::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.as_ptr()) != 0 })
@@ -534,6 +550,7 @@ impl DictionaryValue {
     /// Returns true if this object and `that` object have the same underlying
     /// data.
     pub fn is_same(&self, that: DictionaryValue) -> bool {
+        ffi_event!("DictionaryValue::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.into_raw()) != 0 })
@@ -541,20 +558,26 @@ impl DictionaryValue {
     }
     /// Returns the number of values.
     pub fn len(&self) -> usize {
+        ffi_event!("DictionaryValue::len");
         self.0
             .get_size
             .map(|get_size| unsafe { get_size(self.as_ptr()) })
             .unwrap_or(0)
     }
-    /// Removes all values. Returns true on success.
-    pub fn clear(&self) -> bool {
-        self.0
+    /// Removes all values.
+    pub fn clear(&self) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::clear");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .clear
             .map(|clear| unsafe { clear(self.as_ptr()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "DictionaryValue::clear")
     }
     /// Returns true if the current dictionary has a value for the given key.
     pub fn contains_key(&self, key: &str) -> bool {
+        ffi_event!("DictionaryValue::contains_key");
         self.0
             .has_key
             .map(|has_key| unsafe { has_key(self.as_ptr(), CefString::new(key).as_ptr()) != 0 })
@@ -562,6 +585,7 @@ impl DictionaryValue {
     }
     /// Reads all keys for this dictionary into the specified vector.
     pub fn keys(&self) -> DictionaryValueKeysIter {
+        ffi_event!("DictionaryValue::keys");
         let list = self
             .0
             .get_keys
@@ -579,13 +603,16 @@ impl DictionaryValue {
             _dictionary: PhantomData,
         }
     }
-    /// Removes the value at the specified key. Returns true if the value
-    /// is removed successfully.
-    pub fn remove(&self, key: &str) -> bool {
-        self.0
+    /// Removes the value at the specified key.
+    pub fn remove(&self, key: &str) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::remove");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .remove
             .map(|remove| unsafe { remove(self.as_ptr(), CefString::new(key).as_ptr()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "DictionaryValue::remove")
     }
     /// Returns the value type for the specified key.
     pub(crate) fn get_type(&self, key: &str) -> ValueType {
@@ -621,10 +648,12 @@ impl DictionaryValue {
     }
     /// Returns the value at the specified key.
     pub fn get(&self, key: &str) -> StoredValue {
+        ffi_event!("DictionaryValue::get");
         self.get_value_inner(&key.into()).into()
     }
     /// Returns the value at the specified `key` as type bool.
     pub fn get_bool(&self, key: &str) -> bool {
+        ffi_event!("DictionaryValue::get_bool");
         self.0
             .get_bool
             .map(|get_bool| unsafe { get_bool(self.as_ptr(), CefString::new(key).as_ptr()) != 0 })
@@ -632,6 +661,7 @@ impl DictionaryValue {
     }
     /// Returns the value at the specified `key` as type int.
     pub fn get_int(&self, key: &str) -> i32 {
+        ffi_event!("DictionaryValue::get_int");
         self.0
             .get_int
             .map(|get_int| unsafe { get_int(self.as_ptr(), CefString::new(key).as_ptr()) as i32 })
@@ -639,6 +669,7 @@ impl DictionaryValue {
     }
     /// Returns the value at the specified `key` as type double.
     pub fn get_double(&self, key: &str) -> f64 {
+        ffi_event!("DictionaryValue::get_double");
         self.0
             .get_double
             .map(|get_double| unsafe { get_double(self.as_ptr(), CefString::new(key).as_ptr()) })
@@ -646,6 +677,7 @@ impl DictionaryValue {
     }
     /// Returns the value at the specified `key` as type string.
     pub fn get_string(&self, key: &str) -> String {
+        ffi_event!("DictionaryValue::get_string");
         self.0
             .get_string
             .and_then(|get_string| {
@@ -661,6 +693,7 @@ impl DictionaryValue {
     /// Returns the value at the specified key as type binary. The returned value
     /// will reference existing data.
     pub fn try_get_binary(&self, key: &str) -> Option<BinaryValue> {
+        ffi_event!("DictionaryValue::try_get_binary");
         self.0.get_binary.and_then(|get_binary| unsafe {
             BinaryValue::from_ptr(get_binary(self.as_ptr(), CefString::new(key).as_ptr()))
         })
@@ -669,6 +702,7 @@ impl DictionaryValue {
     /// value will reference existing data and modifications to the value will
     /// modify this object.
     pub fn try_get_dictionary(&self, key: &str) -> Option<DictionaryValue> {
+        ffi_event!("DictionaryValue::try_get_dictionary");
         self.0.get_dictionary.and_then(|get_dictionary| unsafe {
             DictionaryValue::from_ptr(get_dictionary(self.as_ptr(), CefString::new(key).as_ptr()))
         })
@@ -677,6 +711,7 @@ impl DictionaryValue {
     /// will reference existing data and modifications to the value will modify
     /// this object.
     pub fn try_get_list(&self, key: &str) -> Option<ListValue> {
+        ffi_event!("DictionaryValue::try_get_list");
         self.0.get_list.and_then(|get_list| unsafe {
             ListValue::from_ptr(get_list(self.as_ptr(), CefString::new(key).as_ptr()))
         })
@@ -703,18 +738,23 @@ impl DictionaryValue {
     pub(crate) fn insert(&self, key: &str, value: StoredValue) -> bool {
         self.insert_inner(key, value.try_into().unwrap())
     }
-    /// Sets the value at the specified key as type null. Returns true if the
-    /// value was set successfully.
-    pub fn insert_null(&self, key: &str) -> bool {
-        self.0
+    /// Sets the value at the specified key as type null.
+    pub fn insert_null(&self, key: &str) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_null");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_null
             .map(|set_null| unsafe { set_null(self.as_ptr(), CefString::new(key).as_ptr()) != 0 })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type bool. Returns true if the
-    /// value was set successfully.
-    pub fn insert_bool(&self, key: &str, value: bool) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_null")
+    }
+    /// Sets the value at the specified key as type bool.
+    pub fn insert_bool(&self, key: &str, value: bool) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_bool");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_bool
             .map(|set_bool| unsafe {
                 set_bool(
@@ -723,32 +763,41 @@ impl DictionaryValue {
                     if value { 1 } else { 0 },
                 ) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type int. Returns true if the
-    /// value was set successfully.
-    pub fn insert_int(&self, key: &str, value: i32) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_bool")
+    }
+    /// Sets the value at the specified key as type int.
+    pub fn insert_int(&self, key: &str, value: i32) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_int");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_int
             .map(|set_int| unsafe {
                 set_int(self.as_ptr(), CefString::new(key).as_ptr(), value) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type double. Returns true if the
-    /// value was set successfully.
-    pub fn insert_double(&self, key: &str, value: f64) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_int")
+    }
+    /// Sets the value at the specified key as type double.
+    pub fn insert_double(&self, key: &str, value: f64) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_double");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_double
             .map(|set_double| unsafe {
                 set_double(self.as_ptr(), CefString::new(key).as_ptr(), value) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type string. Returns true if the
-    /// value was set successfully.
-    pub fn insert_string(&self, key: &str, value: &str) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_double")
+    }
+    /// Sets the value at the specified key as type string.
+    pub fn insert_string(&self, key: &str, value: &str) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_string");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_string
             .map(|set_string| unsafe {
                 set_string(
@@ -757,15 +806,18 @@ impl DictionaryValue {
                     CefString::new(value).as_ptr(),
                 ) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type binary. Returns true if the
-    /// value was set successfully. If `value` is currently owned by another object
-    /// then the value will be copied and the `value` reference will not change.
-    /// Otherwise, ownership will be transferred to this object and the `value`
-    /// reference will be invalidated.
-    pub fn insert_binary(&self, key: &str, value: BinaryValue) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_string")
+    }
+    /// Sets the value at the specified key as type binary. If `value` is currently
+    /// owned by another object then the value will be copied and the `value` reference
+    /// will not change. Otherwise, ownership will be transferred to this object and the
+    /// `value` reference will be invalidated.
+    pub fn insert_binary(&self, key: &str, value: BinaryValue) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_binary");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_binary
             .map(|set_binary| unsafe {
                 set_binary(
@@ -774,15 +826,18 @@ impl DictionaryValue {
                     value.into_raw(),
                 ) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type dict. Returns true if the
-    /// value was set successfully. If `value` is currently owned by another object
-    /// then the value will be copied and the `value` reference will not change.
-    /// Otherwise, ownership will be transferred to this object and the `value`
-    /// reference will be invalidated.
-    pub fn insert_dictionary(&self, key: &str, value: DictionaryValue) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_binary")
+    }
+    /// Sets the value at the specified key as type dict. If `value` is currently owned
+    /// by another object then the value will be copied and the `value` reference will
+    /// not change. Otherwise, ownership will be transferred to this object and the
+    /// `value` reference will be invalidated.
+    pub fn insert_dictionary(&self, key: &str, value: DictionaryValue) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_dictionary");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_dictionary
             .map(|set_dictionary| unsafe {
                 set_dictionary(
@@ -791,15 +846,18 @@ impl DictionaryValue {
                     value.into_raw(),
                 ) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified key as type list. Returns true if the
-    /// value was set successfully. If `value` is currently owned by another object
-    /// then the value will be copied and the `value` reference will not change.
-    /// Otherwise, ownership will be transferred to this object and the `value`
-    /// reference will be invalidated.
-    pub fn insert_list(&self, key: &str, value: ListValue) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_dictionary")
+    }
+    /// Sets the value at the specified key as type list. If `value` is currently owned
+    /// by another object then the value will be copied and the `value` reference will
+    /// not change. Otherwise, ownership will be transferred to this object and the
+    /// `value` reference will be invalidated.
+    pub fn insert_list(&self, key: &str, value: ListValue) -> Result<(), Error> {
+        ffi_event!("DictionaryValue::insert_list");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_list
             .map(|set_list| unsafe {
                 set_list(
@@ -808,7 +866,8 @@ impl DictionaryValue {
                     value.into_raw(),
                 ) != 0
             })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "DictionaryValue::insert_list")
     }
 }
 
@@ -871,6 +930,7 @@ ref_counted_ptr! {
 
 impl ListValue {
     pub fn new() -> Self {
+        ffi_event!("ListValue::new");
         unsafe { Self::from_ptr_unchecked(cef_list_value_create()) }
     }
     /// Returns true if this object is valid. This object may become invalid if
@@ -878,6 +938,7 @@ impl ListValue {
     /// and that other object is then modified or destroyed. Do not call any other
     /// functions if this function returns false.
     pub fn is_valid(&self) -> bool {
+        ffi_event!("ListValue::is_valid");
         self.0
             .is_valid
             .map(|is_valid| unsafe { is_valid(self.as_ptr()) != 0 })
@@ -885,6 +946,7 @@ impl ListValue {
     }
     /// Returns true if the underlying data is owned by another object.
     pub fn is_owned(&self) -> bool {
+        ffi_event!("ListValue::is_owned");
         self.0
             .is_owned
             .map(|is_owned| unsafe { is_owned(self.as_ptr()) != 0 })
@@ -893,6 +955,7 @@ impl ListValue {
     /// Returns true if the underlying data is read-only. Some APIs may expose
     /// read-only objects.
     pub fn is_read_only(&self) -> bool {
+        ffi_event!("ListValue::is_read_only");
         self.0
             .is_read_only
             .map(|is_read_only| unsafe { is_read_only(self.as_ptr()) != 0 })
@@ -901,39 +964,53 @@ impl ListValue {
     /// Returns true if this object and `that` object have the same underlying
     /// data.
     pub fn is_same(&self, that: ListValue) -> bool {
+        ffi_event!("ListValue::is_same");
         self.0
             .is_same
             .map(|is_same| unsafe { is_same(self.as_ptr(), that.into_raw()) != 0 })
             .unwrap_or(false)
     }
-    /// Sets the number of values. If the number of values is expanded all new
-    /// value slots will default to type None. Returns true on success.
-    pub fn set_len(&self, size: usize) -> bool {
-        self.0
+    /// Sets the number of values. If the number of values is expanded all new value
+    /// slots will default to type None.
+    pub fn set_len(&self, size: usize) -> Result<(), Error> {
+        ffi_event!("ListValue::set_len");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_size
             .map(|set_size| unsafe { set_size(self.as_ptr(), size) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "ListValue::set_len")
     }
     /// Returns the number of values.
     pub fn len(&self) -> usize {
+        ffi_event!("ListValue::len");
         self.0
             .get_size
             .map(|get_size| unsafe { get_size(self.as_ptr()) })
             .unwrap_or(0)
     }
-    /// Removes all values. Returns true on success.
-    pub fn clear(&self) -> bool {
-        self.0
+    /// Removes all values.
+    pub fn clear(&self) -> Result<(), Error> {
+        ffi_event!("ListValue::clear");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .clear
             .map(|clear| unsafe { clear(self.as_ptr()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "ListValue::clear")
     }
     /// Removes the value at the specified index.
-    pub fn remove(&self, index: usize) -> bool {
-        self.0
+    pub fn remove(&self, index: usize) -> Result<(), Error> {
+        ffi_event!("ListValue::remove");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .remove
             .map(|remove| unsafe { remove(self.as_ptr(), index) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "ListValue::remove")
     }
     /// Returns the value type at the specified index.
     pub(crate) fn get_type_inner(&self, index: usize) -> ValueType {
@@ -963,28 +1040,33 @@ impl ListValue {
             .and_then(|get_value| unsafe { Value::from_ptr(get_value(self.as_ptr(), index)) })
     }
     pub fn get(&self, index: usize) -> Option<StoredValue> {
+        ffi_event!("ListValue::get");
         self.get_inner(index).map(StoredValue::from)
     }
     /// Returns the value at the specified index as type bool.
     pub fn get_bool(&self, index: usize) -> Option<bool> {
+        ffi_event!("ListValue::get_bool");
         self.0
             .get_bool
             .map(|get_bool| unsafe { get_bool(self.as_ptr(), index) != 0 })
     }
     /// Returns the value at the specified index as type int.
     pub fn get_int(&self, index: usize) -> Option<i32> {
+        ffi_event!("ListValue::get_int");
         self.0
             .get_int
             .map(|get_int| unsafe { get_int(self.as_ptr(), index) as i32 })
     }
     /// Returns the value at the specified index as type double.
     pub fn get_double(&self, index: usize) -> Option<f64> {
+        ffi_event!("ListValue::get_double");
         self.0
             .get_double
             .map(|get_double| unsafe { get_double(self.as_ptr(), index) })
     }
     /// Returns the value at the specified index as type string.
     pub fn get_string(&self, index: usize) -> Option<String> {
+        ffi_event!("ListValue::get_string");
         self.0.get_string.and_then(|get_string| {
             let s = unsafe { get_string(self.as_ptr(), index) };
             let result = unsafe { CefString::from_ptr(s).map(String::from) };
@@ -997,6 +1079,7 @@ impl ListValue {
     /// Returns the value at the specified index as type binary. The returned value
     /// will reference existing data.
     pub fn get_binary(&self, index: usize) -> Option<BinaryValue> {
+        ffi_event!("ListValue::get_binary");
         self.0.get_binary.and_then(|get_binary| unsafe {
             BinaryValue::from_ptr(get_binary(self.as_ptr(), index))
         })
@@ -1005,6 +1088,7 @@ impl ListValue {
     /// value will reference existing data and modifications to the value will
     /// modify this object.
     pub fn get_dictionary(&self, index: usize) -> Option<DictionaryValue> {
+        ffi_event!("ListValue::get_dictionary");
         self.0.get_dictionary.and_then(|get_dictionary| unsafe {
             DictionaryValue::from_ptr(get_dictionary(self.as_ptr(), index))
         })
@@ -1013,6 +1097,7 @@ impl ListValue {
     /// will reference existing data and modifications to the value will modify
     /// this object.
     pub fn get_list(&self, index: usize) -> Option<ListValue> {
+        ffi_event!("ListValue::get_list");
         self.0
             .get_list
             .and_then(|get_list| unsafe { ListValue::from_ptr(get_list(self.as_ptr(), index)) })
@@ -1029,84 +1114,108 @@ impl ListValue {
             .map(|set_value| unsafe { set_value(self.as_ptr(), index, value.into_raw()) != 0 })
             .unwrap_or(false)
     }
-    /// Sets the value at the specified index as type null. Returns true if the
-    /// value was set successfully.
-    pub fn set_null(&self, index: usize) -> bool {
-        self.0
+    /// Sets the value at the specified index as type null.
+    pub fn set_null(&self, index: usize) -> Result<(), Error> {
+        ffi_event!("ListValue::set_null");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_null
             .map(|set_null| unsafe { set_null(self.as_ptr(), index) != 0 })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type bool. Returns true if the
-    /// value was set successfully.
-    pub fn set_bool(&self, index: usize, value: bool) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_null")
+    }
+    /// Sets the value at the specified index as type bool.
+    pub fn set_bool(&self, index: usize, value: bool) -> Result<(), Error> {
+        ffi_event!("ListValue::set_bool");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_bool
             .map(|set_bool| unsafe {
                 set_bool(self.as_ptr(), index, if value { 1 } else { 0 }) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type int. Returns true if the
-    /// value was set successfully.
-    pub fn set_int(&self, index: usize, value: i32) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_bool")
+    }
+    /// Sets the value at the specified index as type int.
+    pub fn set_int(&self, index: usize, value: i32) -> Result<(), Error> {
+        ffi_event!("ListValue::set_int");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_int
             .map(|set_int| unsafe { set_int(self.as_ptr(), index, value) != 0 })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type double. Returns true if the
-    /// value was set successfully.
-    pub fn set_double(&self, index: usize, value: f64) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_int")
+    }
+    /// Sets the value at the specified index as type double.
+    pub fn set_double(&self, index: usize, value: f64) -> Result<(), Error> {
+        ffi_event!("ListValue::set_double");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_double
             .map(|set_double| unsafe { set_double(self.as_ptr(), index, value) != 0 })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type string. Returns true if the
-    /// value was set successfully.
-    pub fn set_string(&self, index: usize, value: &str) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_double")
+    }
+    /// Sets the value at the specified index as type string.
+    pub fn set_string(&self, index: usize, value: &str) -> Result<(), Error> {
+        ffi_event!("ListValue::set_string");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_string
             .map(|set_string| unsafe {
                 set_string(self.as_ptr(), index, CefString::new(value).as_ptr()) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type binary. Returns true if the
-    /// value was set successfully. If `value` is currently owned by another object
-    /// then the value will be copied and the `value` reference will not change.
-    /// Otherwise, ownership will be transferred to this object and the `value`
-    /// reference will be invalidated.
-    pub fn set_binary(&self, index: usize, value: BinaryValue) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_string")
+    }
+    /// Sets the value at the specified index as type binary. If `value` is currently
+    /// owned by another object then the value will be copied and the `value` reference
+    /// will not change. Otherwise, ownership will be transferred to this object and the
+    /// `value` reference will be invalidated.
+    pub fn set_binary(&self, index: usize, value: BinaryValue) -> Result<(), Error> {
+        ffi_event!("ListValue::set_binary");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_binary
             .map(|set_binary| unsafe { set_binary(self.as_ptr(), index, value.into_raw()) != 0 })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type dict. Returns true if the
-    /// value was set successfully. If `value` is currently owned by another object
-    /// then the value will be copied and the `value` reference will not change.
-    /// Otherwise, ownership will be transferred to this object and the `value`
-    /// reference will be invalidated.
-    pub fn set_dictionary(&self, index: usize, value: DictionaryValue) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_binary")
+    }
+    /// Sets the value at the specified index as type dict. If `value` is currently
+    /// owned by another object then the value will be copied and the `value` reference
+    /// will not change. Otherwise, ownership will be transferred to this object and the
+    /// `value` reference will be invalidated.
+    pub fn set_dictionary(&self, index: usize, value: DictionaryValue) -> Result<(), Error> {
+        ffi_event!("ListValue::set_dictionary");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_dictionary
             .map(|set_dictionary| unsafe {
                 set_dictionary(self.as_ptr(), index, value.into_raw()) != 0
             })
-            .unwrap_or(false)
-    }
-    /// Sets the value at the specified index as type list. Returns true if the
-    /// value was set successfully. If `value` is currently owned by another object
-    /// then the value will be copied and the `value` reference will not change.
-    /// Otherwise, ownership will be transferred to this object and the `value`
-    /// reference will be invalidated.
-    pub fn set_list(&self, index: usize, value: ListValue) -> bool {
-        self.0
+            .unwrap_or(false);
+        check(success, "ListValue::set_dictionary")
+    }
+    /// Sets the value at the specified index as type list. If `value` is currently
+    /// owned by another object then the value will be copied and the `value` reference
+    /// will not change. Otherwise, ownership will be transferred to this object and the
+    /// `value` reference will be invalidated.
+    pub fn set_list(&self, index: usize, value: ListValue) -> Result<(), Error> {
+        ffi_event!("ListValue::set_list");
+        check_writable(self.is_valid(), || self.is_read_only())?;
+        let success = self
+            .0
             .set_list
             .map(|set_list| unsafe { set_list(self.as_ptr(), index, value.into_raw()) != 0 })
-            .unwrap_or(false)
+            .unwrap_or(false);
+        check(success, "ListValue::set_list")
     }
 }
 
@@ -0,0 +1,208 @@
+use crate::capi::{cef_api_hash, cef_version_info};
+use std::{ffi::CStr, fmt, os::raw::c_char};
+
+/// Version information of CEF and the Chromium it's based on, plus the hashes
+/// that identify the C API.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CefVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub commit_number: u32,
+    pub chrome_major: u32,
+    pub chrome_minor: u32,
+    pub chrome_build: u32,
+    pub chrome_patch: u32,
+    /// Hash of the platform-specific C API. Two libraries with the same hash are
+    /// binary compatible.
+    pub api_hash_platform: String,
+    /// Hash of the platform-independent C API.
+    pub api_hash_universal: String,
+    /// The CEF git commit hash.
+    pub commit_hash: String,
+}
+
+impl CefVersion {
+    /// Returns the version of the libcef that is actually loaded into the process.
+    pub fn runtime() -> CefVersion {
+        let version_info = |entry: i32| unsafe { cef_version_info(entry) as u32 };
+        let api_hash = |entry: i32| unsafe { c_string(cef_api_hash(entry)) };
+        CefVersion {
+            major: version_info(0),
+            minor: version_info(1),
+            patch: version_info(2),
+            commit_number: version_info(3),
+            chrome_major: version_info(4),
+            chrome_minor: version_info(5),
+            chrome_build: version_info(6),
+            chrome_patch: version_info(7),
+            api_hash_platform: api_hash(0),
+            api_hash_universal: api_hash(1),
+            commit_hash: api_hash(2),
+        }
+    }
+    /// Returns the version the bindings of this crate were generated for.
+    pub fn compiled() -> CefVersion {
+        CefVersion {
+            major: cef_sys::CEF_VERSION_MAJOR as u32,
+            minor: cef_sys::CEF_VERSION_MINOR as u32,
+            patch: cef_sys::CEF_VERSION_PATCH as u32,
+            commit_number: cef_sys::CEF_COMMIT_NUMBER as u32,
+            chrome_major: cef_sys::CHROME_VERSION_MAJOR as u32,
+            chrome_minor: cef_sys::CHROME_VERSION_MINOR as u32,
+            chrome_build: cef_sys::CHROME_VERSION_BUILD as u32,
+            chrome_patch: cef_sys::CHROME_VERSION_PATCH as u32,
+            api_hash_platform: byte_string(cef_sys::CEF_API_HASH_PLATFORM),
+            api_hash_universal: byte_string(cef_sys::CEF_API_HASH_UNIVERSAL),
+            commit_hash: byte_string(cef_sys::CEF_COMMIT_HASH),
+        }
+    }
+    /// Returns true if both versions implement the same C API.
+    pub fn is_compatible_with(&self, other: &CefVersion) -> bool {
+        self.api_hash_platform == other.api_hash_platform
+    }
+    /// Check that the loaded libcef matches the version the bindings were
+    /// generated for. Returns the runtime version on success.
+    ///
+    /// This is done by [crate::execute_process] and [crate::Context::initialize],
+    /// because calling into a library with a different API is undefined behavior.
+    pub fn check() -> Result<CefVersion, VersionMismatch> {
+        CefVersion::runtime().check_against(CefVersion::compiled())
+    }
+
+    fn check_against(self, compiled: CefVersion) -> Result<CefVersion, VersionMismatch> {
+        if self.is_compatible_with(&compiled) {
+            Ok(self)
+        } else {
+            Err(VersionMismatch {
+                runtime: self,
+                compiled,
+            })
+        }
+    }
+}
+
+impl fmt::Display for CefVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}+g{}+chromium-{}.{}.{}.{}",
+            self.major,
+            self.minor,
+            self.patch,
+            self.commit_hash.get(..7).unwrap_or(&self.commit_hash),
+            self.chrome_major,
+            self.chrome_minor,
+            self.chrome_build,
+            self.chrome_patch,
+        )
+    }
+}
+
+/// The loaded libcef has a different API than the one this crate was built against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionMismatch {
+    pub runtime: CefVersion,
+    pub compiled: CefVersion,
+}
+
+impl fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "loaded libcef {} (API hash {}) is incompatible with the expected {} (API hash {})",
+            self.runtime, self.runtime.api_hash_platform, self.compiled, self.compiled.api_hash_platform,
+        )
+    }
+}
+
+impl std::error::Error for VersionMismatch {}
+
+unsafe fn c_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+fn byte_string(bytes: &[u8]) -> String {
+    let bytes = bytes.split(|&b| b == 0).next().unwrap_or(&[]);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(patch: u32, api_hash: &str) -> CefVersion {
+        CefVersion {
+            major: 81,
+            minor: 3,
+            patch,
+            commit_number: 2310,
+            chrome_major: 81,
+            chrome_minor: 0,
+            chrome_build: 4044,
+            chrome_patch: 138,
+            api_hash_platform: api_hash.to_owned(),
+            api_hash_universal: "universal".to_owned(),
+            commit_hash: "3cea2af1e4b0b1d7bce98d43c4bbd4c3a7f8d9e0".to_owned(),
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            version(2, "abc").to_string(),
+            "81.3.2+g3cea2af+chromium-81.0.4044.138"
+        );
+        let mut short = version(2, "abc");
+        short.commit_hash = "3ce".to_owned();
+        assert_eq!(short.to_string(), "81.3.2+g3ce+chromium-81.0.4044.138");
+    }
+
+    #[test]
+    fn only_the_platform_api_hash_decides_compatibility() {
+        let compiled = version(2, "abc");
+        let mut runtime = version(7, "abc");
+        runtime.api_hash_universal = "other".to_owned();
+        assert!(runtime.is_compatible_with(&compiled));
+        assert_eq!(runtime.clone().check_against(compiled), Ok(runtime));
+    }
+
+    #[test]
+    fn mismatch() {
+        let compiled = version(2, "abc");
+        let runtime = version(2, "def");
+        assert!(!runtime.is_compatible_with(&compiled));
+        let mismatch = runtime.clone().check_against(compiled.clone()).unwrap_err();
+        assert_eq!(
+            mismatch,
+            VersionMismatch {
+                runtime,
+                compiled
+            }
+        );
+        assert_eq!(
+            mismatch.to_string(),
+            "loaded libcef 81.3.2+g3cea2af+chromium-81.0.4044.138 (API hash def) is incompatible \
+             with the expected 81.3.2+g3cea2af+chromium-81.0.4044.138 (API hash abc)"
+        );
+    }
+
+    #[test]
+    fn byte_strings_end_at_nul() {
+        assert_eq!(byte_string(b"abc\0"), "abc");
+        assert_eq!(byte_string(b"abc\0def"), "abc");
+        assert_eq!(byte_string(b"abc"), "abc");
+        assert_eq!(byte_string(b""), "");
+    }
+
+    #[test]
+    fn compiled_version() {
+        let compiled = CefVersion::compiled();
+        assert!(!compiled.api_hash_platform.is_empty());
+        assert!(compiled.is_compatible_with(&compiled));
+    }
+}
@@ -1,4 +1,5 @@
-use cef_sys::{cef_string_userfree_utf16_free, cef_web_plugin_info_t};
+use cef_sys::cef_web_plugin_info_t;
+use crate::capi::cef_string_userfree_utf16_free;
 use std::path::PathBuf;
 use crate::string::CefString;
 
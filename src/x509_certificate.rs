@@ -20,12 +20,14 @@ impl X509Certificate {
     /// this represents the web server.  The common name of the subject should
     /// match the host name of the web server.
     pub fn get_subject(&self) -> X509CertPrincipal {
+        ffi_event!("X509Certificate::get_subject");
         unsafe {
             X509CertPrincipal::from_ptr_unchecked(self.0.get_subject.unwrap()(self.as_ptr()))
         }
     }
     /// Returns the issuer of the X.509 certificate.
     pub fn get_issuer(&self) -> X509CertPrincipal {
+        ffi_event!("X509Certificate::get_issuer");
         unsafe {
             X509CertPrincipal::from_ptr_unchecked(self.0.get_issuer.unwrap()(self.as_ptr()))
         }
@@ -33,6 +35,7 @@ impl X509Certificate {
     /// Returns the DER encoded serial number for the X.509 certificate. The value
     /// possibly includes a leading 00 byte.
     pub fn get_serial_number(&self) -> BinaryValue {
+        ffi_event!("X509Certificate::get_serial_number");
         unsafe {
             BinaryValue::from_ptr_unchecked(self.0.get_serial_number.unwrap()(self.as_ptr()))
         }
@@ -40,6 +43,7 @@ impl X509Certificate {
     /// Returns the date before which the X.509 certificate is invalid.
     /// CefTime.GetTimeT() will return 0 if no date was specified.
     pub fn get_valid_start(&self) -> DateTime<Utc> {
+        ffi_event!("X509Certificate::get_valid_start");
         crate::values::cef_time_to_date_time(unsafe {
             self.0.get_valid_start.unwrap()(self.as_ptr())
         })
@@ -47,18 +51,21 @@ impl X509Certificate {
     /// Returns the date after which the X.509 certificate is invalid.
     /// CefTime.GetTimeT() will return 0 if no date was specified.
     pub fn get_valid_expiry(&self) -> DateTime<Utc> {
+        ffi_event!("X509Certificate::get_valid_expiry");
         crate::values::cef_time_to_date_time(unsafe {
             self.0.get_valid_expiry.unwrap()(self.as_ptr())
         })
     }
     /// Returns the DER encoded data for the X.509 certificate.
     pub fn get_derencoded(&self) -> BinaryValue {
+        ffi_event!("X509Certificate::get_derencoded");
         unsafe {
             BinaryValue::from_ptr_unchecked(self.0.get_derencoded.unwrap()(self.as_ptr()))
         }
     }
     /// Returns the PEM encoded data for the X.509 certificate.
     pub fn get_pemencoded(&self) -> BinaryValue {
+        ffi_event!("X509Certificate::get_pemencoded");
         unsafe {
             BinaryValue::from_ptr_unchecked(self.0.get_pemencoded.unwrap()(self.as_ptr()))
         }
@@ -66,12 +73,14 @@ impl X509Certificate {
     /// Returns the number of certificates in the issuer chain. If 0, the
     /// certificate is self-signed.
     pub fn get_issuer_chain_size(&self) -> usize {
+        ffi_event!("X509Certificate::get_issuer_chain_size");
         unsafe{ self.0.get_issuer_chain_size.unwrap()(self.as_ptr()) }
     }
     /// Returns the DER encoded data for the certificate issuer chain. If we failed
     /// to encode a certificate in the chain it is still present in the array but
     /// is an `None` string.
     pub fn get_der_encoded_issuer_chain(&self, chain: &mut Vec<BinaryValue>) {
+        ffi_event!("X509Certificate::get_der_encoded_issuer_chain");
         // the CEF C API seems basically unusable for this. I've got no idea how to implement it.
         unimplemented!()
     }
@@ -79,6 +88,7 @@ impl X509Certificate {
     /// to encode a certificate in the chain it is still present in the array but
     /// is an `None` string.
     pub fn get_pem_encoded_issuer_chain(&self, chain: &mut Vec<BinaryValue>) {
+        ffi_event!("X509Certificate::get_pem_encoded_issuer_chain");
         // the CEF C API seems basically unusable for this. I've got no idea how to implement it.
         unimplemented!()
     }
@@ -89,36 +99,42 @@ impl X509CertPrincipal {
     /// order: Common Name (CN), Organization Name (O) and Organizational Unit Name
     /// (OU) and returns the first non-NULL one found.
     pub fn get_display_name(&self) -> String {
+        ffi_event!("X509CertPrincipal::get_display_name");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_display_name.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the common name.
     pub fn get_common_name(&self) -> String {
+        ffi_event!("X509CertPrincipal::get_common_name");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_common_name.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the locality name.
     pub fn get_locality_name(&self) -> String {
+        ffi_event!("X509CertPrincipal::get_locality_name");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_locality_name.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the state or province name.
     pub fn get_state_or_province_name(&self) -> String {
+        ffi_event!("X509CertPrincipal::get_state_or_province_name");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_state_or_province_name.unwrap()(self.as_ptr())).into()
         }
     }
     /// Returns the country name.
     pub fn get_country_name(&self) -> String {
+        ffi_event!("X509CertPrincipal::get_country_name");
         unsafe {
             CefString::from_userfree_unchecked(self.0.get_country_name.unwrap()(self.as_ptr())).into()
         }
     }
     /// Retrieve the list of street addresses.
     pub fn get_street_addresses(&self, addresses: &mut Vec<String>) {
+        ffi_event!("X509CertPrincipal::get_street_addresses");
         let mut list = CefStringList::new();
         unsafe {
             self.0.get_street_addresses.unwrap()(self.as_ptr(), list.as_mut_ptr());
@@ -127,6 +143,7 @@ impl X509CertPrincipal {
     }
     /// Retrieve the list of organization names.
     pub fn get_organization_names(&self, names: &mut Vec<String>) {
+        ffi_event!("X509CertPrincipal::get_organization_names");
         let mut list = CefStringList::new();
         unsafe {
             self.0.get_organization_names.unwrap()(self.as_ptr(), list.as_mut_ptr());
@@ -135,6 +152,7 @@ impl X509CertPrincipal {
     }
     /// Retrieve the list of organization unit names.
     pub fn get_organization_unit_names(&self, names: &mut Vec<String>) {
+        ffi_event!("X509CertPrincipal::get_organization_unit_names");
         let mut list = CefStringList::new();
         unsafe {
             self.0.get_organization_unit_names.unwrap()(self.as_ptr(), list.as_mut_ptr());
@@ -143,6 +161,7 @@ impl X509CertPrincipal {
     }
     /// Retrieve the list of domain components.
     pub fn get_domain_components(&self, components: &mut Vec<String>) {
+        ffi_event!("X509CertPrincipal::get_domain_components");
         let mut list = CefStringList::new();
         unsafe {
             self.0.get_domain_components.unwrap()(self.as_ptr(), list.as_mut_ptr());